@@ -4,60 +4,200 @@ extern crate pretty_env_logger;
 extern crate termcolor;
 
 use clap::ArgAction;
-use std::io;
+use std::{io, process};
 
-use eureka::config_manager::ConfigManager;
+use eureka::clock::SystemClock;
+use eureka::config_manager::{ConfigManagement, ConfigManager, ConfigType};
+use eureka::file_handler::{FileHandler, LineEnding};
 use eureka::git::Git;
 use eureka::printer::Printer;
 use eureka::program_access::ProgramAccess;
-use eureka::reader::Reader;
-use eureka::{Eureka, EurekaOptions};
+use eureka::reader::{TerminalReader, HISTORY_FILE_NAME};
+use eureka::sink::GitHubIssueSink;
+use eureka::{Eureka, EurekaOptions, OptionKind};
 use log::error;
 
 const ARG_CLEAR_CONFIG: &str = "clear-config";
+const ARG_SETUP: &str = "setup";
+const ARG_VERIFY_SETUP: &str = "verify-setup";
 const ARG_VIEW: &str = "view";
+const ARG_PREVIEW: &str = "preview";
+const ARG_SUMMARY: &str = "summary";
+const ARG_CLIPBOARD: &str = "clipboard";
+const ARG_INLINE: &str = "inline";
+const ARG_APPEND_TO: &str = "append-to";
+const ARG_COUNT: &str = "count";
+const ARG_NO_PUSH: &str = "no-push";
+const ARG_FLUSH: &str = "flush";
+const ARG_FLUSH_ALL: &str = "flush-all";
+const ARG_MERGE_INTO: &str = "merge-into";
+const ARG_CONTINUE: &str = "continue";
+const ARG_REPLAY: &str = "replay";
+const ARG_STATUS: &str = "status";
+const ARG_HERE: &str = "here";
+const ARG_WELCOME: &str = "welcome";
+const ARG_VERSION_INFO: &str = "version-info";
+const ARG_DATE: &str = "date";
+const ARG_QUEUE: &str = "queue";
+const ARG_PROCESS_QUEUE: &str = "process-queue";
+const ARG_PORCELAIN: &str = "porcelain";
+const ARG_BACKGROUND_PUSH: &str = "background-push";
+const ARG_VERBOSE: &str = "verbose";
+const ARG_RANDOM: &str = "random";
+const ARG_PRIVATE: &str = "private";
+const ARG_OFFLINE: &str = "offline";
+const ARG_NO_CACHE: &str = "no-cache";
+const ARG_COMMIT_BODY: &str = "commit-body";
+const ARG_APPEND: &str = "append";
+const ARG_LIST: &str = "list";
+const ARG_BY_TAG: &str = "by-tag";
+const ARG_SEARCH: &str = "search";
+const ARG_FUZZY: &str = "fuzzy";
+const ARG_BROWSE: &str = "browse";
+const ARG_DIFF: &str = "diff";
+const ARG_JSON: &str = "json";
+const ARG_ARCHIVE_OLDER_THAN: &str = "archive-older-than";
 
-fn main() {
-    pretty_env_logger::init();
-
-    let cli_flags = clap::Command::new("eureka")
+// Every option of `EurekaOptions::describe`, and the hidden ones only eureka
+// itself passes
+fn cli() -> clap::Command {
+    let mut command = clap::Command::new("eureka")
         .author(crate_authors!())
         .version(crate_version!())
-        .about("Input and store your ideas without leaving the terminal")
-        .arg(
-            clap::Arg::new(ARG_CLEAR_CONFIG)
-                .long(ARG_CLEAR_CONFIG)
-                .action(ArgAction::SetTrue)
-                .help("Clear your stored configuration"),
-        )
+        .about("Input and store your ideas without leaving the terminal");
+    for option in EurekaOptions::describe() {
+        let arg = clap::Arg::new(option.name)
+            .long(option.name)
+            .short(option.short)
+            .help(option.description);
+        command = command.arg(match option.kind {
+            OptionKind::Flag => arg.action(ArgAction::SetTrue),
+            OptionKind::Value(value_name) => arg.value_name(value_name),
+        });
+    }
+    command
+        .mut_arg(ARG_BY_TAG, |arg| arg.requires(ARG_LIST))
+        .mut_arg(ARG_FUZZY, |arg| arg.requires(ARG_SEARCH))
+        .mut_arg(ARG_DIFF, |arg| {
+            arg.num_args(0..=1).default_missing_value("1")
+        })
+        .mut_arg(ARG_JSON, |arg| arg.requires(ARG_DIFF))
+        .mut_arg(ARG_INLINE, |arg| {
+            arg.conflicts_with_all([ARG_SUMMARY, ARG_CLIPBOARD])
+        })
+        // Only started by eureka itself, see `background_push` in the config
         .arg(
-            clap::Arg::new(ARG_VIEW)
-                .long(ARG_VIEW)
-                .short(ARG_VIEW.chars().next().unwrap())
-                .action(ArgAction::SetTrue)
-                .help("View ideas with your $PAGER env variable. If unset use less"),
+            clap::Arg::new(ARG_BACKGROUND_PUSH)
+                .long(ARG_BACKGROUND_PUSH)
+                .value_name("BRANCH")
+                .hide(true),
         )
-        .get_matches();
+}
+
+fn main() {
+    pretty_env_logger::init();
+
+    let cli_flags = cli().get_matches();
 
     let stdio = io::stdin();
     let input = stdio.lock();
-    let output = termcolor::StandardStream::stdout(termcolor::ColorChoice::Always);
+    let printer = if cli_flags.get_flag(ARG_PORCELAIN) {
+        // Keep stderr plain, it's read by scripts too
+        let output = termcolor::StandardStream::stderr(termcolor::ColorChoice::Never);
+        Printer::porcelain(output, io::stdout())
+    } else {
+        Printer::new(termcolor::StandardStream::stdout(
+            termcolor::ColorChoice::Always,
+        ))
+    };
+
+    // The editor picked during first time setup, if any
+    let editor = ConfigManager.config_read(ConfigType::Editor).ok();
+    let history_path = ConfigManager
+        .config_dir_path()
+        .ok()
+        .map(|config_dir| config_dir.join(HISTORY_FILE_NAME));
+    let line_ending: LineEnding = ConfigManager
+        .config_read(ConfigType::LineEnding)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_default();
 
     let mut eureka = Eureka::new(
-        ConfigManager::default(),
-        Printer::new(output),
-        Reader::new(input),
+        ConfigManager,
+        printer,
+        TerminalReader::new(input, history_path),
         Git::default(),
-        ProgramAccess::default(),
+        ProgramAccess::new(editor),
+        FileHandler::new(line_ending),
+        SystemClock,
+        GitHubIssueSink::default(),
     );
 
     let opts = EurekaOptions {
+        welcome: cli_flags.get_flag(ARG_WELCOME),
+        version_info: cli_flags.get_flag(ARG_VERSION_INFO),
         clear_config: cli_flags.get_flag(ARG_CLEAR_CONFIG),
+        setup: cli_flags.get_flag(ARG_SETUP),
+        verify_setup: cli_flags.get_flag(ARG_VERIFY_SETUP),
         view: cli_flags.get_flag(ARG_VIEW),
+        preview: cli_flags.get_flag(ARG_PREVIEW),
+        summary: cli_flags.get_one::<String>(ARG_SUMMARY).cloned(),
+        clipboard: cli_flags.get_flag(ARG_CLIPBOARD),
+        inline: cli_flags.get_flag(ARG_INLINE),
+        append_to: cli_flags.get_one::<String>(ARG_APPEND_TO).cloned(),
+        count: cli_flags.get_flag(ARG_COUNT),
+        no_push: cli_flags.get_flag(ARG_NO_PUSH),
+        flush: cli_flags.get_flag(ARG_FLUSH),
+        flush_all: cli_flags.get_flag(ARG_FLUSH_ALL),
+        merge_into: cli_flags.get_one::<String>(ARG_MERGE_INTO).cloned(),
+        continue_merge: cli_flags.get_flag(ARG_CONTINUE),
+        replay: cli_flags.get_flag(ARG_REPLAY),
+        status: cli_flags.get_flag(ARG_STATUS),
+        verbose: cli_flags.get_flag(ARG_VERBOSE),
+        random: cli_flags.get_flag(ARG_RANDOM),
+        private: cli_flags.get_flag(ARG_PRIVATE),
+        offline: cli_flags.get_flag(ARG_OFFLINE),
+        no_cache: cli_flags.get_flag(ARG_NO_CACHE),
+        commit_body: cli_flags.get_flag(ARG_COMMIT_BODY),
+        append: cli_flags.get_one::<String>(ARG_APPEND).cloned(),
+        list: cli_flags.get_flag(ARG_LIST),
+        by_tag: cli_flags.get_flag(ARG_BY_TAG),
+        search: cli_flags.get_one::<String>(ARG_SEARCH).cloned(),
+        fuzzy: cli_flags.get_flag(ARG_FUZZY),
+        browse: cli_flags.get_flag(ARG_BROWSE),
+        diff: cli_flags.get_one::<String>(ARG_DIFF).cloned(),
+        json: cli_flags.get_flag(ARG_JSON),
+        archive_older_than: cli_flags.get_one::<String>(ARG_ARCHIVE_OLDER_THAN).cloned(),
+        here: cli_flags.get_flag(ARG_HERE),
+        date: cli_flags.get_one::<String>(ARG_DATE).cloned(),
+        queue: cli_flags.get_one::<String>(ARG_QUEUE).cloned(),
+        process_queue: cli_flags.get_flag(ARG_PROCESS_QUEUE),
+        background_push: cli_flags.get_one::<String>(ARG_BACKGROUND_PUSH).cloned(),
     };
 
-    match eureka.run(opts) {
-        Ok(_) => {}
-        Err(e) => error!("{}", e),
+    if let Err(e) = eureka.run(opts) {
+        error!("{}", e);
+        process::exit(e.exit_code());
+    }
+}
+
+#[allow(non_snake_case)]
+#[cfg(test)]
+mod tests {
+    use super::cli;
+    use eureka::EurekaOptions;
+
+    #[test]
+    fn test_cli__every_arg_is_described() {
+        let command = cli();
+        let shown = command.get_arguments().filter(|arg| !arg.is_hide_set());
+
+        for arg in shown {
+            let described = EurekaOptions::describe()
+                .iter()
+                .any(|option| arg.get_id() == option.name);
+            assert!(described, "--{} is missing from describe", arg.get_id());
+        }
     }
 }
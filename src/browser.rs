@@ -0,0 +1,336 @@
+use std::io;
+
+// What was picked in the browser, by its index in the browsed ideas
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Browsed {
+    // Enter, open the idea in the editor
+    Open(usize),
+    // `d`, show the diff of the commit that added the idea
+    Diff(usize),
+}
+
+// A full-screen list of `ideas`, narrowed down with `/` and a fuzzy filter.
+// None once the browser is closed with `q` or Esc
+#[cfg(feature = "browse")]
+pub fn browse(ideas: &[String]) -> io::Result<Option<Browsed>> {
+    use crossterm::{cursor, execute, terminal};
+
+    let mut stdout = io::stdout();
+    terminal::enable_raw_mode()?;
+    let result = execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)
+        .and_then(|_| run_browser(&mut stdout, ideas));
+    // Give the terminal back even if drawing failed
+    let restored = execute!(stdout, cursor::Show, terminal::LeaveAlternateScreen)
+        .and_then(|_| terminal::disable_raw_mode());
+    let browsed = result?;
+    restored?;
+    Ok(browsed)
+}
+
+#[cfg(not(feature = "browse"))]
+pub fn browse(_ideas: &[String]) -> io::Result<Option<Browsed>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "eureka was built without the idea browser, rebuild it with --features browse",
+    ))
+}
+
+#[cfg(feature = "browse")]
+fn run_browser(out: &mut impl io::Write, ideas: &[String]) -> io::Result<Option<Browsed>> {
+    use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+
+    let mut state = BrowseState::new(ideas);
+    loop {
+        let (width, height) = crossterm::terminal::size()?;
+        // The first line is the filter
+        let rows = usize::from(height.saturating_sub(1)).max(1);
+        state.scroll(rows);
+        draw(out, ideas, &state, usize::from(width), rows)?;
+
+        let key = match event::read()? {
+            Event::Key(key) if key.kind != KeyEventKind::Release => key,
+            _ => continue,
+        };
+        let key = match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(None),
+            KeyCode::Char(c) => BrowseKey::Char(c),
+            KeyCode::Up => BrowseKey::Up,
+            KeyCode::Down => BrowseKey::Down,
+            KeyCode::Enter => BrowseKey::Enter,
+            KeyCode::Backspace => BrowseKey::Backspace,
+            KeyCode::Esc => BrowseKey::Esc,
+            _ => continue,
+        };
+        if let Some(browsed) = state.press(key, ideas) {
+            return Ok(browsed);
+        }
+    }
+}
+
+#[cfg(feature = "browse")]
+fn draw(
+    out: &mut impl io::Write,
+    ideas: &[String],
+    state: &BrowseState,
+    width: usize,
+    rows: usize,
+) -> io::Result<()> {
+    use crossterm::style::{Attribute, Print, SetAttribute};
+    use crossterm::{cursor, queue, terminal};
+
+    queue!(
+        out,
+        terminal::Clear(terminal::ClearType::All),
+        cursor::MoveTo(0, 0)
+    )?;
+    let header = if state.filtering || !state.query.is_empty() {
+        format!("/{}", state.query)
+    } else {
+        String::from("/ filter, enter open, d diff, q quit")
+    };
+    queue!(out, Print(truncate(&header, width)))?;
+
+    let shown = state
+        .matches
+        .iter()
+        .enumerate()
+        .skip(state.offset)
+        .take(rows);
+    for (row, (position, &index)) in shown.enumerate() {
+        let Ok(y) = u16::try_from(row + 1) else {
+            break;
+        };
+        queue!(out, cursor::MoveTo(0, y))?;
+        if position == state.selected {
+            queue!(out, SetAttribute(Attribute::Reverse))?;
+        }
+        queue!(
+            out,
+            Print(truncate(&ideas[index], width)),
+            SetAttribute(Attribute::Reset)
+        )?;
+    }
+    out.flush()
+}
+
+// At most `width` chars of `text`
+#[cfg(feature = "browse")]
+fn truncate(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
+
+// A key the browser reacts to
+#[cfg(feature = "browse")]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum BrowseKey {
+    Char(char),
+    Up,
+    Down,
+    Enter,
+    Backspace,
+    Esc,
+}
+
+// What's filtered and selected, apart from the terminal so it can be tested
+#[cfg(feature = "browse")]
+#[derive(Debug)]
+struct BrowseState {
+    query: String,
+    // Typing goes to the query, after `/` and until Enter or Esc
+    filtering: bool,
+    // Indices of the ideas matching the query, closest first
+    matches: Vec<usize>,
+    // Position in `matches` of the selected idea
+    selected: usize,
+    // Position in `matches` of the first shown idea
+    offset: usize,
+}
+
+#[cfg(feature = "browse")]
+impl BrowseState {
+    fn new(ideas: &[String]) -> Self {
+        Self {
+            query: String::new(),
+            filtering: false,
+            matches: (0..ideas.len()).collect(),
+            selected: 0,
+            offset: 0,
+        }
+    }
+
+    // Some once the browser is done, with what was picked if anything
+    fn press(&mut self, key: BrowseKey, ideas: &[String]) -> Option<Option<Browsed>> {
+        if self.filtering {
+            match key {
+                BrowseKey::Char(c) => {
+                    self.query.push(c);
+                    self.filter(ideas);
+                }
+                BrowseKey::Backspace => {
+                    self.query.pop();
+                    self.filter(ideas);
+                }
+                BrowseKey::Enter => self.filtering = false,
+                BrowseKey::Esc => {
+                    self.filtering = false;
+                    self.query.clear();
+                    self.filter(ideas);
+                }
+                BrowseKey::Up => self.move_selection(-1),
+                BrowseKey::Down => self.move_selection(1),
+            }
+            return None;
+        }
+
+        match key {
+            BrowseKey::Char('/') => self.filtering = true,
+            BrowseKey::Char('k') | BrowseKey::Up => self.move_selection(-1),
+            BrowseKey::Char('j') | BrowseKey::Down => self.move_selection(1),
+            BrowseKey::Enter => return self.picked().map(|index| Some(Browsed::Open(index))),
+            BrowseKey::Char('d') => return self.picked().map(|index| Some(Browsed::Diff(index))),
+            BrowseKey::Char('q') | BrowseKey::Esc => return Some(None),
+            _ => {}
+        }
+        None
+    }
+
+    // Every idea for an empty query, otherwise the fuzzy matches. Stable, so
+    // equally close ideas keep their order
+    fn filter(&mut self, ideas: &[String]) {
+        let mut scored: Vec<(usize, usize)> = ideas
+            .iter()
+            .enumerate()
+            .filter_map(|(index, idea)| {
+                if self.query.trim().is_empty() {
+                    Some((0, index))
+                } else {
+                    crate::fuzzy_score(&self.query, idea).map(|score| (score, index))
+                }
+            })
+            .collect();
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        self.matches = scored.into_iter().map(|(_, index)| index).collect();
+        self.selected = 0;
+        self.offset = 0;
+    }
+
+    fn move_selection(&mut self, by: isize) {
+        let last = self.matches.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(by).min(last);
+    }
+
+    // Keep the selected idea within the `rows` shown
+    fn scroll(&mut self, rows: usize) {
+        if self.selected < self.offset {
+            self.offset = self.selected;
+        } else if self.selected >= self.offset + rows {
+            self.offset = self.selected + 1 - rows;
+        }
+    }
+
+    fn picked(&self) -> Option<usize> {
+        self.matches.get(self.selected).copied()
+    }
+}
+
+#[allow(non_snake_case)]
+#[cfg(all(test, feature = "browse"))]
+mod tests {
+    use crate::browser::{BrowseKey, BrowseState, Browsed};
+
+    fn ideas() -> Vec<String> {
+        [
+            "Buy a boat (README.md)",
+            "Learn to sail (README.md)",
+            "Paint the fence (todo.md)",
+        ]
+        .map(String::from)
+        .to_vec()
+    }
+
+    fn type_text(state: &mut BrowseState, text: &str, ideas: &[String]) {
+        for c in text.chars() {
+            assert_eq!(state.press(BrowseKey::Char(c), ideas), None);
+        }
+    }
+
+    #[test]
+    fn test_browser__press__moves_within_the_list() {
+        let ideas = ideas();
+        let mut state = BrowseState::new(&ideas);
+
+        state.press(BrowseKey::Up, &ideas);
+        assert_eq!(state.selected, 0);
+        state.press(BrowseKey::Char('j'), &ideas);
+        state.press(BrowseKey::Down, &ideas);
+        state.press(BrowseKey::Down, &ideas);
+        assert_eq!(state.selected, 2);
+        state.press(BrowseKey::Char('k'), &ideas);
+
+        let actual = state.press(BrowseKey::Enter, &ideas);
+        let expected = Some(Some(Browsed::Open(1)));
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_browser__press__fuzzy_filter() {
+        let ideas = ideas();
+        let mut state = BrowseState::new(&ideas);
+
+        type_text(&mut state, "/sial", &ideas);
+        assert_eq!(state.matches, vec![1]);
+        // Typed into the query rather than showing the diff
+        type_text(&mut state, "d", &ideas);
+        assert_eq!(state.query, "siald");
+        state.press(BrowseKey::Backspace, &ideas);
+        state.press(BrowseKey::Enter, &ideas);
+
+        let actual = state.press(BrowseKey::Char('d'), &ideas);
+        let expected = Some(Some(Browsed::Diff(1)));
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_browser__press__esc_clears_the_filter() {
+        let ideas = ideas();
+        let mut state = BrowseState::new(&ideas);
+
+        type_text(&mut state, "/fence", &ideas);
+        assert_eq!(state.matches, vec![2]);
+        state.press(BrowseKey::Esc, &ideas);
+
+        assert_eq!(state.query, "");
+        assert_eq!(state.matches, vec![0, 1, 2]);
+        assert_eq!(state.press(BrowseKey::Esc, &ideas), Some(None));
+    }
+
+    #[test]
+    fn test_browser__press__nothing_matches() {
+        let ideas = ideas();
+        let mut state = BrowseState::new(&ideas);
+
+        type_text(&mut state, "/zzzzzz", &ideas);
+        state.press(BrowseKey::Enter, &ideas);
+
+        assert!(state.matches.is_empty());
+        assert_eq!(state.press(BrowseKey::Enter, &ideas), None);
+        assert_eq!(state.press(BrowseKey::Char('q'), &ideas), Some(None));
+    }
+
+    #[test]
+    fn test_browser__scroll__keeps_selection_shown() {
+        let ideas = ideas();
+        let mut state = BrowseState::new(&ideas);
+
+        state.press(BrowseKey::Down, &ideas);
+        state.press(BrowseKey::Down, &ideas);
+        state.scroll(2);
+        assert_eq!(state.offset, 1);
+        state.press(BrowseKey::Up, &ideas);
+        state.press(BrowseKey::Up, &ideas);
+        state.scroll(2);
+        assert_eq!(state.offset, 0);
+    }
+}
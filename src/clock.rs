@@ -0,0 +1,14 @@
+use chrono::{DateTime, Local};
+
+pub trait Clock {
+    fn now(&self) -> DateTime<Local>;
+}
+
+#[derive(Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+}
@@ -1,22 +1,156 @@
 use crate::dirs::home_dir;
+use crate::file_handler::write_atomic;
+use crate::sanitize::sanitize_filename;
+use crate::{
+    check_file_extension, expand_branch_template, expand_date_layout, expand_file_name_template,
+};
 
 use std::env::var;
-use std::io::{ErrorKind, Read, Write};
-use std::path::PathBuf;
-use std::{fs, io};
+use std::io::{ErrorKind, Read};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use std::{env, fs, io};
 
+use chrono::Local;
 use serde::{Deserialize, Serialize};
 
 const CONFIG_FILE_NAME: &str = "config.json";
+const CACHE_FILE_NAME: &str = "cache.json";
+const DEFAULT_APP_NAME: &str = "eureka";
+const PROJECT_CONFIG_FILE_NAME: &str = ".eureka";
 
 #[derive(Serialize, Deserialize, Default)]
 struct Config {
     repo: PathBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    branch: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    worktree: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    editor: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_file_bytes: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    date_layout: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    encrypt_to: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    auto_stash: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    open_at_end: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    audit_log: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    backend: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    github_repo: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    multiline_summary: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    file_extension: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    footer: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_blob_bytes: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_subject_len: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    line_ending: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    push_strategy: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    notify: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    debounce_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    warn_default_branch: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    file_name_template: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    capture_context: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fork_remote: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    upstream_remote: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ask_open_editor: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    background_push: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    categories: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    append_strategy: Option<String>,
+}
+
+// Project-local config read from a `.eureka` file, overriding the global config
+#[derive(Deserialize, Default)]
+struct ProjectConfig {
+    repo: Option<PathBuf>,
+    branch: Option<String>,
 }
 
 #[derive(Debug, Eq, PartialEq)]
 pub enum ConfigType {
     Repo,
+    // Branch to commit and push ideas to
+    Branch,
+    // Commit ideas from a dedicated worktree under the config dir
+    Worktree,
+    // Editor command to use when $EDITOR is unset
+    Editor,
+    // Archive the idea file once it would grow past this many bytes
+    MaxFileBytes,
+    // strftime-like pattern of the file to write ideas to, e.g. `%Y/%m/%d.md`
+    DateLayout,
+    // GPG recipient to encrypt idea files to, which turns encryption on
+    EncryptTo,
+    // Stash uncommitted changes in the idea repo while an idea is committed
+    AutoStash,
+    // Open the idea file at its last line when writing a new idea
+    OpenAtEnd,
+    // `true` or a path to log every captured idea to, outside of the idea repo
+    AuditLog,
+    // Where ideas are stored, `git` (the default), `github` for issues or
+    // `filesystem` for a plain folder without git
+    Backend,
+    // `owner/name` of the repo to create idea issues in
+    GithubRepo,
+    // What to do with a summary of several lines, `body` (the default) or `ask`
+    MultilineSummary,
+    // Extension of the idea files a date layout names, `md` by default
+    FileExtension,
+    // Added below every new idea, with `{date}` and `{summary}` filled in
+    Footer,
+    // Files bigger than this are only committed when tracked by Git LFS
+    MaxBlobBytes,
+    // Longer summaries are shortened in the commit subject, 0 turns it off
+    MaxSubjectLen,
+    // Line endings written to files, `auto` (the default) matches each file
+    LineEnding,
+    // What to do when origin rejects a push, `plain` (the default) gives up
+    PushStrategy,
+    // Show a desktop notification once an idea is pushed
+    Notify,
+    // Commit only once the idea file went unchanged this long after the editor closed
+    DebounceSecs,
+    // Warn after pushing to another branch than origin's default, unless `false`
+    WarnDefaultBranch,
+    // Name of a file per idea, with `{date}`, `{slug}` and `{ext}` filled in
+    FileNameTemplate,
+    // Record the directory and repo an idea was captured from as commit trailers
+    CaptureContext,
+    // Remote of your fork, ideas are pushed there and a pull request is opened
+    ForkRemote,
+    // Remote of the repo to open pull requests against, `origin` by default
+    UpstreamRemote,
+    // Ask after the summary whether to open the editor, instead of always opening it
+    AskOpenEditor,
+    // Push in a detached process after committing, so capturing returns right away
+    BackgroundPush,
+    // Comma separated folders of the idea repo to pick from for each idea
+    Categories,
+    // How new ideas are appended, `plain` or `keyed` with an id per idea
+    AppendStrategy,
 }
 
 pub trait ConfigManagement {
@@ -25,6 +159,39 @@ pub trait ConfigManagement {
     fn config_read(&self, config_type: ConfigType) -> io::Result<String>;
     fn config_write(&self, config_type: ConfigType, value: String) -> io::Result<()>;
     fn config_rm(&self) -> io::Result<()>;
+    fn config_dir_path(&self) -> io::Result<PathBuf>;
+    // None unless a cache was written with the config files as they are now
+    fn cache_read(&self) -> io::Result<Option<RepoCache>>;
+    fn cache_write(&self, cache: &RepoCache) -> io::Result<()>;
+}
+
+// What a run resolved about the idea repo, so the next runs can skip it
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq)]
+pub struct RepoCache {
+    // The idea repo `repo` resolved to
+    pub repo: String,
+    // URL of origin when `default_branch` was read from it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_branch: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    stamp: CacheStamp,
+    #[serde(flatten)]
+    cache: RepoCache,
+}
+
+// Everything the cached values depend on, a cache with another stamp is stale
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+struct CacheStamp {
+    // Modification times in nanoseconds since the epoch
+    config_modified: Option<u64>,
+    project_config: Option<PathBuf>,
+    project_config_modified: Option<u64>,
+    repo_env: Option<String>,
 }
 
 #[derive(Default)]
@@ -40,27 +207,111 @@ impl ConfigManagement for ConfigManager {
     }
 
     fn config_read(&self, config_type: ConfigType) -> io::Result<String> {
+        // Precedence is project-local `.eureka` > environment > global config
+        if let Some(value) = self.project_value(&config_type)? {
+            return Ok(value);
+        }
+        if let Some(value) = env_value(&config_type) {
+            return Ok(value);
+        }
+
         let config = self.config()?;
         let config_value = match config_type {
-            ConfigType::Repo => config.repo.display().to_string(),
+            ConfigType::Repo => Some(config.repo.display().to_string()),
+            ConfigType::Branch => config.branch,
+            ConfigType::Worktree => config.worktree.map(|worktree| worktree.to_string()),
+            ConfigType::Editor => config.editor,
+            ConfigType::MaxFileBytes => config.max_file_bytes.map(|bytes| bytes.to_string()),
+            ConfigType::DateLayout => config.date_layout,
+            ConfigType::EncryptTo => config.encrypt_to,
+            ConfigType::AutoStash => config.auto_stash.map(|auto_stash| auto_stash.to_string()),
+            ConfigType::OpenAtEnd => config
+                .open_at_end
+                .map(|open_at_end| open_at_end.to_string()),
+            ConfigType::AuditLog => config.audit_log,
+            ConfigType::Backend => config.backend,
+            ConfigType::GithubRepo => config.github_repo,
+            ConfigType::MultilineSummary => config.multiline_summary,
+            ConfigType::FileExtension => config.file_extension,
+            ConfigType::Footer => config.footer,
+            ConfigType::MaxBlobBytes => config.max_blob_bytes.map(|bytes| bytes.to_string()),
+            ConfigType::MaxSubjectLen => config.max_subject_len.map(|len| len.to_string()),
+            ConfigType::LineEnding => config.line_ending,
+            ConfigType::PushStrategy => config.push_strategy,
+            ConfigType::Notify => config.notify.map(|notify| notify.to_string()),
+            ConfigType::DebounceSecs => config.debounce_secs.map(|secs| secs.to_string()),
+            ConfigType::WarnDefaultBranch => {
+                config.warn_default_branch.map(|warn| warn.to_string())
+            }
+            ConfigType::FileNameTemplate => config.file_name_template,
+            ConfigType::CaptureContext => config
+                .capture_context
+                .map(|capture_context| capture_context.to_string()),
+            ConfigType::ForkRemote => config.fork_remote,
+            ConfigType::UpstreamRemote => config.upstream_remote,
+            ConfigType::AskOpenEditor => config.ask_open_editor.map(|ask| ask.to_string()),
+            ConfigType::BackgroundPush => config
+                .background_push
+                .map(|background| background.to_string()),
+            ConfigType::Categories => config.categories.map(|categories| categories.join(",")),
+            ConfigType::AppendStrategy => config.append_strategy,
         };
-        Ok(config_value)
+        config_value.ok_or_else(|| {
+            io::Error::new(
+                ErrorKind::NotFound,
+                format!("{:?} is not configured", config_type),
+            )
+        })
     }
 
     fn config_write(&self, config_type: ConfigType, value: String) -> io::Result<()> {
+        // A bad value would only fail once an idea is captured
+        validate_value(&config_type, &value)?;
         let config_path = self.config_path()?;
 
-        // Create file if it doesn't exist, otherwise get it
-        let mut file = fs::File::create(config_path)?;
-
-        let mut config = self.config()?;
+        // Read the existing config before the file is truncated
+        let mut config = if config_path.exists() {
+            self.config()?
+        } else {
+            Config::default()
+        };
         match config_type {
             ConfigType::Repo => config.repo = PathBuf::from(value),
+            ConfigType::Branch => config.branch = Some(value),
+            ConfigType::Worktree => config.worktree = Some(parse_bool(&value)?),
+            ConfigType::Editor => config.editor = Some(value),
+            ConfigType::MaxFileBytes => config.max_file_bytes = Some(parse_bytes(&value)?),
+            ConfigType::DateLayout => config.date_layout = Some(value),
+            ConfigType::EncryptTo => config.encrypt_to = Some(value),
+            ConfigType::AutoStash => config.auto_stash = Some(parse_bool(&value)?),
+            ConfigType::OpenAtEnd => config.open_at_end = Some(parse_bool(&value)?),
+            ConfigType::AuditLog => config.audit_log = Some(value),
+            ConfigType::Backend => config.backend = Some(value),
+            ConfigType::GithubRepo => config.github_repo = Some(value),
+            ConfigType::MultilineSummary => config.multiline_summary = Some(value),
+            ConfigType::FileExtension => config.file_extension = Some(value),
+            ConfigType::Footer => config.footer = Some(value),
+            ConfigType::MaxBlobBytes => config.max_blob_bytes = Some(parse_bytes(&value)?),
+            ConfigType::MaxSubjectLen => config.max_subject_len = Some(parse_len(&value)?),
+            ConfigType::LineEnding => config.line_ending = Some(value),
+            ConfigType::PushStrategy => config.push_strategy = Some(value),
+            ConfigType::Notify => config.notify = Some(parse_bool(&value)?),
+            ConfigType::DebounceSecs => config.debounce_secs = Some(parse_secs(&value)?),
+            ConfigType::WarnDefaultBranch => config.warn_default_branch = Some(parse_bool(&value)?),
+            ConfigType::FileNameTemplate => config.file_name_template = Some(value),
+            ConfigType::CaptureContext => config.capture_context = Some(parse_bool(&value)?),
+            ConfigType::ForkRemote => config.fork_remote = Some(value),
+            ConfigType::UpstreamRemote => config.upstream_remote = Some(value),
+            ConfigType::AskOpenEditor => config.ask_open_editor = Some(parse_bool(&value)?),
+            ConfigType::BackgroundPush => config.background_push = Some(parse_bool(&value)?),
+            ConfigType::Categories => config.categories = Some(parse_categories(&value)?),
+            ConfigType::AppendStrategy => config.append_strategy = Some(value),
         }
 
         let json = serde_json::to_string(&config)?;
 
-        file.write_all(json.as_bytes())
+        // An interrupted write keeps the previous config
+        write_atomic(&config_path, json.as_bytes())
     }
 
     fn config_rm(&self) -> io::Result<()> {
@@ -69,16 +320,13 @@ impl ConfigManagement for ConfigManager {
         fs::metadata(&config_path)?;
         fs::remove_file(&config_path)
     }
-}
-
-impl ConfigManager {
-    fn config_path(&self) -> io::Result<PathBuf> {
-        Ok(self.config_dir_path()?.join(CONFIG_FILE_NAME))
-    }
 
     fn config_dir_path(&self) -> io::Result<PathBuf> {
+        let app_name = app_name(var("EUREKA_APP_NAME").ok())?;
+
         self.resolve_xdg_config_home()
-            .or_else(|| Some(home_dir().unwrap().join(".config").join("eureka")))
+            .or_else(|| home_dir().map(|home| home.join(".config")))
+            .map(|config_home| config_home.join(app_name))
             .ok_or_else(|| {
                 io::Error::new(
                     ErrorKind::NotFound,
@@ -87,6 +335,43 @@ impl ConfigManager {
             })
     }
 
+    fn cache_read(&self) -> io::Result<Option<RepoCache>> {
+        let contents = match fs::read_to_string(self.config_dir_path()?.join(CACHE_FILE_NAME)) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        // A cache of another version is simply written again
+        let cache_file: CacheFile = match serde_json::from_str(&contents) {
+            Ok(cache_file) => cache_file,
+            Err(_) => return Ok(None),
+        };
+
+        Ok(if cache_file.stamp == self.cache_stamp()? {
+            Some(cache_file.cache)
+        } else {
+            None
+        })
+    }
+
+    fn cache_write(&self, cache: &RepoCache) -> io::Result<()> {
+        let cache_file = CacheFile {
+            stamp: self.cache_stamp()?,
+            cache: cache.clone(),
+        };
+        let json = serde_json::to_string(&cache_file)?;
+        write_atomic(
+            &self.config_dir_path()?.join(CACHE_FILE_NAME),
+            json.as_bytes(),
+        )
+    }
+}
+
+impl ConfigManager {
+    fn config_path(&self) -> io::Result<PathBuf> {
+        Ok(self.config_dir_path()?.join(CONFIG_FILE_NAME))
+    }
+
     fn config(&self) -> io::Result<Config> {
         let config_file = self.config_path()?;
         // Make sure file exists
@@ -103,20 +388,246 @@ impl ConfigManager {
         Ok(serde_json::from_str(&contents)?)
     }
 
+    fn cache_stamp(&self) -> io::Result<CacheStamp> {
+        let project_config = find_project_config(&env::current_dir()?);
+        Ok(CacheStamp {
+            config_modified: modified_nanos(&self.config_path()?),
+            project_config_modified: project_config.as_deref().and_then(modified_nanos),
+            project_config,
+            repo_env: env_value(&ConfigType::Repo),
+        })
+    }
+
+    fn project_value(&self, config_type: &ConfigType) -> io::Result<Option<String>> {
+        let project_config = match find_project_config(&env::current_dir()?) {
+            Some(path) => read_project_config(&path)?,
+            None => return Ok(None),
+        };
+
+        Ok(match config_type {
+            ConfigType::Repo => project_config.repo.map(|repo| repo.display().to_string()),
+            ConfigType::Branch => project_config.branch,
+            _ => None,
+        })
+    }
+
     fn resolve_xdg_config_home(&self) -> Option<PathBuf> {
         match var("XDG_CONFIG_HOME") {
-            Ok(path) => Some(PathBuf::from(path).join("eureka")),
+            Ok(path) => Some(PathBuf::from(path)),
             Err(_) => None,
         }
     }
 }
 
+// Name of the directory the config is stored in, inside the config home
+fn app_name(custom: Option<String>) -> io::Result<String> {
+    match custom {
+        None => Ok(DEFAULT_APP_NAME.to_string()),
+        Some(name) if name.is_empty() => Ok(DEFAULT_APP_NAME.to_string()),
+        Some(name) if name == "." || name == ".." || name.contains(['/', '\\']) => {
+            Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("$EUREKA_APP_NAME must be a directory name, got '{}'", name),
+            ))
+        }
+        Some(name) => Ok(name),
+    }
+}
+
+fn env_value(config_type: &ConfigType) -> Option<String> {
+    let env_var = match config_type {
+        ConfigType::Repo => "EUREKA_REPO",
+        ConfigType::Branch => "EUREKA_BRANCH",
+        _ => return None,
+    };
+    var(env_var).ok().filter(|value| !value.is_empty())
+}
+
+fn modified_nanos(path: &Path) -> Option<u64> {
+    let modified = fs::metadata(path).and_then(|meta| meta.modified()).ok()?;
+    let since_epoch = modified.duration_since(UNIX_EPOCH).ok()?;
+    u64::try_from(since_epoch.as_nanos()).ok()
+}
+
+// The closest `.eureka` file in `dir` or any of its parents
+fn find_project_config(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join(PROJECT_CONFIG_FILE_NAME))
+        .find(|path| path.is_file())
+}
+
+fn read_project_config(path: &Path) -> io::Result<ProjectConfig> {
+    let contents = fs::read_to_string(path)?;
+    let mut project_config: ProjectConfig = toml::from_str(&contents).map_err(|err| {
+        io::Error::new(
+            ErrorKind::InvalidData,
+            format!("Invalid {}: {}", path.display(), err),
+        )
+    })?;
+
+    // A relative repo path is relative to the directory of the `.eureka` file
+    if let (Some(repo), Some(dir)) = (&project_config.repo, path.parent()) {
+        project_config.repo = Some(dir.join(repo));
+    }
+
+    Ok(project_config)
+}
+
+// Check a value as thoroughly as capturing an idea would use it
+fn validate_value(config_type: &ConfigType, value: &str) -> io::Result<()> {
+    let invalid = |reason: String| Err(io::Error::new(ErrorKind::InvalidInput, reason));
+
+    match config_type {
+        // The date doesn't matter, only the placeholders and what they expand to
+        ConfigType::Branch => expand_branch_template(value, Local::now()).map(|_| ()),
+        ConfigType::DateLayout => expand_date_layout(value, Local::now(), "md").map(|_| ()),
+        ConfigType::FileExtension => check_file_extension(value),
+        // Slashes are only refused without a date layout, which isn't known here
+        ConfigType::FileNameTemplate => {
+            expand_file_name_template(value, Local::now(), "idea", "md", true).map(|_| ())
+        }
+        ConfigType::Editor => {
+            let program = value.split_whitespace().next().unwrap_or_default();
+            match which::which(program) {
+                Ok(_) => Ok(()),
+                Err(_) => invalid(format!("Editor '{}' was not found in your PATH", program)),
+            }
+        }
+        ConfigType::Backend => match value {
+            "git" | "github" | "filesystem" => Ok(()),
+            _ => invalid(format!(
+                "Unknown backend '{}', use git, github or filesystem",
+                value
+            )),
+        },
+        ConfigType::MultilineSummary => match value {
+            "body" | "ask" => Ok(()),
+            _ => invalid(format!(
+                "Unknown multiline_summary '{}', use body or ask",
+                value
+            )),
+        },
+        ConfigType::LineEnding => match value {
+            "auto" | "lf" | "crlf" => Ok(()),
+            _ => invalid(format!(
+                "Unknown line_ending '{}', use auto, lf or crlf",
+                value
+            )),
+        },
+        ConfigType::PushStrategy => match value {
+            "plain" | "pull-rebase" | "pull-merge" => Ok(()),
+            _ => invalid(format!(
+                "Unknown push_strategy '{}', use plain, pull-rebase or pull-merge",
+                value
+            )),
+        },
+        ConfigType::AppendStrategy => match value {
+            "plain" | "keyed" => Ok(()),
+            _ => invalid(format!(
+                "Unknown append_strategy '{}', use plain or keyed",
+                value
+            )),
+        },
+        ConfigType::GithubRepo => match value.split_once('/') {
+            Some((owner, name)) if !owner.is_empty() && !name.is_empty() && !name.contains('/') => {
+                Ok(())
+            }
+            _ => invalid(format!(
+                "Expected a GitHub repo as owner/name, got '{}'",
+                value
+            )),
+        },
+        ConfigType::ForkRemote | ConfigType::UpstreamRemote
+            if !git2::Remote::is_valid_name(value) =>
+        {
+            invalid(format!(
+                "Expected the name of a git remote, got '{}'",
+                value
+            ))
+        }
+        ConfigType::EncryptTo if value.trim().is_empty() => {
+            invalid(String::from("Expected a GPG key ID or email to encrypt to"))
+        }
+        ConfigType::Categories => parse_categories(value).map(|_| ()),
+        ConfigType::Footer if value.trim().is_empty() => {
+            invalid(String::from("Expected a footer, remove it to add none"))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn parse_bool(value: &str) -> io::Result<bool> {
+    value.parse().map_err(|_| {
+        io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("Expected true or false, got '{}'", value),
+        )
+    })
+}
+
+// Each category is a folder right below the repo, so a plain file name
+fn parse_categories(value: &str) -> io::Result<Vec<String>> {
+    let categories: Vec<String> = value
+        .split(',')
+        .map(str::trim)
+        .filter(|category| !category.is_empty())
+        .map(String::from)
+        .collect();
+    if categories.is_empty() {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            "Expected folder names separated by commas, e.g. work,personal",
+        ));
+    }
+    match categories
+        .iter()
+        .find(|category| sanitize_filename(category) != category.as_str())
+    {
+        Some(category) => Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("'{}' can't be a folder, use a plain name", category),
+        )),
+        None => Ok(categories),
+    }
+}
+
+fn parse_bytes(value: &str) -> io::Result<u64> {
+    value.parse().map_err(|_| {
+        io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("Expected a number of bytes, got '{}'", value),
+        )
+    })
+}
+
+fn parse_secs(value: &str) -> io::Result<u64> {
+    value.parse().map_err(|_| {
+        io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("Expected a number of seconds, got '{}'", value),
+        )
+    })
+}
+
+fn parse_len(value: &str) -> io::Result<usize> {
+    value.parse().map_err(|_| {
+        io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("Expected a number of characters, got '{}'", value),
+        )
+    })
+}
+
 #[allow(non_snake_case)]
 #[cfg(test)]
 mod tests {
-    use crate::config_manager::{ConfigManagement, ConfigManager, ConfigType};
+    use crate::config_manager::{
+        app_name, find_project_config, read_project_config, validate_value, ConfigManagement,
+        ConfigManager, ConfigType, RepoCache,
+    };
     use std::io::{Read, Write};
     use std::path::{Path, PathBuf};
+    use std::time::{Duration, UNIX_EPOCH};
     use std::{env, fs, io, path};
     use tempfile::TempDir;
 
@@ -124,7 +635,7 @@ mod tests {
 
     #[test]
     fn test_config_manager__config_dir_path() -> TestResult {
-        let cm = ConfigManager::default();
+        let cm = ConfigManager;
         let (_config_dir, tmp_dir) = set_config_dir()?;
 
         // XDG_CONFIG_HOME is set in Github Actions so let's unset it
@@ -144,7 +655,7 @@ mod tests {
     fn test_config_manager__config_dir_path__when__xdg_config_home_env_var_set() -> TestResult {
         use std::path::Path;
 
-        let cm = ConfigManager::default();
+        let cm = ConfigManager;
         env::set_var("XDG_CONFIG_HOME", "/specific-path/.config");
         assert_eq!(
             env::var("XDG_CONFIG_HOME"),
@@ -161,9 +672,87 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_config_manager__app_name__default() {
+        assert_eq!(app_name(None).unwrap(), "eureka");
+        assert_eq!(app_name(Some(String::new())).unwrap(), "eureka");
+    }
+
+    #[test]
+    fn test_config_manager__app_name__custom() {
+        let actual = app_name(Some(String::from("my-namespace-eureka"))).unwrap();
+
+        assert_eq!(actual, "my-namespace-eureka");
+    }
+
+    #[test]
+    fn test_config_manager__app_name__not_a_directory_name__failure() {
+        for name in ["..", ".", "some/path", "some\\path"] {
+            let actual = app_name(Some(String::from(name))).map_err(|e| e.kind());
+
+            assert_eq!(actual, Err(io::ErrorKind::InvalidInput));
+        }
+    }
+
+    #[test]
+    fn test_config_manager__find_project_config__in_parent_dir() -> TestResult {
+        let tmp_dir = TempDir::new()?;
+        let nested_dir = tmp_dir.path().join("some").join("project");
+        fs::create_dir_all(&nested_dir)?;
+        fs::write(tmp_dir.path().join(".eureka"), "")?;
+
+        let actual = find_project_config(&nested_dir);
+        let expected = Some(tmp_dir.path().join(".eureka"));
+
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_manager__find_project_config__closest_wins() -> TestResult {
+        let tmp_dir = TempDir::new()?;
+        let nested_dir = tmp_dir.path().join("project");
+        fs::create_dir_all(&nested_dir)?;
+        fs::write(tmp_dir.path().join(".eureka"), "")?;
+        fs::write(nested_dir.join(".eureka"), "")?;
+
+        let actual = find_project_config(&nested_dir);
+        let expected = Some(nested_dir.join(".eureka"));
+
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_manager__read_project_config__success() -> TestResult {
+        let tmp_dir = TempDir::new()?;
+        let path = tmp_dir.path().join(".eureka");
+        fs::write(&path, "repo = \"ideas\"\nbranch = \"project-ideas\"\n")?;
+
+        let actual = read_project_config(&path)?;
+
+        // A relative repo is resolved from the directory of the file
+        assert_eq!(actual.repo, Some(tmp_dir.path().join("ideas")));
+        assert_eq!(actual.branch, Some(String::from("project-ideas")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_manager__read_project_config__invalid_toml__failure() -> TestResult {
+        let tmp_dir = TempDir::new()?;
+        let path = tmp_dir.path().join(".eureka");
+        fs::write(&path, "repo = ")?;
+
+        let actual = read_project_config(&path).map_err(|e| e.kind()).err();
+        let expected = Some(io::ErrorKind::InvalidData);
+
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
     #[test]
     fn test_config_manager__config_dir_create() -> TestResult {
-        let cm = ConfigManager::default();
+        let cm = ConfigManager;
         let (_config_dir, _tmp_dir) = set_config_dir()?;
 
         let actual = cm.config_dir_create();
@@ -176,7 +765,7 @@ mod tests {
 
     #[test]
     fn test_config_manager__config_dir_exists__success() -> TestResult {
-        let cm = ConfigManager::default();
+        let cm = ConfigManager;
         let (_config_dir, _tmp_dir) = set_and_create_config_dir()?;
 
         let config_dir_exists = cm.config_dir_exists();
@@ -189,7 +778,7 @@ mod tests {
 
     #[test]
     fn test_config_manager__config_dir_exists__failure() -> TestResult {
-        let cm = ConfigManager::default();
+        let cm = ConfigManager;
         let (_config_dir, _tmp_dir) = set_config_dir()?;
 
         // XDG_CONFIG_HOME is set in Github Actions so let's unset it
@@ -206,7 +795,7 @@ mod tests {
 
     #[test]
     fn test_config_manager__config_read__success() -> TestResult {
-        let cm = ConfigManager::default();
+        let cm = ConfigManager;
         let (config_dir, _tmp_dir) = set_and_create_config_dir()?;
         let mut file =
             fs::File::create(path::Path::new(&config_dir.join("config.json").as_os_str()))?;
@@ -223,7 +812,7 @@ mod tests {
 
     #[test]
     fn test_config_manager__config_read__file_is_empty__default_config() -> TestResult {
-        let cm = ConfigManager::default();
+        let cm = ConfigManager;
         let (config_dir, _tmp_dir) = set_and_create_config_dir()?;
         // Create file but leave it empty
         let _file = fs::File::create(path::Path::new(&config_dir.join("config.json").as_os_str()))?;
@@ -239,7 +828,7 @@ mod tests {
 
     #[test]
     fn test_config_manager__config_read__when__file_does_not_exist__failure() -> TestResult {
-        let cm = ConfigManager::default();
+        let cm = ConfigManager;
         let (_config_dir, _tmp_dir) = set_and_create_config_dir()?;
 
         let actual = cm.config_read(ConfigType::Repo).map_err(|e| e.kind());
@@ -254,7 +843,7 @@ mod tests {
     #[test]
     fn test_config_manager__config_write__config_file_does_not_already_exist__success() -> TestResult
     {
-        let cm = ConfigManager::default();
+        let cm = ConfigManager;
         let (config_dir, _tmp_dir) = set_and_create_config_dir()?;
 
         let write_result = cm.config_write(ConfigType::Repo, String::from("this-specific-value"));
@@ -273,7 +862,7 @@ mod tests {
 
     #[test]
     fn test_config_manager__config_write__config_file_already_exists__success() -> TestResult {
-        let cm = ConfigManager::default();
+        let cm = ConfigManager;
         let (config_dir, _tmp_dir) = set_and_create_config_dir()?;
         // Create file but leave it empty
         let _file = fs::File::create(path::Path::new(&config_dir.join("config.json").as_os_str()))?;
@@ -295,9 +884,178 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_config_manager__config_write__keeps_existing_values__success() -> TestResult {
+        let cm = ConfigManager;
+        let (config_dir, _tmp_dir) = set_and_create_config_dir()?;
+
+        cm.config_write(ConfigType::Repo, String::from("this-specific-value"))?;
+        cm.config_write(ConfigType::Worktree, String::from("true"))?;
+
+        env::remove_var("HOME");
+
+        let contents = get_file_contents(&config_dir)?;
+        let expected = "{\"repo\":\"this-specific-value\",\"worktree\":true}";
+
+        assert_eq!(contents, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_manager__config_write__categories_are_a_list__success() -> TestResult {
+        let cm = ConfigManager;
+        let (config_dir, _tmp_dir) = set_and_create_config_dir()?;
+
+        cm.config_write(ConfigType::Repo, String::from("this-specific-value"))?;
+        cm.config_write(
+            ConfigType::Categories,
+            String::from(" work, personal,,tech "),
+        )?;
+        let actual = cm.config_read(ConfigType::Categories)?;
+
+        env::remove_var("HOME");
+
+        let contents = get_file_contents(&config_dir)?;
+        let expected =
+            "{\"repo\":\"this-specific-value\",\"categories\":[\"work\",\"personal\",\"tech\"]}";
+
+        assert_eq!(contents, expected);
+        assert_eq!(actual, "work,personal,tech");
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_manager__config_write__worktree_not_a_bool__failure() -> TestResult {
+        let cm = ConfigManager;
+        let (_config_dir, _tmp_dir) = set_and_create_config_dir()?;
+
+        let actual = cm
+            .config_write(ConfigType::Worktree, String::from("yes"))
+            .map_err(|e| e.kind());
+        let expected = Err(io::ErrorKind::InvalidInput);
+
+        env::remove_var("HOME");
+
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_manager__validate_value__success() {
+        let cases = [
+            (ConfigType::Branch, "ideas/%Y-w%W"),
+            (ConfigType::DateLayout, "%Y/%m/%d.md"),
+            (ConfigType::Editor, "sh -c"),
+            (ConfigType::Backend, "github"),
+            (ConfigType::Backend, "filesystem"),
+            (ConfigType::MultilineSummary, "ask"),
+            (ConfigType::GithubRepo, "owner/name"),
+            (ConfigType::FileExtension, "org"),
+            (ConfigType::FileNameTemplate, "{date}-{slug}.{ext}"),
+            (ConfigType::FileNameTemplate, "ideas/{slug}.md"),
+            (ConfigType::LineEnding, "crlf"),
+            (ConfigType::PushStrategy, "pull-rebase"),
+            (ConfigType::PushStrategy, "pull-merge"),
+            (ConfigType::Footer, "Captured via eureka on {date}"),
+            (ConfigType::ForkRemote, "fork"),
+            (ConfigType::UpstreamRemote, "upstream"),
+            (ConfigType::Categories, "work,personal,tech"),
+            (ConfigType::AppendStrategy, "keyed"),
+            (ConfigType::Repo, "any-path"),
+        ];
+
+        for (config_type, value) in cases {
+            let actual = validate_value(&config_type, value);
+
+            assert!(
+                actual.is_ok(),
+                "{:?} '{}': {:?}",
+                config_type,
+                value,
+                actual
+            );
+        }
+    }
+
+    #[test]
+    fn test_config_manager__validate_value__failure() {
+        let cases = [
+            (ConfigType::Branch, "ideas/%Q"),
+            (ConfigType::Branch, "ideas..%Y"),
+            (ConfigType::DateLayout, "../%Y.md"),
+            (ConfigType::DateLayout, "%Q.md"),
+            (ConfigType::Editor, "some-editor-that-does-not-exist --wait"),
+            (ConfigType::Backend, "gitlab"),
+            (ConfigType::MultilineSummary, "drop"),
+            (ConfigType::GithubRepo, "owner/name/extra"),
+            (ConfigType::EncryptTo, " "),
+            (ConfigType::FileExtension, ".org"),
+            (ConfigType::LineEnding, "CR"),
+            (ConfigType::PushStrategy, "rebase"),
+            (ConfigType::FileExtension, "tar.gz"),
+            (ConfigType::FileExtension, ""),
+            (ConfigType::FileNameTemplate, "../{slug}.md"),
+            (ConfigType::FileNameTemplate, "{title}.md"),
+            (ConfigType::FileNameTemplate, ""),
+            (ConfigType::Footer, "  "),
+            (ConfigType::ForkRemote, "my fork"),
+            (ConfigType::UpstreamRemote, ""),
+            (ConfigType::Categories, " , "),
+            (ConfigType::Categories, "work,../personal"),
+            (ConfigType::Categories, "work/notes"),
+            (ConfigType::AppendStrategy, "union"),
+        ];
+
+        for (config_type, value) in cases {
+            let actual = validate_value(&config_type, value).map_err(|e| e.kind());
+
+            assert_eq!(
+                actual,
+                Err(io::ErrorKind::InvalidInput),
+                "{:?} '{}'",
+                config_type,
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn test_config_manager__config_write__invalid_value_is_not_saved__failure() -> TestResult {
+        let cm = ConfigManager;
+        let (config_dir, _tmp_dir) = set_and_create_config_dir()?;
+
+        let actual = cm
+            .config_write(ConfigType::DateLayout, String::from("/etc/%Y.md"))
+            .map_err(|e| e.kind());
+        let expected = Err(io::ErrorKind::InvalidInput);
+
+        env::remove_var("HOME");
+
+        assert_eq!(actual, expected);
+        assert!(!config_dir.join("config.json").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_manager__config_read__worktree_unset__failure() -> TestResult {
+        let cm = ConfigManager;
+        let (config_dir, _tmp_dir) = set_and_create_config_dir()?;
+        let mut file =
+            fs::File::create(path::Path::new(&config_dir.join("config.json").as_os_str()))?;
+        file.write_all("{\"repo\": \"this-repo-path-value\"}".as_bytes())?;
+
+        let actual = cm.config_read(ConfigType::Worktree).map_err(|e| e.kind());
+        let expected = Err(io::ErrorKind::NotFound);
+
+        env::remove_var("HOME");
+
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
     #[test]
     fn test_config_manager__config_rm__success() -> TestResult {
-        let cm = ConfigManager::default();
+        let cm = ConfigManager;
         let (config_dir, _tmp_dir) = set_and_create_config_dir()?;
         // Create file but leave it empty
         let _file = fs::File::create(path::Path::new(&config_dir.join("config.json").as_os_str()))?;
@@ -312,7 +1070,7 @@ mod tests {
 
     #[test]
     fn test_config_manager__config_rm__file_does_not_exist__failure() -> TestResult {
-        let cm = ConfigManager::default();
+        let cm = ConfigManager;
         let (_config_dir, _tmp_dir) = set_and_create_config_dir()?;
 
         let actual = cm.config_rm().map_err(|e| e.kind());
@@ -324,6 +1082,61 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_config_manager__cache_read__written_cache() -> TestResult {
+        let cm = ConfigManager;
+        let (_config_dir, _tmp_dir) = set_and_create_config_dir()?;
+        cm.config_write(ConfigType::Repo, String::from("this-repo-path-value"))?;
+        let cache = RepoCache {
+            repo: String::from("this-repo-path-value"),
+            remote_url: Some(String::from("git@github.com:owner/ideas.git")),
+            default_branch: Some(String::from("main")),
+        };
+
+        cm.cache_write(&cache)?;
+        let actual = cm.cache_read()?;
+
+        env::remove_var("HOME");
+
+        assert_eq!(actual, Some(cache));
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_manager__cache_read__config_changed__stale() -> TestResult {
+        let cm = ConfigManager;
+        let (config_dir, _tmp_dir) = set_and_create_config_dir()?;
+        cm.config_write(ConfigType::Repo, String::from("this-repo-path-value"))?;
+        cm.cache_write(&RepoCache {
+            repo: String::from("this-repo-path-value"),
+            ..Default::default()
+        })?;
+
+        fs::File::options()
+            .write(true)
+            .open(config_dir.join("config.json"))?
+            .set_modified(UNIX_EPOCH + Duration::from_secs(1))?;
+        let actual = cm.cache_read()?;
+
+        env::remove_var("HOME");
+
+        assert_eq!(actual, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_manager__cache_read__no_cache() -> TestResult {
+        let cm = ConfigManager;
+        let (_config_dir, _tmp_dir) = set_and_create_config_dir()?;
+
+        let actual = cm.cache_read()?;
+
+        env::remove_var("HOME");
+
+        assert_eq!(actual, None);
+        Ok(())
+    }
+
     fn set_config_dir() -> io::Result<(PathBuf, TempDir)> {
         let tmp_dir = TempDir::new()?;
         // Create the config dir. When tmp_dir is destroyed it will be deleted
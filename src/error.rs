@@ -0,0 +1,117 @@
+use std::{error, fmt, io};
+
+// Every way a run of eureka can fail, each with its own exit code
+#[derive(Debug)]
+pub enum EurekaError {
+    // The config is missing, unreadable or could not be written
+    Config(io::Error),
+
+    // A git operation failed, e.g. opening the repo, committing or pushing
+    Git(git2::Error),
+
+    // The user input could not be read or is not valid
+    Input(io::Error),
+
+    // Any other I/O failure, e.g. the editor or pager could not be opened
+    Io(io::Error),
+}
+
+impl EurekaError {
+    // Scripts rely on these, see "Exit codes" in the README
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            EurekaError::Io(_) => 1,
+            EurekaError::Config(_) => 2,
+            EurekaError::Git(_) => 3,
+            EurekaError::Input(_) => 4,
+        }
+    }
+}
+
+impl fmt::Display for EurekaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EurekaError::Config(err) => write!(f, "Config error: {}", err),
+            EurekaError::Git(err) => write!(f, "Git error: {}", err.message()),
+            EurekaError::Input(err) => write!(f, "Input error: {}", err),
+            EurekaError::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl error::Error for EurekaError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            EurekaError::Config(err) | EurekaError::Input(err) | EurekaError::Io(err) => Some(err),
+            EurekaError::Git(err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for EurekaError {
+    fn from(err: io::Error) -> Self {
+        EurekaError::Io(err)
+    }
+}
+
+impl From<git2::Error> for EurekaError {
+    fn from(err: git2::Error) -> Self {
+        EurekaError::Git(err)
+    }
+}
+
+#[allow(non_snake_case)]
+#[cfg(test)]
+mod tests {
+    use crate::error::EurekaError;
+    use std::io;
+
+    #[test]
+    fn test_error__exit_code__io() {
+        let err = EurekaError::Io(io::Error::other("some-error"));
+
+        assert_eq!(err.exit_code(), 1);
+    }
+
+    #[test]
+    fn test_error__exit_code__config() {
+        let err = EurekaError::Config(io::Error::from(io::ErrorKind::NotFound));
+
+        assert_eq!(err.exit_code(), 2);
+    }
+
+    #[test]
+    fn test_error__exit_code__git() {
+        let err = EurekaError::Git(git2::Error::from_str("some-error"));
+
+        assert_eq!(err.exit_code(), 3);
+    }
+
+    #[test]
+    fn test_error__exit_code__input() {
+        let err = EurekaError::Input(io::Error::from(io::ErrorKind::InvalidInput));
+
+        assert_eq!(err.exit_code(), 4);
+    }
+
+    #[test]
+    fn test_error__from__io_error_is_io() {
+        let err = EurekaError::from(io::Error::other("some-error"));
+
+        assert!(matches!(err, EurekaError::Io(_)));
+    }
+
+    #[test]
+    fn test_error__from__git_error_is_git() {
+        let err = EurekaError::from(git2::Error::from_str("some-error"));
+
+        assert!(matches!(err, EurekaError::Git(_)));
+    }
+
+    #[test]
+    fn test_error__display() {
+        let err = EurekaError::Git(git2::Error::from_str("some-error"));
+
+        assert_eq!(err.to_string(), "Git error: some-error");
+    }
+}
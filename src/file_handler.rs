@@ -0,0 +1,1826 @@
+use std::fs;
+use std::io;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::keyed::id_header;
+
+pub trait FileManagement {
+    fn append_idea(&self, file_path: &str, summary: &str, body: &str) -> io::Result<()>;
+    fn append_keyed_idea(
+        &self,
+        file_path: &str,
+        id: &str,
+        summary: &str,
+        body: &str,
+    ) -> io::Result<()>;
+    fn append_bullet(&self, file_path: &str, text: &str) -> io::Result<()>;
+    fn append_footer(&self, file_path: &str, footer: &str) -> io::Result<bool>;
+    fn find_heading_offset(&self, file_path: &str, title: &str) -> io::Result<Vec<Heading>>;
+    fn count_headings(&self, file_path: &str) -> io::Result<usize>;
+    fn idea_headings(&self, file_path: &str) -> io::Result<Vec<Heading>>;
+    fn ensure_gitignore_entry(&self, gitignore_path: &str, entry: &str) -> io::Result<bool>;
+    fn prepare_commit_body(&self, file_path: &str) -> io::Result<()>;
+    fn read_commit_body(&self, file_path: &str) -> io::Result<String>;
+    fn prepare_issue_body(&self, file_path: &str) -> io::Result<()>;
+    fn read_issue_body(&self, file_path: &str) -> io::Result<String>;
+    fn rotate_idea_file(
+        &self,
+        file_path: &str,
+        max_bytes: u64,
+        incoming_bytes: u64,
+        date: &str,
+    ) -> io::Result<Option<PathBuf>>;
+    fn ensure_dir(&self, dir_path: &str) -> io::Result<()>;
+    fn read_front_matter(&self, file_path: &str) -> io::Result<Vec<String>>;
+    fn write_front_matter(&self, file_path: &str, now: &str) -> io::Result<()>;
+    fn idea_files(&self, dir_path: &str, extension: &str) -> io::Result<Vec<PathBuf>>;
+    fn find_git_repos(&self, dir_path: &str, max_depth: usize) -> io::Result<Vec<PathBuf>>;
+    fn looks_like_project(&self, dir_path: &str) -> io::Result<bool>;
+    fn has_marker(&self, file_path: &str) -> bool;
+    fn exists(&self, file_path: &str) -> bool;
+    fn create_marker(&self, file_path: &str) -> io::Result<()>;
+    fn line_count(&self, file_path: &str) -> io::Result<usize>;
+    fn append_log_entry(&self, file_path: &str, entry: &str) -> io::Result<()>;
+    fn queue_idea(&self, file_path: &str, text: &str) -> io::Result<()>;
+    fn read_queue(&self, file_path: &str) -> io::Result<Vec<String>>;
+    fn write_queue(&self, file_path: &str, entries: &[String]) -> io::Result<()>;
+    fn wait_until_settled(&self, file_path: &str, quiet: Duration) -> io::Result<()>;
+    fn archive_ideas(
+        &self,
+        file_path: &str,
+        archive_path: &str,
+        headings: &[Heading],
+    ) -> io::Result<()>;
+    fn try_lock(&self, file_path: &str) -> io::Result<bool>;
+    fn unlock(&self, file_path: &str) -> io::Result<()>;
+}
+
+// Ideas are stored as headings of this level, e.g. `## My idea`
+const IDEA_HEADING_LEVEL: usize = 2;
+
+// Files only a software project has next to its README
+const PROJECT_MANIFESTS: [&str; 10] = [
+    "Cargo.toml",
+    "package.json",
+    "go.mod",
+    "pyproject.toml",
+    "setup.py",
+    "pom.xml",
+    "build.gradle",
+    "CMakeLists.txt",
+    "Makefile",
+    "Gemfile",
+];
+
+// More files than this next to the README that aren't ideas make a project
+const MAX_NON_IDEA_FILES: usize = 5;
+
+const MARKER_CONTENTS: &str = "Ideas are captured here with eureka\n";
+
+const COMMIT_BODY_TEMPLATE: &str = "
+# Write the commit body of your idea above. Lines starting with '#' are
+# ignored, and an empty body commits with the summary only.
+";
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Heading {
+    // Heading text without the leading `#`s
+    pub title: String,
+
+    // Number of leading `#`s
+    pub level: usize,
+
+    // Line number (starting at 1) of the heading
+    pub line: usize,
+
+    // Line number (starting at 1) of the last non-empty line of the heading's section
+    pub section_end: usize,
+}
+
+// Line endings of what's written to a file
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum LineEnding {
+    // Whatever the file's first line ends with, `\n` for a new file
+    #[default]
+    Auto,
+    Lf,
+    Crlf,
+}
+
+impl FromStr for LineEnding {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "auto" => Ok(LineEnding::Auto),
+            "lf" => Ok(LineEnding::Lf),
+            "crlf" => Ok(LineEnding::Crlf),
+            _ => Err(()),
+        }
+    }
+}
+
+impl LineEnding {
+    // `text` with the line endings to add it to `existing` with
+    fn apply(self, existing: &str, text: &str) -> String {
+        let crlf = match self {
+            LineEnding::Auto => existing
+                .split_once('\n')
+                .is_some_and(|(first_line, _)| first_line.ends_with('\r')),
+            LineEnding::Lf => false,
+            LineEnding::Crlf => true,
+        };
+
+        let text = text.replace("\r\n", "\n");
+        if crlf {
+            text.replace('\n', "\r\n")
+        } else {
+            text
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct FileHandler {
+    line_ending: LineEnding,
+}
+
+impl FileHandler {
+    pub fn new(line_ending: LineEnding) -> Self {
+        FileHandler { line_ending }
+    }
+
+    // Append `text` to the file, with its line endings
+    fn append_lines(&self, file_path: &str, text: &str) -> io::Result<()> {
+        let existing = read_existing(file_path)?;
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(file_path)?;
+
+        file.write_all(self.line_ending.apply(&existing, text).as_bytes())
+    }
+}
+
+impl FileManagement for FileHandler {
+    fn append_idea(&self, file_path: &str, summary: &str, body: &str) -> io::Result<()> {
+        append_atomic(file_path, |existing| {
+            let idea = format!("{}{}", separator(existing), format_idea(summary, body));
+            self.line_ending.apply(existing, &idea)
+        })
+    }
+
+    // The idea with an id header above it, see `keyed`
+    fn append_keyed_idea(
+        &self,
+        file_path: &str,
+        id: &str,
+        summary: &str,
+        body: &str,
+    ) -> io::Result<()> {
+        append_atomic(file_path, |existing| {
+            let idea = format!(
+                "{}{}\n{}",
+                separator(existing),
+                id_header(id),
+                format_idea(summary, body)
+            );
+            self.line_ending.apply(existing, &idea)
+        })
+    }
+
+    fn append_bullet(&self, file_path: &str, text: &str) -> io::Result<()> {
+        append_atomic(file_path, |existing| {
+            let bullet = format!("{}- {}\n", bullet_separator(existing), text);
+            self.line_ending.apply(existing, &bullet)
+        })
+    }
+
+    // Returns false if the file already ends with the footer, e.g. after a re-edit
+    fn append_footer(&self, file_path: &str, footer: &str) -> io::Result<bool> {
+        let footer = footer.trim();
+        let existing = read_existing(file_path)?;
+        if existing.trim_end().ends_with(footer) {
+            return Ok(false);
+        }
+
+        append_atomic(file_path, |existing| {
+            let footer = format!("{}{}\n", separator(existing), footer);
+            self.line_ending.apply(existing, &footer)
+        })?;
+        Ok(true)
+    }
+
+    fn find_heading_offset(&self, file_path: &str, title: &str) -> io::Result<Vec<Heading>> {
+        let contents = read_lossy(file_path)?;
+        let title = title.to_lowercase();
+
+        Ok(headings(&contents)
+            .into_iter()
+            .filter(|heading| heading.title.to_lowercase().contains(&title))
+            .collect())
+    }
+
+    fn count_headings(&self, file_path: &str) -> io::Result<usize> {
+        self.idea_headings(file_path).map(|headings| headings.len())
+    }
+
+    fn idea_headings(&self, file_path: &str) -> io::Result<Vec<Heading>> {
+        let contents = match read_lossy(file_path) {
+            Ok(contents) => contents,
+            // No ideas have been stored yet
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(err),
+        };
+
+        Ok(headings(&contents)
+            .into_iter()
+            .filter(|heading| heading.level == IDEA_HEADING_LEVEL)
+            .collect())
+    }
+
+    fn ensure_gitignore_entry(&self, gitignore_path: &str, entry: &str) -> io::Result<bool> {
+        let existing = read_existing(gitignore_path)?;
+
+        let anchored = format!("/{}", entry);
+        if existing
+            .lines()
+            .map(str::trim)
+            .any(|line| line == entry || line == anchored)
+        {
+            return Ok(false);
+        }
+
+        let newline = if existing.is_empty() || existing.ends_with('\n') {
+            ""
+        } else {
+            "\n"
+        };
+        self.append_lines(gitignore_path, &format!("{}{}\n", newline, entry))?;
+
+        Ok(true)
+    }
+
+    fn prepare_commit_body(&self, file_path: &str) -> io::Result<()> {
+        // The buffer is written from scratch, there are no endings to match
+        fs::write(file_path, self.line_ending.apply("", COMMIT_BODY_TEMPLATE))
+    }
+
+    fn read_commit_body(&self, file_path: &str) -> io::Result<String> {
+        let contents = read_lossy(file_path)?;
+        // The buffer is only needed until the body has been read
+        fs::remove_file(file_path)?;
+
+        let body: Vec<&str> = contents
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .collect();
+        Ok(body.join("\n").trim().to_string())
+    }
+
+    fn prepare_issue_body(&self, file_path: &str) -> io::Result<()> {
+        // Unlike a commit body, markdown headings are welcome so there's no template
+        fs::File::create(file_path).map(|_| ())
+    }
+
+    fn read_issue_body(&self, file_path: &str) -> io::Result<String> {
+        let contents = read_lossy(file_path)?;
+        fs::remove_file(file_path)?;
+        Ok(contents.trim().to_string())
+    }
+
+    fn rotate_idea_file(
+        &self,
+        file_path: &str,
+        max_bytes: u64,
+        incoming_bytes: u64,
+        date: &str,
+    ) -> io::Result<Option<PathBuf>> {
+        let size = match fs::metadata(file_path) {
+            Ok(metadata) => metadata.len(),
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        // An empty file can't get any smaller by rotating it
+        if size == 0 || size + incoming_bytes <= max_bytes {
+            return Ok(None);
+        }
+
+        let archive = archive_path(Path::new(file_path), date);
+        fs::rename(file_path, &archive)?;
+        fs::File::create(file_path)?;
+
+        Ok(Some(archive))
+    }
+
+    fn ensure_dir(&self, dir_path: &str) -> io::Result<()> {
+        fs::create_dir_all(dir_path)
+    }
+
+    fn read_front_matter(&self, file_path: &str) -> io::Result<Vec<String>> {
+        let contents = read_lossy(file_path)?;
+        Ok(front_matter_tags(&contents))
+    }
+
+    // Set `updated:` to `now`, keeping `created:`
+    fn write_front_matter(&self, file_path: &str, now: &str) -> io::Result<()> {
+        let existing = read_lossy(file_path)?;
+        let contents = self
+            .line_ending
+            .apply(&existing, &stamp_front_matter(&existing, now));
+        write_atomic(Path::new(file_path), contents.as_bytes())
+    }
+
+    fn idea_files(&self, dir_path: &str, extension: &str) -> io::Result<Vec<PathBuf>> {
+        let mut files = vec![];
+        let mut dirs = vec![PathBuf::from(dir_path)];
+
+        while let Some(dir) = dirs.pop() {
+            for entry in fs::read_dir(&dir)? {
+                let path = entry?.path();
+                let hidden = path
+                    .file_name()
+                    .is_none_or(|name| name.to_string_lossy().starts_with('.'));
+                if hidden {
+                    // Skips .git among others
+                    continue;
+                }
+
+                if path.is_dir() {
+                    dirs.push(path);
+                // README.md is an idea file whatever the extension of the others
+                } else if path
+                    .extension()
+                    .is_some_and(|ext| ext == "md" || ext == extension)
+                {
+                    files.push(path);
+                }
+            }
+        }
+
+        files.sort();
+        Ok(files)
+    }
+
+    fn append_log_entry(&self, file_path: &str, entry: &str) -> io::Result<()> {
+        self.append_lines(file_path, &format!("{}\n", entry))
+    }
+
+    fn queue_idea(&self, file_path: &str, text: &str) -> io::Result<()> {
+        // One idea per line
+        let text: Vec<&str> = text.split_whitespace().collect();
+        self.append_lines(file_path, &format!("{}\n", text.join(" ")))
+    }
+
+    fn read_queue(&self, file_path: &str) -> io::Result<Vec<String>> {
+        match read_lossy(file_path) {
+            Ok(contents) => Ok(contents
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(String::from)
+                .collect()),
+            // Nothing has been queued yet
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(vec![]),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn write_queue(&self, file_path: &str, entries: &[String]) -> io::Result<()> {
+        let contents: String = entries.iter().map(|entry| format!("{}\n", entry)).collect();
+        let contents = self
+            .line_ending
+            .apply(&read_existing(file_path)?, &contents);
+        write_atomic(Path::new(file_path), contents.as_bytes())
+    }
+
+    // Move the sections of `headings` to the end of `archive_path`. The archive
+    // is written first, so a failure never loses an idea.
+    fn archive_ideas(
+        &self,
+        file_path: &str,
+        archive_path: &str,
+        headings: &[Heading],
+    ) -> io::Result<()> {
+        let contents = read_lossy(file_path)?;
+        let lines: Vec<&str> = contents.split_inclusive('\n').collect();
+        let in_section = |number: usize| {
+            headings
+                .iter()
+                .any(|heading| (heading.line..=heading.section_end).contains(&number))
+        };
+
+        let sections: Vec<String> = headings
+            .iter()
+            .map(|heading| {
+                let section = lines[heading.line - 1..heading.section_end].concat();
+                format!("{}\n", section.trim_end())
+            })
+            .collect();
+        append_atomic(archive_path, |existing| {
+            let text = format!("{}{}", separator(existing), sections.join("\n"));
+            self.line_ending.apply(existing, &text)
+        })?;
+
+        let mut kept: Vec<&str> = vec![];
+        let mut after_section = false;
+        for (index, line) in lines.iter().enumerate() {
+            if in_section(index + 1) {
+                after_section = true;
+            } else if !(after_section && line.trim().is_empty()) {
+                after_section = false;
+                kept.push(line);
+            }
+        }
+        while kept.last().is_some_and(|line| line.trim().is_empty()) {
+            kept.pop();
+        }
+        write_atomic(Path::new(file_path), kept.concat().as_bytes())
+    }
+
+    // Take the lock by creating `file_path`, false if another run holds it
+    fn try_lock(&self, file_path: &str) -> io::Result<bool> {
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(file_path)
+        {
+            Ok(_) => Ok(true),
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    // A lock that's already gone is released too
+    fn unlock(&self, file_path: &str) -> io::Result<()> {
+        match fs::remove_file(file_path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    // Block until `file_path` went unchanged for `quiet`, for editors that autosave
+    fn wait_until_settled(&self, file_path: &str, quiet: Duration) -> io::Result<()> {
+        wait_until_settled(Path::new(file_path), quiet)
+    }
+
+    // Git repos in or below `dir_path`, at most `max_depth` directories down.
+    // Hidden directories and the inside of a found repo aren't searched
+    fn find_git_repos(&self, dir_path: &str, max_depth: usize) -> io::Result<Vec<PathBuf>> {
+        let mut repos = vec![];
+        let mut pending = vec![(PathBuf::from(dir_path), 0)];
+
+        while let Some((dir, depth)) = pending.pop() {
+            // `.git` is a file in worktrees and submodules
+            if dir.join(".git").exists() {
+                repos.push(dir);
+                continue;
+            }
+            if depth == max_depth {
+                continue;
+            }
+
+            let entries = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(err) if depth == 0 => return Err(err),
+                // Skip what we may not read, e.g. other users' directories
+                Err(_) => continue,
+            };
+            for entry in entries.flatten() {
+                let is_hidden = entry.file_name().to_string_lossy().starts_with('.');
+                // Symlinks aren't followed, they could loop
+                let is_dir = entry.file_type().is_ok_and(|file_type| file_type.is_dir());
+                if is_dir && !is_hidden {
+                    pending.push((entry.path(), depth + 1));
+                }
+            }
+        }
+
+        repos.sort();
+        Ok(repos)
+    }
+
+    fn looks_like_project(&self, dir_path: &str) -> io::Result<bool> {
+        let readme = match read_lossy(&format!("{}/README.md", dir_path)) {
+            Ok(readme) => readme,
+            // A new idea repo has no README yet
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(false),
+            Err(err) => return Err(err),
+        };
+        // Ideas are level 2 headings, a title above them that isn't about
+        // ideas names a project
+        let has_project_title = readme
+            .lines()
+            .find_map(|line| line.strip_prefix("# "))
+            .is_some_and(|title| !title.to_lowercase().contains("idea"));
+
+        let mut non_idea_files = 0;
+        for entry in fs::read_dir(dir_path)?.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if PROJECT_MANIFESTS.contains(&name.as_str()) {
+                return Ok(true);
+            }
+            let is_idea_file = Path::new(&name)
+                .extension()
+                .is_some_and(|extension| extension == "md");
+            // Folders of a date layout, e.g. `2024`
+            let is_date_dir = name.chars().all(|c| c.is_ascii_digit());
+            if !name.starts_with('.') && !is_idea_file && !is_date_dir {
+                non_idea_files += 1;
+            }
+        }
+
+        Ok(has_project_title || non_idea_files > MAX_NON_IDEA_FILES)
+    }
+
+    fn has_marker(&self, file_path: &str) -> bool {
+        Path::new(file_path).exists()
+    }
+
+    fn exists(&self, file_path: &str) -> bool {
+        Path::new(file_path).exists()
+    }
+
+    fn create_marker(&self, file_path: &str) -> io::Result<()> {
+        fs::write(file_path, MARKER_CONTENTS)
+    }
+
+    fn line_count(&self, file_path: &str) -> io::Result<usize> {
+        match read_lossy(file_path) {
+            Ok(contents) => Ok(contents.lines().count()),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(0),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+// The folder is watched rather than the file, as editors saving atomically
+// replace the file with a new one
+#[cfg(feature = "watch")]
+fn wait_until_settled(file_path: &Path, quiet: Duration) -> io::Result<()> {
+    use file_watcher::{RecursiveMode, Watcher};
+    use std::sync::mpsc::{channel, RecvTimeoutError};
+    use std::time::Instant;
+
+    let dir = match file_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let (sender, receiver) = channel();
+    let mut watcher = file_watcher::recommended_watcher(sender).map_err(io::Error::other)?;
+    watcher
+        .watch(dir, RecursiveMode::NonRecursive)
+        .map_err(io::Error::other)?;
+
+    let mut deadline = Instant::now() + quiet;
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        match receiver.recv_timeout(left) {
+            Ok(Ok(event)) => {
+                let changed = event
+                    .paths
+                    .iter()
+                    .any(|path| path.file_name() == file_path.file_name());
+                if changed && !event.kind.is_access() {
+                    debug!("{} changed, waiting {:?} again", file_path.display(), quiet);
+                    deadline = Instant::now() + quiet;
+                }
+            }
+            Ok(Err(err)) => return Err(io::Error::other(err)),
+            Err(RecvTimeoutError::Timeout) => return Ok(()),
+            Err(RecvTimeoutError::Disconnected) => return Ok(()),
+        }
+    }
+}
+
+#[cfg(not(feature = "watch"))]
+fn wait_until_settled(_file_path: &Path, _quiet: Duration) -> io::Result<()> {
+    Err(io::Error::new(
+        ErrorKind::Unsupported,
+        "eureka was built without file watching, rebuild it with --features watch",
+    ))
+}
+
+// `README.md` is archived as `README.archive.<date>.md`, with a counter added
+// if it's rotated more than once a day
+fn archive_path(file_path: &Path, date: &str) -> PathBuf {
+    let stem = file_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = file_path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    (1..)
+        .map(|n| match n {
+            1 => format!("{}.archive.{}.{}", stem, date, extension),
+            n => format!("{}.archive.{}.{}.{}", stem, date, n, extension),
+        })
+        .map(|name| file_path.with_file_name(name))
+        .find(|path| !path.exists())
+        .unwrap()
+}
+
+// Writes to a temporary file next to `path` and renames it over `path`, so
+// `path` is never left half written if eureka is interrupted
+pub(crate) fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    write_atomic_with(path, contents, |from, to| fs::rename(from, to))
+}
+
+fn write_atomic_with<F>(path: &Path, contents: &[u8], rename: F) -> io::Result<()>
+where
+    F: FnOnce(&Path, &Path) -> io::Result<()>,
+{
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "Path has no file name"))?;
+    // Hidden, and in the same directory since a rename can't cross file systems
+    let tmp_path = path.with_file_name(format!(".{}.tmp", file_name.to_string_lossy()));
+
+    let written = (|| {
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        if let Ok(metadata) = fs::metadata(path) {
+            fs::set_permissions(&tmp_path, metadata.permissions())?;
+        }
+        rename(&tmp_path, path)
+    })();
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    written
+}
+
+// Adds what `text` returns for the existing contents to the end of the file,
+// which is created if needed. The existing bytes are kept as they are
+fn append_atomic<F>(file_path: &str, text: F) -> io::Result<()>
+where
+    F: FnOnce(&str) -> String,
+{
+    let mut contents = match fs::read(file_path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == ErrorKind::NotFound => vec![],
+        Err(err) => return Err(err),
+    };
+    let addition = text(&String::from_utf8_lossy(&contents));
+    contents.extend_from_slice(addition.as_bytes());
+
+    write_atomic(Path::new(file_path), &contents)
+}
+
+// The contents of a file that may not exist yet
+fn read_existing(file_path: &str) -> io::Result<String> {
+    match read_lossy(file_path) {
+        Ok(existing) => Ok(existing),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(String::new()),
+        Err(err) => Err(err),
+    }
+}
+
+// Invalid UTF-8 in the idea file is replaced instead of failing the read
+fn read_lossy(file_path: &str) -> io::Result<String> {
+    fs::read(file_path).map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn headings(contents: &str) -> Vec<Heading> {
+    let lines: Vec<&str> = contents.lines().collect();
+    let mut in_code_block = false;
+    // (index, level, title) of every markdown heading
+    let mut found: Vec<(usize, usize, &str)> = vec![];
+
+    for (index, line) in lines.iter().enumerate() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        let text = line.trim_start_matches('#');
+        let level = line.len() - text.len();
+        if level > 0 && text.starts_with(' ') {
+            found.push((index, level, text.trim()));
+        }
+    }
+
+    found
+        .iter()
+        .enumerate()
+        .map(|(i, (index, level, title))| {
+            // The section runs until the next heading of the same or a higher level
+            let next = found[i + 1..]
+                .iter()
+                .find(|(_, next_level, _)| next_level <= level)
+                .map(|(next_index, _, _)| *next_index)
+                .unwrap_or(lines.len());
+            let section_end = (*index..next)
+                .rev()
+                .find(|j| !lines[*j].trim().is_empty())
+                .unwrap_or(*index);
+
+            Heading {
+                title: title.to_string(),
+                level: *level,
+                line: index + 1,
+                section_end: section_end + 1,
+            }
+        })
+        .collect()
+}
+
+// Tags from a leading `---` block, either as `tags: [a, b]`, `tags: a, b` or
+// one `- tag` per line below `tags:`
+fn front_matter_tags(contents: &str) -> Vec<String> {
+    let mut lines = contents.lines();
+    if lines.next().map(str::trim_end) != Some("---") {
+        return vec![];
+    }
+
+    let mut tags = vec![];
+    let mut in_tags = false;
+    for line in lines.take_while(|line| line.trim_end() != "---") {
+        if let Some(value) = line.strip_prefix("tags:") {
+            in_tags = true;
+            let value = value.trim().trim_start_matches('[').trim_end_matches(']');
+            tags.extend(value.split(',').map(unquote).filter(|tag| !tag.is_empty()));
+        } else if in_tags && line.trim_start().starts_with("- ") {
+            tags.push(unquote(&line.trim_start()[2..]));
+        } else {
+            in_tags = false;
+        }
+    }
+
+    tags
+}
+
+// `contents` with `updated:` in its front matter set to `now`, and `created:`
+// too if it's missing. Without front matter one is added with both.
+fn stamp_front_matter(contents: &str, now: &str) -> String {
+    let contents = contents.replace("\r\n", "\n");
+    let mut lines = contents.split_inclusive('\n');
+    let mut fields = vec![];
+    let mut closed = false;
+    if lines.next().map(str::trim_end) == Some("---") {
+        for line in lines.by_ref() {
+            if line.trim_end() == "---" {
+                closed = true;
+                break;
+            }
+            fields.push(line.trim_end());
+        }
+    }
+    if !closed {
+        return format!(
+            "---\ncreated: {now}\nupdated: {now}\n---\n\n{}",
+            contents,
+            now = now
+        );
+    }
+    let rest: String = lines.collect();
+
+    let updated = format!("updated: {}", now);
+    let mut front_matter: Vec<String> = vec![];
+    if !fields.iter().any(|field| field.starts_with("created:")) {
+        front_matter.push(format!("created: {}", now));
+    }
+    let mut has_updated = false;
+    for field in fields {
+        if field.starts_with("updated:") {
+            has_updated = true;
+            front_matter.push(updated.clone());
+        } else {
+            front_matter.push(field.to_string());
+        }
+    }
+    if !has_updated {
+        front_matter.push(updated);
+    }
+
+    format!("---\n{}\n---\n{}", front_matter.join("\n"), rest)
+}
+
+fn unquote(value: &str) -> String {
+    value.trim().trim_matches(['"', '\'']).to_string()
+}
+
+// Keep one blank line between the existing content and the new idea
+fn separator(existing: &str) -> &'static str {
+    if existing.is_empty() || existing.ends_with("\n\n") {
+        ""
+    } else if existing.ends_with('\n') {
+        "\n"
+    } else {
+        "\n\n"
+    }
+}
+
+// Bullets are added to a list directly following the last one, other content
+// is separated by an empty line
+fn bullet_separator(existing: &str) -> &'static str {
+    let follows_bullet = existing
+        .lines()
+        .last()
+        .is_some_and(|line| line.starts_with("- "));
+
+    if !follows_bullet {
+        separator(existing)
+    } else if existing.ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    }
+}
+
+fn format_idea(summary: &str, body: &str) -> String {
+    if body.is_empty() {
+        format!("## {}\n", summary)
+    } else {
+        format!("## {}\n\n{}\n", summary, body)
+    }
+}
+
+#[allow(non_snake_case)]
+#[cfg(test)]
+mod tests {
+    use crate::file_handler::{
+        archive_path, write_atomic, write_atomic_with, FileHandler, FileManagement, Heading,
+        LineEnding,
+    };
+    use std::fs;
+    use tempfile::TempDir;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    #[test]
+    fn test_file_handler__append_idea__file_does_not_exist__success() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("README.md");
+
+        fh.append_idea(file_path.to_str().unwrap(), "some-summary", "some-body")?;
+
+        let actual = fs::read_to_string(&file_path)?;
+        let expected = "## some-summary\n\nsome-body\n";
+
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__append_idea__keeps_existing_content() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("README.md");
+        fs::write(&file_path, "# Ideas\n")?;
+
+        fh.append_idea(file_path.to_str().unwrap(), "some-summary", "some-body")?;
+
+        let actual = fs::read_to_string(&file_path)?;
+        let expected = "# Ideas\n\n## some-summary\n\nsome-body\n";
+
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__append_keyed_idea__id_header() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("README.md");
+        fs::write(&file_path, "# Ideas\n")?;
+
+        fh.append_keyed_idea(file_path.to_str().unwrap(), "some-id", "some-summary", "")?;
+
+        let actual = fs::read_to_string(&file_path)?;
+        let expected = "# Ideas\n\n<!-- id: some-id -->\n## some-summary\n";
+
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__append_idea__no_trailing_newline() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("README.md");
+        fs::write(&file_path, "# Ideas")?;
+
+        fh.append_idea(file_path.to_str().unwrap(), "some-summary", "")?;
+
+        let actual = fs::read_to_string(&file_path)?;
+        let expected = "# Ideas\n\n## some-summary\n";
+
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__append_bullet__follows_existing_bullets() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("README.md");
+        fs::write(&file_path, "# Ideas\n\n- first thought\n")?;
+
+        fh.append_bullet(file_path.to_str().unwrap(), "second thought")?;
+
+        let actual = fs::read_to_string(&file_path)?;
+        let expected = "# Ideas\n\n- first thought\n- second thought\n";
+
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__append_bullet__after_heading() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("README.md");
+        fs::write(&file_path, "## some-summary")?;
+
+        fh.append_bullet(file_path.to_str().unwrap(), "some thought")?;
+
+        let actual = fs::read_to_string(&file_path)?;
+        let expected = "## some-summary\n\n- some thought\n";
+
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__append_footer__after_idea() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("README.md");
+        fs::write(&file_path, "## some-summary\n")?;
+
+        let actual = fh.append_footer(file_path.to_str().unwrap(), "_Captured via eureka_")?;
+
+        assert!(actual);
+        assert_eq!(
+            fs::read_to_string(&file_path)?,
+            "## some-summary\n\n_Captured via eureka_\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__append_footer__only_once() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("README.md");
+        fs::write(&file_path, "## some-summary\n\n_Captured via eureka_\n\n")?;
+
+        let actual = fh.append_footer(file_path.to_str().unwrap(), "_Captured via eureka_")?;
+
+        assert!(!actual);
+        assert_eq!(
+            fs::read_to_string(&file_path)?,
+            "## some-summary\n\n_Captured via eureka_\n\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__append_idea__multibyte_chars() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("README.md");
+        fs::write(&file_path, "# アイデア\n")?;
+
+        fh.append_idea(file_path.to_str().unwrap(), "💡 新しい考え", "本文 ✨")?;
+
+        let actual = fs::read_to_string(&file_path)?;
+        let expected = "# アイデア\n\n## 💡 新しい考え\n\n本文 ✨\n";
+
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__append_idea__invalid_utf8_in_file() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("README.md");
+        fs::write(&file_path, b"# Ideas \xE2\x9C\n")?;
+
+        fh.append_idea(file_path.to_str().unwrap(), "some-summary", "")?;
+
+        let actual = fs::read(&file_path)?;
+        let expected = b"# Ideas \xE2\x9C\n\n## some-summary\n";
+
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__write_atomic__replaces_contents() -> TestResult {
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("config.json");
+        fs::write(&file_path, "old")?;
+
+        write_atomic(&file_path, b"new")?;
+
+        assert_eq!(fs::read_to_string(&file_path)?, "new");
+        assert!(!dir.path().join(".config.json.tmp").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__write_atomic__failed_rename_keeps_original() -> TestResult {
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("README.md");
+        fs::write(&file_path, "# Ideas\n")?;
+
+        let actual = write_atomic_with(&file_path, b"# Ideas\n\n## half", |_, _| {
+            Err(std::io::Error::other("interrupted"))
+        });
+
+        assert!(actual.is_err());
+        assert_eq!(fs::read_to_string(&file_path)?, "# Ideas\n");
+        assert!(!dir.path().join(".README.md.tmp").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__read_front_matter__inline_list() -> TestResult {
+        let fh = FileHandler::default();
+        let tmp_file = tempfile::NamedTempFile::new()?;
+        fs::write(
+            tmp_file.path(),
+            "---\ntitle: Ideas\ntags: [rust, \"cli tools\"]\n---\n\n## Some idea\n",
+        )?;
+
+        let actual = fh.read_front_matter(tmp_file.path().to_str().unwrap())?;
+
+        assert_eq!(actual, vec!["rust", "cli tools"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__read_front_matter__block_list() -> TestResult {
+        let fh = FileHandler::default();
+        let tmp_file = tempfile::NamedTempFile::new()?;
+        fs::write(
+            tmp_file.path(),
+            "---\ntags:\n  - rust\n  - cli\nauthor: me\n---\n- not a tag\n",
+        )?;
+
+        let actual = fh.read_front_matter(tmp_file.path().to_str().unwrap())?;
+
+        assert_eq!(actual, vec!["rust", "cli"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__write_front_matter__keeps_created() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("idea.md");
+        fs::write(
+            &file_path,
+            "---\ncreated: 2024-05-01\ntags: [rust]\nupdated: 2024-05-02\n---\n\n## some-idea\n",
+        )?;
+
+        fh.write_front_matter(file_path.to_str().unwrap(), "2024-06-01")?;
+
+        let actual = fs::read_to_string(&file_path)?;
+        let expected =
+            "---\ncreated: 2024-05-01\ntags: [rust]\nupdated: 2024-06-01\n---\n\n## some-idea\n";
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__write_front_matter__adds_missing_fields() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("idea.md");
+        fs::write(&file_path, "---\r\ntags: [rust]\r\n---\r\n## some-idea\r\n")?;
+
+        fh.write_front_matter(file_path.to_str().unwrap(), "2024-06-01")?;
+
+        let actual = fs::read_to_string(&file_path)?;
+        let expected = "---\r\ncreated: 2024-06-01\r\ntags: [rust]\r\nupdated: 2024-06-01\r\n---\r\n## some-idea\r\n";
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__write_front_matter__no_front_matter() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("idea.md");
+        // A thematic break, not front matter
+        fs::write(&file_path, "---\n## some-idea\n")?;
+
+        fh.write_front_matter(file_path.to_str().unwrap(), "2024-06-01")?;
+
+        let actual = fs::read_to_string(&file_path)?;
+        let expected = "---\ncreated: 2024-06-01\nupdated: 2024-06-01\n---\n\n---\n## some-idea\n";
+        assert_eq!(actual, expected);
+        assert_eq!(fh.read_front_matter(file_path.to_str().unwrap())?.len(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__read_front_matter__no_front_matter() -> TestResult {
+        let fh = FileHandler::default();
+        let tmp_file = tempfile::NamedTempFile::new()?;
+        fs::write(tmp_file.path(), "# Ideas\ntags: [rust]\n")?;
+
+        let actual = fh.read_front_matter(tmp_file.path().to_str().unwrap())?;
+
+        assert!(actual.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__idea_files__skips_hidden_and_other_files() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        fs::create_dir_all(dir.path().join("2024/06"))?;
+        fs::create_dir_all(dir.path().join(".git"))?;
+        for file in ["README.md", "2024/06/01.md", ".git/HEAD.md", "notes.txt"] {
+            fs::write(dir.path().join(file), "")?;
+        }
+
+        let actual = fh.idea_files(dir.path().to_str().unwrap(), "md")?;
+
+        assert_eq!(
+            actual,
+            vec![
+                dir.path().join("2024/06/01.md"),
+                dir.path().join("README.md")
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__idea_files__other_extension() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        fs::create_dir_all(dir.path().join("2024/06"))?;
+        for file in ["README.md", "2024/06/01.org", "notes.txt"] {
+            fs::write(dir.path().join(file), "")?;
+        }
+
+        let actual = fh.idea_files(dir.path().to_str().unwrap(), "org")?;
+
+        assert_eq!(
+            actual,
+            vec![
+                dir.path().join("2024/06/01.org"),
+                dir.path().join("README.md")
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__find_git_repos__bounded_and_not_inside_repos() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        for repo in [
+            "ideas/.git",
+            "ideas/vendor/nested/.git",
+            "code/project/.git",
+            "code/deep/down/below/.git",
+            ".hidden/repo/.git",
+        ] {
+            fs::create_dir_all(dir.path().join(repo))?;
+        }
+        // A worktree
+        fs::create_dir_all(dir.path().join("code/worktree"))?;
+        fs::write(dir.path().join("code/worktree/.git"), "gitdir: somewhere")?;
+
+        let actual = fh.find_git_repos(dir.path().to_str().unwrap(), 3)?;
+
+        assert_eq!(
+            actual,
+            vec![
+                dir.path().join("code/project"),
+                dir.path().join("code/worktree"),
+                dir.path().join("ideas"),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__find_git_repos__dir_does_not_exist() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+
+        let actual = fh.find_git_repos(dir.path().join("missing").to_str().unwrap(), 3);
+
+        assert!(actual.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__append_idea__lf_file_stays_lf() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("README.md");
+        fs::write(&file_path, "## first\n\nsome-body\n")?;
+
+        fh.append_idea(file_path.to_str().unwrap(), "second", "line\r\nother-line")?;
+
+        let actual = fs::read_to_string(&file_path)?;
+        let expected = "## first\n\nsome-body\n\n## second\n\nline\nother-line\n";
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__append_idea__crlf_file_stays_crlf() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("README.md");
+        fs::write(&file_path, "## first\r\n\r\nsome-body\r\n")?;
+
+        fh.append_idea(file_path.to_str().unwrap(), "second", "line\nother-line")?;
+        fh.append_bullet(file_path.to_str().unwrap(), "some-bullet")?;
+
+        let actual = fs::read_to_string(&file_path)?;
+        let expected = "## first\r\n\r\nsome-body\r\n\r\n## second\r\n\r\nline\r\nother-line\r\n\r\n- some-bullet\r\n";
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__append_bullet__configured_line_ending() -> TestResult {
+        let fh = FileHandler::new(LineEnding::Crlf);
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("README.md");
+
+        fh.append_bullet(file_path.to_str().unwrap(), "first")?;
+        fh.append_bullet(file_path.to_str().unwrap(), "second")?;
+
+        let actual = fs::read_to_string(&file_path)?;
+        assert_eq!(actual, "- first\r\n- second\r\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__append_footer__crlf_file() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("README.md");
+        fs::write(&file_path, "## some-idea\r\n")?;
+
+        let appended = fh.append_footer(file_path.to_str().unwrap(), "some-footer")?;
+        let appended_again = fh.append_footer(file_path.to_str().unwrap(), "some-footer")?;
+
+        let actual = fs::read_to_string(&file_path)?;
+        assert!(appended);
+        assert!(!appended_again);
+        assert_eq!(actual, "## some-idea\r\n\r\nsome-footer\r\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__line_ending__from_str() {
+        assert_eq!("auto".parse(), Ok(LineEnding::Auto));
+        assert_eq!("lf".parse(), Ok(LineEnding::Lf));
+        assert_eq!("crlf".parse(), Ok(LineEnding::Crlf));
+        assert_eq!("CRLF".parse::<LineEnding>(), Err(()));
+    }
+
+    #[test]
+    fn test_file_handler__looks_like_project__idea_repo() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        fs::write(dir.path().join("README.md"), "# Ideas\n\n## some-idea\n")?;
+        fs::create_dir_all(dir.path().join("2024/06"))?;
+        fs::create_dir_all(dir.path().join(".git"))?;
+        fs::write(dir.path().join("PRIVATE.md"), "")?;
+
+        let actual = fh.looks_like_project(dir.path().to_str().unwrap())?;
+
+        assert!(!actual);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__looks_like_project__no_readme() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        fs::write(dir.path().join("Cargo.toml"), "")?;
+
+        let actual = fh.looks_like_project(dir.path().to_str().unwrap())?;
+
+        assert!(!actual);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__looks_like_project__project_readme() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        fs::write(dir.path().join("README.md"), "# some-project\n\nUsage\n")?;
+
+        let actual = fh.looks_like_project(dir.path().to_str().unwrap())?;
+
+        assert!(actual);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__looks_like_project__manifest() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        fs::write(dir.path().join("README.md"), "## some-idea\n")?;
+        fs::write(dir.path().join("package.json"), "{}")?;
+
+        let actual = fh.looks_like_project(dir.path().to_str().unwrap())?;
+
+        assert!(actual);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__create_marker() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let marker = dir.path().join(".eureka-idea-repo");
+        let marker = marker.to_str().unwrap();
+
+        assert!(!fh.has_marker(marker));
+        fh.create_marker(marker)?;
+
+        assert!(fh.has_marker(marker));
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__try_lock__held_until_unlocked() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let lock = dir.path().join("push.lock");
+        let lock = lock.to_str().unwrap();
+
+        assert!(fh.try_lock(lock)?);
+        assert!(!fh.try_lock(lock)?);
+        fh.unlock(lock)?;
+        fh.unlock(lock)?;
+
+        assert!(fh.try_lock(lock)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__append_log_entry__one_entry_per_line() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("eureka.log");
+
+        fh.append_log_entry(file_path.to_str().unwrap(), "first")?;
+        fh.append_log_entry(file_path.to_str().unwrap(), "second")?;
+
+        assert_eq!(fs::read_to_string(&file_path)?, "first\nsecond\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__queue_idea__one_idea_per_line() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("queue");
+        let file_path = file_path.to_str().unwrap();
+
+        fh.queue_idea(file_path, "first idea")?;
+        fh.queue_idea(file_path, "  second\nidea  ")?;
+
+        assert_eq!(fh.read_queue(file_path)?, vec!["first idea", "second idea"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__read_queue__file_does_not_exist() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+
+        let actual = fh.read_queue(dir.path().join("queue").to_str().unwrap())?;
+
+        assert!(actual.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__write_queue__keeps_the_rest() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("queue");
+        let file_path = file_path.to_str().unwrap();
+        fh.queue_idea(file_path, "first idea")?;
+        fh.queue_idea(file_path, "second idea")?;
+
+        fh.write_queue(file_path, &[String::from("second idea")])?;
+
+        assert_eq!(fh.read_queue(file_path)?, vec!["second idea"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__read_issue_body__keeps_headings() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("ISSUE_BODY.md");
+
+        fh.prepare_issue_body(file_path.to_str().unwrap())?;
+        fs::write(&file_path, "\n## Why\n\nBecause\n\n")?;
+        let actual = fh.read_issue_body(file_path.to_str().unwrap())?;
+
+        assert_eq!(actual, "## Why\n\nBecause");
+        assert!(!file_path.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__line_count__success() -> TestResult {
+        let fh = FileHandler::default();
+        let tmp_file = tempfile::NamedTempFile::new()?;
+        fs::write(tmp_file.path(), "# Ideas\n\n## Some idea\n")?;
+
+        let actual = fh.line_count(tmp_file.path().to_str().unwrap())?;
+
+        assert_eq!(actual, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__line_count__file_does_not_exist() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+
+        let actual = fh.line_count(dir.path().join("README.md").to_str().unwrap())?;
+
+        assert_eq!(actual, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__find_heading_offset__success() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("README.md");
+        fs::write(
+            &file_path,
+            "# Ideas\n\n## Buy a boat\n\nA big one\n\n### Sails\n\nRed\n\n## Learn to swim\n",
+        )?;
+
+        let actual = fh.find_heading_offset(file_path.to_str().unwrap(), "buy a BOAT")?;
+        let expected = vec![Heading {
+            title: String::from("Buy a boat"),
+            level: 2,
+            line: 3,
+            section_end: 9,
+        }];
+
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__find_heading_offset__multiple_matches() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("README.md");
+        fs::write(&file_path, "## Boat trip\n## Buy a boat\nsome-body\n")?;
+
+        let actual = fh.find_heading_offset(file_path.to_str().unwrap(), "boat")?;
+        let expected = vec![
+            Heading {
+                title: String::from("Boat trip"),
+                level: 2,
+                line: 1,
+                section_end: 1,
+            },
+            Heading {
+                title: String::from("Buy a boat"),
+                level: 2,
+                line: 2,
+                section_end: 3,
+            },
+        ];
+
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__find_heading_offset__ignores_code_blocks() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("README.md");
+        fs::write(&file_path, "```sh\n# boat comment\n```\n#boat-tag\n")?;
+
+        let actual = fh.find_heading_offset(file_path.to_str().unwrap(), "boat")?;
+
+        assert!(actual.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__count_headings__success() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("README.md");
+        fs::write(
+            &file_path,
+            "# Ideas\n\n## Buy a boat\n\n### Sails\n\n## Learn to swim\n",
+        )?;
+
+        let actual = fh.count_headings(file_path.to_str().unwrap())?;
+
+        assert_eq!(actual, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__count_headings__file_does_not_exist() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("README.md");
+
+        let actual = fh.count_headings(file_path.to_str().unwrap())?;
+
+        assert_eq!(actual, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__find_heading_offset__multibyte_chars() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("README.md");
+        fs::write(&file_path, "## 💡 Ünïcödé idea\n\n## 新しい考え\n\n本文\n")?;
+
+        let actual = fh.find_heading_offset(file_path.to_str().unwrap(), "ÜNÏCÖDÉ")?;
+        let expected = vec![Heading {
+            title: String::from("💡 Ünïcödé idea"),
+            level: 2,
+            line: 1,
+            section_end: 1,
+        }];
+        assert_eq!(actual, expected);
+
+        let actual = fh.find_heading_offset(file_path.to_str().unwrap(), "考え")?;
+        assert_eq!(actual[0].section_end, 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__count_headings__invalid_utf8_in_file() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("README.md");
+        fs::write(&file_path, b"## Idea \xE2\x9C\n\n## Other idea\n")?;
+
+        let actual = fh.count_headings(file_path.to_str().unwrap())?;
+
+        assert_eq!(actual, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__idea_headings__success() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("README.md");
+        fs::write(
+            &file_path,
+            "# Ideas\n\n## Buy a boat\n\n### Sails\n\n## Learn to swim\n",
+        )?;
+
+        let actual: Vec<String> = fh
+            .idea_headings(file_path.to_str().unwrap())?
+            .into_iter()
+            .map(|heading| heading.title)
+            .collect();
+        let expected = vec![String::from("Buy a boat"), String::from("Learn to swim")];
+
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__idea_headings__file_does_not_exist() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("README.md");
+
+        let actual = fh.idea_headings(file_path.to_str().unwrap())?;
+
+        assert!(actual.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__ensure_gitignore_entry__appends_entry() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let gitignore_path = dir.path().join(".gitignore");
+        fs::write(&gitignore_path, "target")?;
+
+        let added = fh.ensure_gitignore_entry(gitignore_path.to_str().unwrap(), "PRIVATE.md")?;
+
+        assert!(added);
+        assert_eq!(fs::read_to_string(&gitignore_path)?, "target\nPRIVATE.md\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__ensure_gitignore_entry__creates_file() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let gitignore_path = dir.path().join(".gitignore");
+
+        fh.ensure_gitignore_entry(gitignore_path.to_str().unwrap(), "PRIVATE.md")?;
+
+        assert_eq!(fs::read_to_string(&gitignore_path)?, "PRIVATE.md\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__ensure_gitignore_entry__already_ignored() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let gitignore_path = dir.path().join(".gitignore");
+        fs::write(&gitignore_path, "/PRIVATE.md\n")?;
+
+        let added = fh.ensure_gitignore_entry(gitignore_path.to_str().unwrap(), "PRIVATE.md")?;
+
+        assert!(!added);
+        assert_eq!(fs::read_to_string(&gitignore_path)?, "/PRIVATE.md\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__read_commit_body__ignores_comments() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("COMMIT_BODY");
+        let file_path = file_path.to_str().unwrap();
+        fh.prepare_commit_body(file_path)?;
+        let template = fs::read_to_string(file_path)?;
+        fs::write(file_path, format!("Some body\n\nover lines\n{}", template))?;
+
+        let actual = fh.read_commit_body(file_path)?;
+
+        assert_eq!(actual, "Some body\n\nover lines");
+        assert!(fs::metadata(file_path).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__read_commit_body__untouched_template() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("COMMIT_BODY");
+        let file_path = file_path.to_str().unwrap();
+        fh.prepare_commit_body(file_path)?;
+
+        let actual = fh.read_commit_body(file_path)?;
+
+        assert_eq!(actual, "");
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__rotate_idea_file__under_limit() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("README.md");
+        fs::write(&file_path, "## Some idea\n")?;
+
+        let actual = fh.rotate_idea_file(file_path.to_str().unwrap(), 100, 10, "2024-06-01")?;
+
+        assert_eq!(actual, None);
+        assert_eq!(fs::read_to_string(&file_path)?, "## Some idea\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__rotate_idea_file__over_limit() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("README.md");
+        fs::write(&file_path, "## Some idea\n")?;
+
+        let archive = fh
+            .rotate_idea_file(file_path.to_str().unwrap(), 20, 10, "2024-06-01")?
+            .unwrap();
+
+        assert_eq!(archive, dir.path().join("README.archive.2024-06-01.md"));
+        assert_eq!(fs::read_to_string(&archive)?, "## Some idea\n");
+        assert_eq!(fs::read_to_string(&file_path)?, "");
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__archive_path__rotated_twice_a_day() -> TestResult {
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("README.md");
+        fs::write(dir.path().join("README.archive.2024-06-01.md"), "")?;
+
+        let actual = archive_path(&file_path, "2024-06-01");
+        let expected = dir.path().join("README.archive.2024-06-01.2.md");
+
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    #[cfg(feature = "watch")]
+    #[test]
+    fn test_file_handler__wait_until_settled__waits_for_last_change() -> TestResult {
+        use std::time::{Duration, Instant};
+
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("README.md");
+        fs::write(&file_path, "## Some idea\n")?;
+
+        let writer_path = file_path.clone();
+        let writer = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(150));
+            fs::write(writer_path, "## Some idea\n\nAutosaved\n").unwrap();
+        });
+        let start = Instant::now();
+        fh.wait_until_settled(file_path.to_str().unwrap(), Duration::from_millis(300))?;
+        writer.join().unwrap();
+
+        assert!(start.elapsed() >= Duration::from_millis(450));
+        Ok(())
+    }
+
+    #[cfg(not(feature = "watch"))]
+    #[test]
+    fn test_file_handler__wait_until_settled__unsupported() {
+        let fh = FileHandler::default();
+
+        let actual = fh
+            .wait_until_settled("README.md", std::time::Duration::from_secs(1))
+            .map_err(|e| e.kind());
+        let expected = Err(std::io::ErrorKind::Unsupported);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_file_handler__archive_ideas__moves_sections() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("README.md");
+        let archive_path = dir.path().join("ARCHIVE.md");
+        fs::write(
+            &file_path,
+            "# Ideas\n\n## Old idea\n\nbody\n\n## Kept idea\n\n## Older idea\n",
+        )?;
+        let file = file_path.to_str().unwrap();
+        let stale: Vec<Heading> = fh
+            .idea_headings(file)?
+            .into_iter()
+            .filter(|heading| heading.title != "Kept idea")
+            .collect();
+
+        fh.archive_ideas(file, archive_path.to_str().unwrap(), &stale)?;
+
+        assert_eq!(fs::read_to_string(&file_path)?, "# Ideas\n\n## Kept idea\n");
+        assert_eq!(
+            fs::read_to_string(&archive_path)?,
+            "## Old idea\n\nbody\n\n## Older idea\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_handler__archive_ideas__appends_to_archive() -> TestResult {
+        let fh = FileHandler::default();
+        let dir = TempDir::new()?;
+        let file_path = dir.path().join("README.md");
+        let archive_path = dir.path().join("ARCHIVE.md");
+        fs::write(&file_path, "## Old idea\r\n\r\n## Kept idea\r\n")?;
+        fs::write(&archive_path, "## Archived idea\r\n")?;
+        let file = file_path.to_str().unwrap();
+        let stale = fh.idea_headings(file)?[..1].to_vec();
+
+        fh.archive_ideas(file, archive_path.to_str().unwrap(), &stale)?;
+
+        assert_eq!(fs::read_to_string(&file_path)?, "## Kept idea\r\n");
+        assert_eq!(
+            fs::read_to_string(&archive_path)?,
+            "## Archived idea\r\n\r\n## Old idea\r\n"
+        );
+        Ok(())
+    }
+}
@@ -1,26 +1,284 @@
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
 use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::keyed::merge_keyed;
+
+const WORKTREE_NAME: &str = "eureka";
+
+// The idea file and the idea files archived when it grew too big
+const IDEA_PATHSPECS: [&str; 2] = ["README.md", "README.archive.*.md"];
+
+// How long to wait for the remote host before warning that it's unreachable
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
 
 pub trait GitManagement {
     fn init(&mut self, repo_path: &str) -> Result<(), git2::Error>;
+    fn discover(&mut self, path: &str) -> Result<String, git2::Error>;
+    fn ensure_worktree(
+        &mut self,
+        worktree_path: &str,
+        branch_name: &str,
+    ) -> Result<(), git2::Error>;
+    fn upstream_branch(&self) -> Result<Option<String>, git2::Error>;
+    fn ensure_upstream(&self, branch_name: &str, remote_name: &str) -> Result<bool, git2::Error>;
+    fn head_branch(&self) -> Result<Option<String>, git2::Error>;
     fn checkout_branch(&self, branch_name: &str) -> Result<(), git2::Error>;
-    fn add(&self) -> Result<(), git2::Error>;
-    fn commit(&self, subject: &str) -> Result<git2::Oid, git2::Error>;
+    fn add(&self) -> Result<usize, git2::Error>;
+    fn oversized_files(&self, max_bytes: u64) -> Result<Vec<(String, u64)>, git2::Error>;
+    fn is_ignored(&self, file_path: &str) -> Result<bool, git2::Error>;
+    fn diff(&self) -> Result<String, git2::Error>;
+    fn commit(
+        &self,
+        subject: &str,
+        body: &str,
+        author_time: Option<git2::Time>,
+        commit_time: Option<git2::Time>,
+    ) -> Result<git2::Oid, git2::Error>;
     fn push(&self, branch_name: &str) -> Result<(), git2::Error>;
+    fn push_to(&self, remote_name: &str, branch_name: &str) -> Result<(), git2::Error>;
+    fn pull_branch(&self, branch_name: &str, rebase: bool) -> Result<git2::Oid, git2::Error>;
+    fn remote_url(&self, remote_name: &str) -> Result<String, git2::Error>;
+    fn pending_commits(&self, branch_name: &str) -> Result<usize, git2::Error>;
+    fn divergence(&self, branch_name: &str) -> Result<Divergence, git2::Error>;
+    fn commit_diff(&self, branch_name: &str, nth: usize)
+        -> Result<Option<CommitDiff>, git2::Error>;
+    fn line_commit_diff(
+        &self,
+        file_path: &str,
+        line: usize,
+    ) -> Result<Option<CommitDiff>, git2::Error>;
+    fn is_clean(&self) -> Result<bool, git2::Error>;
+    fn line_times(&self, file_path: &str) -> Result<Vec<Option<i64>>, git2::Error>;
+    fn branches_ahead_of_upstream(&self) -> Result<Vec<(String, usize)>, git2::Error>;
+    fn probe_remote(&self) -> Result<(), git2::Error>;
+    fn remote_default_branch(&self) -> Result<Option<String>, git2::Error>;
+    fn add_pathspec(&mut self, pathspec: &str);
+    fn skip_idea_pathspecs(&mut self);
+    fn stash_save(&mut self) -> Result<bool, git2::Error>;
+    fn stash_pop(&mut self) -> Result<(), git2::Error>;
+    fn merge_branch(&self, branch_name: &str, into: &str) -> Result<MergeOutcome, git2::Error>;
+    fn finish_merge(&mut self) -> Result<Option<git2::Oid>, git2::Error>;
+}
+
+// How `merge_branch` brought a branch into another
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum MergeOutcome {
+    // Every commit of the branch was already merged
+    UpToDate,
+    // The other branch was moved forward to the branch
+    FastForward,
+    // The branches had diverged and a merge commit was created
+    MergeCommit,
+}
+
+// How a branch compares to `origin`, as of the last fetch or push
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Divergence {
+    // Nothing has been committed to the branch yet
+    Unborn,
+    // The branch has never been pushed, with its number of commits
+    NoUpstream(usize),
+    // Commits the branch is ahead and behind `origin/<branch>`
+    Tracking { ahead: usize, behind: usize },
+}
+
+// What a commit changed, see `commit_diff`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CommitDiff {
+    pub oid: git2::Oid,
+    pub summary: String,
+    // The whole diff as a patch, with file headers
+    pub patch: String,
+    pub hunks: Vec<DiffHunk>,
+}
+
+// One hunk of a `CommitDiff`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DiffHunk {
+    // Path of the file in the commit
+    pub file: String,
+    // e.g. `@@ -1,2 +1,4 @@`
+    pub header: String,
+    // Each starts with `+`, `-` or ` `, without the line ending
+    pub lines: Vec<String>,
 }
 
 #[derive(Default)]
 pub struct Git {
     repo: Option<git2::Repository>,
+    // Path `init` opened `repo` from, to reuse it for the same path
+    opened_path: Option<String>,
+    // Idea files outside of `IDEA_PATHSPECS`, e.g. from a date layout
+    extra_pathspecs: Vec<String>,
+    // Stage only `extra_pathspecs`, e.g. as the idea files are encrypted
+    skip_idea_pathspecs: bool,
 }
 
 impl GitManagement for Git {
+    // Opening the repo again is skipped, e.g. for a process capturing many ideas
     fn init(&mut self, repo_path: &str) -> Result<(), git2::Error> {
-        git2::Repository::open(Path::new(&repo_path)).map(|repo| self.repo = Some(repo))
+        if self.repo.is_some() && self.opened_path.as_deref() == Some(repo_path) {
+            return Ok(());
+        }
+
+        let repo = git2::Repository::open(Path::new(&repo_path))?;
+        self.repo = Some(repo);
+        self.opened_path = Some(repo_path.to_string());
+        Ok(())
+    }
+
+    fn discover(&mut self, path: &str) -> Result<String, git2::Error> {
+        let repo = git2::Repository::discover(Path::new(path))?;
+        let workdir = repo
+            .workdir()
+            .ok_or_else(|| git2::Error::from_str("can't capture ideas in a bare repo"))?
+            // Without the trailing slash
+            .components()
+            .as_path()
+            .display()
+            .to_string();
+
+        self.repo = Some(repo);
+        self.opened_path = None;
+        Ok(workdir)
+    }
+
+    fn ensure_worktree(
+        &mut self,
+        worktree_path: &str,
+        branch_name: &str,
+    ) -> Result<(), git2::Error> {
+        let path = Path::new(worktree_path);
+
+        // `repo` is the worktree from now on, not what `init` opened
+        self.opened_path = None;
+
+        // Reuse the worktree from a previous run, as long as it's still one of
+        // this repo on the idea branch
+        if path.exists() {
+            let repo = self.repo.as_ref().unwrap();
+            let expected_head = format!("refs/heads/{}", branch_name);
+            let worktree_repo = git2::Repository::open(path).ok().filter(|worktree_repo| {
+                let registered = repo
+                    .find_worktree(WORKTREE_NAME)
+                    .ok()
+                    .filter(|worktree| same_path(worktree.path(), path))
+                    .and_then(|worktree| git2::Repository::open_from_worktree(&worktree).ok());
+                let head = worktree_repo.find_reference("HEAD").ok();
+                registered
+                    .is_some_and(|registered| same_path(registered.path(), worktree_repo.path()))
+                    && head.as_ref().and_then(|head| head.symbolic_target())
+                        == Some(expected_head.as_str())
+            });
+            return match worktree_repo {
+                Some(worktree_repo) => {
+                    self.repo = Some(worktree_repo);
+                    Ok(())
+                }
+                None => Err(git2::Error::from_str(&format!(
+                    "{} isn't a worktree of your idea repo on branch '{}', remove it to let eureka create it again",
+                    worktree_path, branch_name
+                ))),
+            };
+        }
+
+        let worktree_repo = {
+            let repo = self.repo.as_ref().unwrap();
+            if is_unborn(repo) {
+                return Err(git2::Error::from_str(
+                    "Your idea repo has no commits yet, capture your first idea without a worktree",
+                ));
+            }
+            let branch = match repo.find_branch(branch_name, git2::BranchType::Local) {
+                Ok(branch) => branch,
+                Err(_) => {
+                    let commit = find_last_commit(repo)?;
+                    repo.branch(branch_name, &commit, false)?
+                }
+            };
+
+            let reference = branch.into_reference();
+            if repo.head()?.name() == reference.name() {
+                return Err(git2::Error::from_str(&format!(
+                    "Branch '{}' is checked out in your repo and can't be used by a worktree",
+                    branch_name
+                )));
+            }
+
+            let mut opts = git2::WorktreeAddOptions::new();
+            opts.reference(Some(&reference));
+            let worktree = repo.worktree(WORKTREE_NAME, path, Some(&opts))?;
+            git2::Repository::open_from_worktree(&worktree)?
+        };
+
+        self.repo = Some(worktree_repo);
+        Ok(())
+    }
+
+    fn upstream_branch(&self) -> Result<Option<String>, git2::Error> {
+        let repo = self.repo.as_ref().unwrap();
+
+        let head = match repo.head() {
+            Ok(head) if head.is_branch() => head,
+            // Detached or unborn HEAD has no upstream
+            _ => return Ok(None),
+        };
+
+        let key = format!("branch.{}.merge", head.shorthand().unwrap());
+        match repo.config()?.get_string(&key) {
+            Ok(merge) => Ok(merge.strip_prefix("refs/heads/").map(String::from)),
+            Err(err) if err.code() == git2::ErrorCode::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    // Track the branch of `remote_name` like `git push -u` does, unless the
+    // branch has an upstream already. Returns whether it was set now
+    fn ensure_upstream(&self, branch_name: &str, remote_name: &str) -> Result<bool, git2::Error> {
+        let mut config = self.repo.as_ref().unwrap().config()?;
+        let remote_key = format!("branch.{}.remote", branch_name);
+        let merge_key = format!("branch.{}.merge", branch_name);
+        let is_set = |key: &str| match config.get_string(key) {
+            Ok(_) => Ok(true),
+            Err(err) if err.code() == git2::ErrorCode::NotFound => Ok(false),
+            Err(err) => Err(err),
+        };
+        if is_set(&remote_key)? && is_set(&merge_key)? {
+            return Ok(false);
+        }
+
+        config.set_str(&remote_key, remote_name)?;
+        config.set_str(&merge_key, &format!("refs/heads/{}", branch_name))?;
+        Ok(true)
+    }
+
+    fn head_branch(&self) -> Result<Option<String>, git2::Error> {
+        let repo = self.repo.as_ref().unwrap();
+        match repo.head() {
+            Ok(head) if head.is_branch() => Ok(head.shorthand().map(String::from)),
+            // An empty repo is still on the branch its first commit creates
+            Err(e) if e.code() == git2::ErrorCode::UnbornBranch => Ok(repo
+                .find_reference("HEAD")?
+                .symbolic_target()
+                .and_then(|target| target.strip_prefix("refs/heads/"))
+                .map(String::from)),
+            // Detached HEAD
+            _ => Ok(None),
+        }
     }
 
     fn checkout_branch(&self, branch_name: &str) -> Result<(), git2::Error> {
         let repo = self.repo.as_ref().unwrap();
 
+        // An empty repo has nothing to check out, the first idea's commit
+        // creates the branch
+        if is_unborn(repo) {
+            return repo.set_head(&format!("refs/heads/{}", branch_name));
+        }
+
         let commit = repo
             .head()
             .map(|head| head.target())
@@ -45,57 +303,914 @@ impl GitManagement for Git {
         repo.set_head(refname.as_str())
     }
 
-    fn add(&self) -> Result<(), git2::Error> {
+    fn add(&self) -> Result<usize, git2::Error> {
+        let pathspecs = self.pathspecs();
+        // No pathspec at all would stage every file
+        if pathspecs.is_empty() {
+            return Ok(0);
+        }
         let mut index = self.repo.as_ref().unwrap().index()?;
 
-        index.add_path(Path::new("README.md"))?;
-        index.write()
+        // Only called for the files that are changed
+        let mut staged = 0;
+        index.add_all(
+            pathspecs,
+            git2::IndexAddOption::DEFAULT,
+            Some(&mut |_path: &Path, _pathspec: &[u8]| {
+                staged += 1;
+                0
+            }),
+        )?;
+        index.write()?;
+
+        Ok(staged)
+    }
+
+    // The changed files `add` would stage that are over `max_bytes`, unless Git
+    // LFS tracks them, with their size
+    fn oversized_files(&self, max_bytes: u64) -> Result<Vec<(String, u64)>, git2::Error> {
+        let repo = self.repo.as_ref().unwrap();
+        let workdir = repo
+            .workdir()
+            .ok_or_else(|| git2::Error::from_str("can't capture ideas in a bare repo"))?;
+
+        let pathspecs = self.pathspecs();
+        if pathspecs.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut opts = git2::StatusOptions::new();
+        for pathspec in pathspecs {
+            opts.pathspec(pathspec);
+        }
+        opts.include_untracked(true).recurse_untracked_dirs(true);
+
+        let mut oversized = vec![];
+        for entry in repo.statuses(Some(&mut opts))?.iter() {
+            let Some(path) = entry.path() else {
+                continue;
+            };
+            // Deleted files have no size to commit
+            let Ok(metadata) = std::fs::metadata(workdir.join(path)) else {
+                continue;
+            };
+            if metadata.len() <= max_bytes {
+                continue;
+            }
+
+            // `filter=lfs` in .gitattributes
+            let filter = repo.get_attr(
+                Path::new(path),
+                "filter",
+                git2::AttrCheckFlags::FILE_THEN_INDEX,
+            )?;
+            if filter != Some("lfs") {
+                oversized.push((path.to_string(), metadata.len()));
+            }
+        }
+
+        Ok(oversized)
+    }
+
+    fn is_ignored(&self, file_path: &str) -> Result<bool, git2::Error> {
+        self.repo
+            .as_ref()
+            .unwrap()
+            .is_path_ignored(Path::new(file_path))
+    }
+
+    fn diff(&self) -> Result<String, git2::Error> {
+        let repo = self.repo.as_ref().unwrap();
+        // Everything is new in an empty repo
+        let head_tree = if is_unborn(repo) {
+            None
+        } else {
+            Some(find_last_commit(repo)?.tree()?)
+        };
+
+        let pathspecs = self.pathspecs();
+        if pathspecs.is_empty() {
+            return Ok(String::new());
+        }
+        let mut opts = git2::DiffOptions::new();
+        for pathspec in pathspecs {
+            opts.pathspec(pathspec);
+        }
+        opts.include_untracked(true).show_untracked_content(true);
+        let diff = repo.diff_tree_to_workdir_with_index(head_tree.as_ref(), Some(&mut opts))?;
+
+        let mut patch = String::new();
+        diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
+            // Content lines carry their origin separately from the content
+            if let '+' | '-' | ' ' = line.origin() {
+                patch.push(line.origin());
+            }
+            patch.push_str(&String::from_utf8_lossy(line.content()));
+            true
+        })?;
+
+        Ok(patch)
     }
 
-    fn commit(&self, subject: &str) -> Result<git2::Oid, git2::Error> {
+    fn commit(
+        &self,
+        subject: &str,
+        body: &str,
+        author_time: Option<git2::Time>,
+        commit_time: Option<git2::Time>,
+    ) -> Result<git2::Oid, git2::Error> {
         let repo = self.repo.as_ref().unwrap();
         let mut index = repo.index()?;
 
-        let signature = repo.signature()?; // Use default user.name and user.email
+        // Use default user.name and user.email, at each time or now
+        let signature = repo.signature()?;
+        let signature_at = |time: Option<git2::Time>| match time {
+            Some(time) => git2::Signature::new(
+                signature.name().unwrap_or_default(),
+                signature.email().unwrap_or_default(),
+                &time,
+            ),
+            None => Ok(signature.clone()),
+        };
+        let author = signature_at(author_time)?;
+        let committer = signature_at(commit_time)?;
 
         let oid = index.write_tree()?;
-        let parent_commit = find_last_commit(self.repo.as_ref().unwrap())?;
+        // The first commit of an empty repo has no parent
+        let parent_commit = if is_unborn(repo) {
+            None
+        } else {
+            Some(find_last_commit(repo)?)
+        };
+        let parents: Vec<&git2::Commit> = parent_commit.iter().collect();
         let tree = repo.find_tree(oid)?;
+        let message = commit_message(subject, body);
 
         repo.commit(
-            Some("HEAD"),      // point HEAD to our new commit
-            &signature,        // author
-            &signature,        // committer
-            subject,           // commit message
-            &tree,             // tree
-            &[&parent_commit], // parent commit
+            Some("HEAD"), // point HEAD to our new commit
+            &author,      // author
+            &committer,   // committer
+            &message,     // commit message
+            &tree,        // tree
+            &parents,     // parent commit, if any
         )
     }
 
     fn push(&self, branch_name: &str) -> Result<(), git2::Error> {
-        with_credentials(self.repo.as_ref().unwrap(), |cred_callback| {
-            let mut remote = self.repo.as_ref().unwrap().find_remote("origin")?;
+        self.push_to("origin", branch_name)
+    }
+
+    // A push the remote rejects, e.g. as not fast-forward, fails with
+    // `ErrorCode::NotFastForward`
+    fn push_to(&self, remote_name: &str, branch_name: &str) -> Result<(), git2::Error> {
+        self.push_refspec(
+            remote_name,
+            &format!("refs/heads/{}:refs/heads/{}", branch_name, branch_name),
+        )
+    }
+
+    // Fetch the branch from origin and bring it into the local one, by
+    // replaying the local commits on top of it or with a merge commit. Both
+    // happen in memory first, so a conflict changes nothing. Returns the new
+    // tip of the branch
+    fn pull_branch(&self, branch_name: &str, rebase: bool) -> Result<git2::Oid, git2::Error> {
+        let repo = self.repo.as_ref().unwrap();
+        with_credentials(repo, |cred_callback| {
+            let mut remote = repo.find_remote("origin")?;
 
             let mut callbacks = git2::RemoteCallbacks::new();
-            let mut options = git2::PushOptions::new();
+            let mut options = git2::FetchOptions::new();
 
             callbacks.credentials(cred_callback);
             options.remote_callbacks(callbacks);
 
-            remote.push(
+            remote.fetch(
                 &[format!(
-                    "refs/heads/{}:refs/heads/{}",
+                    "+refs/heads/{}:refs/remotes/origin/{}",
                     branch_name, branch_name
                 )],
                 Some(&mut options),
+                None,
+            )
+        })?;
+
+        let refname = format!("refs/heads/{}", branch_name);
+        let local = repo.find_reference(&refname)?.peel_to_commit()?;
+        let upstream = repo
+            .find_reference(&format!("refs/remotes/origin/{}", branch_name))?
+            .peel_to_commit()?;
+        let conflict = |index: &git2::Index| -> Result<git2::Error, git2::Error> {
+            Ok(git2::Error::new(
+                git2::ErrorCode::Conflict,
+                git2::ErrorClass::Merge,
+                format!(
+                    "Your ideas conflict with origin/{} in {}, nothing was pulled. Run git pull in your idea repo to resolve it",
+                    branch_name,
+                    conflicted_paths(index)?.join(", ")
+                ),
+            ))
+        };
+
+        let signature = repo.signature()?;
+        let message = format!("Pull origin/{} into {}", branch_name, branch_name);
+        let tip = if repo.graph_descendant_of(upstream.id(), local.id())? {
+            // Nothing local to bring along
+            upstream.clone()
+        } else if rebase {
+            let mut revwalk = repo.revwalk()?;
+            revwalk.push(local.id())?;
+            revwalk.hide(upstream.id())?;
+            revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+
+            let mut onto = upstream.clone();
+            for oid in revwalk {
+                let commit = repo.find_commit(oid?)?;
+                let mut index = repo.cherrypick_commit(&commit, &onto, 0, None)?;
+                resolve_keyed_conflicts(repo, &mut index)?;
+                if index.has_conflicts() {
+                    return Err(conflict(&index)?);
+                }
+                let tree = repo.find_tree(index.write_tree_to(repo)?)?;
+                let oid = repo.commit(
+                    None,
+                    &commit.author(),
+                    &signature,
+                    commit.message().unwrap_or_default(),
+                    &tree,
+                    &[&onto],
+                )?;
+                onto = repo.find_commit(oid)?;
+            }
+            onto
+        } else {
+            let mut index = repo.merge_commits(&local, &upstream, None)?;
+            resolve_keyed_conflicts(repo, &mut index)?;
+            if index.has_conflicts() {
+                return Err(conflict(&index)?);
+            }
+            let tree = repo.find_tree(index.write_tree_to(repo)?)?;
+            let oid = repo.commit(
+                None,
+                &signature,
+                &signature,
+                &message,
+                &tree,
+                &[&local, &upstream],
             )?;
+            repo.find_commit(oid)?
+        };
+
+        let on_branch = repo.head()?.name() == Some(refname.as_str());
+        if on_branch {
+            repo.checkout_tree(tip.as_object(), None)?;
+        }
+        repo.reference(&refname, tip.id(), true, &message)?;
+        Ok(tip.id())
+    }
+
+    fn remote_url(&self, remote_name: &str) -> Result<String, git2::Error> {
+        let remote = self.repo.as_ref().unwrap().find_remote(remote_name)?;
+        remote.url().map(String::from).ok_or_else(|| {
+            git2::Error::from_str(&format!("the URL of remote {} isn't UTF-8", remote_name))
+        })
+    }
+
+    fn pending_commits(&self, branch_name: &str) -> Result<usize, git2::Error> {
+        let repo = self.repo.as_ref().unwrap();
+        let local = match repo.refname_to_id(&format!("refs/heads/{}", branch_name)) {
+            Ok(local) => local,
+            // Nothing has been committed to the branch yet, e.g. in an empty repo
+            Err(e) if e.code() == git2::ErrorCode::NotFound => return Ok(0),
+            Err(e) => return Err(e),
+        };
+
+        match repo.refname_to_id(&format!("refs/remotes/origin/{}", branch_name)) {
+            Ok(upstream) => repo
+                .graph_ahead_behind(local, upstream)
+                .map(|(ahead, _)| ahead),
+            // The branch has never been pushed, so the commits no remote branch
+            // has yet are pending, e.g. not those it branched off from
+            Err(e) if e.code() == git2::ErrorCode::NotFound => {
+                let mut revwalk = repo.revwalk()?;
+                revwalk.push(local)?;
+                revwalk.hide_glob("refs/remotes/*")?;
+                Ok(revwalk.count())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn divergence(&self, branch_name: &str) -> Result<Divergence, git2::Error> {
+        let repo = self.repo.as_ref().unwrap();
+        let local = match repo.refname_to_id(&format!("refs/heads/{}", branch_name)) {
+            Ok(local) => local,
+            Err(e) if e.code() == git2::ErrorCode::NotFound => return Ok(Divergence::Unborn),
+            Err(e) => return Err(e),
+        };
+
+        match repo.refname_to_id(&format!("refs/remotes/origin/{}", branch_name)) {
+            Ok(upstream) => repo
+                .graph_ahead_behind(local, upstream)
+                .map(|(ahead, behind)| Divergence::Tracking { ahead, behind }),
+            // Like `pending_commits`, what another remote branch has is pushed
+            Err(e) if e.code() == git2::ErrorCode::NotFound => {
+                let mut revwalk = repo.revwalk()?;
+                revwalk.push(local)?;
+                revwalk.hide_glob("refs/remotes/*")?;
+                Ok(Divergence::NoUpstream(revwalk.count()))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    // `nth` counts back from the tip of the branch, 1 is its last commit. The
+    // first commit is diffed against the empty tree
+    fn commit_diff(
+        &self,
+        branch_name: &str,
+        nth: usize,
+    ) -> Result<Option<CommitDiff>, git2::Error> {
+        let repo = self.repo.as_ref().unwrap();
+        let tip = match repo.refname_to_id(&format!("refs/heads/{}", branch_name)) {
+            Ok(tip) => tip,
+            Err(e) if e.code() == git2::ErrorCode::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        let mut revwalk = repo.revwalk()?;
+        revwalk.push(tip)?;
+        // Skip what a merge brought in, those aren't commits of the branch
+        revwalk.simplify_first_parent()?;
+        let oid = match revwalk.nth(nth.saturating_sub(1)) {
+            Some(oid) => oid?,
+            None => return Ok(None),
+        };
+        commit_diff_of(repo, oid).map(Some)
+    }
+
+    // The commit that last changed line `line` (starting at 1) of `file_path`,
+    // relative to the repo, as committed. None if the file or line never was
+    fn line_commit_diff(
+        &self,
+        file_path: &str,
+        line: usize,
+    ) -> Result<Option<CommitDiff>, git2::Error> {
+        let repo = self.repo.as_ref().unwrap();
+        if is_unborn(repo) || line == 0 {
+            return Ok(None);
+        }
+        let blame = match repo.blame_file(Path::new(file_path), None) {
+            Ok(blame) => blame,
+            Err(e) if e.code() == git2::ErrorCode::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        match blame.get_line(line) {
+            Some(hunk) => commit_diff_of(repo, hunk.final_commit_id()).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    // Untracked files count as changes, ignored ones don't
+    fn is_clean(&self) -> Result<bool, git2::Error> {
+        let mut opts = git2::StatusOptions::new();
+        opts.include_untracked(true).include_ignored(false);
+        let statuses = self.repo.as_ref().unwrap().statuses(Some(&mut opts))?;
+        Ok(statuses.is_empty())
+    }
+
+    // When each line of `file_path`, relative to the repo, was last committed.
+    // Lines changed since have none, and a file never committed has no lines.
+    fn line_times(&self, file_path: &str) -> Result<Vec<Option<i64>>, git2::Error> {
+        let repo = self.repo.as_ref().unwrap();
+        if is_unborn(repo) {
+            return Ok(vec![]);
+        }
+        let path = Path::new(file_path);
+        let blob = match repo.head()?.peel_to_tree()?.get_path(path) {
+            Ok(entry) => entry.to_object(repo)?.peel_to_blob()?,
+            Err(e) if e.code() == git2::ErrorCode::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e),
+        };
+
+        // Time of each line as committed, blame hunks are in line order
+        let mut committed = vec![];
+        for hunk in repo.blame_file(path, None)?.iter() {
+            let time = hunk.final_signature().when().seconds();
+            committed.extend(std::iter::repeat_n(Some(time), hunk.lines_in_hunk()));
+        }
+
+        let workdir = repo
+            .workdir()
+            .ok_or_else(|| git2::Error::from_str("The idea repo has no working directory"))?;
+        let contents = std::fs::read(workdir.join(path))
+            .map_err(|e| git2::Error::from_str(&format!("Could not read {}: {}", file_path, e)))?;
+        // As much context as there are lines, so one hunk covers the whole file
+        let context = committed.len() + contents.iter().filter(|&&b| b == b'\n').count();
+        let mut opts = git2::DiffOptions::new();
+        opts.context_lines(u32::try_from(context).unwrap_or(u32::MAX));
+        let patch = git2::Patch::from_blob_and_buffer(
+            &blob,
+            Some(path),
+            &contents,
+            Some(path),
+            Some(&mut opts),
+        )?;
+        if patch.num_hunks() == 0 {
+            return Ok(committed);
+        }
+
+        let mut times = vec![];
+        for hunk in 0..patch.num_hunks() {
+            for index in 0..patch.num_lines_in_hunk(hunk)? {
+                let line = patch.line_in_hunk(hunk, index)?;
+                match line.origin() {
+                    ' ' => times.push(
+                        line.old_lineno()
+                            .and_then(|old| committed.get(old as usize - 1).copied().flatten()),
+                    ),
+                    '+' => times.push(None),
+                    _ => {}
+                }
+            }
+        }
+        Ok(times)
+    }
+
+    fn branches_ahead_of_upstream(&self) -> Result<Vec<(String, usize)>, git2::Error> {
+        let repo = self.repo.as_ref().unwrap();
+        let mut ahead = Vec::new();
+
+        for branch in repo.branches(Some(git2::BranchType::Local))? {
+            let (branch, _) = branch?;
+            // Branch names that aren't UTF-8 can't be idea branches
+            let Some(branch_name) = branch.name()? else {
+                continue;
+            };
+            let pending = self.pending_commits(branch_name)?;
+            if pending > 0 {
+                ahead.push((branch_name.to_string(), pending));
+            }
+        }
+
+        Ok(ahead)
+    }
+
+    fn probe_remote(&self) -> Result<(), git2::Error> {
+        let remote = self.repo.as_ref().unwrap().find_remote("origin")?;
+        let (host, port) = match remote.url().and_then(remote_host) {
+            Some(host_port) => host_port,
+            // A local remote is always reachable
+            None => return Ok(()),
+        };
+
+        // The lookup and the connect share the timeout
+        let deadline = Instant::now() + PROBE_TIMEOUT;
+        let address = resolve_within(&host, port, PROBE_TIMEOUT)
+            .ok_or_else(|| git2::Error::from_str(&format!("Could not resolve {}", host)))?;
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Err(git2::Error::from_str(&format!(
+                "Could not reach {}:{}: timed out",
+                host, port
+            )));
+        }
+        TcpStream::connect_timeout(&address, left)
+            .map(|_| ())
+            .map_err(|err| {
+                git2::Error::from_str(&format!("Could not reach {}:{}: {}", host, port, err))
+            })
+    }
+
+    // The branch the HEAD of `origin` points to, as `origin` reports it. An empty
+    // remote has none.
+    fn remote_default_branch(&self) -> Result<Option<String>, git2::Error> {
+        let repo = self.repo.as_ref().unwrap();
+        let mut default_branch = None;
+        with_credentials(repo, |cred_callback| {
+            let mut remote = repo.find_remote("origin")?;
+
+            let mut callbacks = git2::RemoteCallbacks::new();
+            callbacks.credentials(cred_callback);
 
+            let connection = remote.connect_auth(git2::Direction::Fetch, Some(callbacks), None)?;
+            default_branch = match connection.default_branch() {
+                Ok(name) => name
+                    .as_str()
+                    .and_then(|name| name.strip_prefix("refs/heads/"))
+                    .map(String::from),
+                Err(e) if e.code() == git2::ErrorCode::NotFound => None,
+                Err(e) => return Err(e),
+            };
             Ok(())
+        })?;
+        Ok(default_branch)
+    }
+
+    fn add_pathspec(&mut self, pathspec: &str) {
+        self.extra_pathspecs.push(pathspec.to_string());
+    }
+
+    fn skip_idea_pathspecs(&mut self) {
+        self.skip_idea_pathspecs = true;
+    }
+
+    // Returns false if there was nothing to stash
+    fn stash_save(&mut self) -> Result<bool, git2::Error> {
+        let repo = self.repo.as_mut().unwrap();
+        let signature = repo.signature()?;
+
+        match repo.stash_save(&signature, "eureka: auto-stash", None) {
+            Ok(_) => Ok(true),
+            Err(e) if e.code() == git2::ErrorCode::NotFound => Ok(false),
+            // There's no commit to stash changes on top of in an empty repo
+            Err(e) if e.code() == git2::ErrorCode::UnbornBranch => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn stash_pop(&mut self) -> Result<(), git2::Error> {
+        self.repo.as_mut().unwrap().stash_pop(0, None)
+    }
+
+    // Check out `into` and merge `branch_name` into it. On conflicts nothing is
+    // merged, the error names the conflicting files and HEAD stays where it was
+    fn merge_branch(&self, branch_name: &str, into: &str) -> Result<MergeOutcome, git2::Error> {
+        let repo = self.repo.as_ref().unwrap();
+        let branch = repo
+            .find_branch(branch_name, git2::BranchType::Local)
+            .map_err(|_| {
+                git2::Error::from_str(&format!("Branch '{}' doesn't exist", branch_name))
+            })?;
+        let target = repo
+            .find_branch(into, git2::BranchType::Local)
+            .map_err(|_| git2::Error::from_str(&format!("Branch '{}' doesn't exist", into)))?;
+
+        let ours = target.get().peel_to_commit()?;
+        let theirs = branch.get().peel_to_commit()?;
+        let annotated = repo.find_annotated_commit(theirs.id())?;
+        let (analysis, _) = repo.merge_analysis_for_ref(target.get(), &[&annotated])?;
+        let message = format!("Merge branch '{}' into {}", branch_name, into);
+
+        // Merge in memory before checking anything out, so a conflict leaves
+        // HEAD and the checkout untouched
+        let merged = if analysis.is_up_to_date() || analysis.is_fast_forward() {
+            None
+        } else {
+            let mut index = repo.merge_commits(&ours, &theirs, None)?;
+            resolve_keyed_conflicts(repo, &mut index)?;
+            if index.has_conflicts() {
+                return Err(git2::Error::from_str(&format!(
+                    "Merging '{}' into '{}' conflicts in {}, nothing was merged",
+                    branch_name,
+                    into,
+                    conflicted_paths(&index)?.join(", ")
+                )));
+            }
+            Some(repo.find_tree(index.write_tree_to(repo)?)?)
+        };
+
+        let refname = format!("refs/heads/{}", into);
+        repo.checkout_tree(ours.as_object(), None)?;
+        repo.set_head(&refname)?;
+
+        if analysis.is_up_to_date() {
+            return Ok(MergeOutcome::UpToDate);
+        }
+        let Some(tree) = merged else {
+            repo.checkout_tree(theirs.as_object(), None)?;
+            repo.reference(&refname, theirs.id(), true, &message)?;
+            return Ok(MergeOutcome::FastForward);
+        };
+
+        repo.checkout_tree(tree.as_object(), None)?;
+        let signature = repo.signature()?;
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            &message,
+            &tree,
+            &[&ours, &theirs],
+        )?;
+        Ok(MergeOutcome::MergeCommit)
+    }
+
+    // Commit a merge left in progress, e.g. by a `git pull` that conflicted.
+    // Returns None when no merge is in progress
+    fn finish_merge(&mut self) -> Result<Option<git2::Oid>, git2::Error> {
+        let repo = self.repo.as_mut().unwrap();
+        if repo.state() != git2::RepositoryState::Merge {
+            return Ok(None);
+        }
+
+        let mut index = repo.index()?;
+        // Pick up what was staged since the repo was opened
+        index.read(false)?;
+        if index.has_conflicts() {
+            return Err(git2::Error::from_str(&format!(
+                "Resolve the conflicts in {} and stage them first",
+                conflicted_paths(&index)?.join(", ")
+            )));
+        }
+
+        let mut merge_heads = vec![];
+        repo.mergehead_foreach(|oid| {
+            merge_heads.push(*oid);
+            true
+        })?;
+        let mut parents = vec![find_last_commit(repo)?];
+        for oid in merge_heads {
+            parents.push(repo.find_commit(oid)?);
+        }
+        let parents: Vec<&git2::Commit> = parents.iter().collect();
+
+        // The message git prepared in MERGE_MSG, without its comments
+        let message: Vec<String> = repo
+            .message()
+            .unwrap_or_else(|_| String::from("Merge"))
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .map(String::from)
+            .collect();
+        let tree = repo.find_tree(index.write_tree()?)?;
+        let signature = repo.signature()?;
+        let oid = repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message.join("\n").trim(),
+            &tree,
+            &parents,
+        )?;
+        repo.cleanup_state()?;
+
+        Ok(Some(oid))
+    }
+}
+
+impl Git {
+    // A ref the remote rejects fails with `ErrorCode::NotFastForward`
+    fn push_refspec(&self, remote_name: &str, refspec: &str) -> Result<(), git2::Error> {
+        with_credentials(self.repo.as_ref().unwrap(), |cred_callback| {
+            let mut remote = self.repo.as_ref().unwrap().find_remote(remote_name)?;
+
+            // Remotes over the network report rejected refs here and not as an error
+            let mut rejection = None;
+            let mut callbacks = git2::RemoteCallbacks::new();
+            let mut options = git2::PushOptions::new();
+
+            callbacks.credentials(cred_callback);
+            callbacks.push_update_reference(|refname, status| {
+                if let Some(status) = status {
+                    rejection = Some(format!("{} rejected {}: {}", remote_name, refname, status));
+                }
+                Ok(())
+            });
+            options.remote_callbacks(callbacks);
+
+            remote.push(&[refspec], Some(&mut options))?;
+            drop(options);
+
+            match rejection.take() {
+                Some(rejection) => Err(git2::Error::new(
+                    git2::ErrorCode::NotFastForward,
+                    git2::ErrorClass::Reference,
+                    rejection,
+                )),
+                None => Ok(()),
+            }
         })
     }
+
+    fn pathspecs(&self) -> Vec<&str> {
+        let idea_pathspecs: &[&str] = if self.skip_idea_pathspecs {
+            &[]
+        } else {
+            &IDEA_PATHSPECS
+        };
+        idea_pathspecs
+            .iter()
+            .copied()
+            .chain(self.extra_pathspecs.iter().map(String::as_str))
+            .collect()
+    }
+}
+
+// Whether both paths lead to the same place, e.g. with and without symlinks
+fn same_path(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+// The first address of `host`, none if it can't be resolved within `timeout`.
+// A name lookup can't be cancelled, so a slow one is left running on its thread
+fn resolve_within(host: &str, port: u16, timeout: Duration) -> Option<SocketAddr> {
+    let (sender, receiver) = mpsc::channel();
+    let host = host.to_string();
+    thread::spawn(move || {
+        let address = (host.as_str(), port)
+            .to_socket_addrs()
+            .ok()
+            .and_then(|mut addresses| addresses.next());
+        // The probe may have given up on the lookup already
+        let _ = sender.send(address);
+    });
+    receiver.recv_timeout(timeout).ok().flatten()
+}
+
+// Host and port to connect to for a remote URL, e.g. `git@github.com:user/repo`
+// or `https://github.com/user/repo`. Local remotes have none.
+fn remote_host(url: &str) -> Option<(String, u16)> {
+    let (authority, default_port) = match url.split_once("://") {
+        Some((scheme, rest)) => {
+            let default_port = match scheme {
+                "ssh" | "git+ssh" | "ssh+git" => 22,
+                "https" => 443,
+                "http" => 80,
+                "git" => 9418,
+                _ => return None,
+            };
+            (rest.split('/').next().unwrap_or_default(), default_port)
+        }
+        // scp-like SSH, `[user@]host:path`. Anything with a `/` before the `:` is a path
+        None => match url.split_once(':') {
+            Some((authority, _)) if !authority.contains('/') && authority.len() > 1 => {
+                // The part after `:` is the path and not a port
+                let host = authority.rsplit('@').next().unwrap_or_default();
+                return Some((host.to_string(), 22));
+            }
+            _ => return None,
+        },
+    };
+
+    let host_port = authority.rsplit('@').next().unwrap_or_default();
+    let (host, port) = match host_port.strip_prefix('[') {
+        // IPv6, e.g. `[::1]:22`
+        Some(ipv6) => {
+            let (host, rest) = ipv6.split_once(']')?;
+            (host, rest.strip_prefix(':'))
+        }
+        None => match host_port.split_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (host_port, None),
+        },
+    };
+    let port = match port {
+        Some(port) => port.parse().ok()?,
+        None => default_port,
+    };
+
+    if host.is_empty() {
+        None
+    } else {
+        Some((host.to_string(), port))
+    }
+}
+
+// `owner/name` of the GitHub repo a remote URL points to, e.g.
+// `git@github.com:owner/name.git`
+pub(crate) fn github_repo(url: &str) -> Option<String> {
+    let (host, _) = remote_host(url)?;
+    if host != "github.com" {
+        return None;
+    }
+
+    let path = match url.split_once("://") {
+        Some((_, rest)) => rest.split_once('/')?.1,
+        None => url.split_once(':')?.1,
+    };
+    let path = path.trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    match path.split_once('/') {
+        Some((owner, name)) if !owner.is_empty() && !name.is_empty() && !name.contains('/') => {
+            Some(path.to_string())
+        }
+        _ => None,
+    }
+}
+
+fn commit_message(subject: &str, body: &str) -> String {
+    if body.is_empty() {
+        subject.to_string()
+    } else {
+        // A blank line separates the subject from the body
+        format!("{}\n\n{}", subject, body)
+    }
+}
+
+// What commit `oid` changed compared to its first parent
+fn commit_diff_of(repo: &git2::Repository, oid: git2::Oid) -> Result<CommitDiff, git2::Error> {
+    let commit = repo.find_commit(oid)?;
+    let parent_tree = match commit.parents().next() {
+        Some(parent) => Some(parent.tree()?),
+        None => None,
+    };
+    let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+
+    let mut patch = String::new();
+    let mut hunks: Vec<DiffHunk> = vec![];
+    diff.print(git2::DiffFormat::Patch, |delta, hunk, line| {
+        let content = String::from_utf8_lossy(line.content());
+        if let '+' | '-' | ' ' = line.origin() {
+            patch.push(line.origin());
+            if let Some(hunk) = hunks.last_mut() {
+                hunk.lines.push(format!(
+                    "{}{}",
+                    line.origin(),
+                    content.trim_end_matches(['\r', '\n'])
+                ));
+            }
+        } else if let (Some(hunk), 'H') = (hunk, line.origin()) {
+            let file = delta.new_file().path().or_else(|| delta.old_file().path());
+            hunks.push(DiffHunk {
+                file: file
+                    .map(|path| path.display().to_string())
+                    .unwrap_or_default(),
+                header: String::from_utf8_lossy(hunk.header())
+                    .trim_end()
+                    .to_string(),
+                lines: vec![],
+            });
+        }
+        patch.push_str(&content);
+        true
+    })?;
+
+    Ok(CommitDiff {
+        oid,
+        summary: commit.summary().unwrap_or_default().to_string(),
+        patch,
+        hunks,
+    })
+}
+
+// Whether HEAD points to a branch without commits, e.g. in a new repo
+fn is_unborn(repo: &git2::Repository) -> bool {
+    matches!(repo.head(), Err(e) if e.code() == git2::ErrorCode::UnbornBranch)
+}
+
+// Paths of the conflicting files in the index
+fn conflicted_paths(index: &git2::Index) -> Result<Vec<String>, git2::Error> {
+    let mut paths = vec![];
+    for conflict in index.conflicts()? {
+        let conflict = conflict?;
+        if let Some(entry) = conflict.our.or(conflict.their).or(conflict.ancestor) {
+            paths.push(String::from_utf8_lossy(&entry.path).into_owned());
+        }
+    }
+    paths.dedup();
+    Ok(paths)
+}
+
+// Merge conflicting files with keyed ideas idea by idea, see `merge_keyed`.
+// Files that can't be merged that way stay conflicted
+fn resolve_keyed_conflicts(
+    repo: &git2::Repository,
+    index: &mut git2::Index,
+) -> Result<(), git2::Error> {
+    let conflicts = index.conflicts()?.collect::<Result<Vec<_>, _>>()?;
+    let text = |entry: &git2::IndexEntry| -> Result<Option<String>, git2::Error> {
+        let blob = repo.find_blob(entry.id)?;
+        Ok(std::str::from_utf8(blob.content()).ok().map(String::from))
+    };
+
+    for conflict in conflicts {
+        let (Some(ours), Some(theirs)) = (conflict.our, conflict.their) else {
+            continue;
+        };
+        // Added on both sides
+        let ancestor = match &conflict.ancestor {
+            Some(ancestor) => text(ancestor)?,
+            None => Some(String::new()),
+        };
+        let (Some(ancestor), Some(ours_text), Some(theirs_text)) =
+            (ancestor, text(&ours)?, text(&theirs)?)
+        else {
+            continue;
+        };
+        let Some(merged) = merge_keyed(&ancestor, &ours_text, &theirs_text) else {
+            continue;
+        };
+
+        let path = String::from_utf8_lossy(&ours.path).into_owned();
+        // Ancestor, ours and theirs are stages 1 to 3
+        let stages = [(1, conflict.ancestor.is_some()), (2, true), (3, true)];
+        for (stage, _) in stages.into_iter().filter(|(_, present)| *present) {
+            index.remove(Path::new(&path), stage)?;
+        }
+        index.add(&git2::IndexEntry {
+            id: repo.blob(merged.as_bytes())?,
+            file_size: merged.len() as u32,
+            // Stage 0, no longer conflicted
+            flags: ours.path.len().min(0xFFF) as u16,
+            ..ours
+        })?;
+    }
+    Ok(())
 }
 
-fn find_last_commit(repo: &git2::Repository) -> Result<git2::Commit, git2::Error> {
+fn find_last_commit(repo: &git2::Repository) -> Result<git2::Commit<'_>, git2::Error> {
     let obj = repo.head()?.resolve()?.peel(git2::ObjectType::Commit)?;
     obj.into_commit()
         .map_err(|_| git2::Error::from_str("Couldn't find commit"))
@@ -144,8 +1259,14 @@ where
 #[allow(non_snake_case)]
 #[cfg(test)]
 mod tests {
-    use crate::git::{find_last_commit, Git, GitManagement};
+    use crate::git::{
+        find_last_commit, github_repo, remote_host, resolve_within, Divergence, Git, GitManagement,
+        MergeOutcome,
+    };
     use git2::{BranchType, Repository, RepositoryInitOptions, Status};
+    use std::fs;
+    use std::path::Path;
+    use std::time::Duration;
     use tempfile::{NamedTempFile, TempDir};
 
     #[test]
@@ -160,49 +1281,186 @@ mod tests {
     }
 
     #[test]
-    fn test_git__init__invalid_repo() {
+    fn test_git__init__same_path_is_not_opened_again() {
         let mut git = Git::default();
-        // Invalid repo
-        let dir = TempDir::new().unwrap();
+        let (dir, _repo, _file) = repo_init();
+        let repo_path = dir.path().to_str().unwrap();
+        git.init(repo_path).unwrap();
+        // Opening it again would fail now
+        fs::rename(dir.path().join(".git"), dir.path().join("moved.git")).unwrap();
 
-        let actual = git.init(dir.path().to_str().unwrap());
+        let actual = git.init(repo_path);
 
-        assert!(actual.is_err());
+        assert!(actual.is_ok());
     }
 
     #[test]
-    fn test_git__checkout_branch__missing_branch() {
+    fn test_git__init__other_path_is_opened() {
         let mut git = Git::default();
-        let (dir, repo, _file) = repo_init();
+        let (dir, _repo, _file) = repo_init();
         git.init(dir.path().to_str().unwrap()).unwrap();
+        let other_dir = TempDir::new().unwrap();
 
-        // This will create a new branch
-        git.checkout_branch("new-branch-name").unwrap();
-
-        let actual = repo.find_branch("new-branch-name", BranchType::Local);
+        let actual = git.init(other_dir.path().to_str().unwrap());
 
-        assert!(actual.is_ok());
+        assert!(actual.is_err());
     }
 
     #[test]
-    fn test_git__checkout_branch__success() {
+    fn test_git__init__many_captures_reuse_the_repo() {
         let mut git = Git::default();
         let (dir, repo, _file) = repo_init();
-        git.init(dir.path().to_str().unwrap()).unwrap();
-
-        let before = repo.head();
-        assert_eq!(before.unwrap().name().unwrap(), "refs/heads/main");
+        let repo_path = dir.path().to_str().unwrap();
 
-        git.checkout_branch("new-branch-name").unwrap();
-
-        let after = repo.head();
+        for i in 0..20 {
+            git.init(repo_path).unwrap();
+            git.checkout_branch("main").unwrap();
+            let mut readme = fs::read_to_string(dir.path().join("README.md")).unwrap();
+            readme.push_str(&format!("## idea-{}\n", i));
+            fs::write(dir.path().join("README.md"), readme).unwrap();
+            git.add().unwrap();
+            git.commit(&format!("idea-{}", i), "", None, None).unwrap();
+        }
 
-        assert!(after.is_ok());
-        assert_eq!(after.unwrap().name().unwrap(), "refs/heads/new-branch-name");
+        let mut revwalk = repo.revwalk().unwrap();
+        revwalk.push_head().unwrap();
+        // The initial commit and one for every idea
+        assert_eq!(revwalk.count(), 21);
+        let last = find_last_commit(&repo).unwrap();
+        assert_eq!(last.summary(), Some("idea-19"));
     }
 
     #[test]
-    fn test_git__add__success() {
+    fn test_git__init__invalid_repo() {
+        let mut git = Git::default();
+        // Invalid repo
+        let dir = TempDir::new().unwrap();
+
+        let actual = git.init(dir.path().to_str().unwrap());
+
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn test_git__upstream_branch__success() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        {
+            let mut config = repo.config().unwrap();
+            config.set_str("branch.main.remote", "origin").unwrap();
+            config
+                .set_str("branch.main.merge", "refs/heads/ideas")
+                .unwrap();
+        }
+        git.init(dir.path().to_str().unwrap()).unwrap();
+
+        let actual = git.upstream_branch().unwrap();
+
+        assert_eq!(actual, Some(String::from("ideas")));
+    }
+
+    #[test]
+    fn test_git__upstream_branch__no_upstream() {
+        let mut git = Git::default();
+        let (dir, _repo, _file) = repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+
+        let actual = git.upstream_branch().unwrap();
+
+        assert_eq!(actual, None);
+    }
+
+    #[test]
+    fn test_git__ensure_upstream__first_push_sets_it() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        let (_remote_dir, _remote) = remote_init(&repo);
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        assert_eq!(git.upstream_branch().unwrap(), None);
+        git.push("main").unwrap();
+
+        assert!(git.ensure_upstream("main", "origin").unwrap());
+        assert_eq!(git.upstream_branch().unwrap(), Some(String::from("main")));
+        let config = repo.config().unwrap();
+        assert_eq!(config.get_string("branch.main.remote").unwrap(), "origin");
+        // Tracked from then on
+        assert!(!git.ensure_upstream("main", "origin").unwrap());
+    }
+
+    #[test]
+    fn test_git__ensure_upstream__keeps_existing_upstream() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        {
+            let mut config = repo.config().unwrap();
+            config.set_str("branch.main.remote", "upstream").unwrap();
+            config
+                .set_str("branch.main.merge", "refs/heads/ideas")
+                .unwrap();
+        }
+        git.init(dir.path().to_str().unwrap()).unwrap();
+
+        assert!(!git.ensure_upstream("main", "origin").unwrap());
+        assert_eq!(git.upstream_branch().unwrap(), Some(String::from("ideas")));
+    }
+
+    #[test]
+    fn test_git__discover__from_subdirectory() {
+        let mut git = Git::default();
+        let (dir, _repo, _file) = repo_init();
+        let sub_dir = dir.path().join("src").join("nested");
+        std::fs::create_dir_all(&sub_dir).unwrap();
+
+        let actual = git.discover(sub_dir.to_str().unwrap()).unwrap();
+        let expected = dir.path().display().to_string();
+
+        assert_eq!(actual, expected);
+        assert_eq!(git.head_branch().unwrap(), Some("main".to_string()));
+    }
+
+    #[test]
+    fn test_git__discover__not_a_repo() {
+        let mut git = Git::default();
+        let dir = TempDir::new().unwrap();
+
+        let actual = git.discover(dir.path().to_str().unwrap()).is_err();
+
+        assert!(actual);
+    }
+
+    #[test]
+    fn test_git__checkout_branch__missing_branch() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+
+        // This will create a new branch
+        git.checkout_branch("new-branch-name").unwrap();
+
+        let actual = repo.find_branch("new-branch-name", BranchType::Local);
+
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn test_git__checkout_branch__success() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+
+        let before = repo.head();
+        assert_eq!(before.unwrap().name().unwrap(), "refs/heads/main");
+
+        git.checkout_branch("new-branch-name").unwrap();
+
+        let after = repo.head();
+
+        assert!(after.is_ok());
+        assert_eq!(after.unwrap().name().unwrap(), "refs/heads/new-branch-name");
+    }
+
+    #[test]
+    fn test_git__add__success() {
         let mut git = Git::default();
         let (dir, repo, _file) = repo_init();
         git.init(dir.path().to_str().unwrap()).unwrap();
@@ -211,13 +1469,172 @@ mod tests {
         let before = statuses_before.get(0).unwrap();
         assert_eq!(before.status(), Status::WT_NEW);
 
-        git.add().unwrap();
+        let staged = git.add().unwrap();
 
+        assert_eq!(staged, 1);
         let statuses_after = repo.statuses(None).unwrap();
         let after = statuses_after.get(0).unwrap();
         assert_eq!(after.status(), Status::INDEX_NEW);
     }
 
+    #[test]
+    fn test_git__add__archived_idea_file() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        fs::write(dir.path().join("README.archive.2024-06-01.md"), "").unwrap();
+        fs::write(dir.path().join("unrelated.md"), "").unwrap();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+
+        let staged = git.add().unwrap();
+
+        assert_eq!(staged, 2);
+        let status = |path| repo.status_file(Path::new(path)).unwrap();
+        assert_eq!(status("README.md"), Status::INDEX_NEW);
+        assert_eq!(status("README.archive.2024-06-01.md"), Status::INDEX_NEW);
+        assert_eq!(status("unrelated.md"), Status::WT_NEW);
+    }
+
+    #[test]
+    fn test_git__add__extra_pathspec() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        fs::create_dir_all(dir.path().join("2024/06")).unwrap();
+        fs::write(dir.path().join("2024/06/01.md"), "").unwrap();
+        fs::write(dir.path().join("2024/06/02.md"), "").unwrap();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        git.add_pathspec("2024/06/01.md");
+
+        let staged = git.add().unwrap();
+
+        assert_eq!(staged, 2);
+        let status = |path| repo.status_file(Path::new(path)).unwrap();
+        assert_eq!(status("2024/06/01.md"), Status::INDEX_NEW);
+        assert_eq!(status("2024/06/02.md"), Status::WT_NEW);
+    }
+
+    #[test]
+    fn test_git__add__skip_idea_pathspecs() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        fs::write(dir.path().join("README.md.gpg"), "").unwrap();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        git.skip_idea_pathspecs();
+
+        assert_eq!(git.add().unwrap(), 0);
+        git.add_pathspec("README.md.gpg");
+        let staged = git.add().unwrap();
+
+        assert_eq!(staged, 1);
+        let status = |path| repo.status_file(Path::new(path)).unwrap();
+        assert_eq!(status("README.md.gpg"), Status::INDEX_NEW);
+        assert_eq!(status("README.md"), Status::WT_NEW);
+    }
+
+    #[test]
+    fn test_git__oversized_files__over_max_bytes() {
+        let mut git = Git::default();
+        let (dir, _repo, _file) = repo_init();
+        fs::write(dir.path().join("README.md"), "## some-idea\n").unwrap();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+
+        assert_eq!(
+            git.oversized_files(4).unwrap(),
+            vec![(String::from("README.md"), 13)]
+        );
+        assert!(git.oversized_files(13).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_git__oversized_files__tracked_by_lfs() {
+        let mut git = Git::default();
+        let (dir, _repo, _file) = repo_init();
+        fs::write(dir.path().join("README.md"), "## some-idea\n").unwrap();
+        fs::write(
+            dir.path().join(".gitattributes"),
+            "*.md filter=lfs diff=lfs merge=lfs -text\n",
+        )
+        .unwrap();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+
+        assert!(git.oversized_files(4).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_git__is_ignored__covered_by_gitignore() {
+        let mut git = Git::default();
+        let (dir, _repo, _file) = repo_init();
+        fs::write(dir.path().join(".gitignore"), "*.md\n").unwrap();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+
+        assert!(git.is_ignored("README.md").unwrap());
+        assert!(git.is_ignored("2024/06/01.md").unwrap());
+        assert!(!git.is_ignored("notes.txt").unwrap());
+    }
+
+    #[test]
+    fn test_git__stash_save__restores_changes_on_pop() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        let notes = dir.path().join("notes.txt");
+        fs::write(&notes, "committed").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("notes.txt")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = repo.signature().unwrap();
+        let parent = find_last_commit(&repo).unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "add notes", &tree, &[&parent])
+            .unwrap();
+        fs::write(&notes, "in progress").unwrap();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+
+        let stashed = git.stash_save().unwrap();
+
+        assert!(stashed);
+        assert_eq!(fs::read_to_string(&notes).unwrap(), "committed");
+        git.stash_pop().unwrap();
+        assert_eq!(fs::read_to_string(&notes).unwrap(), "in progress");
+    }
+
+    #[test]
+    fn test_git__stash_save__nothing_to_stash() {
+        let mut git = Git::default();
+        let (dir, _repo, _file) = repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+
+        let stashed = git.stash_save().unwrap();
+
+        assert!(!stashed);
+    }
+
+    #[test]
+    fn test_git__stash_save__unborn_head() {
+        let mut git = Git::default();
+        let (dir, _repo) = unborn_repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        fs::write(dir.path().join("README.md"), "## My idea").unwrap();
+
+        let stashed = git.stash_save().unwrap();
+
+        assert!(!stashed);
+    }
+
+    #[test]
+    fn test_git__commit__unborn_head_creates_initial_commit() {
+        let mut git = Git::default();
+        let (dir, repo) = unborn_repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        git.checkout_branch("ideas").unwrap();
+        fs::write(dir.path().join("README.md"), "## My idea").unwrap();
+        git.add().unwrap();
+
+        let oid = git.commit("some-msg", "", None, None).unwrap();
+
+        let commit = repo.find_commit(oid).unwrap();
+        assert_eq!(commit.parent_count(), 0);
+        assert_eq!(repo.head().unwrap().name(), Some("refs/heads/ideas"));
+        assert_eq!(git.pending_commits("ideas").unwrap(), 1);
+    }
+
     #[test]
     fn test_git__commit__success() {
         let mut git = Git::default();
@@ -229,10 +1646,986 @@ mod tests {
         assert_eq!(before.unwrap().summary().unwrap(), "initial-msg");
 
         git.add().unwrap();
-        git.commit("some-subject").unwrap();
+        git.commit("some-subject", "", None, None).unwrap();
+
+        let after = find_last_commit(git.repo.as_ref().unwrap()).unwrap();
+        assert_eq!(after.summary().unwrap(), "some-subject");
+        assert_eq!(after.body(), None);
+    }
+
+    #[test]
+    fn test_git__commit__backdated() {
+        let mut git = Git::default();
+        let (dir, _repo, _file) = repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+
+        git.add().unwrap();
+        let time = git2::Time::new(1_700_000_000, 120);
+        git.commit("some-subject", "", Some(time), Some(time))
+            .unwrap();
+
+        let after = find_last_commit(git.repo.as_ref().unwrap()).unwrap();
+        assert_eq!(after.author().when(), time);
+        assert_eq!(after.committer().when(), time);
+    }
+
+    #[test]
+    fn test_git__commit__author_time_only() {
+        let mut git = Git::default();
+        let (dir, _repo, _file) = repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+
+        git.add().unwrap();
+        let time = git2::Time::new(1_500_000_000, -300);
+        git.commit("some-subject", "", Some(time), None).unwrap();
+
+        let after = find_last_commit(git.repo.as_ref().unwrap()).unwrap();
+        assert_eq!(after.author().when(), time);
+        // Committed now
+        assert!(after.committer().when().seconds() > 1_600_000_000);
+    }
+
+    #[test]
+    fn test_git__commit__with_body() {
+        let mut git = Git::default();
+        let (dir, _repo, _file) = repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+
+        git.add().unwrap();
+        git.commit("some-subject", "some-body\nover lines", None, None)
+            .unwrap();
+
+        let after = find_last_commit(git.repo.as_ref().unwrap()).unwrap();
+        assert_eq!(
+            after.message().unwrap(),
+            "some-subject\n\nsome-body\nover lines"
+        );
+        assert_eq!(after.body(), Some("some-body\nover lines"));
+    }
+
+    #[test]
+    fn test_git__diff__success() {
+        let mut git = Git::default();
+        let (dir, _repo, _file) = repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        fs::write(dir.path().join("README.md"), "some-idea\n").unwrap();
+
+        let actual = git.diff().unwrap();
+
+        assert!(actual.contains("+++ b/README.md"));
+        assert!(actual.contains("+some-idea\n"));
+    }
+
+    #[test]
+    fn test_git__diff__unborn_head() {
+        let mut git = Git::default();
+        let (dir, _repo) = unborn_repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        fs::write(dir.path().join("README.md"), "## My idea\n").unwrap();
+
+        let actual = git.diff().unwrap();
+
+        assert!(actual.contains("+## My idea"));
+    }
+
+    #[test]
+    fn test_git__diff__no_changes() {
+        let mut git = Git::default();
+        let (dir, _repo, _file) = repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        git.add().unwrap();
+        git.commit("some-subject", "", None, None).unwrap();
+
+        let actual = git.diff().unwrap();
+
+        assert!(actual.is_empty());
+    }
+
+    #[test]
+    fn test_git__ensure_worktree__main_checkout_untouched() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        let worktree_dir = TempDir::new().unwrap();
+        let worktree_path = worktree_dir.path().join("worktree");
+        git.init(dir.path().to_str().unwrap()).unwrap();
+
+        git.ensure_worktree(worktree_path.to_str().unwrap(), "ideas")
+            .unwrap();
+        git.checkout_branch("ideas").unwrap();
+        fs::write(worktree_path.join("README.md"), "some-idea").unwrap();
+        git.add().unwrap();
+        git.commit("some-subject", "", None, None).unwrap();
+
+        // The main checkout stays on its branch and commit
+        let head = repo.head().unwrap();
+        assert_eq!(head.name().unwrap(), "refs/heads/main");
+        let main_commit = find_last_commit(&repo).unwrap();
+        assert_eq!(main_commit.summary().unwrap(), "initial-msg");
+
+        // The idea branch got the new commit
+        let ideas = repo.find_branch("ideas", BranchType::Local).unwrap();
+        let ideas_commit = ideas.get().peel_to_commit().unwrap();
+        assert_eq!(ideas_commit.summary().unwrap(), "some-subject");
+    }
+
+    #[test]
+    fn test_git__ensure_worktree__reuses_existing_worktree() {
+        let mut git = Git::default();
+        let (dir, _repo, _file) = repo_init();
+        let worktree_dir = TempDir::new().unwrap();
+        let worktree_path = worktree_dir.path().join("worktree");
+        let worktree_path = worktree_path.to_str().unwrap();
+
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        git.ensure_worktree(worktree_path, "ideas").unwrap();
+
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        let actual = git.ensure_worktree(worktree_path, "ideas");
+
+        assert!(actual.is_ok());
+        assert!(git.repo.as_ref().unwrap().is_worktree());
+    }
+
+    #[test]
+    fn test_git__ensure_worktree__foreign_repo__failure() {
+        let mut git = Git::default();
+        let (dir, _repo, _file) = repo_init();
+        let worktree_dir = TempDir::new().unwrap();
+        let worktree_path = worktree_dir.path().join("worktree");
+        Repository::init(&worktree_path).unwrap();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+
+        let actual = git.ensure_worktree(worktree_path.to_str().unwrap(), "ideas");
 
-        let after = find_last_commit(git.repo.as_ref().unwrap());
-        assert_eq!(after.unwrap().summary().unwrap(), "some-subject");
+        assert!(actual
+            .unwrap_err()
+            .message()
+            .contains("isn't a worktree of your idea repo"));
+    }
+
+    #[test]
+    fn test_git__ensure_worktree__other_branch__failure() {
+        let mut git = Git::default();
+        let (dir, _repo, _file) = repo_init();
+        let worktree_dir = TempDir::new().unwrap();
+        let worktree_path = worktree_dir.path().join("worktree");
+        let worktree_path = worktree_path.to_str().unwrap();
+
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        git.ensure_worktree(worktree_path, "ideas").unwrap();
+
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        let actual = git.ensure_worktree(worktree_path, "other-ideas");
+
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn test_git__ensure_worktree__branch_checked_out__failure() {
+        let mut git = Git::default();
+        let (dir, _repo, _file) = repo_init();
+        let worktree_dir = TempDir::new().unwrap();
+        let worktree_path = worktree_dir.path().join("worktree");
+        git.init(dir.path().to_str().unwrap()).unwrap();
+
+        let actual = git.ensure_worktree(worktree_path.to_str().unwrap(), "main");
+
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn test_git__push__rejected__not_fast_forward() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        let (_remote_dir, remote) = remote_init(&repo);
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        git.push("main").unwrap();
+        remote_commit(&remote, "OTHER.md", "## other-machine\n");
+        git.commit("some-msg", "", None, None).unwrap();
+
+        let actual = git.push("main").unwrap_err();
+
+        assert_eq!(actual.code(), git2::ErrorCode::NotFastForward);
+    }
+
+    #[test]
+    fn test_git__pull_branch__rebase() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        let (_remote_dir, remote) = remote_init(&repo);
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        git.push("main").unwrap();
+        let concurrent = remote_commit(&remote, "OTHER.md", "## other-machine\n");
+        fs::write(dir.path().join("README.md"), "## some-idea\n").unwrap();
+        git.add().unwrap();
+        git.commit("some-idea", "", None, None).unwrap();
+
+        let actual = git.pull_branch("main", true).unwrap();
+
+        let tip = repo.find_commit(actual).unwrap();
+        assert_eq!(tip.message(), Some("some-idea"));
+        assert_eq!(tip.parent_ids().collect::<Vec<_>>(), [concurrent]);
+        assert_eq!(repo.head().unwrap().target(), Some(actual));
+        assert!(dir.path().join("OTHER.md").exists());
+        git.push("main").unwrap();
+        assert_eq!(remote.refname_to_id("refs/heads/main").unwrap(), actual);
+    }
+
+    #[test]
+    fn test_git__pull_branch__merge() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        let (_remote_dir, remote) = remote_init(&repo);
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        git.push("main").unwrap();
+        let concurrent = remote_commit(&remote, "OTHER.md", "## other-machine\n");
+        fs::write(dir.path().join("README.md"), "## some-idea\n").unwrap();
+        git.add().unwrap();
+        let idea = git.commit("some-idea", "", None, None).unwrap();
+
+        let actual = git.pull_branch("main", false).unwrap();
+
+        let tip = repo.find_commit(actual).unwrap();
+        assert_eq!(tip.parent_ids().collect::<Vec<_>>(), [idea, concurrent]);
+        assert_eq!(tip.message(), Some("Pull origin/main into main"));
+        assert!(dir.path().join("OTHER.md").exists());
+    }
+
+    #[test]
+    fn test_git__pull_branch__conflict__failure() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        let (_remote_dir, remote) = remote_init(&repo);
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        git.push("main").unwrap();
+        remote_commit(&remote, "README.md", "## other-machine\n");
+        fs::write(dir.path().join("README.md"), "## some-idea\n").unwrap();
+        git.add().unwrap();
+        let idea = git.commit("some-idea", "", None, None).unwrap();
+
+        let actual = git.pull_branch("main", true).unwrap_err();
+
+        assert_eq!(actual.code(), git2::ErrorCode::Conflict);
+        assert!(actual
+            .message()
+            .contains("in README.md, nothing was pulled"));
+        assert_eq!(repo.head().unwrap().target(), Some(idea));
+        assert_eq!(repo.state(), git2::RepositoryState::Clean);
+        let readme = fs::read_to_string(dir.path().join("README.md")).unwrap();
+        assert_eq!(readme, "## some-idea\n");
+    }
+
+    #[test]
+    fn test_git__pull_branch__keyed_ideas__rebase() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        let (_remote_dir, remote) = remote_init(&repo);
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        git.push("main").unwrap();
+        remote_commit(&remote, "README.md", "<!-- id: b -->\n## other-machine\n");
+        fs::write(
+            dir.path().join("README.md"),
+            "<!-- id: a -->\n## some-idea\n",
+        )
+        .unwrap();
+        git.add().unwrap();
+        git.commit("some-idea", "", None, None).unwrap();
+
+        let actual = git.pull_branch("main", true).unwrap();
+
+        let tip = repo.find_commit(actual).unwrap();
+        assert_eq!(tip.message(), Some("some-idea"));
+        let readme = fs::read_to_string(dir.path().join("README.md")).unwrap();
+        // Replayed onto theirs, so theirs is ours
+        let expected = "<!-- id: b -->\n## other-machine\n\n<!-- id: a -->\n## some-idea\n";
+        assert_eq!(readme, expected);
+        assert!(!repo.index().unwrap().has_conflicts());
+    }
+
+    #[test]
+    fn test_git__pull_branch__keyed_ideas__merge() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        let (_remote_dir, remote) = remote_init(&repo);
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        git.push("main").unwrap();
+        let concurrent = remote_commit(&remote, "README.md", "<!-- id: b -->\n## other-machine\n");
+        fs::write(
+            dir.path().join("README.md"),
+            "<!-- id: a -->\n## some-idea\n",
+        )
+        .unwrap();
+        git.add().unwrap();
+        let idea = git.commit("some-idea", "", None, None).unwrap();
+
+        let actual = git.pull_branch("main", false).unwrap();
+
+        let tip = repo.find_commit(actual).unwrap();
+        assert_eq!(tip.parent_ids().collect::<Vec<_>>(), [idea, concurrent]);
+        let readme = fs::read_to_string(dir.path().join("README.md")).unwrap();
+        let expected = "<!-- id: a -->\n## some-idea\n\n<!-- id: b -->\n## other-machine\n";
+        assert_eq!(readme, expected);
+    }
+
+    #[test]
+    fn test_git__pending_commits__ahead_of_upstream() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        let (_remote_dir, _remote) = remote_init(&repo);
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        git.push("main").unwrap();
+        assert_eq!(git.pending_commits("main").unwrap(), 0);
+
+        git.commit("some-msg", "", None, None).unwrap();
+        git.commit("some-other-msg", "", None, None).unwrap();
+
+        assert_eq!(git.pending_commits("main").unwrap(), 2);
+    }
+
+    #[test]
+    fn test_git__pending_commits__never_pushed() {
+        let mut git = Git::default();
+        let (dir, _repo, _file) = repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+
+        let actual = git.pending_commits("main").unwrap();
+
+        assert_eq!(actual, 1);
+    }
+
+    #[test]
+    fn test_git__pending_commits__never_pushed_branch_of_pushed_one() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        let (_remote_dir, _remote) = remote_init(&repo);
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        git.commit("some-msg", "", None, None).unwrap();
+        git.push("main").unwrap();
+        git.checkout_branch("ideas").unwrap();
+        git.commit("some-idea", "", None, None).unwrap();
+
+        let actual = git.pending_commits("ideas").unwrap();
+
+        assert_eq!(actual, 1);
+    }
+
+    #[test]
+    fn test_git__divergence__ahead_and_behind() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        let (_remote_dir, _remote) = remote_init(&repo);
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        let base = repo.head().unwrap().target().unwrap();
+        git.commit("some-msg", "", None, None).unwrap();
+        git.push("main").unwrap();
+        assert_eq!(
+            git.divergence("main").unwrap(),
+            Divergence::Tracking {
+                ahead: 0,
+                behind: 0
+            }
+        );
+
+        // Like a commit pushed from another machine that wasn't pulled
+        repo.reference("refs/heads/main", base, true, "some-reset")
+            .unwrap();
+        git.commit("some-other-msg", "", None, None).unwrap();
+
+        let actual = git.divergence("main").unwrap();
+
+        assert_eq!(
+            actual,
+            Divergence::Tracking {
+                ahead: 1,
+                behind: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_git__commit_diff__nth_commit() {
+        let mut git = Git::default();
+        let (dir, _repo, _file) = repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        git.checkout_branch("main").unwrap();
+        fs::write(dir.path().join("README.md"), "## some-idea\n").unwrap();
+        git.add().unwrap();
+        git.commit("some-idea", "", None, None).unwrap();
+        fs::write(
+            dir.path().join("README.md"),
+            "## some-idea\n## other-idea\n",
+        )
+        .unwrap();
+        git.add().unwrap();
+        let last = git.commit("other-idea", "", None, None).unwrap();
+
+        let actual = git.commit_diff("main", 1).unwrap().unwrap();
+
+        assert_eq!(actual.oid, last);
+        assert_eq!(actual.summary, "other-idea");
+        assert_eq!(actual.hunks.len(), 1);
+        assert_eq!(actual.hunks[0].file, "README.md");
+        assert_eq!(actual.hunks[0].header, "@@ -1 +1,2 @@");
+        assert_eq!(actual.hunks[0].lines, [" ## some-idea", "+## other-idea"]);
+        assert!(actual.patch.contains("+++ b/README.md\n"));
+        assert!(actual.patch.ends_with(" ## some-idea\n+## other-idea\n"));
+
+        let actual = git.commit_diff("main", 2).unwrap().unwrap();
+        assert_eq!(actual.summary, "some-idea");
+        assert_eq!(actual.hunks[0].lines, ["+## some-idea"]);
+
+        // The initial commit is the third, there's no fourth
+        assert!(git.commit_diff("main", 4).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_git__commit_diff__root_commit() {
+        let mut git = Git::default();
+        let (dir, _repo) = unborn_repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        assert!(git.commit_diff("main", 1).unwrap().is_none());
+        fs::write(dir.path().join("README.md"), "## first-idea\n").unwrap();
+        git.add().unwrap();
+        git.commit("first-idea", "", None, None).unwrap();
+
+        let actual = git.commit_diff("main", 1).unwrap().unwrap();
+
+        assert_eq!(actual.hunks[0].header, "@@ -0,0 +1 @@");
+        assert_eq!(actual.hunks[0].lines, ["+## first-idea"]);
+        assert!(actual.patch.contains("new file mode"));
+    }
+
+    #[test]
+    fn test_git__line_commit_diff__commit_that_added_the_line() {
+        let mut git = Git::default();
+        let (dir, _repo, _file) = repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        git.checkout_branch("main").unwrap();
+        fs::write(dir.path().join("README.md"), "## some-idea\n").unwrap();
+        git.add().unwrap();
+        let first = git.commit("some-idea", "", None, None).unwrap();
+        fs::write(
+            dir.path().join("README.md"),
+            "## some-idea\n## other-idea\n",
+        )
+        .unwrap();
+        git.add().unwrap();
+        let second = git.commit("other-idea", "", None, None).unwrap();
+
+        let actual = git.line_commit_diff("README.md", 1).unwrap().unwrap();
+        assert_eq!(actual.oid, first);
+        assert_eq!(actual.hunks[0].lines, ["+## some-idea"]);
+
+        let actual = git.line_commit_diff("README.md", 2).unwrap().unwrap();
+        assert_eq!(actual.oid, second);
+        assert_eq!(actual.summary, "other-idea");
+    }
+
+    #[test]
+    fn test_git__line_commit_diff__never_committed() {
+        let mut git = Git::default();
+        let (dir, _repo, _file) = repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        fs::write(dir.path().join("README.md"), "## some-idea\n").unwrap();
+        git.add().unwrap();
+        git.commit("some-idea", "", None, None).unwrap();
+
+        assert!(git.line_commit_diff("README.md", 2).unwrap().is_none());
+        assert!(git.line_commit_diff("missing.md", 1).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_git__divergence__never_pushed() {
+        let mut git = Git::default();
+        let (dir, _repo, _file) = repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+
+        let actual = git.divergence("main").unwrap();
+
+        assert_eq!(actual, Divergence::NoUpstream(1));
+    }
+
+    #[test]
+    fn test_git__divergence__never_pushed_branch_of_pushed_one() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        let (_remote_dir, _remote) = remote_init(&repo);
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        git.push("main").unwrap();
+        git.checkout_branch("ideas").unwrap();
+
+        let actual = git.divergence("ideas").unwrap();
+
+        assert_eq!(actual, Divergence::NoUpstream(0));
+    }
+
+    #[test]
+    fn test_git__divergence__unborn_head() {
+        let mut git = Git::default();
+        let (dir, _repo) = unborn_repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+
+        let actual = git.divergence("main").unwrap();
+
+        assert_eq!(actual, Divergence::Unborn);
+    }
+
+    #[test]
+    fn test_git__is_clean__untracked_file() {
+        let mut git = Git::default();
+        let (dir, _repo, _file) = repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        fs::write(dir.path().join(".gitignore"), "PRIVATE.md\n").unwrap();
+        git.add_pathspec(".gitignore");
+        git.add().unwrap();
+        git.commit("some-msg", "", None, None).unwrap();
+        fs::write(dir.path().join("PRIVATE.md"), "## some-idea").unwrap();
+        assert!(git.is_clean().unwrap());
+
+        fs::write(dir.path().join("notes.txt"), "some-notes").unwrap();
+
+        assert!(!git.is_clean().unwrap());
+    }
+
+    #[test]
+    fn test_git__branches_ahead_of_upstream__success() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        let (_remote_dir, _remote) = remote_init(&repo);
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        git.push("main").unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.branch("ideas/2024-w22", &head, false).unwrap();
+        repo.branch("ideas/2024-w23", &head, false).unwrap();
+        git.checkout_branch("ideas/2024-w23").unwrap();
+        git.commit("some-msg", "", None, None).unwrap();
+
+        let actual = git.branches_ahead_of_upstream().unwrap();
+        // main is pushed, so a never pushed branch only counts its own commits
+        let expected = vec![("ideas/2024-w23".to_string(), 1)];
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_git__pending_commits__unborn_head() {
+        let mut git = Git::default();
+        let (dir, _repo) = unborn_repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+
+        let actual = git.pending_commits("main").unwrap();
+
+        assert_eq!(actual, 0);
+        assert_eq!(git.head_branch().unwrap(), Some("main".to_string()));
+    }
+
+    #[test]
+    fn test_git__remote_host__success() {
+        let cases = [
+            ("git@github.com:user/ideas.git", Some(("github.com", 22))),
+            ("github.com:user/ideas.git", Some(("github.com", 22))),
+            (
+                "ssh://git@example.com:2222/ideas.git",
+                Some(("example.com", 2222)),
+            ),
+            (
+                "https://user@github.com/user/ideas",
+                Some(("github.com", 443)),
+            ),
+            ("http://localhost:8080/ideas", Some(("localhost", 8080))),
+            ("git://example.com/ideas", Some(("example.com", 9418))),
+            ("ssh://[::1]:2222/ideas", Some(("::1", 2222))),
+            ("/absolute/path/to/ideas", None),
+            ("file:///absolute/path/to/ideas", None),
+            ("./relative:path", None),
+        ];
+
+        for (url, expected) in cases {
+            let actual = remote_host(url);
+            let expected = expected.map(|(host, port)| (host.to_string(), port));
+            assert_eq!(actual, expected, "{}", url);
+        }
+    }
+
+    #[test]
+    fn test_git__github_repo() {
+        let cases = [
+            ("git@github.com:owner/ideas.git", Some("owner/ideas")),
+            ("https://github.com/owner/ideas", Some("owner/ideas")),
+            (
+                "https://user@github.com/owner/ideas.git/",
+                Some("owner/ideas"),
+            ),
+            ("ssh://git@github.com/owner/ideas.git", Some("owner/ideas")),
+            ("git@gitlab.com:owner/ideas.git", None),
+            ("https://github.com/owner", None),
+            ("https://github.com/owner/ideas/extra", None),
+            ("/absolute/path/to/ideas", None),
+        ];
+
+        for (url, expected) in cases {
+            let actual = github_repo(url);
+            assert_eq!(actual.as_deref(), expected, "{}", url);
+        }
+    }
+
+    #[test]
+    fn test_git__push_to__other_remote() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        let fork_dir = TempDir::new().unwrap();
+        let fork = Repository::init_bare(fork_dir.path()).unwrap();
+        repo.remote("fork", fork_dir.path().to_str().unwrap())
+            .unwrap();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+
+        git.push_to("fork", "main").unwrap();
+
+        let actual = fork.refname_to_id("refs/heads/main").unwrap();
+        assert_eq!(actual, find_last_commit(&repo).unwrap().id());
+        assert_eq!(
+            git.remote_url("fork").unwrap(),
+            fork_dir.path().to_str().unwrap()
+        );
+        assert!(git.remote_url("upstream").is_err());
+    }
+
+    #[test]
+    fn test_git__probe_remote__local_remote() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        let (_remote_dir, _remote) = remote_init(&repo);
+        git.init(dir.path().to_str().unwrap()).unwrap();
+
+        let actual = git.probe_remote();
+
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn test_git__probe_remote__unreachable() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        // Nothing listens on port 1
+        repo.remote("origin", "http://127.0.0.1:1/ideas.git")
+            .unwrap();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+
+        let actual = git.probe_remote().unwrap_err();
+
+        assert!(actual.message().starts_with("Could not reach 127.0.0.1:1"));
+    }
+
+    #[test]
+    fn test_git__resolve_within__success() {
+        let actual = resolve_within("127.0.0.1", 22, Duration::from_secs(2));
+
+        assert_eq!(actual, Some("127.0.0.1:22".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_git__merge_branch__fast_forward() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        git.checkout_branch("ideas").unwrap();
+        fs::write(dir.path().join("README.md"), "## some-idea\n").unwrap();
+        git.add().unwrap();
+        let idea = git.commit("some-idea", "", None, None).unwrap();
+
+        let actual = git.merge_branch("ideas", "main").unwrap();
+
+        assert_eq!(actual, MergeOutcome::FastForward);
+        assert_eq!(repo.head().unwrap().shorthand(), Some("main"));
+        assert_eq!(repo.refname_to_id("refs/heads/main").unwrap(), idea);
+    }
+
+    #[test]
+    fn test_git__merge_branch__up_to_date() {
+        let mut git = Git::default();
+        let (dir, _repo, _file) = repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        git.checkout_branch("ideas").unwrap();
+
+        let actual = git.merge_branch("ideas", "main").unwrap();
+
+        assert_eq!(actual, MergeOutcome::UpToDate);
+    }
+
+    #[test]
+    fn test_git__merge_branch__diverged_creates_merge_commit() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        git.checkout_branch("ideas").unwrap();
+        fs::write(dir.path().join("README.md"), "## some-idea\n").unwrap();
+        git.add().unwrap();
+        git.commit("some-idea", "", None, None).unwrap();
+        git.checkout_branch("main").unwrap();
+        git.add_pathspec("NOTES.md");
+        fs::write(dir.path().join("NOTES.md"), "some-notes\n").unwrap();
+        git.add().unwrap();
+        git.commit("some-notes", "", None, None).unwrap();
+
+        let actual = git.merge_branch("ideas", "main").unwrap();
+
+        assert_eq!(actual, MergeOutcome::MergeCommit);
+        let merge = find_last_commit(&repo).unwrap();
+        assert_eq!(merge.parent_count(), 2);
+        assert_eq!(merge.message(), Some("Merge branch 'ideas' into main"));
+        let readme = fs::read_to_string(dir.path().join("README.md")).unwrap();
+        assert_eq!(readme, "## some-idea\n");
+    }
+
+    #[test]
+    fn test_git__merge_branch__conflict_is_aborted() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        git.checkout_branch("ideas").unwrap();
+        fs::write(dir.path().join("README.md"), "## some-idea\n").unwrap();
+        git.add().unwrap();
+        git.commit("some-idea", "", None, None).unwrap();
+        git.checkout_branch("main").unwrap();
+        fs::write(dir.path().join("README.md"), "## some-other-idea\n").unwrap();
+        git.add().unwrap();
+        let main = git.commit("some-other-idea", "", None, None).unwrap();
+
+        let actual = git.merge_branch("ideas", "main").unwrap_err();
+
+        assert_eq!(
+            actual.message(),
+            "Merging 'ideas' into 'main' conflicts in README.md, nothing was merged"
+        );
+        assert_eq!(find_last_commit(&repo).unwrap().id(), main);
+        let readme = fs::read_to_string(dir.path().join("README.md")).unwrap();
+        assert_eq!(readme, "## some-other-idea\n");
+    }
+
+    #[test]
+    fn test_git__merge_branch__conflict_keeps_head() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        git.checkout_branch("ideas").unwrap();
+        git.checkout_branch("main").unwrap();
+        fs::write(dir.path().join("README.md"), "## some-other-idea\n").unwrap();
+        git.add().unwrap();
+        git.commit("some-other-idea", "", None, None).unwrap();
+        git.checkout_branch("ideas").unwrap();
+        fs::write(dir.path().join("README.md"), "## some-idea\n").unwrap();
+        git.add().unwrap();
+        let ideas = git.commit("some-idea", "", None, None).unwrap();
+
+        git.merge_branch("ideas", "main").unwrap_err();
+
+        let head = repo.head().unwrap();
+        assert_eq!(head.name(), Some("refs/heads/ideas"));
+        assert_eq!(head.target(), Some(ideas));
+        let readme = fs::read_to_string(dir.path().join("README.md")).unwrap();
+        assert_eq!(readme, "## some-idea\n");
+    }
+
+    #[test]
+    fn test_git__merge_branch__missing_branch() {
+        let mut git = Git::default();
+        let (dir, _repo, _file) = repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+
+        let actual = git.merge_branch("ideas", "main").unwrap_err();
+
+        assert_eq!(actual.message(), "Branch 'ideas' doesn't exist");
+    }
+
+    #[test]
+    fn test_git__finish_merge__commits_resolved_merge() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        let (ours, theirs) = conflicting_merge(&mut git, &repo, dir.path());
+        fs::write(dir.path().join("README.md"), "## resolved\n").unwrap();
+        git.add().unwrap();
+
+        let actual = git.finish_merge().unwrap().unwrap();
+
+        let merge = repo.find_commit(actual).unwrap();
+        let parents: Vec<git2::Oid> = merge.parent_ids().collect();
+        assert_eq!(parents, vec![ours, theirs]);
+        assert_eq!(repo.state(), git2::RepositoryState::Clean);
+    }
+
+    #[test]
+    fn test_git__finish_merge__unresolved_conflicts() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        conflicting_merge(&mut git, &repo, dir.path());
+
+        let actual = git.finish_merge().unwrap_err();
+
+        assert_eq!(
+            actual.message(),
+            "Resolve the conflicts in README.md and stage them first"
+        );
+        assert_eq!(repo.state(), git2::RepositoryState::Merge);
+    }
+
+    #[test]
+    fn test_git__finish_merge__no_merge_in_progress() {
+        let mut git = Git::default();
+        let (dir, _repo, _file) = repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+
+        assert_eq!(git.finish_merge().unwrap(), None);
+    }
+
+    // Leave a conflicting merge of `ideas` into `main` in progress, returns
+    // both heads
+    #[test]
+    fn test_git__line_times__committed_and_changed_lines() {
+        let mut git = Git::default();
+        let (dir, _repo, file) = repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        fs::write(file.path(), "## Old idea\n\nbody\n").unwrap();
+        git.add().unwrap();
+        let time = git2::Time::new(1_700_000_000, 0);
+        git.commit("some-msg", "", Some(time), Some(time)).unwrap();
+        fs::write(file.path(), "## New idea\n\n## Old idea\n\nbody\n").unwrap();
+
+        let actual = git.line_times("README.md").unwrap();
+        let old = Some(1_700_000_000);
+
+        assert_eq!(actual, vec![None, None, old, old, old]);
+    }
+
+    #[test]
+    fn test_git__line_times__unchanged_file() {
+        let mut git = Git::default();
+        let (dir, _repo, file) = repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        fs::write(file.path(), "## Old idea\n").unwrap();
+        git.add().unwrap();
+        let time = Some(git2::Time::new(1_700_000_000, 0));
+        git.commit("some-msg", "", time, time).unwrap();
+
+        let actual = git.line_times("README.md").unwrap();
+
+        assert_eq!(actual, vec![Some(1_700_000_000)]);
+    }
+
+    #[test]
+    fn test_git__line_times__never_committed() {
+        let mut git = Git::default();
+        let (dir, _repo, file) = repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        fs::write(file.path(), "## New idea\n").unwrap();
+
+        let actual = git.line_times("README.md").unwrap();
+
+        assert_eq!(actual, vec![]);
+    }
+
+    #[test]
+    fn test_git__line_times__unborn_head() {
+        let mut git = Git::default();
+        let (dir, _repo) = unborn_repo_init();
+        git.init(dir.path().to_str().unwrap()).unwrap();
+
+        let actual = git.line_times("README.md").unwrap();
+
+        assert_eq!(actual, vec![]);
+    }
+
+    #[test]
+    fn test_git__remote_default_branch__head_of_remote() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        let (_remote_dir, remote) = remote_init(&repo);
+        git.init(dir.path().to_str().unwrap()).unwrap();
+        git.push("main").unwrap();
+        remote.set_head("refs/heads/main").unwrap();
+
+        let actual = git.remote_default_branch().unwrap();
+
+        assert_eq!(actual, Some("main".to_string()));
+    }
+
+    #[test]
+    fn test_git__remote_default_branch__empty_remote() {
+        let mut git = Git::default();
+        let (dir, repo, _file) = repo_init();
+        let (_remote_dir, _remote) = remote_init(&repo);
+        git.init(dir.path().to_str().unwrap()).unwrap();
+
+        let actual = git.remote_default_branch().unwrap();
+
+        assert_eq!(actual, None);
+    }
+
+    fn conflicting_merge(git: &mut Git, repo: &Repository, dir: &Path) -> (git2::Oid, git2::Oid) {
+        git.checkout_branch("ideas").unwrap();
+        fs::write(dir.join("README.md"), "## some-idea\n").unwrap();
+        git.add().unwrap();
+        let theirs = git.commit("some-idea", "", None, None).unwrap();
+        git.checkout_branch("main").unwrap();
+        fs::write(dir.join("README.md"), "## some-other-idea\n").unwrap();
+        git.add().unwrap();
+        let ours = git.commit("some-other-idea", "", None, None).unwrap();
+
+        let annotated = repo.find_annotated_commit(theirs).unwrap();
+        repo.merge(&[&annotated], None, None).unwrap();
+        (ours, theirs)
+    }
+
+    fn remote_init(repo: &Repository) -> (TempDir, Repository) {
+        let td = TempDir::new().unwrap();
+        let remote = Repository::init_bare(td.path()).unwrap();
+        {
+            let mut config = remote.config().unwrap();
+            config.set_str("user.name", "some-name").unwrap();
+            config.set_str("user.email", "some-email").unwrap();
+        }
+        repo.remote("origin", td.path().to_str().unwrap()).unwrap();
+        // Return dir to not drop it and make it disappear
+        (td, remote)
+    }
+
+    // Like a commit pushed from another machine, writes `file` on top of main
+    fn remote_commit(remote: &Repository, file: &str, content: &str) -> git2::Oid {
+        let parent = remote
+            .find_reference("refs/heads/main")
+            .unwrap()
+            .peel_to_commit()
+            .unwrap();
+        let blob = remote.blob(content.as_bytes()).unwrap();
+        let mut builder = remote.treebuilder(Some(&parent.tree().unwrap())).unwrap();
+        builder.insert(file, blob, 0o100644).unwrap();
+        let tree = remote.find_tree(builder.write().unwrap()).unwrap();
+        let sig = remote.signature().unwrap();
+        remote
+            .commit(
+                Some("refs/heads/main"),
+                &sig,
+                &sig,
+                "other-machine-msg",
+                &tree,
+                &[&parent],
+            )
+            .unwrap()
+    }
+
+    // A repo right after `git init`, without any commits
+    fn unborn_repo_init() -> (TempDir, Repository) {
+        let td = TempDir::new().unwrap();
+        let mut opts = RepositoryInitOptions::new();
+        opts.initial_head("main");
+        let repo = Repository::init_opts(td.path(), &opts).unwrap();
+        {
+            let mut config = repo.config().unwrap();
+            config.set_str("user.name", "some-name").unwrap();
+            config.set_str("user.email", "some-email").unwrap();
+        }
+        (td, repo)
     }
 
     fn repo_init() -> (TempDir, Repository, NamedTempFile) {
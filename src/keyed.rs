@@ -0,0 +1,200 @@
+// With the `keyed` append_strategy every idea starts with an id header like
+// `<!-- id: 0f8e6c1a-... -->`, so ideas appended on two machines can be merged
+// by their id instead of conflicting
+
+const ID_PREFIX: &str = "<!-- id: ";
+const ID_SUFFIX: &str = " -->";
+
+// The header line starting the idea with `id`, without a line ending
+pub fn id_header(id: &str) -> String {
+    format!("{}{}{}", ID_PREFIX, id, ID_SUFFIX)
+}
+
+// A version 4 UUID made of 128 random bits
+pub fn format_uuid(high: u64, low: u64) -> String {
+    let high = (high & !0xF000) | 0x4000;
+    let low = (low & 0x3FFF_FFFF_FFFF_FFFF) | 0x8000_0000_0000_0000;
+    format!(
+        "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+        high >> 32,
+        (high >> 16) & 0xFFFF,
+        high & 0xFFFF,
+        low >> 48,
+        low & 0xFFFF_FFFF_FFFF
+    )
+}
+
+// Three-way merge of an idea file idea by idea. Ideas added on either side are
+// all kept, ours first, and an idea changed or removed on one side only takes
+// that change. None when either side has no keyed ideas, an id is used twice,
+// or both sides changed the same idea or what comes before the first one
+pub fn merge_keyed(ancestor: &str, ours: &str, theirs: &str) -> Option<String> {
+    let (ours_preamble, ours_ideas) = split_keyed(ours)?;
+    let (theirs_preamble, theirs_ideas) = split_keyed(theirs)?;
+    if ours_ideas.is_empty() || theirs_ideas.is_empty() {
+        return None;
+    }
+    let (ancestor_preamble, ancestor_ideas) = split_keyed(ancestor)?;
+
+    let lookup = |ideas: &Vec<(String, String)>, id: &str| {
+        ideas
+            .iter()
+            .find(|(idea_id, _)| idea_id == id)
+            .map(|(_, text)| text.clone())
+    };
+
+    let mut parts = vec![];
+    let preamble = merge_part(
+        Some(ancestor_preamble),
+        Some(ours_preamble),
+        Some(theirs_preamble),
+    )?;
+    parts.extend(preamble.filter(|preamble| !preamble.is_empty()));
+
+    let theirs_only = theirs_ideas
+        .iter()
+        .filter(|(id, _)| lookup(&ours_ideas, id).is_none());
+    for (id, _) in ours_ideas.iter().chain(theirs_only) {
+        let merged = merge_part(
+            lookup(&ancestor_ideas, id),
+            lookup(&ours_ideas, id),
+            lookup(&theirs_ideas, id),
+        )?;
+        parts.extend(merged);
+    }
+
+    if parts.is_empty() {
+        return Some(String::new());
+    }
+    let line_ending = if ours.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut merged = parts.join(&line_ending.repeat(2));
+    merged.push_str(line_ending);
+    Some(merged)
+}
+
+// One side's change of a part of the file, Some(None) if it was removed and
+// None if both sides changed it
+fn merge_part(
+    ancestor: Option<String>,
+    ours: Option<String>,
+    theirs: Option<String>,
+) -> Option<Option<String>> {
+    if ours == theirs || ancestor == ours {
+        Some(theirs)
+    } else if ancestor == theirs {
+        Some(ours)
+    } else {
+        None
+    }
+}
+
+// What comes before the first keyed idea, and each idea by its id. Trailing
+// whitespace is dropped, so an empty line that separates the next idea doesn't
+// count as a change. None if an id is used twice
+fn split_keyed(text: &str) -> Option<(String, Vec<(String, String)>)> {
+    let mut preamble = String::new();
+    let mut ideas: Vec<(String, String)> = vec![];
+    for line in text.split_inclusive('\n') {
+        let id = line
+            .trim_end()
+            .strip_prefix(ID_PREFIX)
+            .and_then(|rest| rest.strip_suffix(ID_SUFFIX))
+            .map(str::trim);
+        match (id, ideas.last_mut()) {
+            (Some(id), _) => {
+                if ideas.iter().any(|(idea_id, _)| idea_id == id) {
+                    return None;
+                }
+                ideas.push((id.to_string(), line.to_string()));
+            }
+            (None, Some((_, idea))) => idea.push_str(line),
+            (None, None) => preamble.push_str(line),
+        }
+    }
+
+    let trim = |text: &mut String| text.truncate(text.trim_end().len());
+    trim(&mut preamble);
+    ideas.iter_mut().for_each(|(_, idea)| trim(idea));
+    Some((preamble, ideas))
+}
+
+#[allow(non_snake_case)]
+#[cfg(test)]
+mod tests {
+    use crate::keyed::{format_uuid, id_header, merge_keyed};
+
+    #[test]
+    fn test_keyed__format_uuid__is_version_4() {
+        let actual = format_uuid(u64::MAX, 0);
+
+        assert_eq!(actual, "ffffffff-ffff-4fff-8000-000000000000");
+        assert_eq!(id_header("abc"), "<!-- id: abc -->");
+    }
+
+    #[test]
+    fn test_keyed__merge_keyed__union_of_added_ideas() {
+        let ancestor = "# Ideas\n\n<!-- id: a -->\n## Boat\n";
+        let ours = "# Ideas\n\n<!-- id: a -->\n## Boat\n\n<!-- id: b -->\n## Sail\n\nWith a map\n";
+        let theirs = "# Ideas\n\n<!-- id: a -->\n## Boat\n\n<!-- id: c -->\n## Fence\n";
+
+        let actual = merge_keyed(ancestor, ours, theirs);
+        let expected = "# Ideas\n\n<!-- id: a -->\n## Boat\n\n<!-- id: b -->\n## Sail\n\nWith a map\n\n<!-- id: c -->\n## Fence\n";
+
+        assert_eq!(actual.as_deref(), Some(expected));
+    }
+
+    #[test]
+    fn test_keyed__merge_keyed__changes_of_one_side() {
+        let ancestor = "<!-- id: a -->\n## Boat\n\n<!-- id: b -->\n## Sail\n";
+        // Ours removed b, theirs edited a and added c
+        let ours = "<!-- id: a -->\n## Boat\n";
+        let theirs =
+            "<!-- id: a -->\n## Boat\n\nA big one\n\n<!-- id: b -->\n## Sail\n\n<!-- id: c -->\n## Fence\n";
+
+        let actual = merge_keyed(ancestor, ours, theirs);
+        let expected = "<!-- id: a -->\n## Boat\n\nA big one\n\n<!-- id: c -->\n## Fence\n";
+
+        assert_eq!(actual.as_deref(), Some(expected));
+    }
+
+    #[test]
+    fn test_keyed__merge_keyed__keeps_crlf() {
+        let ours = "<!-- id: a -->\r\n## Boat\r\n";
+        let theirs = "<!-- id: b -->\r\n## Sail\r\n";
+
+        let actual = merge_keyed("", ours, theirs);
+        let expected = "<!-- id: a -->\r\n## Boat\r\n\r\n<!-- id: b -->\r\n## Sail\r\n";
+
+        assert_eq!(actual.as_deref(), Some(expected));
+    }
+
+    #[test]
+    fn test_keyed__merge_keyed__cannot_merge() {
+        let ancestor = "<!-- id: a -->\n## Boat\n";
+        let cases = [
+            // Both changed the same idea
+            (
+                "<!-- id: a -->\n## Boat\n\nRed\n",
+                "<!-- id: a -->\n## Boat\n\nBlue\n",
+            ),
+            // Both changed what comes before the ideas
+            (
+                "# Mine\n\n<!-- id: a -->\n## Boat\n",
+                "# Yours\n\n<!-- id: a -->\n## Boat\n",
+            ),
+            // An id used twice
+            (
+                "<!-- id: a -->\n## Boat\n\n<!-- id: a -->\n## Sail\n",
+                "<!-- id: a -->\n## Boat\n\n<!-- id: c -->\n## Fence\n",
+            ),
+            // Not keyed
+            ("## Boat\n\n## Sail\n", "## Boat\n\n## Fence\n"),
+        ];
+
+        for (ours, theirs) in cases {
+            let actual = merge_keyed(ancestor, ours, theirs);
+
+            assert_eq!(actual, None, "{:?} {:?}", ours, theirs);
+        }
+    }
+}
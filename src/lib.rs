@@ -3,22 +3,83 @@ extern crate dirs;
 extern crate log;
 extern crate core;
 
-use std::io;
-use std::io::{Error, ErrorKind};
-
-use crate::config_manager::ConfigManagement;
-use crate::config_manager::ConfigType::Repo;
-use crate::git::GitManagement;
+use crate::browser::Browsed;
+use crate::clock::Clock;
+use crate::config_manager::ConfigType::{
+    AppendStrategy, AskOpenEditor, AuditLog, AutoStash, Backend, BackgroundPush, Branch,
+    CaptureContext, Categories, DateLayout, DebounceSecs, Editor, EncryptTo, FileExtension,
+    FileNameTemplate, Footer, ForkRemote, GithubRepo, MaxBlobBytes, MaxFileBytes, MaxSubjectLen,
+    MultilineSummary, Notify, OpenAtEnd, PushStrategy, Repo, UpstreamRemote, WarnDefaultBranch,
+    Worktree,
+};
+use crate::config_manager::{ConfigManagement, RepoCache};
+use crate::error::EurekaError;
+use crate::file_handler::{FileManagement, Heading};
+use crate::git::{github_repo, Divergence, GitManagement, MergeOutcome};
+use crate::keyed::format_uuid;
 use crate::printer::{Print, PrintColor};
 use crate::program_access::ProgramOpener;
 use crate::reader::ReadInput;
-use std::path::Path;
+use crate::sanitize::{sanitize_filename, sanitize_ref_component};
+use crate::sink::IdeaSink;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, TimeZone};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use std::{env, io};
 
+pub mod browser;
+pub mod clock;
 pub mod config_manager;
+pub mod error;
+pub mod file_handler;
 pub mod git;
+pub mod keyed;
 pub mod printer;
 pub mod program_access;
 pub mod reader;
+pub mod sanitize;
+pub mod sink;
+
+const BRANCH_NAME: &str = "main";
+const WORKTREE_DIR_NAME: &str = "worktree";
+const PREVIEW_MAX_LINES: usize = 200;
+const PRIVATE_FILE_NAME: &str = "PRIVATE.md";
+const COMMIT_BODY_FILE_NAME: &str = "COMMIT_BODY";
+const ISSUE_BODY_FILE_NAME: &str = "ISSUE_BODY.md";
+const AUDIT_LOG_FILE_NAME: &str = "eureka.log";
+// Held while pushing when `background_push` is on, see `acquire_push_lock`
+const PUSH_LOCK_FILE_NAME: &str = "push.lock";
+// How long a run waits for a background push before giving up on the lock
+const PUSH_LOCK_TIMEOUT_SECS: u64 = 60;
+const QUEUE_FILE_NAME: &str = "queue";
+const ARCHIVE_FILE_NAME: &str = "ARCHIVE.md";
+const IDEA_REPO_MARKER: &str = ".eureka-idea-repo";
+const VERIFY_SETUP_SUBJECT: &str = "Verify that eureka can push";
+const FRONT_MATTER_TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%:z";
+const DEFAULT_FILE_EXTENSION: &str = "md";
+// Files over 10 MiB are only committed when Git LFS tracks them
+const DEFAULT_MAX_BLOB_BYTES: u64 = 10 * 1024 * 1024;
+// Git's convention for the length of a commit subject
+const DEFAULT_MAX_SUBJECT_LEN: usize = 72;
+// How deep below $EUREKA_SCAN_DIR (or $HOME) setup looks for git repos
+const REPO_SCAN_DEPTH: usize = 3;
+// Wrong answers to a setup prompt before the setup gives up
+const SETUP_MAX_ATTEMPTS: usize = 5;
+// How many ideas `--search --fuzzy` prints, closest first
+const FUZZY_MAX_RESULTS: usize = 10;
+// Ideas scoring below this (out of 100) don't match `--search --fuzzy`
+const FUZZY_MIN_SCORE: usize = 50;
+// Slugs of long summaries are cut, to keep file names short
+const MAX_SLUG_LEN: usize = 60;
+const UNTAGGED: &str = "(untagged)";
+const NO_IDEAS_HINT: &str = "No ideas stored yet, run eureka to capture your first one";
+const WELCOME_USAGE: &str =
+    "Run `eureka` to capture an idea, it's committed and pushed to your idea repo.
+View your ideas with `eureka --view`, and see every option with `eureka --help`.";
 
 pub struct Eureka<
     CM: ConfigManagement,
@@ -26,43 +87,534 @@ pub struct Eureka<
     R: ReadInput,
     G: GitManagement,
     PO: ProgramOpener,
+    FH: FileManagement,
+    C: Clock,
+    S: IdeaSink,
 > {
     cm: CM,
     printer: W,
     reader: R,
     git: G,
     program_opener: PO,
+    file_handler: FH,
+    clock: C,
+    sink: S,
+    // Trace every git step, set from `EurekaOptions::verbose`
+    verbose: bool,
+    // Uncommitted changes of the idea repo were stashed, see `auto_stash`
+    stashed: bool,
+    // Capture into the repo of the current directory, set from `EurekaOptions::here`
+    here: bool,
+    // The time to date the idea to instead of now, set from `EurekaOptions::date`
+    backdate: Option<DateTime<Local>>,
+    // Ignore what earlier runs cached, set from `EurekaOptions::no_cache`
+    no_cache: bool,
+    // What's known about the idea repo once it's opened, see `open_repo`
+    cache: Option<RepoCache>,
+    // This run holds the push lock, see `acquire_push_lock`
+    holds_push_lock: bool,
+    // Idea files decrypted and not encrypted again yet, see `decrypt_idea_file`
+    plaintext: Vec<IdeaTarget>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct EurekaOptions {
+    // Print the banner and a short usage summary, nothing else
+    pub welcome: bool,
+
+    // Print the eureka and libgit2 versions, and libgit2's features, as JSON
+    pub version_info: bool,
+
     // Clear the stored config
     pub clear_config: bool,
 
+    // Run the whole first time setup again, with the current values as
+    // defaults, without capturing an idea
+    pub setup: bool,
+
+    // After setup, commit and push the idea repo marker to check that ideas
+    // can be pushed. On its own it only does the check
+    pub verify_setup: bool,
+
     // Open idea document with $PAGER (fall back to `less`)
     pub view: bool,
+
+    // Show the diff of the idea and ask for confirmation before committing
+    pub preview: bool,
+
+    // Use this as idea summary instead of asking for it
+    pub summary: Option<String>,
+
+    // Use the clipboard contents as idea body instead of opening $EDITOR
+    pub clipboard: bool,
+
+    // Read the summary and then the body at one prompt instead of opening $EDITOR
+    pub inline: bool,
+
+    // Add to the existing idea with this title instead of writing a new one
+    pub append_to: Option<String>,
+
+    // Print the number of stored ideas
+    pub count: bool,
+
+    // Commit the idea without pushing it, see `flush`
+    pub no_push: bool,
+
+    // Push all committed ideas that haven't been pushed yet
+    pub flush: bool,
+
+    // Like `flush`, for every local branch matching the idea branch
+    pub flush_all: bool,
+
+    // Merge the idea branch into this branch and push it
+    pub merge_into: Option<String>,
+
+    // Commit the merge left in progress in the idea repo and push it
+    pub continue_merge: bool,
+
+    // Commit and push the uncommitted changes to the idea files, e.g. of a
+    // run that stopped before committing, without opening the editor
+    pub replay: bool,
+
+    // Print how the idea branch compares to origin and if the repo is clean
+    pub status: bool,
+
+    // Print each git step and how long it took
+    pub verbose: bool,
+
+    // Print a random stored idea and offer to open it
+    pub random: bool,
+
+    // Save the idea to a gitignored file, without any git operations
+    pub private: bool,
+
+    // Skip checking that the remote can be reached before capturing
+    pub offline: bool,
+
+    // Resolve the idea repo and origin's default branch again instead of
+    // using the cache under the config dir, which is then written anew
+    pub no_cache: bool,
+
+    // Open a second editor buffer for the commit body after the idea is written
+    pub commit_body: bool,
+
+    // Add this as a bullet to the idea file and commit it, without any prompts
+    pub append: Option<String>,
+
+    // List the titles of the stored ideas
+    pub list: bool,
+
+    // Group the listed ideas by the front matter tags of their file
+    pub by_tag: bool,
+
+    // Print the ideas whose title contains this, ignoring case
+    pub search: Option<String>,
+
+    // Rank the `search` results by how closely their title matches, allowing typos
+    pub fuzzy: bool,
+
+    // Browse the stored ideas in a full-screen list, to open them or show their diff
+    pub browse: bool,
+
+    // Print what the Nth most recent idea commit changed, 1 is the last one
+    pub diff: Option<String>,
+
+    // Print `diff` as JSON, with its hunks
+    pub json: bool,
+
+    // Move ideas last changed more than this many days ago to the archive file
+    pub archive_older_than: Option<String>,
+
+    // Use the repo of the current directory, on its current branch, for this run
+    pub here: bool,
+
+    // Date the idea, and its commit, to this ISO 8601 date instead of now
+    pub date: Option<String>,
+
+    // Add this to the queue under the config dir, without touching git
+    pub queue: Option<String>,
+
+    // Commit every queued idea, push them once and empty the queue
+    pub process_queue: bool,
+
+    // Push this branch and release the push lock, for the detached run a
+    // capture with `background_push` starts. Not in `describe`
+    pub background_push: Option<String>,
+}
+
+// How an option is given on the command line
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum OptionKind {
+    // On when given, off by default
+    Flag,
+    // Takes a value, shown with this name in help, e.g. `--summary <SUMMARY>`
+    Value(&'static str),
+}
+
+// One command line option, see `EurekaOptions::describe`. `--porcelain` is read
+// by the binary, not `EurekaOptions`
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct OptionDescription {
+    // Long name, e.g. `no-push` for `--no-push`
+    pub name: &'static str,
+    pub short: Option<char>,
+    pub kind: OptionKind,
+    // One line for help output, with the default of whatever it depends on,
+    // e.g. `main` for the idea branch
+    pub description: &'static str,
+}
+
+const OPTION_DESCRIPTIONS: [OptionDescription; 39] = [
+    OptionDescription {
+        name: "clear-config",
+        short: None,
+        kind: OptionKind::Flag,
+        description: "Clear your stored configuration",
+    },
+    OptionDescription {
+        name: "setup",
+        short: None,
+        kind: OptionKind::Flag,
+        description: "Run the first time setup again, suggesting your current values",
+    },
+    OptionDescription {
+        name: "verify-setup",
+        short: None,
+        kind: OptionKind::Flag,
+        description:
+            "Push a marker commit to origin to check that your ideas can be pushed, after setup if it runs",
+    },
+    OptionDescription {
+        name: "view",
+        short: Some('v'),
+        kind: OptionKind::Flag,
+        description: "View ideas with your $PAGER env variable. If unset use less",
+    },
+    OptionDescription {
+        name: "preview",
+        short: None,
+        kind: OptionKind::Flag,
+        description: "Preview the diff of your idea and confirm before committing",
+    },
+    OptionDescription {
+        name: "summary",
+        short: None,
+        kind: OptionKind::Value("SUMMARY"),
+        description: "Summary of your idea. If unset you will be asked for it",
+    },
+    OptionDescription {
+        name: "clipboard",
+        short: None,
+        kind: OptionKind::Flag,
+        description: "Use your clipboard as idea instead of your $EDITOR",
+    },
+    OptionDescription {
+        name: "inline",
+        short: None,
+        kind: OptionKind::Flag,
+        description: "Type your summary and then its body at one prompt, without your $EDITOR",
+    },
+    OptionDescription {
+        name: "append-to",
+        short: None,
+        kind: OptionKind::Value("TITLE"),
+        description: "Add more to the existing idea with this title in your $EDITOR",
+    },
+    OptionDescription {
+        name: "count",
+        short: None,
+        kind: OptionKind::Flag,
+        description: "Print the number of ideas stored on your idea branch, main by default",
+    },
+    OptionDescription {
+        name: "no-push",
+        short: None,
+        kind: OptionKind::Flag,
+        description: "Commit your idea without pushing it, by default it's pushed to origin",
+    },
+    OptionDescription {
+        name: "flush",
+        short: None,
+        kind: OptionKind::Flag,
+        description: "Push all ideas committed with --no-push to origin, with your push_strategy (plain by default)",
+    },
+    OptionDescription {
+        name: "flush-all",
+        short: None,
+        kind: OptionKind::Flag,
+        description: "Push every local idea branch that is ahead of origin",
+    },
+    OptionDescription {
+        name: "merge-into",
+        short: None,
+        kind: OptionKind::Value("BRANCH"),
+        description: "Merge your idea branch, main by default, into BRANCH and push it to origin",
+    },
+    OptionDescription {
+        name: "continue",
+        short: None,
+        kind: OptionKind::Flag,
+        description: "Commit the merge you resolved in your idea repo and push it",
+    },
+    OptionDescription {
+        name: "replay",
+        short: None,
+        kind: OptionKind::Flag,
+        description: "Commit and push an idea left uncommitted in your idea file, README.md by default",
+    },
+    OptionDescription {
+        name: "status",
+        short: None,
+        kind: OptionKind::Flag,
+        description: "Show if your idea branch is ahead or behind origin, and uncommitted changes",
+    },
+    OptionDescription {
+        name: "porcelain",
+        short: None,
+        kind: OptionKind::Flag,
+        description:
+            "Print status tokens for scripts to stdout and everything else to stderr, instead of all to stdout",
+    },
+    OptionDescription {
+        name: "verbose",
+        short: None,
+        kind: OptionKind::Flag,
+        description: "Print each git step and how long it took",
+    },
+    OptionDescription {
+        name: "random",
+        short: None,
+        kind: OptionKind::Flag,
+        description: "Print a random stored idea and offer to open it",
+    },
+    OptionDescription {
+        name: "private",
+        short: None,
+        kind: OptionKind::Flag,
+        description: "Save your idea to PRIVATE.md, which is never committed or pushed",
+    },
+    OptionDescription {
+        name: "offline",
+        short: None,
+        kind: OptionKind::Flag,
+        description: "Skip checking that origin can be reached, which is checked before each idea by default",
+    },
+    OptionDescription {
+        name: "no-cache",
+        short: None,
+        kind: OptionKind::Flag,
+        description: "Look up your idea repo and origin's default branch again, not from the cache",
+    },
+    OptionDescription {
+        name: "commit-body",
+        short: None,
+        kind: OptionKind::Flag,
+        description: "Write a commit body in your $EDITOR after writing your idea",
+    },
+    OptionDescription {
+        name: "append",
+        short: None,
+        kind: OptionKind::Value("TEXT"),
+        description: "Add TEXT as a bullet to your idea file, README.md by default, and push it without any prompts",
+    },
+    OptionDescription {
+        name: "list",
+        short: None,
+        kind: OptionKind::Flag,
+        description: "List the titles of your stored ideas, in the order of their files",
+    },
+    OptionDescription {
+        name: "by-tag",
+        short: None,
+        kind: OptionKind::Flag,
+        description: "Group the listed ideas by the tags in the front matter of their file, untagged ones last",
+    },
+    OptionDescription {
+        name: "search",
+        short: None,
+        kind: OptionKind::Value("QUERY"),
+        description: "Print the ideas whose title contains QUERY, ignoring case",
+    },
+    OptionDescription {
+        name: "fuzzy",
+        short: None,
+        kind: OptionKind::Flag,
+        description: "Rank the ideas found by --search by how closely they match, allowing typos",
+    },
+    OptionDescription {
+        name: "browse",
+        short: None,
+        kind: OptionKind::Flag,
+        description: "Browse your ideas in a full-screen list, open them or show their diff",
+    },
+    OptionDescription {
+        name: "diff",
+        short: None,
+        kind: OptionKind::Value("N"),
+        description: "Print what your Nth most recent idea commit changed, the last one without N",
+    },
+    OptionDescription {
+        name: "json",
+        short: None,
+        kind: OptionKind::Flag,
+        description: "Print the --diff as JSON, with the lines of each hunk, instead of a colored diff",
+    },
+    OptionDescription {
+        name: "archive-older-than",
+        short: None,
+        kind: OptionKind::Value("DAYS"),
+        description: "Move ideas unchanged for more than DAYS days to ARCHIVE.md and push it",
+    },
+    OptionDescription {
+        name: "here",
+        short: None,
+        kind: OptionKind::Flag,
+        description: "Use the git repo of the current directory, on its current branch, instead of your configured repo",
+    },
+    OptionDescription {
+        name: "welcome",
+        short: None,
+        kind: OptionKind::Flag,
+        description: "Print the welcome banner and how to get started",
+    },
+    OptionDescription {
+        name: "version-info",
+        short: None,
+        kind: OptionKind::Flag,
+        description: "Print the versions of eureka and libgit2 as JSON, for bug reports",
+    },
+    OptionDescription {
+        name: "date",
+        short: None,
+        kind: OptionKind::Value("DATE"),
+        description: "Date your idea, and its commit, to this ISO 8601 date instead of now",
+    },
+    OptionDescription {
+        name: "queue",
+        short: None,
+        kind: OptionKind::Value("TEXT"),
+        description: "Queue TEXT as an idea to commit later with --process-queue, without touching git",
+    },
+    OptionDescription {
+        name: "process-queue",
+        short: None,
+        kind: OptionKind::Flag,
+        description: "Commit every queued idea and push them at once to origin",
+    },
+];
+
+impl EurekaOptions {
+    // Every command line option in the order of `--help`, for wrappers to
+    // build their help and completions from
+    pub fn describe() -> &'static [OptionDescription] {
+        &OPTION_DESCRIPTIONS
+    }
+}
+
+// Where ideas are stored, see `backend` in the config
+enum IdeaBackend {
+    Git,
+    GitHub,
+    Filesystem,
+}
+
+// Where an idea is written and committed to
+#[derive(Clone)]
+struct IdeaTarget {
+    // Root of the repo or worktree the idea is committed from
+    dir: String,
+    file: String,
+    branch: String,
+}
+
+// Where ideas are pushed to when they're contributed with a pull request
+struct ForkTarget {
+    // Remote of the fork the idea branch is pushed to
+    remote: String,
+    // `owner/name` of the fork on GitHub
+    repo: String,
+    // `owner/name` of the repo the pull request is opened against
+    upstream_repo: String,
 }
 
-impl<CM, W, R, G, PO> Eureka<CM, W, R, G, PO>
+impl<CM, W, R, G, PO, FH, C, S> Eureka<CM, W, R, G, PO, FH, C, S>
 where
     CM: ConfigManagement,
     W: Print + PrintColor,
     R: ReadInput,
     G: GitManagement,
     PO: ProgramOpener,
+    FH: FileManagement,
+    C: Clock,
+    S: IdeaSink,
 {
-    pub fn new(cm: CM, printer: W, reader: R, git: G, program_opener: PO) -> Self {
+    // Every dependency is injected so it can be mocked
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        cm: CM,
+        printer: W,
+        reader: R,
+        git: G,
+        program_opener: PO,
+        file_handler: FH,
+        clock: C,
+        sink: S,
+    ) -> Self {
         Eureka {
             cm,
             printer,
             reader,
             git,
             program_opener,
+            file_handler,
+            clock,
+            sink,
+            verbose: false,
+            stashed: false,
+            here: false,
+            backdate: None,
+            no_cache: false,
+            cache: None,
+            holds_push_lock: false,
+            plaintext: vec![],
         }
     }
 
-    pub fn run(&mut self, opts: EurekaOptions) -> io::Result<()> {
+    pub fn run(&mut self, opts: EurekaOptions) -> Result<(), EurekaError> {
         debug!("Running with options: {:?}", &opts);
+        self.verbose = opts.verbose;
+        self.here = opts.here;
+        self.no_cache = opts.no_cache;
+        self.backdate = opts
+            .date
+            .as_deref()
+            .map(|date| parse_backdate(date, self.clock.now()))
+            .transpose()
+            .map_err(EurekaError::Input)?;
+
+        let result = self.run_command(opts);
+        // Never leave an idea file in plaintext, e.g. when the editor failed
+        let encrypted = self.encrypt_left_plaintext();
+        // Restore stashed changes whether or not the idea made it
+        let restored = self.restore_auto_stash();
+        result.and(encrypted).and(restored)
+    }
+
+    fn run_command(&mut self, opts: EurekaOptions) -> Result<(), EurekaError> {
+        if opts.welcome {
+            self.printer.fts_banner()?;
+            self.printer.println(WELCOME_USAGE)?;
+            return Ok(());
+        }
+
+        if opts.version_info {
+            self.printer.println(&version_info().to_string())?;
+            return Ok(());
+        }
+
+        if let Some(branch) = &opts.background_push {
+            return self.run_background_push(branch);
+        }
 
         if opts.clear_config {
             self.clear_config()?;
@@ -70,107 +622,3292 @@ where
             return Ok(());
         }
 
+        if opts.setup {
+            return self.run_setup(false, opts.verify_setup);
+        }
+
+        if opts.verify_setup && (self.here || !self.is_config_missing()) {
+            return self.verify_setup();
+        }
+
         if opts.view {
             self.open_idea_file()?;
             return Ok(());
         }
 
-        if self.is_config_missing() {
+        if opts.count {
+            return self.print_idea_count();
+        }
+
+        if opts.list {
+            return self.list_ideas(opts.by_tag);
+        }
+
+        if let Some(query) = &opts.search {
+            return self.search_ideas(query, opts.fuzzy);
+        }
+
+        if opts.browse {
+            return self.browse_ideas();
+        }
+
+        if let Some(nth) = &opts.diff {
+            return self.print_idea_diff(nth, opts.json);
+        }
+
+        if let Some(days) = &opts.archive_older_than {
+            return self.archive_stale_ideas(days, &opts);
+        }
+
+        if let Some(title) = &opts.append_to {
+            return self.append_to_idea(title, &opts);
+        }
+
+        if opts.flush {
+            return self.flush_ideas();
+        }
+
+        if opts.flush_all {
+            return self.flush_all_ideas();
+        }
+
+        if let Some(into) = &opts.merge_into {
+            return self.merge_idea_branch(into, &opts);
+        }
+
+        if opts.continue_merge {
+            return self.continue_merge(&opts);
+        }
+
+        if opts.replay {
+            return self.replay_idea(&opts);
+        }
+
+        if opts.status {
+            return self.print_status();
+        }
+
+        if opts.random {
+            return self.recall_random_idea();
+        }
+
+        if let Some(text) = &opts.queue {
+            return self.queue_idea(text);
+        }
+
+        // The repo of the current directory needs no setup
+        if !self.here && self.is_config_missing() {
             debug!("Config is missing");
+            self.run_setup(true, opts.verify_setup)
+        } else if opts.private {
+            self.save_private_idea(&opts)
+        } else if let Some(text) = &opts.append {
+            self.append_bullet(text, &opts)
+        } else if opts.process_queue {
+            self.process_queue(&opts)
+        } else {
+            self.ask_for_idea(&opts)
+        }
+    }
 
-            // If config dir is missing - create it
-            if !self.cm.config_dir_exists() {
-                self.cm.config_dir_create()?;
-                debug!("Created config dir");
+    fn ask_for_idea(&mut self, opts: &EurekaOptions) -> Result<(), EurekaError> {
+        // The repo of the current directory is always committed to
+        if !self.here {
+            match self.idea_backend()? {
+                IdeaBackend::Git => {}
+                IdeaBackend::GitHub => return self.create_idea_issue(opts),
+                IdeaBackend::Filesystem => return self.save_idea_file(opts),
             }
+        }
 
-            self.printer.fts_banner()?;
+        let mut target = self.init_idea_target()?;
+        if !self.confirm_idea_repo(&target)? {
+            return Ok(());
+        }
+        self.auto_stash()?;
+        self.apply_category(&mut target)?;
+        self.apply_date_layout(&mut target)?;
+        self.decrypt_idea_file(&target)?;
+        if !opts.offline {
+            self.warn_if_remote_unreachable()?;
+        }
+
+        let (idea_summary, summary_rest) = self.write_idea(&mut target, opts)?;
+        self.commit_idea(&target, idea_summary, summary_rest, opts)
+    }
+
+    // Write the idea to a plain folder, e.g. a synced one, without any git
+    fn save_idea_file(&mut self, opts: &EurekaOptions) -> Result<(), EurekaError> {
+        let dir = self.cm.config_read(Repo).map_err(EurekaError::Config)?;
+        self.file_handler.ensure_dir(&dir)?;
+        let mut target = IdeaTarget {
+            file: format!("{}/README.md", &dir),
+            dir,
+            branch: String::new(),
+        };
+        self.apply_category(&mut target)?;
+        self.apply_date_layout(&mut target)?;
+        self.decrypt_idea_file(&target)?;
+
+        self.write_idea(&mut target, opts)?;
+        self.encrypt_idea_file(&target)?;
+        self.printer
+            .println(&format!("Saved your idea to {}", &target.file))?;
+        Ok(())
+    }
 
-            // If repo path is missing - ask for it
-            if self.cm.config_read(Repo).is_err() {
-                self.setup_repo_path()?;
-                debug!("Setup repo path successfully");
+    // Ask for the idea and write it to the idea file, returns the summary and
+    // the rest of it
+    fn write_idea(
+        &mut self,
+        target: &mut IdeaTarget,
+        opts: &EurekaOptions,
+    ) -> Result<(String, String), EurekaError> {
+        let (idea_summary, summary_rest, idea_body) = if opts.inline {
+            let (summary, body) = self.inline_idea()?;
+            (summary, String::new(), body)
+        } else {
+            let (summary, rest) = self.idea_summary(opts)?;
+            let body = if opts.clipboard {
+                self.reader.read_clipboard().map_err(EurekaError::Input)?
+            } else {
+                String::new()
+            };
+            (summary, rest, body)
+        };
+        if self.apply_file_name_template(target, &idea_summary)? {
+            self.decrypt_idea_file(target)?;
+        }
+        self.rotate_idea_file(
+            &target.file,
+            (idea_summary.len() + idea_body.trim().len()) as u64,
+        )?;
+
+        // An inline idea without a body is just its heading
+        if idea_body.trim().is_empty() && !opts.inline {
+            let open_editor = if opts.clipboard {
+                self.printer
+                    .println("Clipboard is empty, opening your editor instead")?;
+                true
+            } else {
+                self.wants_editor()?
+            };
+            if !open_editor {
+                // A quick thought, the summary alone is the idea
+                self.append_new_idea(&target.file, &idea_summary, "")?;
+            } else if self.appends_keyed()? {
+                // The id goes above the idea, so it's written before the
+                // editor opens below it
+                self.append_new_idea(&target.file, &idea_summary, "")?;
+                let line_count = self.file_handler.line_count(&target.file)?;
+                self.program_opener
+                    .open_editor_at(&target.file, line_count)?;
+                self.wait_for_edits(&target.file)?;
+            } else {
+                self.open_idea_editor(&target.file)?;
             }
+        } else {
+            self.append_new_idea(&target.file, &idea_summary, idea_body.trim())?;
+        }
+        self.append_footer(&target.file, &idea_summary)?;
 
-            self.printer
-                .println("First time setup complete. Happy ideation!")?;
-            Ok(())
+        Ok((idea_summary, summary_rest))
+    }
+
+    // Append the idea to `file_path` as the append_strategy says
+    fn append_new_idea(
+        &mut self,
+        file_path: &str,
+        summary: &str,
+        body: &str,
+    ) -> Result<(), EurekaError> {
+        if self.appends_keyed()? {
+            self.file_handler
+                .append_keyed_idea(file_path, &new_idea_id(), summary, body)?;
         } else {
-            self.ask_for_idea()
+            self.file_handler.append_idea(file_path, summary, body)?;
+        }
+        Ok(())
+    }
+
+    fn appends_keyed(&self) -> Result<bool, EurekaError> {
+        match self.cm.config_read(AppendStrategy) {
+            Ok(strategy) => match strategy.as_str() {
+                "plain" => Ok(false),
+                "keyed" => Ok(true),
+                _ => Err(EurekaError::Config(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unknown append_strategy '{}', use plain or keyed", strategy),
+                ))),
+            },
+            Err(_) => Ok(false),
         }
     }
 
-    fn ask_for_idea(&mut self) -> io::Result<()> {
-        let mut idea_summary = String::new();
+    // File today's idea under the configured date layout instead of README.md
+    fn apply_date_layout(&mut self, target: &mut IdeaTarget) -> Result<(), EurekaError> {
+        let pattern = match self.cm.config_read(DateLayout) {
+            Ok(pattern) => pattern,
+            Err(_) => return Ok(()),
+        };
 
-        while idea_summary.is_empty() {
-            self.printer.input_header(">> Idea summary")?;
-            idea_summary = self.reader.read_input()?;
+        let extension = self.file_extension()?;
+        let layout_path = expand_date_layout(&pattern, self.idea_time(), &extension)
+            .map_err(EurekaError::Config)?;
+        // Within the category folder, if any, else at the root of the repo
+        let file = Path::new(&target.file)
+            .parent()
+            .unwrap_or_else(|| Path::new(&target.dir))
+            .join(&layout_path);
+        if let Some(parent) = file.parent() {
+            self.file_handler
+                .ensure_dir(&parent.display().to_string())?;
         }
 
-        let repo_path = self.cm.config_read(Repo)?;
-        // We can set initialize git now as we have the repo path
-        self.git
-            .init(&repo_path)
-            .map_err(|git_err| Error::new(ErrorKind::InvalidInput, git_err))?;
+        let relative_path = file.strip_prefix(&target.dir).unwrap_or(&file);
+        let pathspec = relative_path.display().to_string();
+        debug!("Using date layout file {}", &pathspec);
+        self.add_idea_pathspec(&pathspec);
+        target.file = file.display().to_string();
+        Ok(())
+    }
 
-        self.program_opener
-            .open_editor(&format!("{}/README.md", &repo_path))
-            .and(self.git_add_commit_push(idea_summary))
+    // With `categories`, ask which folder of the idea repo the idea goes in.
+    // None keeps it at the root
+    fn apply_category(&mut self, target: &mut IdeaTarget) -> Result<(), EurekaError> {
+        let categories: Vec<String> = match self.cm.config_read(Categories) {
+            Ok(categories) => categories.split(',').map(String::from).collect(),
+            Err(_) => return Ok(()),
+        };
+
+        for (i, category) in categories.iter().enumerate() {
+            self.printer.println(&format!("{}. {}", i + 1, category))?;
+        }
+        let category = loop {
+            self.printer
+                .input_header("Number or name of the folder for your idea (default: none)")?;
+            let user_input = self.reader.read_input().map_err(EurekaError::Input)?;
+            if user_input.is_empty() || user_input.eq_ignore_ascii_case("none") {
+                return Ok(());
+            }
+
+            match pick_by_number_or_name(&categories, &user_input) {
+                Some(category) => break category.to_string(),
+                None => self.printer.error(&format!(
+                    "Choose a number between 1 and {}, type part of a name or none",
+                    categories.len()
+                ))?,
+            }
+        };
+
+        let dir = Path::new(&target.dir).join(&category);
+        self.file_handler.ensure_dir(&dir.display().to_string())?;
+        let pathspec = format!("{}/README.md", category);
+        debug!("Using category file {}", &pathspec);
+        self.add_idea_pathspec(&pathspec);
+        target.file = dir.join("README.md").display().to_string();
+        Ok(())
     }
 
-    fn clear_config(&self) -> io::Result<()> {
-        self.cm.config_rm()
+    // Write the idea to a file of its own once its summary is known, returns
+    // whether the target changed
+    fn apply_file_name_template(
+        &mut self,
+        target: &mut IdeaTarget,
+        summary: &str,
+    ) -> Result<bool, EurekaError> {
+        let template = match self.cm.config_read(FileNameTemplate) {
+            Ok(template) => template,
+            Err(_) => return Ok(false),
+        };
+
+        let nested = self.cm.config_read(DateLayout).is_ok();
+        let extension = self.file_extension()?;
+        let name =
+            expand_file_name_template(&template, self.idea_time(), summary, &extension, nested)
+                .map_err(EurekaError::Config)?;
+        // Next to the date layout file, if any, else at the root of the repo
+        let file = Path::new(&target.file)
+            .parent()
+            .unwrap_or_else(|| Path::new(&target.dir))
+            .join(name);
+        if let Some(parent) = file.parent() {
+            self.file_handler
+                .ensure_dir(&parent.display().to_string())?;
+        }
+
+        let relative_path = file.strip_prefix(&target.dir).unwrap_or(&file);
+        let pathspec = relative_path.display().to_string();
+        debug!("Using idea file {}", &pathspec);
+        self.add_idea_pathspec(&pathspec);
+        target.file = file.display().to_string();
+        Ok(true)
     }
 
-    fn open_idea_file(&self) -> io::Result<()> {
-        self.program_opener
-            .open_pager(&format!("{}/README.md", self.cm.config_read(Repo)?))
+    // Only `<file>.gpg` is left of encrypted idea files, which reading ideas
+    // would take for no ideas at all. `--here` reads no config
+    fn reject_encrypted_ideas(&self, option: &str) -> Result<(), EurekaError> {
+        if self.here || self.cm.config_read(EncryptTo).is_err() {
+            return Ok(());
+        }
+        Err(EurekaError::Input(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{} can't read encrypted ideas yet, unset encrypt_to to use it",
+                option
+            ),
+        )))
+    }
+
+    // Encrypted ideas are committed as the `<file>.gpg` that
+    // `encrypt_idea_file` stages, never as the plaintext file
+    fn add_idea_pathspec(&mut self, pathspec: &str) {
+        if self.cm.config_read(EncryptTo).is_err() {
+            self.git.add_pathspec(pathspec);
+        }
+    }
+
+    // Close the idea with the configured footer, if any
+    fn append_footer(&mut self, file_path: &str, summary: &str) -> Result<(), EurekaError> {
+        let footer = match self.cm.config_read(Footer) {
+            Ok(footer) => footer,
+            Err(_) => return Ok(()),
+        };
+
+        let date = self.idea_time().format("%Y-%m-%d").to_string();
+        let footer = footer
+            .replace("{date}", &date)
+            .replace("{summary}", summary);
+        if !self.file_handler.append_footer(file_path, &footer)? {
+            debug!("Footer is already at the end of {}", file_path);
+        }
+        Ok(())
+    }
+
+    // Extension of the idea files named by a date layout
+    fn file_extension(&self) -> Result<String, EurekaError> {
+        match self.cm.config_read(FileExtension) {
+            Ok(extension) => {
+                check_file_extension(&extension).map_err(EurekaError::Config)?;
+                Ok(extension)
+            }
+            Err(_) => Ok(DEFAULT_FILE_EXTENSION.to_string()),
+        }
+    }
+
+    fn append_bullet(&mut self, text: &str, opts: &EurekaOptions) -> Result<(), EurekaError> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(EurekaError::Input(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Nothing to append, --append needs some text",
+            )));
+        }
+
+        let mut target = self.init_idea_target()?;
+        if !self.confirm_idea_repo(&target)? {
+            return Ok(());
+        }
+        self.auto_stash()?;
+        self.apply_date_layout(&mut target)?;
+        self.decrypt_idea_file(&target)?;
+        if !opts.offline {
+            self.warn_if_remote_unreachable()?;
+        }
+        self.rotate_idea_file(&target.file, text.len() as u64)?;
+
+        self.file_handler.append_bullet(&target.file, text)?;
+        self.commit_idea(&target, text.to_string(), String::new(), opts)
+    }
+
+    fn queue_idea(&mut self, text: &str) -> Result<(), EurekaError> {
+        if text.trim().is_empty() {
+            return Err(EurekaError::Input(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Nothing to queue, --queue needs some text",
+            )));
+        }
+
+        if !self.cm.config_dir_exists() {
+            self.cm.config_dir_create().map_err(EurekaError::Config)?;
+        }
+        let queue_path = self.queue_path()?;
+        self.file_handler.queue_idea(&queue_path, text)?;
+        self.printer
+            .println("Queued, run eureka --process-queue to commit it")?;
+        Ok(())
+    }
+
+    fn process_queue(&mut self, opts: &EurekaOptions) -> Result<(), EurekaError> {
+        let queue_path = self.queue_path()?;
+        let mut queue = self.file_handler.read_queue(&queue_path)?;
+        if queue.is_empty() {
+            self.printer
+                .println("The queue is empty, add to it with eureka --queue")?;
+            return Ok(());
+        }
+
+        let mut target = self.init_idea_target()?;
+        if !self.confirm_idea_repo(&target)? {
+            return Ok(());
+        }
+        self.auto_stash()?;
+        self.apply_date_layout(&mut target)?;
+        if !opts.offline {
+            self.warn_if_remote_unreachable()?;
+        }
+
+        let mut committed = vec![];
+        while !queue.is_empty() {
+            let entry = queue.remove(0);
+            let (author_date, text) = split_queued_date(&entry, self.clock.now());
+            let text = text.to_string();
+            self.decrypt_idea_file(&target)?;
+            self.rotate_idea_file(&target.file, text.len() as u64)?;
+            self.file_handler.append_bullet(&target.file, &text)?;
+            self.encrypt_idea_file(&target)?;
+            let oid = self.git_add_commit(&target, &text, "", author_date)?;
+            // Never commit a queued idea twice, even if a later one fails
+            self.file_handler.write_queue(&queue_path, &queue)?;
+            committed.push((text, oid));
+        }
+
+        if opts.no_push {
+            self.printer
+                .println("Not pushed, run with --flush to push your ideas")?;
+        } else {
+            self.printer
+                .println(&format!("Pushing {} queued idea(s)..", committed.len()))?;
+            self.push(&target.branch)?;
+            self.printer.println("Pushed!")?;
+        }
+
+        for (text, oid) in committed {
+            self.log_audit_entry(&text, oid, !opts.no_push)?;
+        }
+        Ok(())
+    }
+
+    fn queue_path(&self) -> Result<String, EurekaError> {
+        Ok(self
+            .cm
+            .config_dir_path()
+            .map_err(EurekaError::Config)?
+            .join(QUEUE_FILE_NAME)
+            .display()
+            .to_string())
     }
 
-    fn git_add_commit_push(&mut self, commit_subject: String) -> io::Result<()> {
-        let branch_name = "main";
+    // With `ask_open_editor` only open the editor when asked to, it always opens otherwise
+    fn wants_editor(&mut self) -> Result<bool, EurekaError> {
+        let ask = self
+            .cm
+            .config_read(AskOpenEditor)
+            .map(|value| value == "true")
+            .unwrap_or(false);
+        if !ask {
+            return Ok(true);
+        }
+        self.printer
+            .input_header("Open editor to add details? [y/N]")?;
+        self.confirm_yes()
+    }
+
+    // Open the editor to write a new idea, at the end of the file if configured
+    fn open_idea_editor(&mut self, file_path: &str) -> Result<(), EurekaError> {
+        let at_end = self
+            .cm
+            .config_read(OpenAtEnd)
+            .map(|value| value == "true")
+            .unwrap_or(false);
+        let line_count = if at_end {
+            self.file_handler.line_count(file_path)?
+        } else {
+            0
+        };
+
+        if line_count > 0 {
+            self.program_opener.open_editor_at(file_path, line_count)?;
+        } else {
+            self.program_opener.open_editor(file_path)?;
+        }
+        self.wait_for_edits(file_path)
+    }
+
+    // With a `debounce_secs`, wait for an editor that autosaves to stop writing
+    // so the idea is committed once, not once per save
+    fn wait_for_edits(&mut self, file_path: &str) -> Result<(), EurekaError> {
+        let secs = match self.cm.config_read(DebounceSecs).map(|value| value.parse()) {
+            Ok(Ok(secs)) if secs > 0 => secs,
+            _ => return Ok(()),
+        };
+
         self.printer.println(&format!(
-            "Adding and committing your new idea to {}..",
-            &branch_name
+            "Waiting for {} to go unchanged for {}s",
+            file_path, secs
         ))?;
-        self.git
-            .checkout_branch(branch_name)
-            .and_then(|_| self.git.add())
-            .and_then(|_| self.git.commit(commit_subject.as_str()))
-            .map_err(|err| io::Error::new(ErrorKind::Other, err))?;
-        self.printer.println("Added and committed!")?;
+        let quiet = std::time::Duration::from_secs(secs);
+        if let Err(err) = self.file_handler.wait_until_settled(file_path, quiet) {
+            self.printer.error(&format!(
+                "Could not watch {} for edits, committing right away: {}",
+                file_path, err
+            ))?;
+        }
+        Ok(())
+    }
 
-        self.printer.println("Pushing your new idea..")?;
-        self.git
-            .push(branch_name)
-            .map_err(|err| io::Error::new(ErrorKind::Other, err))?;
-        self.printer.println("Pushed!")?;
+    // Capture the idea as a GitHub issue instead of committing it
+    fn create_idea_issue(&mut self, opts: &EurekaOptions) -> Result<(), EurekaError> {
+        let github_repo = self
+            .cm
+            .config_read(GithubRepo)
+            .map_err(EurekaError::Config)?;
+
+        let (idea_summary, summary_rest) = self.idea_summary(opts)?;
+        let clipboard = if opts.clipboard {
+            self.reader.read_clipboard().map_err(EurekaError::Input)?
+        } else {
+            String::new()
+        };
+        let idea_body = if clipboard.trim().is_empty() {
+            self.ask_for_issue_body()?
+        } else {
+            clipboard.trim().to_string()
+        };
+
+        self.printer
+            .println(&format!("Creating an issue in {}..", &github_repo))?;
+        let idea_body = join_paragraphs(&[&summary_rest, &idea_body]);
+        let url = self.sink.store(&github_repo, &idea_summary, &idea_body)?;
+        self.printer.println(&format!("Created {}", url))?;
+        Ok(())
+    }
+
+    fn ask_for_issue_body(&mut self) -> Result<String, EurekaError> {
+        let file_path = self
+            .cm
+            .config_dir_path()
+            .map_err(EurekaError::Config)?
+            .join(ISSUE_BODY_FILE_NAME);
+        let file_path = file_path.display().to_string();
+
+        self.file_handler.prepare_issue_body(&file_path)?;
+        self.program_opener.open_editor(&file_path)?;
+        Ok(self.file_handler.read_issue_body(&file_path)?)
+    }
+
+    fn idea_backend(&self) -> Result<IdeaBackend, EurekaError> {
+        match self.cm.config_read(Backend) {
+            Ok(backend) => match backend.as_str() {
+                "git" => Ok(IdeaBackend::Git),
+                "github" => Ok(IdeaBackend::GitHub),
+                "filesystem" => Ok(IdeaBackend::Filesystem),
+                _ => Err(EurekaError::Config(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Unknown backend '{}', use git, github or filesystem",
+                        backend
+                    ),
+                ))),
+            },
+            Err(_) => Ok(IdeaBackend::Git),
+        }
+    }
 
+    // Better to know about being offline before writing the idea than when pushing it
+    fn warn_if_remote_unreachable(&mut self) -> Result<(), EurekaError> {
+        if let Err(err) = self.git.probe_remote() {
+            self.printer.error(&format!(
+                "{}, pushing your idea will probably fail (skip this check with --offline)",
+                err.message()
+            ))?;
+        }
         Ok(())
     }
 
-    fn setup_repo_path(&mut self) -> io::Result<()> {
+    // The first line typed is the summary, the lines after it the body
+    fn inline_idea(&mut self) -> Result<(String, String), EurekaError> {
         loop {
             self.printer
-                .input_header("Absolute path to your idea repo")?;
-            let user_input = &self.reader.read_input()?;
-
-            if user_input.is_empty() {
-                continue;
+                .input_header(">> Idea summary, then its body. End with an empty line")?;
+            let input = self
+                .reader
+                .read_multiline_input()
+                .map_err(EurekaError::Input)?;
+            let (summary, body) = input.split_once('\n').unwrap_or((&input, ""));
+            if !summary.trim().is_empty() {
+                return Ok((summary.trim().to_string(), body.trim().to_string()));
             }
+        }
+    }
 
-            let path = Path::new(user_input);
+    // The summary's first line, and the lines after it if it has several
+    fn idea_summary(&mut self, opts: &EurekaOptions) -> Result<(String, String), EurekaError> {
+        let mut idea_summary = opts
+            .summary
+            .as_deref()
+            .map(str::trim)
+            .unwrap_or_default()
+            .to_string();
 
-            if path.is_absolute() {
-                break self.cm.config_write(Repo, path.display().to_string());
-            } else {
-                self.printer.error("Path must be absolute")?;
+        loop {
+            while idea_summary.is_empty() {
+                self.printer.input_header(">> Idea summary")?;
+                idea_summary = self.reader.read_input().map_err(EurekaError::Input)?;
+            }
+
+            // Pasted text can span several lines, a commit subject can't
+            let Some((subject, rest)) = idea_summary.split_once('\n') else {
+                return Ok((idea_summary, String::new()));
+            };
+            if !self.asks_again_for_multiline_summary()? {
+                return Ok((subject.trim().to_string(), rest.trim().to_string()));
             }
+
+            self.printer.error("The summary has to be a single line")?;
+            idea_summary = String::new();
         }
     }
 
-    fn is_config_missing(&self) -> bool {
-        self.cm.config_read(Repo).is_err()
+    fn asks_again_for_multiline_summary(&self) -> Result<bool, EurekaError> {
+        match self.cm.config_read(MultilineSummary) {
+            Ok(behavior) => match behavior.as_str() {
+                "body" => Ok(false),
+                "ask" => Ok(true),
+                _ => Err(EurekaError::Config(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unknown multiline_summary '{}', use body or ask", behavior),
+                ))),
+            },
+            Err(_) => Ok(false),
+        }
+    }
+
+    fn save_private_idea(&mut self, opts: &EurekaOptions) -> Result<(), EurekaError> {
+        let (idea_summary, summary_rest) = self.idea_summary(opts)?;
+        let repo_path = self.cm.config_read(Repo).map_err(EurekaError::Config)?;
+        let file = format!("{}/{}", &repo_path, PRIVATE_FILE_NAME);
+
+        // Make sure the private file never ends up in a commit
+        self.file_handler
+            .ensure_gitignore_entry(&format!("{}/.gitignore", &repo_path), PRIVATE_FILE_NAME)?;
+
+        let clipboard = if opts.clipboard {
+            self.reader.read_clipboard().map_err(EurekaError::Input)?
+        } else {
+            String::new()
+        };
+        // Without a commit the rest of the summary is part of the idea itself
+        let idea_body = join_paragraphs(&[&summary_rest, &clipboard]);
+        self.file_handler
+            .append_idea(&file, &idea_summary, idea_body.trim())?;
+        if idea_body.trim().is_empty() {
+            self.open_idea_editor(&file)?;
+        }
+
+        self.printer.println(&format!(
+            "Saved your private idea to {}, it's never committed or pushed",
+            &file
+        ))?;
+        Ok(())
+    }
+
+    fn append_to_idea(&mut self, title: &str, opts: &EurekaOptions) -> Result<(), EurekaError> {
+        let target = self.init_idea_target()?;
+        if !self.confirm_idea_repo(&target)? {
+            return Ok(());
+        }
+        self.auto_stash()?;
+        self.decrypt_idea_file(&target)?;
+        let mut headings = self.file_handler.find_heading_offset(&target.file, title)?;
+
+        let heading = match headings.len() {
+            0 => {
+                self.printer.input_header(&format!(
+                    "No idea titled '{}' found. Create it as a new idea? [Y/n]",
+                    title
+                ))?;
+                if !self.confirm()? {
+                    return Ok(());
+                }
+                self.append_new_idea(&target.file, title, "")?;
+                self.open_idea_editor(&target.file)?;
+                return self.commit_idea(&target, title.to_string(), String::new(), opts);
+            }
+            1 => headings.remove(0),
+            _ => {
+                let index = self.choose_heading(&headings)?;
+                headings.remove(index)
+            }
+        };
+
+        self.program_opener
+            .open_editor_at(&target.file, heading.section_end)?;
+        self.wait_for_edits(&target.file)?;
+        // When the idea was edited, not when it was had, so never backdated
+        let now = self
+            .clock
+            .now()
+            .format(FRONT_MATTER_TIME_FORMAT)
+            .to_string();
+        self.file_handler.write_front_matter(&target.file, &now)?;
+        self.commit_idea(
+            &target,
+            format!("Add to '{}'", heading.title),
+            String::new(),
+            opts,
+        )
+    }
+
+    // Archive the idea file if the new idea would grow it past the configured size
+    fn rotate_idea_file(
+        &mut self,
+        file_path: &str,
+        incoming_bytes: u64,
+    ) -> Result<(), EurekaError> {
+        let max_bytes = match self.cm.config_read(MaxFileBytes).map(|value| value.parse()) {
+            Ok(Ok(max_bytes)) => max_bytes,
+            // Rotation is off unless configured
+            _ => return Ok(()),
+        };
+
+        let date = self.clock.now().format("%Y-%m-%d").to_string();
+        let archive =
+            self.file_handler
+                .rotate_idea_file(file_path, max_bytes, incoming_bytes, &date)?;
+        if let Some(archive) = archive {
+            let name = |path: &Path| {
+                path.file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default()
+            };
+            self.printer.println(&format!(
+                "{} is over {} bytes, archived it as {}",
+                name(Path::new(file_path)),
+                max_bytes,
+                name(&archive)
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    fn flush_ideas(&mut self) -> Result<(), EurekaError> {
+        let target = self.init_idea_target()?;
+        let pending = self.git.pending_commits(&target.branch)?;
+
+        if pending == 0 {
+            self.printer
+                .println(&format!("Nothing to push, {} is up to date", target.branch))?;
+            return Ok(());
+        }
+
+        self.printer
+            .println(&format!("Pushing {} pending commit(s)..", pending))?;
+        self.push(&target.branch)?;
+        self.printer.println("Pushed!")?;
+
+        Ok(())
+    }
+
+    fn print_status(&mut self) -> Result<(), EurekaError> {
+        let target = self.init_idea_target()?;
+        let branch = &target.branch;
+        self.printer.println(&format!("On branch {}", branch))?;
+
+        let divergence = match self.git.divergence(branch)? {
+            Divergence::Unborn => String::from("No ideas committed yet"),
+            Divergence::NoUpstream(commits) => format!(
+                "Never pushed, {} commit(s) only exist locally (push them with --flush)",
+                commits
+            ),
+            Divergence::Tracking {
+                ahead: 0,
+                behind: 0,
+            } => format!("Up to date with origin/{}", branch),
+            Divergence::Tracking { ahead, behind: 0 } => format!(
+                "{} commit(s) ahead of origin/{} (push them with --flush)",
+                ahead, branch
+            ),
+            Divergence::Tracking { ahead: 0, behind } => format!(
+                "{} commit(s) behind origin/{} (git pull in your idea repo)",
+                behind, branch
+            ),
+            Divergence::Tracking { ahead, behind } => format!(
+                "{} commit(s) ahead and {} behind origin/{} (git pull in your idea repo, then --flush)",
+                ahead, behind, branch
+            ),
+        };
+        self.printer.println(&divergence)?;
+
+        if self.git.is_clean()? {
+            self.printer.println("Nothing uncommitted")?;
+        } else {
+            self.printer
+                .println(&format!("Uncommitted changes in {}", &target.dir))?;
+        }
+        Ok(())
+    }
+
+    fn print_idea_diff(&mut self, nth: &str, json: bool) -> Result<(), EurekaError> {
+        let nth = match nth.parse::<usize>() {
+            Ok(nth) if nth > 0 => nth,
+            _ => {
+                return Err(EurekaError::Input(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Expected a number of idea commits back from 1, got '{}'",
+                        nth
+                    ),
+                )))
+            }
+        };
+        let target = self.init_idea_target()?;
+        let branch = &target.branch;
+
+        let diff = match self.trace_git(
+            &format!("diff commit {} of {} with its parent", nth, branch),
+            |git| git.commit_diff(branch, nth),
+            |diff| {
+                format!(
+                    "{} hunk(s)",
+                    diff.as_ref().map_or(0, |diff| diff.hunks.len())
+                )
+            },
+        )? {
+            Some(diff) => diff,
+            None if nth == 1 => {
+                self.printer
+                    .println(&format!("No ideas committed to {} yet", branch))?;
+                return Ok(());
+            }
+            None => {
+                self.printer
+                    .println(&format!("{} has fewer than {} idea commits", branch, nth))?;
+                return Ok(());
+            }
+        };
+
+        if json {
+            let hunks: Vec<_> = diff
+                .hunks
+                .iter()
+                .map(|hunk| {
+                    serde_json::json!({
+                        "file": hunk.file,
+                        "header": hunk.header,
+                        "lines": hunk.lines,
+                    })
+                })
+                .collect();
+            let diff = serde_json::json!({
+                "commit": diff.oid.to_string(),
+                "summary": diff.summary,
+                "hunks": hunks,
+            });
+            self.printer.println(&diff.to_string())?;
+        } else {
+            let oid = diff.oid.to_string();
+            self.printer
+                .println(&format!("{} {}", &oid[..7], diff.summary))?;
+            self.printer.diff(&diff.patch)?;
+        }
+        Ok(())
+    }
+
+    fn flush_all_ideas(&mut self) -> Result<(), EurekaError> {
+        let template = if self.here {
+            self.init_here_target()?.branch
+        } else {
+            self.open_repo()?;
+            // Match the template, not today's branch, to get every week or month
+            match self.cm.config_read(Branch) {
+                Ok(template) => template,
+                Err(_) => self.idea_branch()?,
+            }
+        };
+
+        let ahead: Vec<(String, usize)> = self
+            .git
+            .branches_ahead_of_upstream()?
+            .into_iter()
+            .filter(|(branch, _)| matches_branch_template(&template, branch))
+            .collect();
+        if ahead.is_empty() {
+            self.printer
+                .println("Nothing to push, every idea branch is up to date")?;
+            return Ok(());
+        }
+
+        let mut failed = 0;
+        for (branch, pending) in &ahead {
+            match self.push(branch) {
+                Ok(()) => self
+                    .printer
+                    .println(&format!("{}: pushed {} commit(s)", branch, pending))?,
+                Err(e) => {
+                    failed += 1;
+                    self.printer
+                        .println(&format!("{}: not pushed, {}", branch, e))?;
+                }
+            }
+        }
+
+        if failed > 0 {
+            return Err(EurekaError::Git(git2::Error::from_str(&format!(
+                "{} of {} idea branches could not be pushed",
+                failed,
+                ahead.len()
+            ))));
+        }
+        Ok(())
+    }
+
+    fn merge_idea_branch(&mut self, into: &str, opts: &EurekaOptions) -> Result<(), EurekaError> {
+        // Merged in the checkout of the repo, never in the worktree
+        let branch = if self.here {
+            self.init_here_target()?.branch
+        } else {
+            self.open_repo()?;
+            self.idea_branch()?
+        };
+        if branch == into {
+            return Err(EurekaError::Input(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is your idea branch, merge it into another branch", into),
+            )));
+        }
+
+        self.printer
+            .println(&format!("Merging {} into {}..", branch, into))?;
+        let outcome = self.trace_git(
+            &format!("check out {} and merge {}", into, branch),
+            |git| git.merge_branch(&branch, into),
+            |outcome| format!("{:?}", outcome),
+        )?;
+        match outcome {
+            MergeOutcome::UpToDate => {
+                self.printer
+                    .println(&format!("Nothing to merge, {} is up to date", into))?;
+                return Ok(());
+            }
+            MergeOutcome::FastForward => self
+                .printer
+                .println(&format!("Fast-forwarded {} to {}", into, branch))?,
+            MergeOutcome::MergeCommit => self.printer.println(&format!(
+                "Merged {} into {} with a merge commit",
+                branch, into
+            ))?,
+        }
+
+        if opts.no_push {
+            self.printer
+                .println(&format!("Not pushed, {} is only merged locally", into))?;
+            return Ok(());
+        }
+        self.printer.println(&format!("Pushing {}..", into))?;
+        self.push(into)?;
+        self.printer.println("Pushed!")?;
+
+        Ok(())
+    }
+
+    // Finish a merge resolved by hand, e.g. after pulling ideas pushed elsewhere
+    fn continue_merge(&mut self, opts: &EurekaOptions) -> Result<(), EurekaError> {
+        let target = self.init_idea_target()?;
+        let merge = self.trace_git(
+            "commit the merge in progress",
+            |git| git.finish_merge(),
+            |merge| match merge {
+                Some(oid) => format!("committed {:.7}", oid.to_string()),
+                None => String::from("no merge in progress"),
+            },
+        )?;
+        if merge.is_none() {
+            self.printer
+                .println("No merge in progress, nothing to continue")?;
+            return Ok(());
+        }
+        self.printer.println("Merge committed!")?;
+
+        let branch = self.git.head_branch()?.unwrap_or(target.branch);
+        if opts.no_push {
+            self.printer
+                .println("Not pushed, run with --flush to push your ideas")?;
+            return Ok(());
+        }
+        self.printer.println(&format!("Pushing {}..", branch))?;
+        self.push(&branch)?;
+        self.printer.println("Pushed!")?;
+
+        Ok(())
+    }
+
+    // Commit an idea left uncommitted in the idea file, e.g. by a crash after
+    // the editor closed. The summary defaults to the last heading it added
+    fn replay_idea(&mut self, opts: &EurekaOptions) -> Result<(), EurekaError> {
+        let target = self.init_idea_target()?;
+        if !self.confirm_idea_repo(&target)? {
+            return Ok(());
+        }
+        let patch = self.trace_git(
+            "diff idea files with HEAD",
+            |git| git.diff(),
+            |patch| format!("{} changed line(s)", patch.lines().count()),
+        )?;
+        if patch.trim().is_empty() {
+            self.printer
+                .println("Nothing to replay, your idea files have no uncommitted changes")?;
+            return Ok(());
+        }
+
+        let (idea_summary, summary_rest) = match last_added_heading(&patch) {
+            Some(heading) if opts.summary.is_none() => {
+                self.printer
+                    .input_header(&format!(">> Idea summary (default: {})", heading))?;
+                let summary = self.reader.read_input().map_err(EurekaError::Input)?;
+                match summary.trim() {
+                    "" => (heading, String::new()),
+                    summary => (summary.to_string(), String::new()),
+                }
+            }
+            _ => self.idea_summary(opts)?,
+        };
+        self.commit_idea(&target, idea_summary, summary_rest, opts)
+    }
+
+    fn recall_random_idea(&mut self) -> Result<(), EurekaError> {
+        self.reject_encrypted_ideas("--random")?;
+        let target = self.init_idea_target()?;
+        // Each idea of every idea file, with the file it is in
+        let mut ideas: Vec<(String, Heading)> = vec![];
+        let extension = self.file_extension()?;
+        for file in self.file_handler.idea_files(&target.dir, &extension)? {
+            let file_path = file.display().to_string();
+            for heading in self.file_handler.idea_headings(&file_path)? {
+                ideas.push((file_path.clone(), heading));
+            }
+        }
+
+        if ideas.is_empty() {
+            self.printer.println(NO_IDEAS_HINT)?;
+            return Ok(());
+        }
+
+        let seed = random_seed()
+            .unwrap_or_else(|| self.clock.now().timestamp_nanos_opt().unwrap_or_default() as u64);
+        let (file_path, heading) = &ideas[random_index(ideas.len(), seed)];
+        self.printer
+            .println(&format!("Idea of the day: {}", heading.title))?;
+
+        self.printer.input_header("Open it? [Y/n]")?;
+        if self.confirm()? {
+            self.program_opener
+                .open_editor_at(file_path, heading.line)?;
+        }
+
+        Ok(())
+    }
+
+    fn print_idea_count(&mut self) -> Result<(), EurekaError> {
+        self.reject_encrypted_ideas("--count")?;
+        let target = self.init_idea_target()?;
+        let extension = self.file_extension()?;
+        let mut count = 0;
+        for file in self.file_handler.idea_files(&target.dir, &extension)? {
+            count += self
+                .file_handler
+                .count_headings(&file.display().to_string())?;
+        }
+        self.printer.println(&count.to_string())?;
+        Ok(())
+    }
+
+    fn list_ideas(&mut self, by_tag: bool) -> Result<(), EurekaError> {
+        self.reject_encrypted_ideas("--list")?;
+        let target = self.init_idea_target()?;
+        // Tag (or UNTAGGED) to the ideas filed under it, as `title (file)`
+        let mut ideas_by_tag: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        let mut untagged = vec![];
+
+        let extension = self.file_extension()?;
+        for file in self.file_handler.idea_files(&target.dir, &extension)? {
+            let file_path = file.display().to_string();
+            let name = file.strip_prefix(&target.dir).unwrap_or(&file).display();
+            let ideas: Vec<String> = self
+                .file_handler
+                .idea_headings(&file_path)?
+                .into_iter()
+                .map(|heading| format!("{} ({})", heading.title, name))
+                .collect();
+
+            let tags = if by_tag {
+                self.file_handler.read_front_matter(&file_path)?
+            } else {
+                vec![]
+            };
+            if tags.is_empty() {
+                untagged.extend(ideas);
+            } else {
+                for tag in tags {
+                    ideas_by_tag.entry(tag).or_default().extend(ideas.clone());
+                }
+            }
+        }
+
+        if ideas_by_tag.is_empty() && untagged.is_empty() {
+            self.printer.println(NO_IDEAS_HINT)?;
+            return Ok(());
+        }
+        if !by_tag {
+            for idea in untagged {
+                self.printer.println(&format!("- {}", idea))?;
+            }
+            return Ok(());
+        }
+
+        // Untagged ideas are listed last
+        let groups = ideas_by_tag
+            .into_iter()
+            .chain((!untagged.is_empty()).then(|| (UNTAGGED.to_string(), untagged)));
+        for (tag, ideas) in groups {
+            self.printer.println(&tag)?;
+            for idea in ideas {
+                self.printer.println(&format!("  - {}", idea))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn search_ideas(&mut self, query: &str, fuzzy: bool) -> Result<(), EurekaError> {
+        self.reject_encrypted_ideas("--search")?;
+        let target = self.init_idea_target()?;
+        let needle = query.to_lowercase();
+        // Score of each found idea, as `title (file)`, 100 for plain matches
+        let mut found: Vec<(usize, String)> = vec![];
+
+        let extension = self.file_extension()?;
+        for file in self.file_handler.idea_files(&target.dir, &extension)? {
+            let file_path = file.display().to_string();
+            let name = file.strip_prefix(&target.dir).unwrap_or(&file).display();
+            for heading in self.file_handler.idea_headings(&file_path)? {
+                let score = if fuzzy {
+                    fuzzy_score(query, &heading.title)
+                } else {
+                    heading
+                        .title
+                        .to_lowercase()
+                        .contains(&needle)
+                        .then_some(100)
+                };
+                if let Some(score) = score {
+                    found.push((score, format!("{} ({})", heading.title, name)));
+                }
+            }
+        }
+
+        if found.is_empty() {
+            self.printer
+                .println(&format!("No idea matches '{}'", query))?;
+            return Ok(());
+        }
+        if !fuzzy {
+            for (_, idea) in found {
+                self.printer.println(&format!("- {}", idea))?;
+            }
+            return Ok(());
+        }
+
+        // Stable, so equally close ideas keep the order of their files
+        found.sort_by_key(|(score, _)| Reverse(*score));
+        for (score, idea) in found.into_iter().take(FUZZY_MAX_RESULTS) {
+            self.printer.println(&format!("- {} [{}]", idea, score))?;
+        }
+        Ok(())
+    }
+
+    // Until the browser is closed, so the list is read again after each idea
+    // is opened or its diff shown
+    fn browse_ideas(&mut self) -> Result<(), EurekaError> {
+        self.reject_encrypted_ideas("--browse")?;
+        let target = self.init_idea_target()?;
+        let extension = self.file_extension()?;
+        loop {
+            // Each idea as `title (file)`, and where it is
+            let mut labels = vec![];
+            let mut ideas: Vec<(String, String, Heading)> = vec![];
+            for file in self.file_handler.idea_files(&target.dir, &extension)? {
+                let file_path = file.display().to_string();
+                let name = file
+                    .strip_prefix(&target.dir)
+                    .unwrap_or(&file)
+                    .display()
+                    .to_string();
+                for heading in self.file_handler.idea_headings(&file_path)? {
+                    labels.push(format!("{} ({})", heading.title, name));
+                    ideas.push((name.clone(), file_path.clone(), heading));
+                }
+            }
+            if ideas.is_empty() {
+                self.printer.println(NO_IDEAS_HINT)?;
+                return Ok(());
+            }
+
+            match self.reader.browse(&labels).map_err(EurekaError::Input)? {
+                Some(Browsed::Open(index)) => {
+                    let (_, file_path, heading) = &ideas[index];
+                    self.program_opener
+                        .open_editor_at(file_path, heading.line)?;
+                }
+                Some(Browsed::Diff(index)) => {
+                    let (name, _, heading) = &ideas[index];
+                    self.print_heading_diff(name, heading)?;
+                    self.printer
+                        .input_header("Press enter to go back to your ideas")?;
+                    self.reader.read_input().map_err(EurekaError::Input)?;
+                }
+                None => return Ok(()),
+            }
+        }
+    }
+
+    // The commit that added, or last changed, the heading of an idea in
+    // `file`, relative to the idea repo
+    fn print_heading_diff(&mut self, file: &str, heading: &Heading) -> Result<(), EurekaError> {
+        let diff = self.trace_git(
+            &format!("diff the commit of line {} of {}", heading.line, file),
+            |git| git.line_commit_diff(file, heading.line),
+            |diff| {
+                format!(
+                    "{} hunk(s)",
+                    diff.as_ref().map_or(0, |diff| diff.hunks.len())
+                )
+            },
+        )?;
+        match diff {
+            Some(diff) => {
+                let oid = diff.oid.to_string();
+                self.printer
+                    .println(&format!("{} {}", &oid[..7], diff.summary))?;
+                self.printer.diff(&diff.patch)?;
+            }
+            None => {
+                self.printer
+                    .println(&format!("{} isn't committed yet", heading.title))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn archive_stale_ideas(&mut self, days: &str, opts: &EurekaOptions) -> Result<(), EurekaError> {
+        let days: u32 = days.parse().map_err(|_| {
+            EurekaError::Input(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Expected a number of days, got '{}'", days),
+            ))
+        })?;
+        self.reject_encrypted_ideas("--archive-older-than")?;
+        let target = self.init_idea_target()?;
+        if !self.confirm_idea_repo(&target)? {
+            return Ok(());
+        }
+        let cutoff = (self.clock.now() - Duration::days(days.into())).timestamp();
+        let archive = format!("{}/{}", &target.dir, ARCHIVE_FILE_NAME);
+
+        // Idea file, relative to the repo and as a path, to its stale ideas
+        let mut stale: Vec<(String, String, Vec<Heading>)> = vec![];
+        let extension = self.file_extension()?;
+        for file in self.file_handler.idea_files(&target.dir, &extension)? {
+            let file_path = file.display().to_string();
+            if file_path == archive {
+                continue;
+            }
+            let name = file
+                .strip_prefix(&target.dir)
+                .unwrap_or(&file)
+                .display()
+                .to_string();
+            let times = self.git.line_times(&name)?;
+
+            let mut headings = vec![];
+            for heading in self.file_handler.idea_headings(&file_path)? {
+                let Some(touched) = last_touched(&times, &heading) else {
+                    continue;
+                };
+                if touched < cutoff {
+                    let date = Local
+                        .timestamp_opt(touched, 0)
+                        .single()
+                        .map(|date| date.format("%Y-%m-%d").to_string())
+                        .unwrap_or_default();
+                    self.printer.println(&format!(
+                        "- {} ({}, last changed {})",
+                        heading.title, name, date
+                    ))?;
+                    headings.push(heading);
+                }
+            }
+            if !headings.is_empty() {
+                stale.push((name, file_path, headings));
+            }
+        }
+
+        let count: usize = stale.iter().map(|(_, _, headings)| headings.len()).sum();
+        if count == 0 {
+            self.printer
+                .println(&format!("No ideas unchanged for more than {} days", days))?;
+            return Ok(());
+        }
+        self.printer.input_header(&format!(
+            "Move these {} idea(s) to {}? [y/N]",
+            count, ARCHIVE_FILE_NAME
+        ))?;
+        if !self.confirm_yes()? {
+            self.printer.println("Aborted, nothing was archived")?;
+            return Ok(());
+        }
+
+        let mut titles = vec![];
+        for (name, file_path, headings) in &stale {
+            self.file_handler
+                .archive_ideas(file_path, &archive, headings)?;
+            self.git.add_pathspec(name);
+            titles.extend(
+                headings
+                    .iter()
+                    .map(|heading| format!("- {}", heading.title)),
+            );
+        }
+        self.git.add_pathspec(ARCHIVE_FILE_NAME);
+        self.commit_idea(
+            &target,
+            format!("Archive {} idea(s) older than {} days", count, days),
+            titles.join("\n"),
+            opts,
+        )
+    }
+
+    fn choose_heading(&mut self, headings: &[Heading]) -> Result<usize, EurekaError> {
+        for (i, heading) in headings.iter().enumerate() {
+            self.printer.println(&format!(
+                "{}. {} (line {})",
+                i + 1,
+                heading.title,
+                heading.line
+            ))?;
+        }
+
+        loop {
+            self.printer.input_header("Number of the idea to add to")?;
+            let user_input = self.reader.read_input().map_err(EurekaError::Input)?;
+
+            match user_input.parse::<usize>() {
+                Ok(number) if (1..=headings.len()).contains(&number) => break Ok(number - 1),
+                _ => self
+                    .printer
+                    .error(&format!("Choose a number between 1 and {}", headings.len()))?,
+            }
+        }
+    }
+
+    // Open the idea repo (or its worktree) and resolve where the idea goes
+    fn init_idea_target(&mut self) -> Result<IdeaTarget, EurekaError> {
+        // Not even a plaintext README.md left behind is staged then. `--here`
+        // reads no config
+        if !self.here && self.cm.config_read(EncryptTo).is_ok() {
+            self.git.skip_idea_pathspecs();
+        }
+        if self.here {
+            return self.init_here_target();
+        }
+
+        let repo_path = self.open_repo()?;
+        let branch = self.idea_branch()?;
+
+        let idea_dir = if self.uses_worktree() {
+            // Keep the checkout of the idea repo untouched
+            let worktree_path = self
+                .cm
+                .config_dir_path()
+                .map_err(EurekaError::Config)?
+                .join(WORKTREE_DIR_NAME);
+            let worktree_path = worktree_path.display().to_string();
+            self.trace_git(
+                &format!("open worktree {} on {}", &worktree_path, &branch),
+                |git| git.ensure_worktree(&worktree_path, &branch),
+                |_| String::from("opened"),
+            )?;
+            debug!("Using worktree at {}", &worktree_path);
+            worktree_path
+        } else {
+            repo_path
+        };
+
+        Ok(IdeaTarget {
+            file: format!("{}/README.md", &idea_dir),
+            dir: idea_dir,
+            branch,
+        })
+    }
+
+    // Pointing eureka at a project repo by mistake would append ideas to its
+    // README, so ask once before writing to a repo that looks like one
+    fn confirm_idea_repo(&mut self, target: &IdeaTarget) -> Result<bool, EurekaError> {
+        // The repo of the current directory is chosen on purpose
+        if self.here {
+            return Ok(true);
+        }
+
+        let marker = format!("{}/{}", &target.dir, IDEA_REPO_MARKER);
+        if self.file_handler.has_marker(&marker)
+            || !self.file_handler.looks_like_project(&target.dir)?
+        {
+            return Ok(true);
+        }
+
+        self.printer.error(&format!(
+            "{} looks like a project rather than an idea repo, your ideas would go to its README.md",
+            &target.dir
+        ))?;
+        self.printer
+            .input_header("Capture your ideas in it anyway? [y/N]")?;
+        if !self.confirm_yes()? {
+            self.printer
+                .println("Aborted, change the repo in your config to capture ideas elsewhere")?;
+            return Ok(false);
+        }
+
+        self.file_handler.create_marker(&marker)?;
+        debug!("Marked {} as an idea repo", &target.dir);
+        Ok(true)
+    }
+
+    // Open the configured idea repo, not its worktree
+    fn open_repo(&mut self) -> Result<String, EurekaError> {
+        self.cache = self.read_cache();
+        let repo_path = match &self.cache {
+            Some(cache) => cache.repo.clone(),
+            None => self.cm.config_read(Repo).map_err(EurekaError::Config)?,
+        };
+        self.trace_git(
+            &format!("open repo {}", &repo_path),
+            |git| git.init(&repo_path),
+            |_| String::from("opened"),
+        )?;
+        if self.cache.is_none() {
+            self.write_cache(RepoCache {
+                repo: repo_path.clone(),
+                ..Default::default()
+            });
+        }
+        Ok(repo_path)
+    }
+
+    // The cache is only a shortcut, so a broken one is logged and skipped
+    fn read_cache(&self) -> Option<RepoCache> {
+        if self.no_cache {
+            return None;
+        }
+        match self.cm.cache_read() {
+            Ok(cache) => cache,
+            Err(err) => {
+                debug!("Could not read the cache: {}", err);
+                None
+            }
+        }
+    }
+
+    fn write_cache(&mut self, cache: RepoCache) {
+        if let Err(err) = self.cm.cache_write(&cache) {
+            debug!("Could not write the cache: {}", err);
+        }
+        self.cache = Some(cache);
+    }
+
+    // Only good while origin is the remote it was read from
+    fn cached_default_branch(&self) -> Option<String> {
+        let cache = self.cache.as_ref()?;
+        let default_branch = cache.default_branch.clone()?;
+        let url = self.git.remote_url("origin").ok()?;
+        (cache.remote_url.as_deref() == Some(url.as_str())).then_some(default_branch)
+    }
+
+    // The repo the current directory is in, without touching the config
+    fn init_here_target(&mut self) -> Result<IdeaTarget, EurekaError> {
+        let current_dir = env::current_dir().map_err(EurekaError::Io)?;
+        let current_dir = current_dir.display().to_string();
+        let repo_path = self.trace_git(
+            &format!("discover repo from {}", &current_dir),
+            |git| git.discover(&current_dir),
+            |repo_path| format!("found {}", repo_path),
+        )?;
+        let branch = self.git.head_branch()?.ok_or_else(|| {
+            git2::Error::from_str("HEAD is detached, check out a branch to use --here")
+        })?;
+
+        Ok(IdeaTarget {
+            file: format!("{}/README.md", &repo_path),
+            dir: repo_path,
+            branch,
+        })
+    }
+
+    // When the idea is from, now unless it's backdated with --date
+    fn idea_time(&self) -> DateTime<Local> {
+        self.backdate.unwrap_or_else(|| self.clock.now())
+    }
+
+    // The configured branch, else the upstream of the repo's HEAD, else main
+    fn idea_branch(&self) -> Result<String, EurekaError> {
+        if let Ok(branch) = self.cm.config_read(Branch) {
+            return expand_branch_template(&branch, self.idea_time()).map_err(EurekaError::Config);
+        }
+
+        let upstream = self.git.upstream_branch()?;
+        Ok(upstream.unwrap_or_else(|| BRANCH_NAME.to_string()))
+    }
+
+    fn commit_idea(
+        &mut self,
+        target: &IdeaTarget,
+        subject: String,
+        body: String,
+        opts: &EurekaOptions,
+    ) -> Result<(), EurekaError> {
+        let confirmed = !opts.preview || self.confirm_preview()?;
+        // Never leave the idea in plaintext, even if it's not committed
+        self.encrypt_idea_file(target)?;
+        if !confirmed {
+            self.printer
+                .println("Aborted, your idea is left uncommitted")?;
+            return Ok(());
+        }
+
+        let body = if opts.commit_body {
+            join_paragraphs(&[&body, &self.ask_for_commit_body()?])
+        } else {
+            body
+        };
+        let body = join_paragraphs(&[&body, &self.context_trailers()?]);
+
+        self.git_add_commit_push(target, subject, body, !opts.no_push)
+    }
+
+    // `Captured-From:` trailers with the current directory and the repo it's
+    // in, if any, when capture_context is on
+    fn context_trailers(&self) -> Result<String, EurekaError> {
+        let capture_context = self
+            .cm
+            .config_read(CaptureContext)
+            .map(|value| value == "true")
+            .unwrap_or(false);
+        if !capture_context {
+            return Ok(String::new());
+        }
+
+        let current_dir = env::current_dir().map_err(EurekaError::Io)?;
+        let mut trailers = format!("Captured-From: {}", current_dir.display());
+        if let Some(repo) = current_dir
+            .ancestors()
+            .find(|dir| dir.join(".git").exists())
+        {
+            trailers.push_str(&format!("\nCaptured-From-Repo: {}", repo.display()));
+        }
+        Ok(trailers)
+    }
+
+    // Restore the plaintext idea file from `<file>.gpg` to write to it. It's
+    // encrypted again by the end of the run, whatever ends it
+    fn decrypt_idea_file(&mut self, target: &IdeaTarget) -> Result<(), EurekaError> {
+        if self.cm.config_read(EncryptTo).is_err() {
+            return Ok(());
+        }
+        if !self.plaintext.iter().any(|left| left.file == target.file) {
+            self.plaintext.push(target.clone());
+        }
+
+        let encrypted_path = format!("{}.gpg", &target.file);
+        if self
+            .program_opener
+            .decrypt_file(&encrypted_path, &target.file)?
+        {
+            debug!("Decrypted {}", &encrypted_path);
+        }
+        Ok(())
+    }
+
+    // Replace the idea file with `<file>.gpg`, which is what gets committed
+    fn encrypt_idea_file(&mut self, target: &IdeaTarget) -> Result<(), EurekaError> {
+        let recipient = match self.cm.config_read(EncryptTo) {
+            Ok(recipient) => recipient,
+            Err(_) => return Ok(()),
+        };
+
+        self.program_opener.encrypt_file(&target.file, &recipient)?;
+        self.plaintext.retain(|left| left.file != target.file);
+        let file = Path::new(&target.file);
+        let relative_path = file.strip_prefix(&target.dir).unwrap_or(file);
+        self.git
+            .add_pathspec(&format!("{}.gpg", relative_path.display()));
+        Ok(())
+    }
+
+    // What a failed or aborted capture decrypted, unless it was never written
+    fn encrypt_left_plaintext(&mut self) -> Result<(), EurekaError> {
+        for target in std::mem::take(&mut self.plaintext) {
+            if self.file_handler.exists(&target.file) {
+                debug!("Encrypting {} again", &target.file);
+                self.encrypt_idea_file(&target)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn ask_for_commit_body(&mut self) -> Result<String, EurekaError> {
+        let file_path = self
+            .cm
+            .config_dir_path()
+            .map_err(EurekaError::Config)?
+            .join(COMMIT_BODY_FILE_NAME);
+        let file_path = file_path.display().to_string();
+
+        self.file_handler.prepare_commit_body(&file_path)?;
+        self.program_opener.open_editor(&file_path)?;
+        Ok(self.file_handler.read_commit_body(&file_path)?)
+    }
+
+    fn confirm_preview(&mut self) -> Result<bool, EurekaError> {
+        let patch = self.git.diff()?;
+        let lines: Vec<&str> = patch.lines().collect();
+
+        if lines.len() > PREVIEW_MAX_LINES {
+            self.printer.diff(&lines[..PREVIEW_MAX_LINES].join("\n"))?;
+            self.printer.println(&format!(
+                "... diff truncated, {} more lines not shown",
+                lines.len() - PREVIEW_MAX_LINES
+            ))?;
+        } else {
+            self.printer.diff(&patch)?;
+        }
+
+        self.printer.input_header("Commit this idea? [Y/n]")?;
+        self.confirm()
+    }
+
+    // Read a yes/no answer where anything but no counts as yes
+    fn confirm(&mut self) -> Result<bool, EurekaError> {
+        let answer = self
+            .reader
+            .read_input()
+            .map_err(EurekaError::Input)?
+            .to_lowercase();
+        Ok(!matches!(answer.as_str(), "n" | "no"))
+    }
+
+    // Like `confirm`, for prompts that default to no
+    fn confirm_yes(&mut self) -> Result<bool, EurekaError> {
+        let answer = self
+            .reader
+            .read_input()
+            .map_err(EurekaError::Input)?
+            .to_lowercase();
+        Ok(matches!(answer.as_str(), "y" | "yes"))
+    }
+
+    // Checking out the idea branch fails on conflicting uncommitted changes, so
+    // opt-in to set them aside until the idea is committed
+    fn auto_stash(&mut self) -> Result<(), EurekaError> {
+        let enabled = self
+            .cm
+            .config_read(AutoStash)
+            .map(|value| value == "true")
+            .unwrap_or(false);
+        // A worktree never touches the changes in the idea repo
+        if !enabled || self.uses_worktree() {
+            return Ok(());
+        }
+
+        self.stashed = self.trace_git(
+            "stash uncommitted changes",
+            |git| git.stash_save(),
+            |stashed| {
+                String::from(if *stashed {
+                    "stashed"
+                } else {
+                    "nothing to stash"
+                })
+            },
+        )?;
+        if self.stashed {
+            self.printer
+                .println("Stashed your uncommitted changes in the idea repo")?;
+        }
+        Ok(())
+    }
+
+    fn restore_auto_stash(&mut self) -> Result<(), EurekaError> {
+        if !self.stashed {
+            return Ok(());
+        }
+        self.stashed = false;
+
+        match self.trace_git(
+            "pop stash",
+            |git| git.stash_pop(),
+            |_| String::from("popped"),
+        ) {
+            Ok(()) => {
+                self.printer.println("Restored your stashed changes")?;
+                Ok(())
+            }
+            Err(err) => {
+                self.printer.error(
+                    "Could not restore your stashed changes, run `git stash pop` in the idea repo",
+                )?;
+                Err(err)
+            }
+        }
+    }
+
+    fn uses_worktree(&self) -> bool {
+        self.cm
+            .config_read(Worktree)
+            .map(|value| value == "true")
+            .unwrap_or(false)
+    }
+
+    fn clear_config(&self) -> Result<(), EurekaError> {
+        self.cm.config_rm().map_err(EurekaError::Config)
+    }
+
+    fn open_idea_file(&self) -> Result<(), EurekaError> {
+        let repo_path = self.cm.config_read(Repo).map_err(EurekaError::Config)?;
+        let file_path = format!("{}/README.md", repo_path);
+        if self.cm.config_read(EncryptTo).is_ok() {
+            self.program_opener
+                .open_pager_decrypted(&format!("{}.gpg", file_path))?;
+        } else {
+            self.program_opener.open_pager(&file_path)?;
+        }
+        Ok(())
+    }
+
+    fn git_add_commit_push(
+        &mut self,
+        target: &IdeaTarget,
+        commit_subject: String,
+        commit_body: String,
+        push: bool,
+    ) -> Result<(), EurekaError> {
+        let branch_name = target.branch.as_str();
+        // A fork that can't be pushed to fails before anything is committed
+        let fork = if push { self.fork_target()? } else { None };
+        let oid = self.git_add_commit(target, &commit_subject, &commit_body, None)?;
+
+        if !push {
+            self.printer
+                .println("Not pushed, run with --flush to push your ideas")?;
+            return self.log_audit_entry(&commit_subject, oid, false);
+        }
+
+        // The detached run pushes, and logs how it went
+        if fork.is_none()
+            && self.pushes_in_background()
+            && self.spawn_background_push(branch_name)?
+        {
+            self.printer
+                .println("Pushing your new idea in the background..")?;
+            return self.log_audit_entry(&commit_subject, oid, false);
+        }
+
+        self.printer.println("Pushing your new idea..")?;
+        match &fork {
+            Some(fork) => self.push_to_fork(&fork.remote, branch_name)?,
+            None => self.push(branch_name)?,
+        }
+        self.printer.println("Pushed!")?;
+        match &fork {
+            Some(fork) => {
+                self.open_pull_request(fork, branch_name, &commit_subject, &commit_body)?
+            }
+            None => self.warn_if_not_default_branch(branch_name)?,
+        }
+        self.notify_pushed(&commit_subject);
+
+        self.log_audit_entry(&commit_subject, oid, true)
+    }
+
+    // The configured fork and the GitHub repos of it and of upstream, None
+    // unless fork_remote is set. `--here` always pushes to origin
+    fn fork_target(&mut self) -> Result<Option<ForkTarget>, EurekaError> {
+        let remote = match self.cm.config_read(ForkRemote) {
+            Ok(remote) if !self.here => remote,
+            _ => return Ok(None),
+        };
+        let upstream_remote = self
+            .cm
+            .config_read(UpstreamRemote)
+            .unwrap_or_else(|_| String::from("origin"));
+        if remote == upstream_remote {
+            return Err(EurekaError::Config(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "fork_remote and upstream_remote are both {}, set fork_remote to the remote of your fork",
+                    remote
+                ),
+            )));
+        }
+
+        Ok(Some(ForkTarget {
+            repo: self.github_repo_of(&remote)?,
+            upstream_repo: self.github_repo_of(&upstream_remote)?,
+            remote,
+        }))
+    }
+
+    fn github_repo_of(&mut self, remote_name: &str) -> Result<String, EurekaError> {
+        let url = self.trace_git(
+            &format!("read the URL of remote {}", remote_name),
+            |git| git.remote_url(remote_name),
+            |url| url.clone(),
+        )?;
+        github_repo(&url).ok_or_else(|| {
+            EurekaError::Config(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Remote {} is {}, which isn't a GitHub repo to open pull requests in",
+                    remote_name, url
+                ),
+            ))
+        })
+    }
+
+    // The idea is pushed already, without a token the pull request can still
+    // be opened from the printed URL
+    fn open_pull_request(
+        &mut self,
+        fork: &ForkTarget,
+        branch_name: &str,
+        title: &str,
+        body: &str,
+    ) -> Result<(), EurekaError> {
+        let owner = fork.repo.split('/').next().unwrap_or_default();
+        let head = format!("{}:{}", owner, branch_name);
+        self.printer.println(&format!(
+            "Opening a pull request against {}..",
+            &fork.upstream_repo
+        ))?;
+        match self
+            .sink
+            .open_pull_request(&fork.upstream_repo, &head, branch_name, title, body)
+        {
+            Ok(url) => self.printer.println(&format!("Opened {}", url))?,
+            Err(err) => {
+                self.printer
+                    .error(&format!("Could not open a pull request: {}", err))?;
+                self.printer.println(&format!(
+                    "Open it at https://github.com/{}/compare/{}...{}?expand=1",
+                    &fork.upstream_repo, branch_name, head
+                ))?;
+            }
+        }
+        Ok(())
+    }
+
+    // After the first push of a branch without an upstream, track origin's
+    // like `git push -u` would
+    fn ensure_upstream(&mut self, branch_name: &str) -> Result<(), EurekaError> {
+        let set = self.trace_git(
+            &format!("set the upstream of {}", branch_name),
+            |git| git.ensure_upstream(branch_name, "origin"),
+            |set| String::from(if *set { "set" } else { "already set" }),
+        )?;
+        if set {
+            self.printer
+                .println(&format!("Set upstream to origin/{}.", branch_name))?;
+        }
+        Ok(())
+    }
+
+    // A push to a side branch succeeds, but nobody may ever look at it
+    fn warn_if_not_default_branch(&mut self, branch_name: &str) -> Result<(), EurekaError> {
+        let enabled = self
+            .cm
+            .config_read(WarnDefaultBranch)
+            .map(|value| value != "false")
+            .unwrap_or(true);
+        // The branch of `--here` and of a branch template are picked on purpose
+        let is_template = self
+            .cm
+            .config_read(Branch)
+            .is_ok_and(|branch| branch.contains('%'));
+        if !enabled || is_template || self.here {
+            return Ok(());
+        }
+
+        let default_branch = match self.cached_default_branch() {
+            Some(default_branch) => {
+                debug!(
+                    "Default branch of origin is {}, from the cache",
+                    default_branch
+                );
+                Ok(Some(default_branch))
+            }
+            None => self.trace_git(
+                "connect to origin for its default branch",
+                |git| git.remote_default_branch(),
+                |branch| format!("default branch is {}", branch.as_deref().unwrap_or("unset")),
+            ),
+        };
+        if let (Ok(Some(default_branch)), Some(cache)) = (&default_branch, self.cache.clone()) {
+            if cache.default_branch.as_ref() != Some(default_branch) {
+                if let Ok(url) = self.git.remote_url("origin") {
+                    self.write_cache(RepoCache {
+                        remote_url: Some(url),
+                        default_branch: Some(default_branch.clone()),
+                        ..cache
+                    });
+                }
+            }
+        }
+        let default_branch = match default_branch {
+            Ok(Some(default_branch)) => default_branch,
+            Ok(None) => return Ok(()),
+            // The idea is pushed already, this is only a hint
+            Err(err) => {
+                debug!("Could not read the default branch of origin: {}", err);
+                return Ok(());
+            }
+        };
+        if default_branch != branch_name {
+            self.printer.error(&format!(
+                "Pushed to {}, but the default branch of origin is {}, your ideas may go unnoticed there",
+                branch_name, default_branch
+            ))?;
+            self.printer.println(
+                "Change `branch` in your config or run eureka --clear-config to set it up again, \
+                 or set `warn_default_branch` to false if the branch is on purpose",
+            )?;
+        }
+        Ok(())
+    }
+
+    // Opt-in, for captures from a hotkey where no one watches the terminal.
+    // The idea is pushed already, so failing to notify is only logged
+    fn notify_pushed(&self, summary: &str) {
+        let enabled = self
+            .cm
+            .config_read(Notify)
+            .map(|value| value == "true")
+            .unwrap_or(false);
+        if !enabled {
+            return;
+        }
+
+        if let Err(err) = self.program_opener.notify("Idea pushed", summary) {
+            warn!("Could not show a notification: {}", err);
+        }
+    }
+
+    fn git_add_commit(
+        &mut self,
+        target: &IdeaTarget,
+        commit_subject: &str,
+        commit_body: &str,
+        author_date: Option<DateTime<Local>>,
+    ) -> Result<git2::Oid, EurekaError> {
+        let branch_name = target.branch.as_str();
+        // Staging an ignored file is a silent no-op, which would commit nothing
+        if self.is_idea_file_ignored(target)? {
+            return Err(EurekaError::Git(git2::Error::from_str(
+                "Your idea file is gitignored; ideas won't be committed.",
+            )));
+        }
+
+        self.printer.println(&format!(
+            "Adding and committing your new idea to {}..",
+            &branch_name
+        ))?;
+        self.trace_git(
+            &format!("checkout branch {}", branch_name),
+            |git| git.checkout_branch(branch_name),
+            |_| String::from("checked out"),
+        )?;
+        self.refuse_oversized_files()?;
+        self.trace_git(
+            "stage idea files",
+            |git| git.add(),
+            |staged| format!("staged {} file(s)", staged),
+        )?;
+        let (commit_subject, commit_body) = self.fit_commit_subject(commit_subject, commit_body);
+        let git_time = |date: DateTime<Local>| {
+            git2::Time::new(date.timestamp(), date.offset().local_minus_utc() / 60)
+        };
+        let commit_time = self.backdate.map(git_time);
+        // An imported idea keeps when it was had as its author date
+        let author_time = author_date.map(git_time).or(commit_time);
+        let oid = self.trace_git(
+            "commit",
+            |git| git.commit(&commit_subject, &commit_body, author_time, commit_time),
+            |oid| format!("committed {:.7}", oid.to_string()),
+        )?;
+        self.printer.println("Added and committed!")?;
+        self.printer.status(&format!("IDEA_COMMITTED {}", oid))?;
+
+        Ok(oid)
+    }
+
+    // A summary over max_subject_len is shortened in the subject and kept in
+    // full at the start of the body
+    fn fit_commit_subject(&self, subject: &str, body: &str) -> (String, String) {
+        let max_len = match self
+            .cm
+            .config_read(MaxSubjectLen)
+            .map(|value| value.parse())
+        {
+            Ok(Ok(max_len)) => max_len,
+            _ => DEFAULT_MAX_SUBJECT_LEN,
+        };
+
+        match shorten_subject(subject, max_len) {
+            Some(shortened) => (shortened, join_paragraphs(&[subject, body])),
+            None => (subject.to_string(), body.to_string()),
+        }
+    }
+
+    // Large files bloat the idea repo for good, unless Git LFS stores them
+    fn refuse_oversized_files(&mut self) -> Result<(), EurekaError> {
+        let max_bytes = match self.cm.config_read(MaxBlobBytes).map(|value| value.parse()) {
+            Ok(Ok(max_bytes)) => max_bytes,
+            _ => DEFAULT_MAX_BLOB_BYTES,
+        };
+
+        let oversized = self.git.oversized_files(max_bytes)?;
+        for (path, bytes) in &oversized {
+            self.printer.error(&format!(
+                "{} is {} bytes, track it with Git LFS (git lfs track \"{}\") or raise max_blob_bytes",
+                path, bytes, path
+            ))?;
+        }
+        if !oversized.is_empty() {
+            return Err(EurekaError::Git(git2::Error::from_str(&format!(
+                "Refusing to commit files over {} bytes that Git LFS doesn't track",
+                max_bytes
+            ))));
+        }
+        Ok(())
+    }
+
+    // Whether the file that gets committed, `<file>.gpg` when encrypting, is ignored
+    fn is_idea_file_ignored(&self, target: &IdeaTarget) -> Result<bool, EurekaError> {
+        let file = if self.cm.config_read(EncryptTo).is_ok() {
+            format!("{}.gpg", &target.file)
+        } else {
+            target.file.clone()
+        };
+        let relative_path = Path::new(&file)
+            .strip_prefix(&target.dir)
+            .unwrap_or(Path::new(&file))
+            .display()
+            .to_string();
+
+        Ok(self.git.is_ignored(&relative_path)?)
+    }
+
+    // Append the captured idea to the audit log as a line of JSON, if enabled
+    fn log_audit_entry(
+        &mut self,
+        summary: &str,
+        oid: git2::Oid,
+        pushed: bool,
+    ) -> Result<(), EurekaError> {
+        let Some(log_path) = self.audit_log_path()? else {
+            return Ok(());
+        };
+        let repo_path = self.cm.config_read(Repo).map_err(EurekaError::Config)?;
+
+        let entry = serde_json::json!({
+            "timestamp": self.clock.now().to_rfc3339(),
+            "summary": summary,
+            "commit": oid.to_string(),
+            "repo": repo_path,
+            "pushed": pushed,
+        });
+        self.append_audit_entry(&log_path, &entry)
+    }
+
+    // How a background push went, as a line of JSON with the `error` if it
+    // failed. It's the only place to find out, the push has no terminal
+    fn log_background_push(
+        &mut self,
+        branch_name: &str,
+        result: &Result<(), EurekaError>,
+    ) -> Result<(), EurekaError> {
+        let Some(log_path) = self.audit_log_path()? else {
+            return Ok(());
+        };
+        let repo_path = self.cm.config_read(Repo).map_err(EurekaError::Config)?;
+
+        let entry = serde_json::json!({
+            "timestamp": self.clock.now().to_rfc3339(),
+            "repo": repo_path,
+            "branch": branch_name,
+            "pushed": result.is_ok(),
+            "error": result.as_ref().err().map(|err| err.to_string()),
+        });
+        self.append_audit_entry(&log_path, &entry)
+    }
+
+    // None unless `audit_log` is on
+    fn audit_log_path(&self) -> Result<Option<String>, EurekaError> {
+        match self.cm.config_read(AuditLog) {
+            Ok(value) if value == "true" => Ok(Some(
+                self.cm
+                    .config_dir_path()
+                    .map_err(EurekaError::Config)?
+                    .join(AUDIT_LOG_FILE_NAME)
+                    .display()
+                    .to_string(),
+            )),
+            Ok(value) if value != "false" => Ok(Some(value)),
+            _ => Ok(None),
+        }
+    }
+
+    fn append_audit_entry(
+        &mut self,
+        log_path: &str,
+        entry: &serde_json::Value,
+    ) -> Result<(), EurekaError> {
+        // The idea is safe in git already, a missing log entry shouldn't fail the run
+        if let Err(err) = self
+            .file_handler
+            .append_log_entry(log_path, &entry.to_string())
+        {
+            self.printer.error(&format!(
+                "Could not write to the audit log {}: {}",
+                &log_path, err
+            ))?;
+        }
+        Ok(())
+    }
+
+    fn push(&mut self, branch_name: &str) -> Result<(), EurekaError> {
+        // Wait for a background push instead of racing it
+        if !self.holds_push_lock && self.pushes_in_background() {
+            self.acquire_push_lock()?;
+            let result = self.push(branch_name);
+            let released = self.release_push_lock();
+            return result.and(released);
+        }
+
+        let description = format!(
+            "connect to origin and push refs/heads/{}:refs/heads/{}",
+            branch_name, branch_name
+        );
+        match self.push_once(&description, branch_name) {
+            // Another machine pushed ideas since our last pull, take them in and try once more
+            Err(EurekaError::Git(err)) if err.code() == git2::ErrorCode::NotFastForward => {
+                let rebase = match self.cm.config_read(PushStrategy).as_deref() {
+                    Ok("pull-rebase") if !self.here => true,
+                    Ok("pull-merge") if !self.here => false,
+                    _ => return Err(EurekaError::Git(err)),
+                };
+                self.printer.println(&format!(
+                    "origin/{} has ideas from elsewhere, pulling them in..",
+                    branch_name
+                ))?;
+                self.trace_git(
+                    &format!(
+                        "{} origin/{} into {}",
+                        if rebase { "rebase onto" } else { "merge" },
+                        branch_name,
+                        branch_name
+                    ),
+                    |git| git.pull_branch(branch_name, rebase),
+                    |oid| format!("{} is now at {}", branch_name, oid),
+                )?;
+                self.push_once(&description, branch_name)?;
+            }
+            result => result?,
+        }
+        self.ensure_upstream(branch_name)
+    }
+
+    // `--here` pushes the repo of the current directory, never in the background
+    fn pushes_in_background(&self) -> bool {
+        !self.here
+            && self
+                .cm
+                .config_read(BackgroundPush)
+                .map(|value| value == "true")
+                .unwrap_or(false)
+    }
+
+    // Take the push lock and start the detached run that pushes `branch_name`
+    // and releases it. False if it couldn't be started, to push right away
+    fn spawn_background_push(&mut self, branch_name: &str) -> Result<bool, EurekaError> {
+        self.acquire_push_lock()?;
+        let args = [String::from("--background-push"), branch_name.to_string()];
+        match self.program_opener.spawn_detached(&args) {
+            Ok(()) => Ok(true),
+            Err(err) => {
+                self.release_push_lock()?;
+                self.printer.error(&format!(
+                    "Could not push in the background, pushing now: {}",
+                    err
+                ))?;
+                Ok(false)
+            }
+        }
+    }
+
+    // The detached run, with the push lock taken for it by the capture
+    fn run_background_push(&mut self, branch_name: &str) -> Result<(), EurekaError> {
+        self.holds_push_lock = true;
+        let result = self.open_repo().and_then(|_| self.push(branch_name));
+        self.log_background_push(branch_name, &result)?;
+        let released = self.release_push_lock();
+        result.and(released)
+    }
+
+    // One push at a time while `background_push` is on, a capture can't
+    // start another before the last background push is done
+    fn acquire_push_lock(&mut self) -> Result<(), EurekaError> {
+        let lock_path = self.push_lock_path()?;
+        let start = Instant::now();
+        let mut waiting = false;
+        while !self.file_handler.try_lock(&lock_path)? {
+            if start.elapsed().as_secs() >= PUSH_LOCK_TIMEOUT_SECS {
+                return Err(EurekaError::Io(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!(
+                        "A push has held {} for {}s, remove it if no eureka is running",
+                        lock_path, PUSH_LOCK_TIMEOUT_SECS
+                    ),
+                )));
+            }
+            if !waiting {
+                self.printer
+                    .println("Waiting for the background push to finish..")?;
+                waiting = true;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+        self.holds_push_lock = true;
+        Ok(())
+    }
+
+    fn release_push_lock(&mut self) -> Result<(), EurekaError> {
+        let lock_path = self.push_lock_path()?;
+        self.file_handler.unlock(&lock_path)?;
+        self.holds_push_lock = false;
+        Ok(())
+    }
+
+    fn push_lock_path(&self) -> Result<String, EurekaError> {
+        Ok(self
+            .cm
+            .config_dir_path()
+            .map_err(EurekaError::Config)?
+            .join(PUSH_LOCK_FILE_NAME)
+            .display()
+            .to_string())
+    }
+
+    fn push_once(&mut self, description: &str, branch_name: &str) -> Result<(), EurekaError> {
+        self.trace_git(
+            description,
+            |git| git.push(branch_name),
+            |_| String::from("pushed"),
+        )?;
+        self.printer.status("PUSHED origin")?;
+        Ok(())
+    }
+
+    fn push_to_fork(&mut self, remote_name: &str, branch_name: &str) -> Result<(), EurekaError> {
+        self.trace_git(
+            &format!(
+                "connect to {} and push refs/heads/{}:refs/heads/{}",
+                remote_name, branch_name, branch_name
+            ),
+            |git| git.push_to(remote_name, branch_name),
+            |_| String::from("pushed"),
+        )?;
+        self.printer.status(&format!("PUSHED {}", remote_name))?;
+        Ok(())
+    }
+
+    // Run a git step, with --verbose print what it does and how long it took
+    fn trace_git<T>(
+        &mut self,
+        description: &str,
+        step: impl FnOnce(&mut G) -> Result<T, git2::Error>,
+        outcome: impl FnOnce(&T) -> String,
+    ) -> Result<T, EurekaError> {
+        if !self.verbose {
+            return step(&mut self.git).map_err(EurekaError::from);
+        }
+
+        self.printer.trace(&format!("git: {}..", description))?;
+        let start = Instant::now();
+        let result = step(&mut self.git);
+        let elapsed = start.elapsed();
+        match &result {
+            Ok(value) => {
+                self.printer
+                    .trace(&format!("git: {} in {:.2?}", outcome(value), elapsed))?
+            }
+            Err(err) => self.printer.trace(&format!(
+                "git: failed after {:.2?}: {}",
+                elapsed,
+                err.message()
+            ))?,
+        }
+
+        result.map_err(EurekaError::from)
+    }
+
+    // Ask for the repo, branch and editor, each defaulting to what's configured.
+    // Used for the first time setup and for --setup
+    fn run_setup(&mut self, first_time: bool, verify: bool) -> Result<(), EurekaError> {
+        // If config dir is missing - create it
+        if !self.cm.config_dir_exists() {
+            self.cm.config_dir_create().map_err(EurekaError::Config)?;
+            debug!("Created config dir");
+        }
+
+        self.printer.fts_banner()?;
+
+        // If repo path is missing - ask for it
+        if !first_time || self.cm.config_read(Repo).is_err() {
+            let Some(repo_path) = self.setup_repo_path()? else {
+                self.printer.println("Setup aborted, nothing was saved")?;
+                return Ok(());
+            };
+            debug!("Setup repo path successfully");
+            self.setup_branch(&repo_path)?;
+            debug!("Setup branch successfully");
+            self.setup_editor()?;
+            debug!("Setup editor successfully");
+        }
+
+        self.printer.println(if first_time {
+            "First time setup complete. Happy ideation!"
+        } else {
+            "Setup complete. Happy ideation!"
+        })?;
+        self.printer.status("SETUP_COMPLETE")?;
+        if verify {
+            return self.verify_setup();
+        }
+        Ok(())
+    }
+
+    // A wrong remote or missing credentials would otherwise only show up when
+    // the first idea is pushed. Commits the idea repo marker, or an empty
+    // commit once it's committed, and pushes it
+    fn verify_setup(&mut self) -> Result<(), EurekaError> {
+        let target = self.init_idea_target()?;
+        if !self.confirm_idea_repo(&target)? {
+            return Ok(());
+        }
+        let marker = format!("{}/{}", &target.dir, IDEA_REPO_MARKER);
+        if !self.file_handler.has_marker(&marker) {
+            self.file_handler.create_marker(&marker)?;
+        }
+        // Uncommitted changes to the ideas stay out of the check's commit
+        self.git.skip_idea_pathspecs();
+        self.git.add_pathspec(IDEA_REPO_MARKER);
+
+        let fork = self.fork_target()?;
+        self.git_add_commit(&target, VERIFY_SETUP_SUBJECT, "", None)?;
+        self.printer
+            .println(&format!("Pushing {} to check your setup..", &target.branch))?;
+        match &fork {
+            Some(fork) => self.push_to_fork(&fork.remote, &target.branch)?,
+            None => self.push(&target.branch)?,
+        }
+        self.printer
+            .println("Pushed! Your ideas can be committed and pushed")?;
+        Ok(())
+    }
+
+    // None if the user quits the setup
+    fn setup_repo_path(&mut self) -> Result<Option<String>, EurekaError> {
+        let current = self.cm.config_read(Repo).ok();
+        let repos = self.scan_for_repos()?;
+        for (i, repo) in repos.iter().enumerate() {
+            self.printer
+                .println(&format!("{}. {}", i + 1, repo.display()))?;
+        }
+        let header = if repos.is_empty() {
+            "Path to your idea repo"
+        } else {
+            "Number or path of your idea repo"
+        };
+        let header = with_default(header, current.as_deref());
+
+        for attempt in 1..=SETUP_MAX_ATTEMPTS {
+            self.printer.input_header(&header)?;
+            let user_input = &self.reader.read_input().map_err(EurekaError::Input)?;
+            if user_input == "q" {
+                return Ok(None);
+            }
+            if let (true, Some(current)) = (user_input.is_empty(), &current) {
+                return Ok(Some(current.clone()));
+            }
+
+            let reason = if user_input.is_empty() {
+                String::from("No path given")
+            } else {
+                let path = match user_input.parse::<usize>() {
+                    Ok(number) if !repos.is_empty() => number
+                        .checked_sub(1)
+                        .and_then(|i| repos.get(i))
+                        .map(PathBuf::as_path),
+                    _ => Some(Path::new(user_input)),
+                };
+
+                let current_dir = env::current_dir().map_err(EurekaError::Io)?;
+                match path.map(|path| normalize_repo_path(path, dirs::home_dir(), &current_dir)) {
+                    // Only a path that opens as a git repo is saved
+                    Some(Ok(path)) => {
+                        let repo_path = path.display().to_string();
+                        match self.git.init(&repo_path) {
+                            Ok(()) => {
+                                self.cm
+                                    .config_write(Repo, repo_path.clone())
+                                    .map_err(EurekaError::Config)?;
+                                return Ok(Some(repo_path));
+                            }
+                            Err(err) => {
+                                debug!("Could not open {}: {}", repo_path, err);
+                                format!("{} isn't a git repo", repo_path)
+                            }
+                        }
+                    }
+                    Some(Err(err)) => err.to_string(),
+                    None => format!(
+                        "Choose a number between 1 and {} or type a path",
+                        repos.len()
+                    ),
+                }
+            };
+
+            if attempt < SETUP_MAX_ATTEMPTS {
+                self.printer.error(&format!(
+                    "{}, try again or type q to quit the setup",
+                    reason
+                ))?;
+            }
+        }
+
+        Err(EurekaError::Input(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "No idea repo given in {} attempts, run eureka again to retry the setup",
+                SETUP_MAX_ATTEMPTS
+            ),
+        )))
+    }
+
+    // Git repos to pick the idea repo from, none if the scan failed
+    fn scan_for_repos(&mut self) -> Result<Vec<PathBuf>, EurekaError> {
+        let scan_dir = match env::var_os("EUREKA_SCAN_DIR")
+            .map(PathBuf::from)
+            .or_else(dirs::home_dir)
+        {
+            Some(scan_dir) => scan_dir.display().to_string(),
+            None => return Ok(vec![]),
+        };
+
+        match self.file_handler.find_git_repos(&scan_dir, REPO_SCAN_DEPTH) {
+            Ok(repos) => {
+                if !repos.is_empty() {
+                    self.printer
+                        .println(&format!("Found these git repos in {}:", &scan_dir))?;
+                }
+                Ok(repos)
+            }
+            Err(err) => {
+                debug!("Could not look for git repos in {}: {}", &scan_dir, err);
+                Ok(vec![])
+            }
+        }
+    }
+
+    fn setup_branch(&mut self, repo_path: &str) -> Result<(), EurekaError> {
+        // Suggest the configured branch, or the one the repo already pushes to
+        let default_branch = match self.cm.config_read(Branch) {
+            Ok(branch) => branch,
+            Err(_) => self
+                .git
+                .init(repo_path)
+                .and_then(|_| self.git.upstream_branch())
+                .ok()
+                .flatten()
+                .unwrap_or_else(|| BRANCH_NAME.to_string()),
+        };
+
+        loop {
+            self.printer
+                .input_header(&format!("Name of branch (default: {})", default_branch))?;
+            let user_input = self.reader.read_input().map_err(EurekaError::Input)?;
+            let branch = if user_input.is_empty() {
+                default_branch.clone()
+            } else {
+                user_input
+            };
+
+            if git2::Branch::name_is_valid(&branch).unwrap_or(false) {
+                break self
+                    .cm
+                    .config_write(Branch, branch)
+                    .map_err(EurekaError::Config);
+            } else {
+                self.printer.error("Not a valid branch name")?;
+            }
+        }
+    }
+
+    fn setup_editor(&mut self) -> Result<(), EurekaError> {
+        let current = self.cm.config_read(Editor).ok();
+        // $EDITOR is respected as is, only ask when there's nothing to go on
+        // or an editor to change
+        if current.is_none() && self.program_opener.resolve_editor().is_some() {
+            return Ok(());
+        }
+
+        let editors = self.program_opener.available_editors();
+        let editor = if editors.is_empty() {
+            self.setup_editor_command(current.as_deref())?
+        } else {
+            self.choose_editor(&editors, current.as_deref())?
+        };
+        self.cm
+            .config_write(Editor, editor)
+            .map_err(EurekaError::Config)
+    }
+
+    fn choose_editor(
+        &mut self,
+        editors: &[String],
+        current: Option<&str>,
+    ) -> Result<String, EurekaError> {
+        for (i, editor) in editors.iter().enumerate() {
+            self.printer.println(&format!("{}. {}", i + 1, editor))?;
+        }
+
+        let header = with_default("Number or name of the editor to write ideas with", current);
+        loop {
+            self.printer.input_header(&header)?;
+            let user_input = self.reader.read_input().map_err(EurekaError::Input)?;
+            if let (true, Some(current)) = (user_input.is_empty(), current) {
+                break Ok(current.to_string());
+            }
+
+            match pick_by_number_or_name(editors, &user_input) {
+                Some(editor) => break Ok(editor.to_string()),
+                None => self.printer.error(&format!(
+                    "Choose a number between 1 and {} or type part of a name",
+                    editors.len()
+                ))?,
+            }
+        }
+    }
+
+    fn setup_editor_command(&mut self, current: Option<&str>) -> Result<String, EurekaError> {
+        self.printer
+            .println("Found no common editor in your PATH")?;
+
+        let header = with_default("Command to open your editor with", current);
+        loop {
+            self.printer.input_header(&header)?;
+            let user_input = self.reader.read_input().map_err(EurekaError::Input)?;
+
+            if !user_input.is_empty() {
+                break Ok(user_input);
+            }
+            if let Some(current) = current {
+                break Ok(current.to_string());
+            }
+        }
+    }
+
+    fn is_config_missing(&self) -> bool {
+        self.cm.config_read(Repo).is_err()
+    }
+}
+
+// A setup prompt, mentioning the value an empty answer keeps
+fn with_default(header: &str, current: Option<&str>) -> String {
+    match current {
+        Some(current) => format!("{} (default: {})", header, current),
+        None => header.to_string(),
+    }
+}
+
+// Pick an editor or category by its number in the list, or by a part of its
+// name that matches only one of them
+fn pick_by_number_or_name<'a>(names: &'a [String], user_input: &str) -> Option<&'a str> {
+    if let Ok(number) = user_input.parse::<usize>() {
+        return number
+            .checked_sub(1)
+            .and_then(|i| names.get(i))
+            .map(String::as_str);
+    }
+
+    let needle = user_input.to_lowercase();
+    let mut matches = names
+        .iter()
+        .filter(|name| !needle.is_empty() && name.to_lowercase().contains(&needle));
+    match (matches.next(), matches.next()) {
+        (Some(name), None) => Some(name.as_str()),
+        _ => None,
+    }
+}
+
+// Title of the last idea heading among the added lines of `patch`
+fn last_added_heading(patch: &str) -> Option<String> {
+    patch
+        .lines()
+        .rev()
+        .filter_map(|line| line.strip_prefix('+'))
+        // Not the `+++ b/README.md` header
+        .filter(|line| !line.starts_with("++"))
+        .filter_map(|line| line.trim_end().strip_prefix("## "))
+        .map(str::trim)
+        .find(|title| !title.is_empty())
+        .map(String::from)
+}
+
+// $EUREKA_RANDOM_SEED makes the pick of --random repeatable
+fn random_seed() -> Option<u64> {
+    env::var("EUREKA_RANDOM_SEED")
+        .ok()
+        .and_then(|seed| seed.parse().ok())
+}
+
+// One round of SplitMix64, plenty random for picking an idea
+fn random_index(len: usize, seed: u64) -> usize {
+    (splitmix64(seed) % len as u64) as usize
+}
+
+fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+// A random id for a keyed idea. Seeded from the system time rather than the
+// clock, which may be backdated, and the process so two machines or two ideas
+// captured at once don't share it
+fn new_idea_id() -> String {
+    static COUNT: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_nanos() as u64)
+        .unwrap_or_default();
+    let seed =
+        nanos ^ (u64::from(std::process::id()) << 32) ^ COUNT.fetch_add(1, Ordering::Relaxed);
+    let high = splitmix64(seed);
+    format_uuid(high, splitmix64(high))
+}
+
+// Path of the idea file for `now`, relative to the idea repo. `extension` is
+// added when the pattern doesn't end in one
+pub(crate) fn expand_date_layout(
+    pattern: &str,
+    now: DateTime<Local>,
+    extension: &str,
+) -> io::Result<PathBuf> {
+    let invalid = |reason: &str| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid date layout '{}': {}", pattern, reason),
+        )
+    };
+
+    let mut path =
+        PathBuf::from(format_date(pattern, now).ok_or_else(|| invalid("unknown % specifier"))?);
+
+    let inside_repo = path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if !inside_repo {
+        return Err(invalid("must be a relative path inside the idea repo"));
+    }
+    if path.file_name().is_none() {
+        return Err(invalid("must name a file"));
+    }
+    if path.extension().is_none() {
+        path.set_extension(extension);
+    }
+
+    Ok(path)
+}
+
+// An idea file extension, which is only letters and digits, e.g. `md` or `org`
+pub(crate) fn check_file_extension(extension: &str) -> io::Result<()> {
+    if extension.is_empty() || !extension.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Invalid file extension '{}': use only letters and digits, e.g. md or org",
+                extension
+            ),
+        ));
+    }
+    Ok(())
+}
+
+// Path of the file for one idea, relative to the folder it's written to.
+// Slashes are only allowed when `nested`, i.e. with a date layout
+pub(crate) fn expand_file_name_template(
+    template: &str,
+    now: DateTime<Local>,
+    summary: &str,
+    extension: &str,
+    nested: bool,
+) -> io::Result<PathBuf> {
+    let invalid = |reason: &str| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid file name template '{}': {}", template, reason),
+        )
+    };
+
+    let name = template
+        .replace("{date}", &now.format("%Y-%m-%d").to_string())
+        .replace("{slug}", &slugify(summary))
+        .replace("{ext}", extension);
+    if name.contains(['{', '}']) {
+        return Err(invalid("use only {date}, {slug} and {ext}"));
+    }
+
+    let path = PathBuf::from(&name);
+    let mut components = path.components();
+    let safe = components.all(|component| matches!(component, Component::Normal(_)));
+    if name.is_empty() || !safe || name.ends_with('/') {
+        return Err(invalid("must be a relative path inside the idea repo"));
+    }
+    if !nested && (name.contains('/') || name.contains('\\')) {
+        return Err(invalid(
+            "must be a single file name, set date_layout for folders",
+        ));
+    }
+
+    Ok(path)
+}
+
+// The summary as a file name, e.g. `Buy a boat!` becomes `buy-a-boat`
+fn slugify(summary: &str) -> String {
+    let mut slug = String::new();
+    for c in summary.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+        if slug.chars().count() >= MAX_SLUG_LEN {
+            break;
+        }
+    }
+
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        String::from("idea")
+    } else {
+        sanitize_filename(slug)
+    }
+}
+
+// The repo path typed during setup as an absolute path, with a leading `~`
+// expanded and without trailing slashes. A path that exists is canonicalized
+fn normalize_repo_path(
+    path: &Path,
+    home_dir: Option<PathBuf>,
+    current_dir: &Path,
+) -> io::Result<PathBuf> {
+    let path = match path.strip_prefix("~") {
+        Ok(rest) => home_dir
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "Could not find your home directory to expand ~",
+                )
+            })?
+            .join(rest),
+        Err(_) => path.to_path_buf(),
+    };
+    // Relative paths are relative to where the setup runs
+    let path = current_dir.join(path);
+
+    match path.canonicalize() {
+        Ok(path) => Ok(path),
+        Err(_) => Ok(path.components().collect()),
+    }
+}
+
+// A queued idea may start with the `[date]` it was had, e.g. when imported
+// from another tool. Text in brackets that isn't a date is part of the idea
+fn split_queued_date(entry: &str, now: DateTime<Local>) -> (Option<DateTime<Local>>, &str) {
+    let dated = entry
+        .strip_prefix('[')
+        .and_then(|rest| rest.split_once(']'))
+        .and_then(|(date, text)| Some((parse_backdate(date.trim(), now).ok()?, text.trim_start())));
+    match dated {
+        Some((date, text)) if !text.is_empty() => (Some(date), text),
+        _ => (None, entry),
+    }
+}
+
+// An ISO 8601 date, with or without a time, to backdate an idea to
+fn parse_backdate(date: &str, now: DateTime<Local>) -> io::Result<DateTime<Local>> {
+    let invalid = |reason: &str| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid date '{}': {}", date, reason),
+        )
+    };
+
+    let parsed = match DateTime::parse_from_rfc3339(date) {
+        Ok(parsed) => parsed.with_timezone(&Local),
+        // Without an offset it's a local date, at midnight without a time
+        Err(_) => ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"]
+            .iter()
+            .find_map(|format| NaiveDateTime::parse_from_str(date, format).ok())
+            .or_else(|| {
+                NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                    .ok()
+                    .and_then(|day| day.and_hms_opt(0, 0, 0))
+            })
+            .and_then(|naive| Local.from_local_datetime(&naive).earliest())
+            .ok_or_else(|| invalid("use e.g. 2024-06-01 or 2024-06-01T09:30"))?,
+    };
+    // A day of slack for dates written in another time zone
+    if parsed > now + Duration::days(1) {
+        return Err(invalid("it's in the future"));
+    }
+
+    Ok(parsed)
+}
+
+// The non-empty `parts`, separated by a blank line
+fn join_paragraphs(parts: &[&str]) -> String {
+    parts
+        .iter()
+        .map(|part| part.trim())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+// `subject` cut at the last word that fits in `max_len` characters, ellipsis
+// included, or None if it fits already or `max_len` is 0
+fn shorten_subject(subject: &str, max_len: usize) -> Option<String> {
+    if max_len == 0 || subject.chars().count() <= max_len {
+        return None;
+    }
+
+    let fitting: String = subject.chars().take(max_len - 1).collect();
+    let ends_at_word = subject
+        .chars()
+        .nth(max_len - 1)
+        .is_some_and(char::is_whitespace);
+    let cut = match fitting.rfind(' ') {
+        _ if ends_at_word => fitting.as_str(),
+        Some(space) if !fitting[..space].trim().is_empty() => &fitting[..space],
+        // A single long word is cut where it doesn't fit
+        _ => fitting.as_str(),
+    };
+    Some(format!("{}…", cut.trim_end()))
+}
+
+// When the section of `heading` was last committed, none when any of its
+// lines wasn't committed yet
+fn last_touched(times: &[Option<i64>], heading: &Heading) -> Option<i64> {
+    (heading.line..=heading.section_end)
+        .map(|number| times.get(number - 1).copied().flatten())
+        .try_fold(i64::MIN, |latest, time| time.map(|time| latest.max(time)))
+}
+
+// How closely `title` matches `query` from 0 to 100, if at least `FUZZY_MIN_SCORE`.
+// Each word of the query is matched to its closest word of the title, so typos
+// and a different word order still match, e.g. `boet` finds `Buy a boat`
+fn fuzzy_score(query: &str, title: &str) -> Option<usize> {
+    let query = words(query);
+    let title = words(title);
+    if query.is_empty() || title.is_empty() {
+        return None;
+    }
+
+    let by_word = query
+        .iter()
+        .map(|q| title.iter().map(|t| similarity(q, t)).max().unwrap_or(0))
+        .sum::<usize>()
+        / query.len();
+    let whole = similarity(&query.concat(), &title.concat());
+    let score = by_word.max(whole);
+    (score >= FUZZY_MIN_SCORE).then_some(score)
+}
+
+// Lowercase words of `text`, without punctuation
+fn words(text: &str) -> Vec<Vec<char>> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase().chars().collect())
+        .collect()
+}
+
+// 100 minus the share of `a` and `b` that differs, by Levenshtein distance
+fn similarity(a: &[char], b: &[char]) -> usize {
+    let len = a.len().max(b.len());
+    if len == 0 {
+        return 100;
+    }
+    (len - levenshtein(a, b)) * 100 / len
+}
+
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+// How libgit2 was built decides e.g. if SSH remotes can be pushed to at all
+fn version_info() -> serde_json::Value {
+    let git2 = git2::Version::get();
+    let (major, minor, patch) = git2.libgit2_version();
+    serde_json::json!({
+        "eureka": env!("CARGO_PKG_VERSION"),
+        "git2": git2.crate_version(),
+        "libgit2": format!("{}.{}.{}", major, minor, patch),
+        "libgit2_vendored": git2.vendored(),
+        "https": git2.https(),
+        "ssh": git2.ssh(),
+        "threads": git2.threads(),
+    })
+}
+
+// A branch name with date placeholders, e.g. `ideas/%Y-w%W`, for `now`
+pub(crate) fn expand_branch_template(template: &str, now: DateTime<Local>) -> io::Result<String> {
+    // Plain branch names are used as they are
+    if !template.contains('%') {
+        return Ok(template.to_string());
+    }
+
+    let invalid = |reason: String| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid branch template '{}': {}", template, reason),
+        )
+    };
+    let branch = format_date_with(template, now, sanitize_ref_component)
+        .ok_or_else(|| invalid(String::from("unknown % specifier")))?;
+    if !git2::Reference::is_valid_name(&format!("refs/heads/{}", branch)) {
+        return Err(invalid(format!("'{}' is not a valid branch name", branch)));
+    }
+
+    Ok(branch)
+}
+
+// Whether `branch` can be expanded from `template`, every date placeholder
+// matches one or more characters other than `/`
+fn matches_branch_template(template: &str, branch: &str) -> bool {
+    fn matches(template: &[char], branch: &[char]) -> bool {
+        match template {
+            [] => branch.is_empty(),
+            ['%', '%', rest @ ..] => branch.first() == Some(&'%') && matches(rest, &branch[1..]),
+            ['%', rest @ ..] => {
+                // Skip modifiers like the padding in `%-d`, up to the specifier
+                let modifiers = rest.iter().take_while(|c| !c.is_ascii_alphabetic());
+                let rest = rest.get(modifiers.count() + 1..).unwrap_or_default();
+                let segment = branch.iter().take_while(|&&c| c != '/').count();
+                (1..=segment).any(|len| matches(rest, &branch[len..]))
+            }
+            [c, rest @ ..] => branch.first() == Some(c) && matches(rest, &branch[1..]),
+        }
+    }
+
+    let template: Vec<char> = template.chars().collect();
+    let branch: Vec<char> = branch.chars().collect();
+    matches(&template, &branch)
+}
+
+// `pattern` formatted with strftime specifiers, None if it has unknown ones
+fn format_date(pattern: &str, now: DateTime<Local>) -> Option<String> {
+    let items: Vec<Item> = StrftimeItems::new(pattern).collect();
+    if items.contains(&Item::Error) {
+        return None;
+    }
+    Some(now.format_with_items(items.into_iter()).to_string())
+}
+
+// Like `format_date`, with what each specifier expands to passed through
+// `sanitize`, e.g. so the `:` of `%T` can't end up in a branch name. The text
+// around the specifiers is kept as it is
+fn format_date_with(
+    pattern: &str,
+    now: DateTime<Local>,
+    sanitize: impl Fn(&str) -> String,
+) -> Option<String> {
+    let mut formatted = String::new();
+    let mut rest = pattern;
+    while let Some(start) = rest.find('%') {
+        formatted.push_str(&rest[..start]);
+        // Modifiers like the padding in `%-d` come before the specifier
+        let spec = &rest[start..];
+        let len = spec[1..]
+            .find(|c: char| c.is_ascii_alphabetic() || c == '%')
+            .map_or(spec.len(), |i| i + 2);
+        formatted.push_str(&sanitize(&format_date(&spec[..len], now)?));
+        rest = &spec[len..];
+    }
+    formatted.push_str(rest);
+    Some(formatted)
+}
+
+#[allow(non_snake_case)]
+#[cfg(test)]
+mod tests {
+    use crate::{
+        expand_branch_template, expand_file_name_template, fuzzy_score, last_added_heading,
+        levenshtein, normalize_repo_path, shorten_subject, slugify, split_queued_date,
+    };
+    use chrono::{Local, TimeZone};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use tempfile::TempDir;
+
+    fn chars(text: &str) -> Vec<char> {
+        text.chars().collect()
+    }
+
+    #[test]
+    fn test_lib__shorten_subject__fits() {
+        assert_eq!(shorten_subject("some summary", 12), None);
+        assert_eq!(shorten_subject("some summary", 0), None);
+    }
+
+    #[test]
+    fn test_lib__shorten_subject__cut_at_word() {
+        assert_eq!(
+            shorten_subject("some longer summary", 11),
+            Some(String::from("some…"))
+        );
+        // `some longer` fits exactly with the ellipsis
+        assert_eq!(
+            shorten_subject("some longer summary", 12),
+            Some(String::from("some longer…"))
+        );
+    }
+
+    #[test]
+    fn test_lib__shorten_subject__no_space() {
+        assert_eq!(
+            shorten_subject("supercalifragilistic", 10),
+            Some(String::from("supercali…"))
+        );
+        assert_eq!(
+            shorten_subject("アイデアアイデア", 5),
+            Some(String::from("アイデア…"))
+        );
+    }
+
+    #[test]
+    fn test_lib__levenshtein() {
+        assert_eq!(levenshtein(&chars("boat"), &chars("boat")), 0);
+        assert_eq!(levenshtein(&chars("boet"), &chars("boat")), 1);
+        assert_eq!(levenshtein(&chars("kitten"), &chars("sitting")), 3);
+        assert_eq!(levenshtein(&chars(""), &chars("boat")), 4);
+    }
+
+    #[test]
+    fn test_lib__fuzzy_score__typo() {
+        assert_eq!(fuzzy_score("boet", "Buy a boat"), Some(75));
+        assert_eq!(fuzzy_score("BOAT", "Buy a boat"), Some(100));
+    }
+
+    #[test]
+    fn test_lib__fuzzy_score__word_order() {
+        assert_eq!(fuzzy_score("boat buy", "Buy a boat!"), Some(100));
+    }
+
+    #[test]
+    fn test_lib__fuzzy_score__ranks_closer_titles_higher() {
+        let close = fuzzy_score("sail wrld", "Sail the world");
+        let far = fuzzy_score("sail wrld", "Sell my wardrobe");
+        assert!(close > far, "{:?} <= {:?}", close, far);
+    }
+
+    #[test]
+    fn test_lib__fuzzy_score__no_match() {
+        assert_eq!(fuzzy_score("boet", "Learn to swim"), None);
+        assert_eq!(fuzzy_score("", "Buy a boat"), None);
+        assert_eq!(fuzzy_score("?!", "Buy a boat"), None);
+    }
+
+    #[test]
+    fn test_lib__slugify() {
+        assert_eq!(slugify("Buy a boat!"), "buy-a-boat");
+        assert_eq!(slugify("  Über-fast   CI, now "), "über-fast-ci-now");
+        assert_eq!(slugify("../etc/passwd"), "etc-passwd");
+        assert_eq!(slugify("?!"), "idea");
+        assert_eq!(slugify(&"a ".repeat(100)).len(), 59);
+        assert_eq!(slugify("CON"), "con_");
+    }
+
+    #[test]
+    fn test_lib__last_added_heading() {
+        let patch = "diff --git a/README.md b/README.md\n--- a/README.md\n+++ b/README.md\n@@ -1 +1,5 @@\n ## Old idea\n+\n+## Buy a boat\n+\n+## Learn to sail \n+Some details\n";
+
+        assert_eq!(last_added_heading(patch).as_deref(), Some("Learn to sail"));
+        assert_eq!(
+            last_added_heading(" ## Old idea\n+- a bullet\n-## Gone\n"),
+            None
+        );
+        assert_eq!(last_added_heading(""), None);
+    }
+
+    #[test]
+    fn test_lib__split_queued_date() {
+        let now = Local.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap();
+        let imported = Local.with_ymd_and_hms(2023, 5, 1, 9, 30, 0).unwrap();
+
+        assert_eq!(
+            split_queued_date("[2023-05-01T09:30] Buy a boat", now),
+            (Some(imported), "Buy a boat")
+        );
+        assert_eq!(split_queued_date("Buy a boat", now), (None, "Buy a boat"));
+        assert_eq!(
+            split_queued_date("[WIP] Buy a boat", now),
+            (None, "[WIP] Buy a boat")
+        );
+        assert_eq!(
+            split_queued_date("[2099-01-01] Later", now),
+            (None, "[2099-01-01] Later")
+        );
+        assert_eq!(
+            split_queued_date("[2023-05-01]", now),
+            (None, "[2023-05-01]")
+        );
+    }
+
+    #[test]
+    fn test_lib__expand_branch_template__sanitizes_placeholders() {
+        let now = Local.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap();
+
+        let actual = expand_branch_template("ideas/%Y-w%W", now).unwrap();
+        assert_eq!(actual, "ideas/2024-w22");
+
+        let actual = expand_branch_template("ideas/%F_%T", now).unwrap();
+        assert_eq!(actual, "ideas/2024-06-01_12-00-00");
+
+        let actual = expand_branch_template("ideas/%c", now).unwrap();
+        assert_eq!(actual, "ideas/Sat-Jun-1-12-00-00-2024");
+
+        let actual = expand_branch_template("ideas/100%%/%-d", now).unwrap();
+        assert_eq!(actual, "ideas/100%/1");
+    }
+
+    #[test]
+    fn test_lib__expand_branch_template__failure() {
+        let now = Local.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap();
+
+        for template in ["ideas/%Q", "ideas..%Y", "ideas/%Y:", "ideas/%"] {
+            let actual = expand_branch_template(template, now);
+
+            assert!(actual.is_err(), "'{}': {:?}", template, actual);
+        }
+    }
+
+    #[test]
+    fn test_lib__expand_file_name_template__success() {
+        let now = Local.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap();
+
+        let actual =
+            expand_file_name_template("{date}-{slug}.{ext}", now, "Buy a boat", "org", false);
+        assert_eq!(actual.unwrap(), PathBuf::from("2024-06-01-buy-a-boat.org"));
+
+        let actual = expand_file_name_template("{slug}/README.md", now, "Buy a boat", "md", true);
+        assert_eq!(actual.unwrap(), PathBuf::from("buy-a-boat/README.md"));
+    }
+
+    #[test]
+    fn test_lib__expand_file_name_template__failure() {
+        let now = Local.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap();
+        let cases = [
+            ("ideas/{slug}.md", false),
+            ("../{slug}.md", true),
+            ("/tmp/{slug}.md", true),
+            ("{slug}/", true),
+            ("{title}.md", true),
+            ("", false),
+        ];
+
+        for (template, nested) in cases {
+            let actual = expand_file_name_template(template, now, "Buy a boat", "md", nested);
+
+            assert!(
+                actual.is_err(),
+                "'{}' nested {}: {:?}",
+                template,
+                nested,
+                actual
+            );
+        }
+    }
+
+    #[test]
+    fn test_lib__normalize_repo_path__tilde() {
+        let home = Some(PathBuf::from("/home/some-user"));
+        let cwd = Path::new("/some/cwd");
+
+        let actual = normalize_repo_path(Path::new("~/ideas/"), home.clone(), cwd);
+        assert_eq!(actual.unwrap(), PathBuf::from("/home/some-user/ideas"));
+
+        let actual = normalize_repo_path(Path::new("~"), home, cwd);
+        assert_eq!(actual.unwrap(), PathBuf::from("/home/some-user"));
+
+        let actual = normalize_repo_path(Path::new("~/ideas"), None, cwd);
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn test_lib__normalize_repo_path__relative() {
+        let actual = normalize_repo_path(Path::new("./ideas"), None, Path::new("/some/cwd"));
+        assert_eq!(actual.unwrap(), PathBuf::from("/some/cwd/ideas"));
+
+        // Not the home dir of a user named `ideas`
+        let actual = normalize_repo_path(Path::new("~ideas"), None, Path::new("/some/cwd"));
+        assert_eq!(actual.unwrap(), PathBuf::from("/some/cwd/~ideas"));
+    }
+
+    #[test]
+    fn test_lib__normalize_repo_path__trailing_slash() {
+        let actual = normalize_repo_path(Path::new("/some/ideas//"), None, Path::new("/"));
+        assert_eq!(actual.unwrap(), PathBuf::from("/some/ideas"));
+    }
+
+    #[test]
+    fn test_lib__normalize_repo_path__existing_dir_is_canonicalized() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("ideas")).unwrap();
+
+        let actual = normalize_repo_path(Path::new("ideas/../ideas/"), None, dir.path());
+
+        let expected = dir.path().join("ideas").canonicalize().unwrap();
+        assert_eq!(actual.unwrap(), expected);
     }
 }
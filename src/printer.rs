@@ -4,16 +4,21 @@ use std::io::Write;
 pub trait Print {
     fn print(&mut self, value: &str) -> io::Result<()>;
     fn println(&mut self, value: &str) -> io::Result<()>;
+    fn status(&mut self, token: &str) -> io::Result<()>;
 }
 
 pub trait PrintColor {
     fn fts_banner(&mut self) -> io::Result<()>;
     fn input_header(&mut self, value: &str) -> io::Result<()>;
     fn error(&mut self, value: &str) -> io::Result<()>;
+    fn diff(&mut self, value: &str) -> io::Result<()>;
+    fn trace(&mut self, value: &str) -> io::Result<()>;
 }
 
-pub struct Printer<W> {
+pub struct Printer<W, S = io::Stdout> {
     writer: W,
+    // Where status tokens go with --porcelain, `writer` is then stderr
+    status: Option<S>,
 }
 
 #[derive(Clone, Copy)]
@@ -24,11 +29,24 @@ pub struct PrintOptions {
 
 impl<W: Write + termcolor::WriteColor> Printer<W> {
     pub fn new(writer: W) -> Self {
-        Self { writer }
+        Self {
+            writer,
+            status: None,
+        }
     }
 }
 
-impl<W: Write> Print for Printer<W> {
+impl<W: Write + termcolor::WriteColor, S: Write> Printer<W, S> {
+    // Human text goes to `writer` and one status token per line to `status`
+    pub fn porcelain(writer: W, status: S) -> Self {
+        Self {
+            writer,
+            status: Some(status),
+        }
+    }
+}
+
+impl<W: Write, S: Write> Print for Printer<W, S> {
     fn print(&mut self, value: &str) -> io::Result<()> {
         write!(self.writer, "{}", value)
     }
@@ -36,9 +54,20 @@ impl<W: Write> Print for Printer<W> {
     fn println(&mut self, value: &str) -> io::Result<()> {
         writeln!(self.writer, "{}", value)
     }
+
+    // Only printed with --porcelain, e.g. `IDEA_COMMITTED <hash>`
+    fn status(&mut self, token: &str) -> io::Result<()> {
+        match &mut self.status {
+            Some(status) => {
+                writeln!(status, "{}", token)?;
+                status.flush()
+            }
+            None => Ok(()),
+        }
+    }
 }
 
-impl<W: Write + termcolor::WriteColor> PrintColor for Printer<W> {
+impl<W: Write + termcolor::WriteColor, S: Write> PrintColor for Printer<W, S> {
     fn fts_banner(&mut self) -> io::Result<()> {
         let opts = PrintOptions {
             color: termcolor::Color::Yellow,
@@ -83,9 +112,46 @@ to begin writing down ideas.
         self.println_styled(value, opts)?;
         self.writer.flush()
     }
+
+    fn diff(&mut self, value: &str) -> io::Result<()> {
+        for line in value.lines() {
+            let color = if line.starts_with("+++") || line.starts_with("---") {
+                None
+            } else if line.starts_with('+') {
+                Some(termcolor::Color::Green)
+            } else if line.starts_with('-') {
+                Some(termcolor::Color::Red)
+            } else if line.starts_with("@@") {
+                Some(termcolor::Color::Cyan)
+            } else {
+                None
+            };
+
+            match color {
+                Some(color) => self.println_styled(
+                    line,
+                    PrintOptions {
+                        color,
+                        is_bold: false,
+                    },
+                )?,
+                None => self.println(line)?,
+            }
+        }
+        self.writer.flush()
+    }
+
+    fn trace(&mut self, value: &str) -> io::Result<()> {
+        let opts = PrintOptions {
+            color: termcolor::Color::Blue,
+            is_bold: false,
+        };
+        self.println_styled(value, opts)?;
+        self.writer.flush()
+    }
 }
 
-impl<W: Write + termcolor::WriteColor> Printer<W> {
+impl<W: Write + termcolor::WriteColor, S: Write> Printer<W, S> {
     fn println_styled(&mut self, value: &str, opts: PrintOptions) -> io::Result<()> {
         let mut color_spec = termcolor::ColorSpec::new();
         color_spec.set_fg(Some(opts.color)).set_bold(opts.is_bold);
@@ -103,8 +169,9 @@ mod tests {
     #[test]
     fn test_printer__print__success() {
         let mut output = Vec::new();
-        let mut printer = Printer {
+        let mut printer: Printer<_> = Printer {
             writer: &mut output,
+            status: None,
         };
 
         let print_result = printer.print("this value");
@@ -119,8 +186,9 @@ mod tests {
     #[test]
     fn test_printer__println__success() {
         let mut output = Vec::new();
-        let mut printer = Printer {
+        let mut printer: Printer<_> = Printer {
             writer: &mut output,
+            status: None,
         };
 
         let print_result = printer.println("this value");
@@ -132,6 +200,29 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_printer__status__porcelain() {
+        let mut output = termcolor::Ansi::new(vec![]);
+        let mut status = Vec::new();
+        let mut printer = Printer::porcelain(&mut output, &mut status);
+
+        printer.println("Pushed!").unwrap();
+        printer.status("PUSHED origin").unwrap();
+
+        assert_eq!(String::from_utf8(output.into_inner()).unwrap(), "Pushed!\n");
+        assert_eq!(String::from_utf8(status).unwrap(), "PUSHED origin\n");
+    }
+
+    #[test]
+    fn test_printer__status__not_porcelain() {
+        let mut output = termcolor::Ansi::new(vec![]);
+        let mut printer = Printer::new(&mut output);
+
+        printer.status("PUSHED origin").unwrap();
+
+        assert!(output.into_inner().is_empty());
+    }
+
     #[test]
     fn test_printer__fts_banner__success() {
         let mut output = termcolor::Ansi::new(vec![]);
@@ -182,6 +273,38 @@ to begin writing down ideas.";
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_printer__diff__success() {
+        let mut output = termcolor::Ansi::new(vec![]);
+        let mut printer = Printer::new(&mut output);
+
+        printer
+            .diff("+++ b/README.md\n@@ -1 +1 @@\n-old line\n+new line\n context")
+            .unwrap();
+
+        let actual = String::from_utf8(output.into_inner()).unwrap();
+        let expected = "+++ b/README.md\n\
+            \u{1b}[0m\u{1b}[36m@@ -1 +1 @@\n\u{1b}[0m\
+            \u{1b}[0m\u{1b}[31m-old line\n\u{1b}[0m\
+            \u{1b}[0m\u{1b}[32m+new line\n\u{1b}[0m \
+            context\n";
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_printer__trace__success() {
+        let mut output = termcolor::Ansi::new(vec![]);
+        let mut printer = Printer::new(&mut output);
+
+        printer.trace("some-value").unwrap();
+
+        let actual = String::from_utf8(output.into_inner()).unwrap();
+        let expected = "\u{1b}[0m\u{1b}[34msome-value\n\u{1b}[0m";
+
+        assert_eq!(actual, expected);
+    }
+
     #[test]
     fn test_printer__println_styled__success() {
         let mut output_1 = termcolor::Ansi::new(vec![]);
@@ -1,35 +1,172 @@
+use std::ffi::OsStr;
 use std::io::ErrorKind;
-use std::path::PathBuf;
-use std::process::Command;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 use std::{env, fs, io};
 
+// Editors that accept `+<line>` to open a file at a specific line
+const LINE_ARG_EDITORS: [&str; 7] = ["vi", "vim", "nvim", "nano", "emacs", "micro", "kak"];
+
+// Editors offered during first time setup, and the command to run them with
+const COMMON_EDITORS: [(&str, &str); 5] = [
+    ("vim", "vim"),
+    ("nano", "nano"),
+    // Without --wait the idea would be committed before it's written
+    ("code", "code --wait"),
+    ("emacs", "emacs"),
+    ("micro", "micro"),
+];
+
 pub trait ProgramOpener {
     fn open_editor(&self, file_path: &str) -> io::Result<()>;
+    fn open_editor_at(&self, file_path: &str, line: usize) -> io::Result<()>;
     fn open_pager(&self, file_path: &str) -> io::Result<()>;
+    fn resolve_editor(&self) -> Option<String>;
+    fn available_editors(&self) -> Vec<String>;
+    fn encrypt_file(&self, file_path: &str, recipient: &str) -> io::Result<()>;
+    fn decrypt_file(&self, encrypted_path: &str, file_path: &str) -> io::Result<bool>;
+    fn open_pager_decrypted(&self, encrypted_path: &str) -> io::Result<()>;
+    fn notify(&self, title: &str, body: &str) -> io::Result<()>;
+    fn spawn_detached(&self, args: &[String]) -> io::Result<()>;
 }
 
 #[derive(Default)]
-pub struct ProgramAccess;
+pub struct ProgramAccess {
+    // Editor command from the config, used when $EDITOR is unset
+    editor: Option<String>,
+}
 
 impl ProgramOpener for ProgramAccess {
     fn open_editor(&self, file_path: &str) -> io::Result<()> {
-        self.open_with_fallback(file_path, "EDITOR", "vi")
+        let (program, mut args) = self.editor_command()?;
+        args.push(file_path.to_string());
+        self.open(program, file_path, &args)
+    }
+
+    fn open_editor_at(&self, file_path: &str, line: usize) -> io::Result<()> {
+        let (program, mut args) = self.editor_command()?;
+        args.extend(editor_line_args(&program, file_path, line));
+        self.open(program, file_path, &args)
     }
 
     fn open_pager(&self, file_path: &str) -> io::Result<()> {
         self.open_with_fallback(file_path, "PAGER", "less")
     }
+
+    fn resolve_editor(&self) -> Option<String> {
+        env::var("EDITOR").ok().or_else(|| self.editor.clone())
+    }
+
+    fn available_editors(&self) -> Vec<String> {
+        env::var_os("PATH")
+            .map(|path| editors_in_path(&path))
+            .unwrap_or_default()
+    }
+
+    // Replaces the file with `<file>.gpg`, so only the encrypted file is left
+    fn encrypt_file(&self, file_path: &str, recipient: &str) -> io::Result<()> {
+        let gpg = self.get_if_available("gpg")?;
+        let status = Command::new(gpg)
+            .args(gpg_encrypt_args(file_path, recipient))
+            .status()?;
+        if !status.success() {
+            return Err(io::Error::other(format!(
+                "gpg could not encrypt {} to {}",
+                file_path, recipient
+            )));
+        }
+        fs::remove_file(file_path)
+    }
+
+    // Returns false if there's no encrypted file to decrypt yet
+    fn decrypt_file(&self, encrypted_path: &str, file_path: &str) -> io::Result<bool> {
+        if !Path::new(encrypted_path).exists() {
+            return Ok(false);
+        }
+
+        let gpg = self.get_if_available("gpg")?;
+        let status = Command::new(gpg)
+            .args(["--batch", "--yes", "--quiet", "--decrypt", "--output"])
+            .args([file_path, encrypted_path])
+            .status()?;
+        if !status.success() {
+            return Err(io::Error::other(format!(
+                "gpg could not decrypt {}",
+                encrypted_path
+            )));
+        }
+        Ok(true)
+    }
+
+    // The decrypted ideas are only ever piped to the pager, never written to disk
+    fn open_pager_decrypted(&self, encrypted_path: &str) -> io::Result<()> {
+        // Make sure file exists
+        fs::metadata(encrypted_path)?;
+        let gpg = self.get_if_available("gpg")?;
+        let pager = self.program_with_fallback("PAGER", "less")?;
+
+        let mut decrypt = Command::new(gpg)
+            .args(["--batch", "--quiet", "--decrypt", encrypted_path])
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let decrypted = decrypt.stdout.take().ok_or_else(|| {
+            io::Error::new(ErrorKind::BrokenPipe, "Could not read the output of gpg")
+        })?;
+        Command::new(pager).stdin(decrypted).status()?;
+        decrypt.wait().map(|_| ())
+    }
+
+    // A desktop notification, for when the terminal isn't watched
+    fn notify(&self, title: &str, body: &str) -> io::Result<()> {
+        notify(title, body)
+    }
+
+    // eureka itself with `args`, without a terminal and not waited for, so
+    // this run can exit while it works
+    fn spawn_detached(&self, args: &[String]) -> io::Result<()> {
+        Command::new(env::current_exe()?)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map(|_| ())
+    }
 }
 
 impl ProgramAccess {
+    pub fn new(editor: Option<String>) -> Self {
+        ProgramAccess { editor }
+    }
+
+    fn editor_command(&self) -> io::Result<(PathBuf, Vec<String>)> {
+        if let Ok(editor) = env::var("EDITOR") {
+            return Ok((PathBuf::from(editor), Vec::new()));
+        }
+
+        match &self.editor {
+            Some(editor) => split_command(editor),
+            None => self
+                .get_if_available("vi")
+                .map(|program| (program, Vec::new())),
+        }
+    }
+
     fn open_with_fallback(&self, file_path: &str, env_var: &str, fallback: &str) -> io::Result<()> {
-        let program = env::var(env_var)
+        let program = self.program_with_fallback(env_var, fallback)?;
+        self.open(program, file_path, &[file_path.to_string()])
+    }
+
+    fn program_with_fallback(&self, env_var: &str, fallback: &str) -> io::Result<PathBuf> {
+        env::var(env_var)
             .map(PathBuf::from)
-            .or_else(|_| self.get_if_available(fallback))?;
+            .or_else(|_| self.get_if_available(fallback))
+    }
 
+    fn open(&self, program: PathBuf, file_path: &str, args: &[String]) -> io::Result<()> {
         // Make sure file exists
         fs::metadata(file_path)?;
-        Command::new(program).arg(file_path).status().map(|_| ())
+        Command::new(program).args(args).status().map(|_| ())
     }
 
     fn get_if_available(&self, program: &str) -> io::Result<PathBuf> {
@@ -37,11 +174,84 @@ impl ProgramAccess {
     }
 }
 
+#[cfg(feature = "notify")]
+fn notify(title: &str, body: &str) -> io::Result<()> {
+    notify_rust::Notification::new()
+        .appname("eureka")
+        .summary(title)
+        .body(body)
+        .show()
+        .map(|_| ())
+        .map_err(io::Error::other)
+}
+
+#[cfg(not(feature = "notify"))]
+fn notify(_title: &str, _body: &str) -> io::Result<()> {
+    Err(io::Error::new(
+        ErrorKind::Unsupported,
+        "eureka was built without notification support, rebuild it with --features notify",
+    ))
+}
+
+fn split_command(command: &str) -> io::Result<(PathBuf, Vec<String>)> {
+    let mut parts = command.split_whitespace().map(String::from);
+    let program = parts
+        .next()
+        .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "Editor command is empty"))?;
+    Ok((PathBuf::from(program), parts.collect()))
+}
+
+fn editors_in_path(path: &OsStr) -> Vec<String> {
+    let cwd = env::current_dir().unwrap_or_default();
+    COMMON_EDITORS
+        .iter()
+        .filter(|(name, _)| which::which_in(name, Some(path), &cwd).is_ok())
+        .map(|(_, command)| command.to_string())
+        .collect()
+}
+
+fn gpg_encrypt_args(file_path: &str, recipient: &str) -> Vec<String> {
+    [
+        "--batch",
+        "--yes",
+        "--quiet",
+        "--encrypt",
+        "--recipient",
+        recipient,
+        "--output",
+        &format!("{}.gpg", file_path),
+        file_path,
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect()
+}
+
+fn editor_line_args(program: &Path, file_path: &str, line: usize) -> Vec<String> {
+    let name = program
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    if LINE_ARG_EDITORS.contains(&name.as_str()) {
+        vec![format!("+{}", line), file_path.to_string()]
+    } else if name == "code" {
+        vec!["-g".to_string(), format!("{}:{}", file_path, line)]
+    } else {
+        // The editor can't jump to a line, open the file as usual
+        vec![file_path.to_string()]
+    }
+}
+
 #[allow(non_snake_case)]
 #[cfg(test)]
 mod tests {
-    use crate::program_access::{ProgramAccess, ProgramOpener};
+    use crate::program_access::{
+        editor_line_args, editors_in_path, gpg_encrypt_args, split_command, ProgramAccess,
+        ProgramOpener,
+    };
     use std::env;
+    use std::path::{Path, PathBuf};
 
     type TestResult = Result<(), Box<dyn std::error::Error>>;
 
@@ -119,4 +329,115 @@ mod tests {
         env::set_var("PAGER", pager_value);
         Ok(())
     }
+
+    #[test]
+    fn test_program_access__open_editor_at__success() -> TestResult {
+        let program_access = ProgramAccess::default();
+        let tmp_file = tempfile::NamedTempFile::new()?;
+        let file_path = tmp_file.path().to_str().unwrap();
+        let editor_value = env::var("EDITOR").unwrap_or_else(|_| "vi".to_string());
+        env::set_var("EDITOR", "echo");
+
+        program_access.open_editor_at(file_path, 12)?;
+
+        env::set_var("EDITOR", editor_value);
+        Ok(())
+    }
+
+    #[test]
+    fn test_program_access__editor_line_args__supported_editor() {
+        let actual = editor_line_args(Path::new("/usr/bin/vim"), "some-file", 12);
+        let expected = vec!["+12".to_string(), "some-file".to_string()];
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_program_access__editor_line_args__vscode() {
+        let actual = editor_line_args(Path::new("code"), "some-file", 12);
+        let expected = vec!["-g".to_string(), "some-file:12".to_string()];
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_program_access__editor_line_args__unsupported_editor() {
+        let actual = editor_line_args(Path::new("/usr/bin/some-editor"), "some-file", 12);
+        let expected = vec!["some-file".to_string()];
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_program_access__gpg_encrypt_args() {
+        let actual = gpg_encrypt_args("ideas/README.md", "me@example.com");
+
+        assert_eq!(
+            actual,
+            vec![
+                "--batch",
+                "--yes",
+                "--quiet",
+                "--encrypt",
+                "--recipient",
+                "me@example.com",
+                "--output",
+                "ideas/README.md.gpg",
+                "ideas/README.md",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_program_access__decrypt_file__nothing_encrypted_yet() -> TestResult {
+        let program_access = ProgramAccess::default();
+        let tmp_dir = tempfile::tempdir()?;
+        let file_path = tmp_dir.path().join("README.md");
+        let encrypted_path = tmp_dir.path().join("README.md.gpg");
+
+        let actual = program_access.decrypt_file(
+            encrypted_path.to_str().unwrap(),
+            file_path.to_str().unwrap(),
+        )?;
+
+        assert!(!actual);
+        assert!(!file_path.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_program_access__split_command__with_args() -> TestResult {
+        let (program, args) = split_command("code --wait")?;
+
+        assert_eq!(program, PathBuf::from("code"));
+        assert_eq!(args, vec!["--wait".to_string()]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_program_access__split_command__empty() {
+        let actual = split_command("  ");
+
+        assert!(actual.is_err())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_program_access__editors_in_path__finds_executables() -> TestResult {
+        use std::fs;
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp_dir = tempfile::tempdir()?;
+        for name in ["nano", "code", "not-an-editor"] {
+            let program = tmp_dir.path().join(name);
+            fs::write(&program, "")?;
+            fs::set_permissions(&program, fs::Permissions::from_mode(0o755))?;
+        }
+
+        let actual = editors_in_path(tmp_dir.path().as_os_str());
+        let expected = vec!["nano".to_string(), "code --wait".to_string()];
+
+        assert_eq!(actual, expected);
+        Ok(())
+    }
 }
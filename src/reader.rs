@@ -1,7 +1,20 @@
+use crate::browser::{browse, Browsed};
 use std::io;
+#[cfg(feature = "readline")]
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+
+#[cfg(feature = "readline")]
+use log::debug;
+
+// Where the line editor keeps its history, inside the config directory
+pub const HISTORY_FILE_NAME: &str = "history";
 
 pub trait ReadInput {
     fn read_input(&mut self) -> io::Result<String>;
+    fn read_multiline_input(&mut self) -> io::Result<String>;
+    fn read_clipboard(&mut self) -> io::Result<String>;
+    fn browse(&mut self, ideas: &[String]) -> io::Result<Option<Browsed>>;
 }
 
 pub struct Reader<R> {
@@ -16,16 +29,198 @@ impl<R> Reader<R> {
 
 impl<R: io::BufRead> ReadInput for Reader<R> {
     fn read_input(&mut self) -> io::Result<String> {
-        let mut input = String::new();
-        self.reader.read_line(&mut input)?;
-        Ok(input.trim().to_string())
+        // Read raw bytes, a terminal can hand over invalid UTF-8 when pasting
+        let mut input = Vec::new();
+        self.reader.read_until(b'\n', &mut input)?;
+        Ok(String::from_utf8_lossy(&input).trim().to_string())
+    }
+
+    // Lines until an empty one, or the end of the input
+    fn read_multiline_input(&mut self) -> io::Result<String> {
+        let mut lines = vec![];
+        loop {
+            let mut input = Vec::new();
+            if self.reader.read_until(b'\n', &mut input)? == 0 {
+                break;
+            }
+            let line = String::from_utf8_lossy(&input).trim_end().to_string();
+            if line.trim().is_empty() {
+                break;
+            }
+            lines.push(line);
+        }
+        Ok(lines.join("\n"))
+    }
+
+    fn read_clipboard(&mut self) -> io::Result<String> {
+        read_clipboard()
+    }
+
+    fn browse(&mut self, ideas: &[String]) -> io::Result<Option<Browsed>> {
+        browse(ideas)
+    }
+}
+
+// A line editor with history, for when a person is typing at a terminal
+#[cfg(feature = "readline")]
+pub struct LineEditor {
+    editor: rustyline::DefaultEditor,
+    history_path: PathBuf,
+}
+
+#[cfg(feature = "readline")]
+impl LineEditor {
+    pub fn new(history_path: PathBuf) -> io::Result<Self> {
+        let mut editor = rustyline::DefaultEditor::new().map_err(readline_error)?;
+        // There's no history before the first input
+        if history_path.exists() {
+            editor.load_history(&history_path).map_err(readline_error)?;
+        }
+        Ok(Self {
+            editor,
+            history_path,
+        })
+    }
+}
+
+#[cfg(feature = "readline")]
+impl ReadInput for LineEditor {
+    fn read_input(&mut self) -> io::Result<String> {
+        // The printer already wrote the prompt, move back over it so the
+        // editor can draw its own
+        print!("\r");
+        io::stdout().flush()?;
+
+        let input = match self.editor.readline("> ") {
+            Ok(line) => line.trim().to_string(),
+            // Same as reading an empty stdin
+            Err(rustyline::error::ReadlineError::Eof) => return Ok(String::new()),
+            Err(err) => return Err(readline_error(err)),
+        };
+
+        if !input.is_empty() {
+            // Losing the history is no reason to lose the input
+            let saved = self
+                .editor
+                .add_history_entry(input.as_str())
+                .and_then(|_| self.editor.save_history(&self.history_path));
+            if let Err(err) = saved {
+                debug!("Could not save input history: {}", err);
+            }
+        }
+        Ok(input)
+    }
+
+    // Only the lines, not what they make up together, are kept in the history
+    fn read_multiline_input(&mut self) -> io::Result<String> {
+        let mut lines = vec![];
+        loop {
+            let line = self.read_input()?;
+            if line.is_empty() {
+                break;
+            }
+            lines.push(line);
+        }
+        Ok(lines.join("\n"))
+    }
+
+    fn read_clipboard(&mut self) -> io::Result<String> {
+        read_clipboard()
+    }
+
+    fn browse(&mut self, ideas: &[String]) -> io::Result<Option<Browsed>> {
+        browse(ideas)
     }
 }
 
+#[cfg(feature = "readline")]
+fn readline_error(err: rustyline::error::ReadlineError) -> io::Error {
+    match err {
+        rustyline::error::ReadlineError::Io(err) => err,
+        rustyline::error::ReadlineError::Interrupted => {
+            io::Error::new(io::ErrorKind::Interrupted, "Input was interrupted")
+        }
+        err => io::Error::other(err),
+    }
+}
+
+// Reads input with a line editor when built with the readline feature and
+// stdin is a terminal, otherwise straight from stdin
+pub enum TerminalReader<R> {
+    Plain(Reader<R>),
+    #[cfg(feature = "readline")]
+    Editor(Box<LineEditor>),
+}
+
+impl<R> TerminalReader<R> {
+    #[cfg(feature = "readline")]
+    pub fn new(reader: R, history_path: Option<PathBuf>) -> Self {
+        let editor = history_path
+            .filter(|_| io::stdin().is_terminal())
+            .and_then(|history_path| LineEditor::new(history_path).ok())
+            .map(Box::new);
+        match editor {
+            Some(editor) => TerminalReader::Editor(editor),
+            // Piped input, or a terminal the editor doesn't support
+            None => TerminalReader::Plain(Reader::new(reader)),
+        }
+    }
+
+    #[cfg(not(feature = "readline"))]
+    pub fn new(reader: R, _history_path: Option<PathBuf>) -> Self {
+        TerminalReader::Plain(Reader::new(reader))
+    }
+}
+
+impl<R: io::BufRead> ReadInput for TerminalReader<R> {
+    fn read_input(&mut self) -> io::Result<String> {
+        match self {
+            TerminalReader::Plain(reader) => reader.read_input(),
+            #[cfg(feature = "readline")]
+            TerminalReader::Editor(editor) => editor.read_input(),
+        }
+    }
+
+    fn read_multiline_input(&mut self) -> io::Result<String> {
+        match self {
+            TerminalReader::Plain(reader) => reader.read_multiline_input(),
+            #[cfg(feature = "readline")]
+            TerminalReader::Editor(editor) => editor.read_multiline_input(),
+        }
+    }
+
+    fn read_clipboard(&mut self) -> io::Result<String> {
+        read_clipboard()
+    }
+
+    fn browse(&mut self, ideas: &[String]) -> io::Result<Option<Browsed>> {
+        browse(ideas)
+    }
+}
+
+#[cfg(feature = "clipboard")]
+fn read_clipboard() -> io::Result<String> {
+    let mut clipboard = arboard::Clipboard::new().map_err(io::Error::other)?;
+    match clipboard.get_text() {
+        Ok(text) => Ok(text),
+        // Nothing (or nothing textual) has been copied
+        Err(arboard::Error::ContentNotAvailable) => Ok(String::new()),
+        Err(err) => Err(io::Error::other(err)),
+    }
+}
+
+#[cfg(not(feature = "clipboard"))]
+fn read_clipboard() -> io::Result<String> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "eureka was built without clipboard support, rebuild it with --features clipboard",
+    ))
+}
+
 #[allow(non_snake_case)]
 #[cfg(test)]
 mod tests {
-    use crate::reader::{ReadInput, Reader};
+    use crate::reader::{ReadInput, Reader, TerminalReader};
 
     #[test]
     fn test_reader__read_input__success() {
@@ -37,4 +232,84 @@ mod tests {
 
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn test_reader__read_input__multibyte_chars() {
+        let input = "\u{3000}💡 アイデア\u{3000}\n".as_bytes();
+        let mut reader = Reader::new(input);
+
+        let actual = reader.read_input().unwrap();
+        let expected = "💡 アイデア".to_string();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_reader__read_input__invalid_utf8_is_replaced() {
+        // A multibyte char cut in half
+        let input = b"idea \xE2\x9C\n";
+        let mut reader = Reader::new(&input[..]);
+
+        let actual = reader.read_input().unwrap();
+        let expected = "idea \u{FFFD}".to_string();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_reader__read_multiline_input__until_empty_line() {
+        let input = b"Buy a boat  \nA small one\n  with sails\n\nnext input\n";
+        let mut reader = Reader::new(&input[..]);
+
+        let actual = reader.read_multiline_input().unwrap();
+        let expected = "Buy a boat\nA small one\n  with sails".to_string();
+
+        assert_eq!(actual, expected);
+        assert_eq!(reader.read_input().unwrap(), "next input");
+    }
+
+    #[test]
+    fn test_reader__read_multiline_input__until_end_of_input() {
+        let input = b"Buy a boat\r\nA small one";
+        let mut reader = Reader::new(&input[..]);
+
+        let actual = reader.read_multiline_input().unwrap();
+        let expected = "Buy a boat\nA small one".to_string();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[cfg(not(feature = "clipboard"))]
+    #[test]
+    fn test_reader__read_clipboard__unsupported() {
+        let mut reader = Reader::new(&b""[..]);
+
+        let actual = reader.read_clipboard().map_err(|e| e.kind());
+        let expected = Err(std::io::ErrorKind::Unsupported);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_terminal_reader__read_input__plain() {
+        let input = b"  my piped input  \n";
+        let mut reader = TerminalReader::Plain(Reader::new(&input[..]));
+
+        let actual = reader.read_input().unwrap();
+        let expected = "my piped input".to_string();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[cfg(feature = "readline")]
+    #[test]
+    fn test_line_editor__new__missing_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let history_path = dir.path().join("history");
+
+        let actual = crate::reader::LineEditor::new(history_path.clone()).is_ok();
+
+        assert!(actual);
+        assert!(!history_path.exists());
+    }
 }
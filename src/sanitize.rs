@@ -0,0 +1,181 @@
+// Text that ends up in a branch or file name, like a summary or a formatted
+// date, goes through these so it can't produce an invalid ref or path
+
+// Besides control characters and whitespace, see `git check-ref-format`.
+// `/` would start another component and `{` is dropped to rule out `@{`
+const REF_UNSAFE_CHARS: [char; 9] = ['~', '^', ':', '?', '*', '[', '\\', '/', '{'];
+// Not allowed in file names on Windows, `/` also separates folders elsewhere
+const FILE_NAME_UNSAFE_CHARS: [char; 9] = ['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+// Devices on Windows, even with an extension like `con.md`
+const RESERVED_FILE_NAMES: [&str; 22] = [
+    "con", "prn", "aux", "nul", "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8",
+    "com9", "lpt1", "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+];
+
+// `text` as one component of a ref, e.g. `12:00` becomes `12-00`. Unsafe
+// characters become `-`, `..` becomes `.`, and it never starts or ends with
+// `-` or `.` or ends with `.lock`. `_` if nothing is left
+pub fn sanitize_ref_component(text: &str) -> String {
+    let replaced = replace_unsafe(text, |c| {
+        c.is_control() || c.is_whitespace() || REF_UNSAFE_CHARS.contains(&c)
+    });
+    let mut component = String::new();
+    for c in replaced.chars() {
+        if !(c == '.' && component.ends_with('.')) {
+            component.push(c);
+        }
+    }
+
+    let mut component = component.as_str();
+    loop {
+        let trimmed = component.trim_matches(['-', '.']);
+        let trimmed = trimmed.strip_suffix(".lock").unwrap_or(trimmed);
+        if trimmed == component {
+            break;
+        }
+        component = trimmed;
+    }
+
+    if component.is_empty() || component == "@" {
+        String::from("_")
+    } else {
+        component.to_string()
+    }
+}
+
+// `text` as a file name that works on every common file system. Unsafe
+// characters become `-`, it never starts with `.` or ends with `.` or a space,
+// and Windows device names like `con` get a `_`. `_` if nothing is left
+pub fn sanitize_filename(text: &str) -> String {
+    let replaced = replace_unsafe(text, |c| {
+        c.is_control() || FILE_NAME_UNSAFE_CHARS.contains(&c)
+    });
+    let name = replaced
+        .trim_start_matches(['.', ' '])
+        .trim_end_matches(['.', ' ']);
+    if name.is_empty() {
+        return String::from("_");
+    }
+
+    let (stem, extension) = match name.find('.') {
+        Some(dot) => name.split_at(dot),
+        None => (name, ""),
+    };
+    if RESERVED_FILE_NAMES.contains(&stem.to_lowercase().as_str()) {
+        format!("{}_{}", stem, extension)
+    } else {
+        name.to_string()
+    }
+}
+
+// Replace each run of unsafe characters with a single `-`
+fn replace_unsafe(text: &str, is_unsafe: impl Fn(char) -> bool) -> String {
+    let mut replaced = String::new();
+    let mut in_unsafe_run = false;
+    for c in text.chars() {
+        if is_unsafe(c) {
+            if !in_unsafe_run {
+                replaced.push('-');
+            }
+            in_unsafe_run = true;
+        } else {
+            replaced.push(c);
+            in_unsafe_run = false;
+        }
+    }
+    replaced
+}
+
+#[allow(non_snake_case)]
+#[cfg(test)]
+mod tests {
+    use crate::sanitize::{sanitize_filename, sanitize_ref_component};
+
+    #[test]
+    fn test_sanitize__ref_component__safe_text_is_kept() {
+        assert_eq!(sanitize_ref_component("2024-w22"), "2024-w22");
+        assert_eq!(sanitize_ref_component("Über_Ideen"), "Über_Ideen");
+        assert_eq!(sanitize_ref_component("v1.2"), "v1.2");
+    }
+
+    #[test]
+    fn test_sanitize__ref_component__unsafe_characters() {
+        assert_eq!(sanitize_ref_component("12:00:00"), "12-00-00");
+        assert_eq!(sanitize_ref_component("a~b^c?d*e[f"), "a-b-c-d-e-f");
+        assert_eq!(sanitize_ref_component("back\\slash"), "back-slash");
+        assert_eq!(sanitize_ref_component("ideas/today"), "ideas-today");
+        assert_eq!(sanitize_ref_component("Sat Jun  1"), "Sat-Jun-1");
+        assert_eq!(
+            sanitize_ref_component("tab\tand\nnewline"),
+            "tab-and-newline"
+        );
+        assert_eq!(sanitize_ref_component("at@{1}"), "at@-1}");
+    }
+
+    #[test]
+    fn test_sanitize__ref_component__dots() {
+        assert_eq!(sanitize_ref_component("a..b"), "a.b");
+        assert_eq!(sanitize_ref_component(".hidden"), "hidden");
+        assert_eq!(sanitize_ref_component("trailing."), "trailing");
+        assert_eq!(sanitize_ref_component("index.lock"), "index");
+        assert_eq!(sanitize_ref_component("a.lock.lock"), "a");
+        assert_eq!(sanitize_ref_component("x.lock."), "x");
+    }
+
+    #[test]
+    fn test_sanitize__ref_component__nothing_left() {
+        assert_eq!(sanitize_ref_component(""), "_");
+        assert_eq!(sanitize_ref_component("..."), "_");
+        assert_eq!(sanitize_ref_component(":?*"), "_");
+        assert_eq!(sanitize_ref_component("@"), "_");
+    }
+
+    #[test]
+    fn test_sanitize__ref_component__is_a_valid_ref() {
+        let cases = [
+            "12:00", "a..b", ".x", "x.", "x.lock", "@", "@{u}", "~1", "a b", "-x-", "\u{7f}", "x/",
+            "*", "",
+        ];
+        for case in cases {
+            let component = sanitize_ref_component(case);
+            let branch = format!("refs/heads/ideas/{}", component);
+
+            assert!(git2::Reference::is_valid_name(&branch), "{:?}", branch);
+        }
+    }
+
+    #[test]
+    fn test_sanitize__filename__safe_text_is_kept() {
+        assert_eq!(sanitize_filename("buy-a-boat"), "buy-a-boat");
+        assert_eq!(sanitize_filename("Sat Jun  1 2024"), "Sat Jun  1 2024");
+        assert_eq!(sanitize_filename("notes.md"), "notes.md");
+        assert_eq!(sanitize_filename("über"), "über");
+    }
+
+    #[test]
+    fn test_sanitize__filename__unsafe_characters() {
+        assert_eq!(sanitize_filename("12:00:00"), "12-00-00");
+        assert_eq!(sanitize_filename("a/b\\c"), "a-b-c");
+        assert_eq!(sanitize_filename("why?*"), "why-");
+        assert_eq!(sanitize_filename("<\"quoted\">|"), "-quoted-");
+        assert_eq!(sanitize_filename("line\nbreak"), "line-break");
+    }
+
+    #[test]
+    fn test_sanitize__filename__dots_and_spaces() {
+        assert_eq!(sanitize_filename(".hidden"), "hidden");
+        assert_eq!(sanitize_filename(".."), "_");
+        assert_eq!(sanitize_filename("trailing. "), "trailing");
+        assert_eq!(sanitize_filename("  padded"), "padded");
+        assert_eq!(sanitize_filename(""), "_");
+    }
+
+    #[test]
+    fn test_sanitize__filename__reserved_names() {
+        assert_eq!(sanitize_filename("con"), "con_");
+        assert_eq!(sanitize_filename("NUL"), "NUL_");
+        assert_eq!(sanitize_filename("com1.md"), "com1_.md");
+        assert_eq!(sanitize_filename("console"), "console");
+        assert_eq!(sanitize_filename("com10"), "com10");
+    }
+}
@@ -0,0 +1,268 @@
+use std::io;
+
+#[cfg(feature = "github")]
+use std::env;
+
+const GITHUB_API_URL: &str = "https://api.github.com";
+
+// Where an idea is stored instead of being committed to the idea repo
+pub trait IdeaSink {
+    // Store the idea in `destination` and return where it can be found
+    fn store(&self, destination: &str, title: &str, body: &str) -> io::Result<String>;
+    // Open a pull request of `head` into `base` of `repo`, returns its URL
+    fn open_pull_request(
+        &self,
+        repo: &str,
+        head: &str,
+        base: &str,
+        title: &str,
+        body: &str,
+    ) -> io::Result<String>;
+}
+
+pub struct GitHubIssueSink {
+    // Only used when built with the `github` feature
+    #[cfg_attr(not(feature = "github"), allow(dead_code))]
+    api_url: String,
+}
+
+impl Default for GitHubIssueSink {
+    fn default() -> Self {
+        GitHubIssueSink::new(GITHUB_API_URL)
+    }
+}
+
+impl GitHubIssueSink {
+    pub fn new(api_url: &str) -> Self {
+        GitHubIssueSink {
+            api_url: api_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    // POST `payload` to `path` of the API, returns the `html_url` of what's
+    // created. `kind` (plural) and `what` name it in the errors
+    #[cfg(feature = "github")]
+    fn create(
+        &self,
+        path: &str,
+        payload: serde_json::Value,
+        kind: &str,
+        what: &str,
+    ) -> io::Result<String> {
+        let token = env::var("EUREKA_GITHUB_TOKEN")
+            .or_else(|_| env::var("GITHUB_TOKEN"))
+            .map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("Set $GITHUB_TOKEN to a token that can create {}", kind),
+                )
+            })?;
+
+        let url = format!("{}{}", self.api_url, path);
+        let response = ureq::post(&url)
+            .set("Accept", "application/vnd.github+json")
+            .set("Authorization", &format!("Bearer {}", token))
+            .set("User-Agent", "eureka")
+            .send_string(&payload.to_string())
+            .map_err(|err| match err {
+                ureq::Error::Status(status, _) => io::Error::other(format!(
+                    "GitHub refused to create the {} (HTTP {})",
+                    what, status
+                )),
+                ureq::Error::Transport(err) => io::Error::other(err.to_string()),
+            })?;
+
+        let created: serde_json::Value = serde_json::from_str(&response.into_string()?)?;
+        created["html_url"]
+            .as_str()
+            .map(String::from)
+            .ok_or_else(|| {
+                io::Error::other(format!("GitHub didn't return the URL of the {}", what))
+            })
+    }
+}
+
+#[cfg(not(feature = "github"))]
+fn without_github() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "eureka was built without GitHub support, rebuild it with --features github",
+    )
+}
+
+impl IdeaSink for GitHubIssueSink {
+    // `destination` is the `owner/name` of the repo to open the issue in
+    #[cfg(feature = "github")]
+    fn store(&self, destination: &str, title: &str, body: &str) -> io::Result<String> {
+        let path = format!("/repos/{}/issues", destination);
+        let payload = serde_json::json!({ "title": title, "body": body });
+        self.create(
+            &path,
+            payload,
+            "issues",
+            &format!("issue in {}", destination),
+        )
+    }
+
+    #[cfg(not(feature = "github"))]
+    fn store(&self, _destination: &str, _title: &str, _body: &str) -> io::Result<String> {
+        Err(without_github())
+    }
+
+    // `head` is `owner:branch` of the fork the branch was pushed to
+    #[cfg(feature = "github")]
+    fn open_pull_request(
+        &self,
+        repo: &str,
+        head: &str,
+        base: &str,
+        title: &str,
+        body: &str,
+    ) -> io::Result<String> {
+        let path = format!("/repos/{}/pulls", repo);
+        let payload =
+            serde_json::json!({ "title": title, "body": body, "head": head, "base": base });
+        self.create(
+            &path,
+            payload,
+            "pull requests",
+            &format!("pull request in {}", repo),
+        )
+    }
+
+    #[cfg(not(feature = "github"))]
+    fn open_pull_request(
+        &self,
+        _repo: &str,
+        _head: &str,
+        _base: &str,
+        _title: &str,
+        _body: &str,
+    ) -> io::Result<String> {
+        Err(without_github())
+    }
+}
+
+#[allow(non_snake_case)]
+#[cfg(all(test, feature = "github"))]
+mod tests {
+    use crate::sink::{GitHubIssueSink, IdeaSink};
+    use std::env;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::Mutex;
+    use std::thread;
+
+    // The tests share $EUREKA_GITHUB_TOKEN
+    static TOKEN_LOCK: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn test_sink__github_issue__creates_issue() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let api_url = format!("http://{}", listener.local_addr().unwrap());
+        let _token_lock = TOKEN_LOCK.lock().unwrap();
+        env::set_var("EUREKA_GITHUB_TOKEN", "some-token");
+
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut head = vec![];
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+                head.push(line.trim_end().to_lowercase());
+            }
+            let length: usize = head
+                .iter()
+                .find_map(|line| line.strip_prefix("content-length: "))
+                .unwrap()
+                .parse()
+                .unwrap();
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+
+            let response = r#"{"html_url":"https://github.com/owner/ideas/issues/1"}"#;
+            write!(
+                &stream,
+                "HTTP/1.1 201 Created\r\nContent-Length: {}\r\n\r\n{}",
+                response.len(),
+                response
+            )
+            .unwrap();
+            (head, String::from_utf8(body).unwrap())
+        });
+
+        let actual = GitHubIssueSink::new(&api_url).store("owner/ideas", "some-title", "some-body");
+
+        let (head, body) = server.join().unwrap();
+        env::remove_var("EUREKA_GITHUB_TOKEN");
+        assert_eq!(actual.unwrap(), "https://github.com/owner/ideas/issues/1");
+        assert_eq!(head[0], "post /repos/owner/ideas/issues http/1.1");
+        assert!(head.contains(&"authorization: bearer some-token".to_string()));
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["title"], "some-title");
+        assert_eq!(body["body"], "some-body");
+    }
+
+    #[test]
+    fn test_sink__github_issue__opens_pull_request() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let api_url = format!("http://{}", listener.local_addr().unwrap());
+        let _token_lock = TOKEN_LOCK.lock().unwrap();
+        env::set_var("EUREKA_GITHUB_TOKEN", "some-token");
+
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut head = vec![];
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+                head.push(line.trim_end().to_lowercase());
+            }
+            let length: usize = head
+                .iter()
+                .find_map(|line| line.strip_prefix("content-length: "))
+                .unwrap()
+                .parse()
+                .unwrap();
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+
+            let response = r#"{"html_url":"https://github.com/owner/ideas/pull/1"}"#;
+            write!(
+                &stream,
+                "HTTP/1.1 201 Created\r\nContent-Length: {}\r\n\r\n{}",
+                response.len(),
+                response
+            )
+            .unwrap();
+            (head, String::from_utf8(body).unwrap())
+        });
+
+        let actual = GitHubIssueSink::new(&api_url).open_pull_request(
+            "owner/ideas",
+            "me:main",
+            "main",
+            "some-title",
+            "some-body",
+        );
+
+        let (head, body) = server.join().unwrap();
+        env::remove_var("EUREKA_GITHUB_TOKEN");
+        assert_eq!(actual.unwrap(), "https://github.com/owner/ideas/pull/1");
+        assert_eq!(head[0], "post /repos/owner/ideas/pulls http/1.1");
+        assert!(head.contains(&"authorization: bearer some-token".to_string()));
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["title"], "some-title");
+        assert_eq!(body["body"], "some-body");
+        assert_eq!(body["head"], "me:main");
+        assert_eq!(body["base"], "main");
+    }
+}
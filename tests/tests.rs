@@ -1,864 +1,8481 @@
 #[cfg(test)]
 mod tests {
-    use eureka::config_manager::{ConfigManagement, ConfigType};
+    use eureka::browser::Browsed;
+    use eureka::clock::Clock;
+    use eureka::config_manager::{ConfigManagement, ConfigType, RepoCache};
+    use eureka::error::EurekaError;
     use eureka::printer::{Print, PrintColor};
     use eureka::reader::ReadInput;
-    use eureka::{Eureka, EurekaOptions};
+    use eureka::sink::IdeaSink;
+    use eureka::{Eureka, EurekaOptions, OptionKind};
 
-    use eureka::git::GitManagement;
+    use chrono::{DateTime, Local, TimeZone};
+    use eureka::file_handler::{FileManagement, Heading};
+    use eureka::git::{CommitDiff, DiffHunk, Divergence, GitManagement, MergeOutcome};
     use eureka::program_access::ProgramOpener;
     use git2::Oid;
     use std::cmp::Ordering as CmpOrdering;
+    use std::env;
     use std::io;
     use std::io::{Error, ErrorKind};
-    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::time::Duration;
 
     #[test]
     fn test_clear_config() {
-        struct MockConfigManager;
         static READ_COUNTER: AtomicUsize = AtomicUsize::new(0);
         static RM_COUNTER: AtomicUsize = AtomicUsize::new(0);
 
-        impl ConfigManagement for MockConfigManager {
-            fn config_dir_create(&self) -> io::Result<()> {
-                unimplemented!()
-            }
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_exists: Some(|| unimplemented!()),
+                config_read: Some(|file| {
+                    let counter = READ_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    if counter > 0 {
+                        panic!("Should only be read once");
+                    }
 
-            fn config_dir_exists(&self) -> bool {
-                unimplemented!()
-            }
+                    assert_eq!(file, ConfigType::Repo);
+                    Ok("some-path".to_string())
+                }),
+                config_rm: Some(|| {
+                    RM_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockPrinter::default(),
+            MockReader::default(),
+            MockGit::default(),
+            MockProgramOpener::default(),
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            clear_config: true,
+            view: false,
+            ..Default::default()
+        };
 
-            fn config_read(&self, file: ConfigType) -> io::Result<String> {
-                let counter = READ_COUNTER.fetch_add(1, Ordering::SeqCst);
-                if counter > 0 {
-                    panic!("Should only be read once");
-                }
+        let actual = eureka.run(opts);
 
-                assert_eq!(file, ConfigType::Repo);
-                Ok("some-path".to_string())
-            }
+        assert!(actual.is_ok());
 
-            fn config_write(&self, _file: ConfigType, _value: String) -> io::Result<()> {
-                unimplemented!()
-            }
+        let rm_counter = RM_COUNTER.fetch_add(1, Ordering::SeqCst);
+        assert_eq!(rm_counter, 1);
+    }
 
-            fn config_rm(&self) -> io::Result<()> {
-                RM_COUNTER.fetch_add(1, Ordering::SeqCst);
-                Ok(())
-            }
-        }
+    #[test]
+    fn test_welcome_prints_banner_without_config() {
+        static BANNER_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static PRINTLN_COUNTER: AtomicUsize = AtomicUsize::new(0);
 
         let mut eureka = Eureka::new(
-            MockConfigManager {},
-            DefaultMockPrinter {},
-            DefaultMockReader {},
-            DefaultGit {},
-            DefaultMockProgramOpener {},
+            MockConfigManager::default(),
+            MockPrinter {
+                println: Some(|value| {
+                    PRINTLN_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    assert!(value.starts_with("Run `eureka` to capture an idea"));
+                    Ok(())
+                }),
+                fts_banner: Some(|| {
+                    BANNER_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit::default(),
+            MockProgramOpener::default(),
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
         );
         let opts = EurekaOptions {
-            clear_config: true,
-            view: false,
+            welcome: true,
+            ..Default::default()
         };
 
         let actual = eureka.run(opts);
 
         assert!(actual.is_ok());
+        assert_eq!(BANNER_COUNTER.load(Ordering::SeqCst), 1);
+        assert_eq!(PRINTLN_COUNTER.load(Ordering::SeqCst), 1);
+    }
 
-        let rm_counter = RM_COUNTER.fetch_add(1, Ordering::SeqCst);
-        assert_eq!(rm_counter, 1);
+    #[test]
+    fn test_version_info_prints_libgit2_features_as_json() {
+        static PRINTLN_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager::default(),
+            MockPrinter {
+                println: Some(|value| {
+                    PRINTLN_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    let info: serde_json::Value = serde_json::from_str(value).unwrap();
+                    assert_eq!(info["eureka"], env!("CARGO_PKG_VERSION"));
+                    assert!(info["libgit2"].as_str().unwrap().starts_with("1."));
+                    for feature in ["libgit2_vendored", "https", "ssh", "threads"] {
+                        assert!(info[feature].is_boolean(), "{} is not a bool", feature);
+                    }
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit::default(),
+            MockProgramOpener::default(),
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            version_info: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert_eq!(PRINTLN_COUNTER.load(Ordering::SeqCst), 1);
     }
 
     #[test]
     fn test_view_ideas() {
-        struct MockConfigManager;
         static READ_COUNTER: AtomicUsize = AtomicUsize::new(0);
 
-        impl ConfigManagement for MockConfigManager {
-            fn config_dir_create(&self) -> io::Result<()> {
-                unimplemented!()
-            }
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_exists: Some(|| unimplemented!()),
+                config_read: Some(|file| {
+                    if file == ConfigType::EncryptTo {
+                        return Err(Error::new(ErrorKind::NotFound, "not-configured"));
+                    }
+                    let counter = READ_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    if counter > 0 {
+                        panic!("Should only be read once");
+                    }
 
-            fn config_dir_exists(&self) -> bool {
-                unimplemented!()
-            }
+                    assert_eq!(file, ConfigType::Repo);
+                    Ok("specific-repo-path".to_string())
+                }),
+                config_rm: Some(|| Ok(())),
+                ..Default::default()
+            },
+            MockPrinter::default(),
+            MockReader::default(),
+            MockGit::default(),
+            MockProgramOpener {
+                open_pager: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo-path/README.md");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            clear_config: false,
+            view: true,
+            ..Default::default()
+        };
 
-            fn config_read(&self, file: ConfigType) -> io::Result<String> {
-                let counter = READ_COUNTER.fetch_add(1, Ordering::SeqCst);
-                if counter > 0 {
-                    panic!("Should only be read once");
-                }
+        let actual = eureka.run(opts);
 
-                assert_eq!(file, ConfigType::Repo);
-                Ok("specific-repo-path".to_string())
-            }
+        assert!(actual.is_ok());
+    }
 
-            fn config_write(&self, _file: ConfigType, _value: String) -> io::Result<()> {
-                unimplemented!()
-            }
+    #[test]
+    fn test_view_encrypted_ideas() {
+        static READ_COUNTER: AtomicUsize = AtomicUsize::new(0);
 
-            fn config_rm(&self) -> io::Result<()> {
-                Ok(())
-            }
-        }
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_exists: Some(|| unimplemented!()),
+                config_read: Some(|file| {
+                    if file == ConfigType::EncryptTo {
+                        return Ok("specific-recipient".to_string());
+                    }
+                    let counter = READ_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    if counter > 0 {
+                        panic!("Should only be read once");
+                    }
+
+                    assert_eq!(file, ConfigType::Repo);
+                    Ok("specific-repo-path".to_string())
+                }),
+                config_rm: Some(|| Ok(())),
+                ..Default::default()
+            },
+            MockPrinter::default(),
+            MockReader::default(),
+            MockGit::default(),
+            MockProgramOpener {
+                open_pager: Some(|_file_path| {
+                    panic!("Encrypted ideas should be decrypted for the pager");
+                }),
+                open_pager_decrypted: Some(|encrypted_path| {
+                    assert_eq!(encrypted_path, "specific-repo-path/README.md.gpg");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            clear_config: false,
+            view: true,
+            ..Default::default()
+        };
 
-        struct MockProgramAccess;
+        let actual = eureka.run(opts);
 
-        impl ProgramOpener for MockProgramAccess {
-            fn open_editor(&self, _file_path: &str) -> io::Result<()> {
-                unimplemented!()
-            }
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn test_view_ideas_without_config_is_config_error() {
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_exists: Some(|| unimplemented!()),
+                config_read: Some(|_file| Err(Error::new(ErrorKind::NotFound, "some-error"))),
+                ..Default::default()
+            },
+            MockPrinter::default(),
+            MockReader::default(),
+            MockGit::default(),
+            MockProgramOpener::default(),
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            view: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts).unwrap_err();
+
+        assert!(matches!(actual, EurekaError::Config(_)));
+        assert_eq!(actual.exit_code(), 2);
+    }
+
+    #[test]
+    fn test_config_dir_is_missing() {
+        static READ_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_create: Some(|| Ok(())),
+                config_dir_exists: Some(|| {
+                    // Config dir is missing
+                    false
+                }),
+                config_read: Some(|_file| {
+                    let counter = READ_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    if counter == 0 {
+                        // First it checks if any config can be found and
+                        // based on that it decides to create the config dir
+                        Err(Error::other("some-error"))
+                    } else {
+                        Ok(String::from("some-ok"))
+                    }
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    assert_eq!(value, "First time setup complete. Happy ideation!");
+                    Ok(())
+                }),
+                fts_banner: Some(|| Ok(())),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit::default(),
+            MockProgramOpener::default(),
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            clear_config: false,
+            view: false,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(counter_equals(2, &READ_COUNTER));
+    }
+
+    #[test]
+    fn test_setup_repo() {
+        static INPUT_HEADER_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static READ_INPUT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_create: Some(|| Ok(())),
+                config_read: Some(|_file| Err(Error::other("some-error"))),
+                config_write: Some(|file, value| {
+                    match file {
+                        ConfigType::Repo => {
+                            assert_eq!(value, "/absolute/path/to/specific-repo-path")
+                        }
+                        ConfigType::Branch => assert_eq!(value, "specific-branch"),
+                        _ => unimplemented!(),
+                    }
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    assert_eq!(value, "First time setup complete. Happy ideation!");
+                    Ok(())
+                }),
+                fts_banner: Some(|| Ok(())),
+                input_header: Some(|value| {
+                    let counter = INPUT_HEADER_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    if counter == 0 {
+                        assert_eq!(value, "Path to your idea repo");
+                    } else {
+                        assert_eq!(value, "Name of branch (default: main)");
+                    }
+
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| {
+                    let counter = READ_INPUT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => Ok(String::from("/absolute/path/to/specific-repo-path")),
+                        _ => Ok(String::from("specific-branch")),
+                    }
+                }),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "/absolute/path/to/specific-repo-path");
+                    Ok(())
+                }),
+                checkout_branch: Some(|_branch_name| unimplemented!()),
+                add: Some(|| unimplemented!()),
+                push: Some(|_branch_name| unimplemented!()),
+                is_ignored: Some(|_file_path| unimplemented!()),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                resolve_editor: Some(|| Some(String::from("vim"))),
+                ..Default::default()
+            },
+            MockFileHandler {
+                find_git_repos: Some(|_dir_path, _max_depth| Ok(vec![])),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            clear_config: false,
+            view: false,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn test_setup_repo_picks_a_found_repo_by_number() {
+        static INPUT_HEADER_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static READ_INPUT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static LINE: AtomicUsize = AtomicUsize::new(0);
+
+        env::set_var("EUREKA_SCAN_DIR", "/home/some-user");
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_create: Some(|| Ok(())),
+                config_read: Some(|_file| Err(Error::other("some-error"))),
+                config_write: Some(|file, value| {
+                    match file {
+                        ConfigType::Repo => {
+                            assert_eq!(value, "/absolute/path/to/specific-repo-path")
+                        }
+                        ConfigType::Branch => assert_eq!(value, "specific-branch"),
+                        _ => unimplemented!(),
+                    }
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    const EXPECTED: [&str; 4] = [
+                        "Found these git repos in /home/some-user:",
+                        "1. /home/some-user/code/some-project",
+                        "2. /absolute/path/to/specific-repo-path",
+                        "First time setup complete. Happy ideation!",
+                    ];
+                    assert_eq!(value, EXPECTED[LINE.fetch_add(1, Ordering::SeqCst)]);
+                    Ok(())
+                }),
+                fts_banner: Some(|| Ok(())),
+                input_header: Some(|value| {
+                    let counter = INPUT_HEADER_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    if counter == 0 {
+                        assert_eq!(value, "Number or path of your idea repo");
+                    } else {
+                        assert_eq!(value, "Name of branch (default: main)");
+                    }
+
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| {
+                    let counter = READ_INPUT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => Ok(String::from("2")),
+                        _ => Ok(String::from("specific-branch")),
+                    }
+                }),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "/absolute/path/to/specific-repo-path");
+                    Ok(())
+                }),
+                checkout_branch: Some(|_branch_name| unimplemented!()),
+                add: Some(|| unimplemented!()),
+                push: Some(|_branch_name| unimplemented!()),
+                is_ignored: Some(|_file_path| unimplemented!()),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                resolve_editor: Some(|| Some(String::from("vim"))),
+                ..Default::default()
+            },
+            MockFileHandler {
+                find_git_repos: Some(|_dir_path, max_depth| {
+                    assert_eq!(max_depth, 3);
+                    Ok(vec![
+                        PathBuf::from("/home/some-user/code/some-project"),
+                        PathBuf::from("/absolute/path/to/specific-repo-path"),
+                    ])
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            clear_config: false,
+            view: false,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+        env::remove_var("EUREKA_SCAN_DIR");
+
+        assert!(actual.is_ok());
+        assert_eq!(LINE.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn test_setup_defaults_to_main_branch() {
+        static INPUT_HEADER_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static READ_INPUT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_create: Some(|| Ok(())),
+                config_read: Some(|_file| Err(Error::other("some-error"))),
+                config_write: Some(|file, value| {
+                    match file {
+                        ConfigType::Repo => {
+                            assert_eq!(value, "/absolute/path/to/specific-repo-path")
+                        }
+                        ConfigType::Branch => assert_eq!(value, "main"),
+                        _ => unimplemented!(),
+                    }
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    assert_eq!(value, "First time setup complete. Happy ideation!");
+                    Ok(())
+                }),
+                fts_banner: Some(|| Ok(())),
+                input_header: Some(|value| {
+                    let counter = INPUT_HEADER_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    if counter == 0 {
+                        assert_eq!(value, "Path to your idea repo");
+                    } else {
+                        assert_eq!(value, "Name of branch (default: main)");
+                    }
+
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| {
+                    let counter = READ_INPUT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => Ok(String::from("/absolute/path/to/specific-repo-path")),
+                        // Use the default branch
+                        _ => Ok(String::new()),
+                    }
+                }),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "/absolute/path/to/specific-repo-path");
+                    Ok(())
+                }),
+                checkout_branch: Some(|_branch_name| unimplemented!()),
+                add: Some(|| unimplemented!()),
+                push: Some(|_branch_name| unimplemented!()),
+                is_ignored: Some(|_file_path| unimplemented!()),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                resolve_editor: Some(|| Some(String::from("vim"))),
+                ..Default::default()
+            },
+            MockFileHandler {
+                find_git_repos: Some(|_dir_path, _max_depth| Ok(vec![])),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            clear_config: false,
+            view: false,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn test_setup_defaults_to_upstream_branch() {
+        static INPUT_HEADER_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static READ_INPUT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_create: Some(|| Ok(())),
+                config_read: Some(|_file| Err(Error::other("some-error"))),
+                config_write: Some(|file, value| {
+                    match file {
+                        ConfigType::Repo => {
+                            assert_eq!(value, "/absolute/path/to/specific-repo-path")
+                        }
+                        ConfigType::Branch => assert_eq!(value, "ideas"),
+                        _ => unimplemented!(),
+                    }
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    assert_eq!(value, "First time setup complete. Happy ideation!");
+                    Ok(())
+                }),
+                fts_banner: Some(|| Ok(())),
+                input_header: Some(|value| {
+                    let counter = INPUT_HEADER_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    if counter == 0 {
+                        assert_eq!(value, "Path to your idea repo");
+                    } else {
+                        assert_eq!(value, "Name of branch (default: ideas)");
+                    }
+
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| {
+                    let counter = READ_INPUT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => Ok(String::from("/absolute/path/to/specific-repo-path")),
+                        // Use the default branch
+                        _ => Ok(String::new()),
+                    }
+                }),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "/absolute/path/to/specific-repo-path");
+                    Ok(())
+                }),
+                checkout_branch: Some(|_branch_name| unimplemented!()),
+                add: Some(|| unimplemented!()),
+                push: Some(|_branch_name| unimplemented!()),
+                upstream_branch: Some(|| Ok(Some(String::from("ideas")))),
+                is_ignored: Some(|_file_path| unimplemented!()),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                resolve_editor: Some(|| Some(String::from("vim"))),
+                ..Default::default()
+            },
+            MockFileHandler {
+                find_git_repos: Some(|_dir_path, _max_depth| Ok(vec![])),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            clear_config: false,
+            view: false,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn test_setup_picks_editor_when_editor_is_unset() {
+        static INPUT_HEADER_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static READ_INPUT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_create: Some(|| Ok(())),
+                config_read: Some(|_file| Err(Error::other("some-error"))),
+                config_write: Some(|file, value| {
+                    match file {
+                        ConfigType::Repo => {
+                            assert_eq!(value, "/absolute/path/to/specific-repo-path")
+                        }
+                        ConfigType::Branch => assert_eq!(value, "main"),
+                        ConfigType::Editor => assert_eq!(value, "code --wait"),
+                        _ => unimplemented!(),
+                    }
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    assert!([
+                        "1. vim",
+                        "2. code --wait",
+                        "First time setup complete. Happy ideation!"
+                    ]
+                    .contains(&value));
+                    Ok(())
+                }),
+                fts_banner: Some(|| Ok(())),
+                input_header: Some(|value| {
+                    let counter = INPUT_HEADER_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => assert_eq!(value, "Path to your idea repo"),
+                        1 => assert_eq!(value, "Name of branch (default: main)"),
+                        _ => assert_eq!(value, "Number or name of the editor to write ideas with"),
+                    }
+
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| {
+                    let counter = READ_INPUT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => Ok(String::from("/absolute/path/to/specific-repo-path")),
+                        // Use the default branch
+                        1 => Ok(String::new()),
+                        _ => Ok(String::from("code")),
+                    }
+                }),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "/absolute/path/to/specific-repo-path");
+                    Ok(())
+                }),
+                checkout_branch: Some(|_branch_name| unimplemented!()),
+                add: Some(|| unimplemented!()),
+                push: Some(|_branch_name| unimplemented!()),
+                is_ignored: Some(|_file_path| unimplemented!()),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                resolve_editor: Some(|| None),
+                available_editors: Some(|| vec![String::from("vim"), String::from("code --wait")]),
+                ..Default::default()
+            },
+            MockFileHandler {
+                find_git_repos: Some(|_dir_path, _max_depth| Ok(vec![])),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            clear_config: false,
+            view: false,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn test_setup_runs_again_with_current_values_as_defaults() {
+        static INPUT_HEADER_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static READ_INPUT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_create: Some(|| Ok(())),
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok(String::from("/absolute/path/to/current-repo")),
+                    ConfigType::Branch => Ok(String::from("ideas")),
+                    ConfigType::Editor => Ok(String::from("vim")),
+                    _ => Err(Error::other("some-error")),
+                }),
+                config_write: Some(|file, value| {
+                    match file {
+                        ConfigType::Branch => assert_eq!(value, "ideas"),
+                        ConfigType::Editor => assert_eq!(value, "code --wait"),
+                        _ => unimplemented!(),
+                    }
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    assert!([
+                        "1. vim",
+                        "2. code --wait",
+                        "Setup complete. Happy ideation!"
+                    ]
+                    .contains(&value));
+                    Ok(())
+                }),
+                fts_banner: Some(|| Ok(())),
+                input_header: Some(|value| {
+                    let counter = INPUT_HEADER_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => assert_eq!(
+                            value,
+                            "Path to your idea repo (default: /absolute/path/to/current-repo)"
+                        ),
+                        1 => assert_eq!(value, "Name of branch (default: ideas)"),
+                        _ => assert_eq!(
+                            value,
+                            "Number or name of the editor to write ideas with (default: vim)"
+                        ),
+                    }
+
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| {
+                    let counter = READ_INPUT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        // Keep the repo and the branch
+                        0 | 1 => Ok(String::new()),
+                        _ => Ok(String::from("code")),
+                    }
+                }),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|_repo_path| {
+                    panic!("The configured branch is suggested");
+                }),
+                checkout_branch: Some(|_branch_name| unimplemented!()),
+                add: Some(|| unimplemented!()),
+                push: Some(|_branch_name| unimplemented!()),
+                is_ignored: Some(|_file_path| unimplemented!()),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                resolve_editor: Some(|| Some(String::from("vim"))),
+                available_editors: Some(|| vec![String::from("vim"), String::from("code --wait")]),
+                ..Default::default()
+            },
+            MockFileHandler {
+                find_git_repos: Some(|_dir_path, _max_depth| Ok(vec![])),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            setup: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn test_setup_repo_path_asks_until_user_provides_value() {
+        static INPUT_HEADER_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static READ_INPUT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_create: Some(|| Ok(())),
+                config_read: Some(|_file| Err(Error::other("some-error"))),
+                config_write: Some(|file, value| {
+                    match file {
+                        ConfigType::Repo => {
+                            assert_eq!(value, "/absolute/path/to/specific-repo-path")
+                        }
+                        ConfigType::Branch => assert_eq!(value, "main"),
+                        _ => unimplemented!(),
+                    }
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    assert_eq!(value, "First time setup complete. Happy ideation!");
+                    Ok(())
+                }),
+                fts_banner: Some(|| Ok(())),
+                input_header: Some(|value| {
+                    let counter = INPUT_HEADER_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    if counter <= 4 {
+                        assert_eq!(value, "Path to your idea repo");
+                    } else {
+                        assert_eq!(value, "Name of branch (default: main)");
+                    }
+                    Ok(())
+                }),
+                error: Some(|value| {
+                    assert_eq!(
+                        value,
+                        "No path given, try again or type q to quit the setup"
+                    );
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| {
+                    let counter = READ_INPUT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    if counter < 4 {
+                        // Return empty string to prompt it to ask again
+                        Ok(String::new())
+                    } else if counter == 4 {
+                        // Stored without the trailing slashes
+                        Ok(String::from("/absolute/path/to/specific-repo-path//"))
+                    } else {
+                        // Use the default branch
+                        Ok(String::new())
+                    }
+                }),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "/absolute/path/to/specific-repo-path");
+                    Ok(())
+                }),
+                checkout_branch: Some(|_branch_name| unimplemented!()),
+                add: Some(|| unimplemented!()),
+                push: Some(|_branch_name| unimplemented!()),
+                is_ignored: Some(|_file_path| unimplemented!()),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                resolve_editor: Some(|| Some(String::from("vim"))),
+                ..Default::default()
+            },
+            MockFileHandler {
+                find_git_repos: Some(|_dir_path, _max_depth| Ok(vec![])),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            clear_config: false,
+            view: false,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn test_setup_repo_path_quits_on_q() {
+        static INPUT_HEADER_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static READ_INPUT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_create: Some(|| Ok(())),
+                config_read: Some(|_file| Err(Error::other("some-error"))),
+                config_write: Some(|_file, _value| {
+                    panic!("Nothing should be saved");
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    assert_eq!(value, "Setup aborted, nothing was saved");
+                    Ok(())
+                }),
+                fts_banner: Some(|| Ok(())),
+                input_header: Some(|value| {
+                    let counter = INPUT_HEADER_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    assert!(counter < 5);
+                    assert_eq!(value, "Path to your idea repo");
+                    Ok(())
+                }),
+                error: Some(|value| {
+                    assert_eq!(
+                        value,
+                        "No path given, try again or type q to quit the setup"
+                    );
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| {
+                    let counter = READ_INPUT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    if counter == 0 {
+                        Ok(String::new())
+                    } else {
+                        Ok(String::from("q"))
+                    }
+                }),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|_repo_path| unimplemented!()),
+                checkout_branch: Some(|_branch_name| unimplemented!()),
+                add: Some(|| unimplemented!()),
+                push: Some(|_branch_name| unimplemented!()),
+                is_ignored: Some(|_file_path| unimplemented!()),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                resolve_editor: Some(|| Some(String::from("vim"))),
+                ..Default::default()
+            },
+            MockFileHandler {
+                find_git_repos: Some(|_dir_path, _max_depth| Ok(vec![])),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            clear_config: false,
+            view: false,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(counter_equals(2, &READ_INPUT_COUNTER));
+    }
+
+    #[test]
+    fn test_setup_repo_path_gives_up_after_max_attempts() {
+        static INPUT_HEADER_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static READ_INPUT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_create: Some(|| Ok(())),
+                config_read: Some(|_file| Err(Error::other("some-error"))),
+                config_write: Some(|_file, _value| {
+                    panic!("Nothing should be saved");
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    panic!("Nothing should be printed, got {}", value);
+                }),
+                fts_banner: Some(|| Ok(())),
+                input_header: Some(|value| {
+                    let counter = INPUT_HEADER_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    assert!(counter < 5);
+                    assert_eq!(value, "Path to your idea repo");
+                    Ok(())
+                }),
+                error: Some(|value| {
+                    assert_eq!(
+                        value,
+                        "No path given, try again or type q to quit the setup"
+                    );
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| {
+                    READ_INPUT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    Ok(String::new())
+                }),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|_repo_path| unimplemented!()),
+                checkout_branch: Some(|_branch_name| unimplemented!()),
+                add: Some(|| unimplemented!()),
+                push: Some(|_branch_name| unimplemented!()),
+                is_ignored: Some(|_file_path| unimplemented!()),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                resolve_editor: Some(|| Some(String::from("vim"))),
+                ..Default::default()
+            },
+            MockFileHandler {
+                find_git_repos: Some(|_dir_path, _max_depth| Ok(vec![])),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            clear_config: false,
+            view: false,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_err());
+        assert!(counter_equals(5, &READ_INPUT_COUNTER));
+    }
+
+    #[test]
+    fn test_setup_repo_path_asks_again_for_a_path_that_isnt_a_repo() {
+        static READ_INPUT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static ERROR_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_create: Some(|| Ok(())),
+                config_read: Some(|_file| Err(Error::other("some-error"))),
+                config_write: Some(|file, value| {
+                    match file {
+                        ConfigType::Repo => {
+                            assert_eq!(value, "/absolute/path/to/specific-repo-path")
+                        }
+                        ConfigType::Branch => assert_eq!(value, "specific-branch"),
+                        _ => unimplemented!(),
+                    }
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                fts_banner: Some(|| Ok(())),
+                input_header: Some(|_value| Ok(())),
+                error: Some(|value| {
+                    ERROR_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    assert_eq!(
+                        value,
+                        "/not/a/repo isn't a git repo, try again or type q to quit the setup"
+                    );
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| {
+                    let counter = READ_INPUT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => Ok(String::from("/not/a/repo")),
+                        1 => Ok(String::from("/absolute/path/to/specific-repo-path")),
+                        _ => Ok(String::from("specific-branch")),
+                    }
+                }),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| match repo_path {
+                    "/not/a/repo" => Err(git2::Error::from_str("could not find repository")),
+                    _ => {
+                        assert_eq!(repo_path, "/absolute/path/to/specific-repo-path");
+                        Ok(())
+                    }
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                resolve_editor: Some(|| Some(String::from("vim"))),
+                ..Default::default()
+            },
+            MockFileHandler {
+                find_git_repos: Some(|_dir_path, _max_depth| Ok(vec![])),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+
+        let actual = eureka.run(EurekaOptions::default());
+
+        assert!(actual.is_ok());
+        assert_eq!(ERROR_COUNTER.load(Ordering::SeqCst), 1);
+        assert!(counter_equals(3, &READ_INPUT_COUNTER));
+    }
+
+    #[test]
+    fn test_setup_repo_path_gives_up_after_max_paths_that_arent_repos() {
+        static READ_INPUT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static ERROR_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_create: Some(|| Ok(())),
+                config_read: Some(|_file| Err(Error::other("some-error"))),
+                config_write: Some(|_file, _value| panic!("Nothing should be saved")),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| panic!("Nothing should be printed, got {}", value)),
+                fts_banner: Some(|| Ok(())),
+                input_header: Some(|_value| Ok(())),
+                error: Some(|value| {
+                    ERROR_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    assert!(value.starts_with("/not/a/repo isn't a git repo"));
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| {
+                    READ_INPUT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    Ok(String::from("/not/a/repo"))
+                }),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|_repo_path| Err(git2::Error::from_str("could not find repository"))),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                find_git_repos: Some(|_dir_path, _max_depth| Ok(vec![])),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+
+        let actual = eureka.run(EurekaOptions::default());
+
+        assert!(actual.is_err());
+        assert!(counter_equals(5, &READ_INPUT_COUNTER));
+        assert_eq!(ERROR_COUNTER.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn test_idea_summary_asks_until_user_provides_value() {
+        static INPUT_HEADER_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static READ_INPUT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_create: Some(|| Ok(())),
+                config_read: Some(|file| match file {
+                    ConfigType::DateLayout
+                    | ConfigType::EncryptTo
+                    | ConfigType::AuditLog
+                    | ConfigType::Backend
+                    | ConfigType::Footer
+                    | ConfigType::FileNameTemplate
+                    | ConfigType::ForkRemote
+                    | ConfigType::Categories
+                    | ConfigType::AppendStrategy => {
+                        Err(Error::new(ErrorKind::NotFound, "not-configured"))
+                    }
+                    _ => Ok(String::from("specific-config-string")),
+                }),
+                config_write: Some(|file, value| {
+                    match file {
+                        ConfigType::Repo => assert_eq!(value, "specific-repo-path"),
+                        _ => unimplemented!(),
+                    }
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                print: Some(|value| {
+                    assert_eq!(value, "First time setup complete. Happy ideation!");
+                    Ok(())
+                }),
+                fts_banner: Some(|| Ok(())),
+                input_header: Some(|value| {
+                    let counter = INPUT_HEADER_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    if counter <= 5 {
+                        assert_eq!(value, ">> Idea summary");
+                    } else {
+                        assert_eq!(value, "Name of branch (default: main)");
+                    }
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| {
+                    let counter = READ_INPUT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter.cmp(&5) {
+                        CmpOrdering::Less => {
+                            // Return empty string to prompt it to ask again
+                            Ok(String::new())
+                        }
+                        CmpOrdering::Equal => Ok(String::from("specific-idea-summary")),
+                        CmpOrdering::Greater => unimplemented!(),
+                    }
+                }),
+                ..Default::default()
+            },
+            MockGit {
+                commit: Some(|_subject, _body, _author_time, _commit_time| Ok(Oid::zero())),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                open_editor: Some(|_file_path| Ok(())),
+                open_pager: Some(|_file_path| Ok(())),
+                ..Default::default()
+            },
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            clear_config: false,
+            view: false,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn test_e2e_happy_path() {
+        static PRINT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager::default(),
+            MockPrinter {
+                println: Some(|value| {
+                    let counter = PRINT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => assert_eq!(value, "Adding and committing your new idea to main.."),
+                        1 => assert_eq!(value, "Added and committed!"),
+                        2 => assert_eq!(value, "Pushing your new idea.."),
+                        3 => assert_eq!(value, "Pushed!"),
+                        _ => panic!("Unknown state"),
+                    }
+
+                    Ok(())
+                }),
+                input_header: Some(|value| {
+                    assert_eq!(value, ">> Idea summary");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| Ok(String::from("read-input-string"))),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "read-input-string");
+                    Ok(Oid::zero())
+                }),
+                push: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                open_editor: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            clear_config: false,
+            view: false,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn test_push_to_other_branch_than_remote_default_warns() {
+        static PRINT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static WARNED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager::default(),
+            MockPrinter {
+                println: Some(|value| {
+                    let counter = PRINT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => assert_eq!(value, "Adding and committing your new idea to main.."),
+                        1 => assert_eq!(value, "Added and committed!"),
+                        2 => assert_eq!(value, "Pushing your new idea.."),
+                        3 => assert_eq!(value, "Pushed!"),
+                        4 => assert!(value.starts_with("Change `branch` in your config")),
+                        _ => panic!("Unknown state"),
+                    }
+
+                    Ok(())
+                }),
+                input_header: Some(|value| {
+                    assert_eq!(value, ">> Idea summary");
+                    Ok(())
+                }),
+                error: Some(|value| {
+                    assert_eq!(
+                        value,
+                        "Pushed to main, but the default branch of origin is master, your ideas may go unnoticed there"
+                    );
+                    WARNED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| Ok(String::from("read-input-string"))),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "read-input-string");
+                    Ok(Oid::zero())
+                }),
+                push: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                remote_default_branch: Some(|| Ok(Some("master".to_string()))),
+                remote_url: Some(|remote_name| {
+                    assert_eq!(remote_name, "origin");
+                    Ok(String::from("git@github.com:some-owner/ideas.git"))
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                open_editor: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            clear_config: false,
+            view: false,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(WARNED.load(Ordering::SeqCst));
+        assert_eq!(PRINT_COUNTER.load(Ordering::SeqCst), 5);
+    }
+
+    #[test]
+    fn test_cache_skips_asking_origin_for_default_branch() {
+        static PRINT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static WARNED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                cache_read: Some(|| {
+                    Ok(Some(RepoCache {
+                        repo: String::from("specific-repo"),
+                        remote_url: Some(String::from("git@github.com:some-owner/ideas.git")),
+                        default_branch: Some(String::from("master")),
+                    }))
+                }),
+                cache_write: Some(|_cache| {
+                    panic!("The cache is up to date");
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    let counter = PRINT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => assert_eq!(value, "Adding and committing your new idea to main.."),
+                        1 => assert_eq!(value, "Added and committed!"),
+                        2 => assert_eq!(value, "Pushing your new idea.."),
+                        3 => assert_eq!(value, "Pushed!"),
+                        4 => assert!(value.starts_with("Change `branch` in your config")),
+                        _ => panic!("Unknown state"),
+                    }
+
+                    Ok(())
+                }),
+                input_header: Some(|value| {
+                    assert_eq!(value, ">> Idea summary");
+                    Ok(())
+                }),
+                error: Some(|value| {
+                    assert_eq!(
+                        value,
+                        "Pushed to main, but the default branch of origin is master, your ideas may go unnoticed there"
+                    );
+                    WARNED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| Ok(String::from("read-input-string"))),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "read-input-string");
+                    Ok(Oid::zero())
+                }),
+                push: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                remote_default_branch: Some(|| {
+                    panic!("The default branch of origin is cached");
+                }),
+                remote_url: Some(|remote_name| {
+                    assert_eq!(remote_name, "origin");
+                    Ok(String::from("git@github.com:some-owner/ideas.git"))
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                open_editor: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            clear_config: false,
+            view: false,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(WARNED.load(Ordering::SeqCst));
+        assert_eq!(PRINT_COUNTER.load(Ordering::SeqCst), 5);
+    }
+
+    #[test]
+    fn test_no_cache_resolves_again_and_writes_cache() {
+        static PRINT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static WARNED: AtomicBool = AtomicBool::new(false);
+        static CACHE_WRITES: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                cache_read: Some(|| {
+                    panic!("--no-cache should not read the cache");
+                }),
+                cache_write: Some(|cache| {
+                    let mut expected = RepoCache {
+                        repo: String::from("specific-repo"),
+                        ..Default::default()
+                    };
+                    if CACHE_WRITES.fetch_add(1, Ordering::SeqCst) == 1 {
+                        expected.remote_url =
+                            Some(String::from("git@github.com:some-owner/ideas.git"));
+                        expected.default_branch = Some(String::from("master"));
+                    }
+                    assert_eq!(cache, &expected);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    let counter = PRINT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => assert_eq!(value, "Adding and committing your new idea to main.."),
+                        1 => assert_eq!(value, "Added and committed!"),
+                        2 => assert_eq!(value, "Pushing your new idea.."),
+                        3 => assert_eq!(value, "Pushed!"),
+                        4 => assert!(value.starts_with("Change `branch` in your config")),
+                        _ => panic!("Unknown state"),
+                    }
+
+                    Ok(())
+                }),
+                input_header: Some(|value| {
+                    assert_eq!(value, ">> Idea summary");
+                    Ok(())
+                }),
+                error: Some(|value| {
+                    assert_eq!(
+                        value,
+                        "Pushed to main, but the default branch of origin is master, your ideas may go unnoticed there"
+                    );
+                    WARNED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| Ok(String::from("read-input-string"))),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "read-input-string");
+                    Ok(Oid::zero())
+                }),
+                push: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                remote_default_branch: Some(|| Ok(Some("master".to_string()))),
+                remote_url: Some(|remote_name| {
+                    assert_eq!(remote_name, "origin");
+                    Ok(String::from("git@github.com:some-owner/ideas.git"))
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                open_editor: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            no_cache: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(WARNED.load(Ordering::SeqCst));
+        assert_eq!(PRINT_COUNTER.load(Ordering::SeqCst), 5);
+        assert_eq!(CACHE_WRITES.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_fork_remote_pushes_to_fork_and_opens_pull_request() {
+        static PRINT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static OPENED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::ForkRemote => Ok("fork".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    let counter = PRINT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => assert_eq!(value, "Adding and committing your new idea to main.."),
+                        1 => assert_eq!(value, "Added and committed!"),
+                        2 => assert_eq!(value, "Pushing your new idea.."),
+                        3 => assert_eq!(value, "Pushed!"),
+                        4 => assert_eq!(value, "Opening a pull request against team/ideas.."),
+                        5 => assert_eq!(value, "Opened https://github.com/team/ideas/pull/1"),
+                        _ => panic!("Unknown state"),
+                    }
+
+                    Ok(())
+                }),
+                input_header: Some(|value| {
+                    assert_eq!(value, ">> Idea summary");
+                    Ok(())
+                }),
+                error: Some(|value| {
+                    panic!("Unexpected error '{}'", value);
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| Ok(String::from("read-input-string"))),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "read-input-string");
+                    Ok(Oid::zero())
+                }),
+                push: Some(|_branch_name| {
+                    panic!("Should push to the fork and not to origin");
+                }),
+                remote_default_branch: Some(|| {
+                    panic!("Should not warn about the default branch of origin");
+                }),
+                push_to: Some(|remote_name, branch_name| {
+                    assert_eq!(remote_name, "fork");
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                remote_url: Some(|remote_name| match remote_name {
+                    "fork" => Ok("git@github.com:me/ideas.git".to_string()),
+                    "origin" => Ok("https://github.com/team/ideas".to_string()),
+                    _ => panic!("Unknown remote {}", remote_name),
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                open_editor: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink {
+                open_pull_request: Some(|repo, head, base, title, body| {
+                    assert_eq!(repo, "team/ideas");
+                    assert_eq!(head, "me:main");
+                    assert_eq!(base, "main");
+                    assert_eq!(title, "read-input-string");
+                    assert_eq!(body, "");
+                    OPENED.store(true, Ordering::SeqCst);
+                    Ok(String::from("https://github.com/team/ideas/pull/1"))
+                }),
+                ..Default::default()
+            },
+        );
+        let opts = EurekaOptions {
+            clear_config: false,
+            view: false,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(OPENED.load(Ordering::SeqCst));
+        assert_eq!(PRINT_COUNTER.load(Ordering::SeqCst), 6);
+    }
+
+    #[test]
+    fn test_fork_remote_without_token_prints_pull_request_url() {
+        static PRINT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static WARNED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::ForkRemote => Ok("fork".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    let counter = PRINT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => assert_eq!(value, "Adding and committing your new idea to main.."),
+                        1 => assert_eq!(value, "Added and committed!"),
+                        2 => assert_eq!(value, "Pushing your new idea.."),
+                        3 => assert_eq!(value, "Pushed!"),
+                        4 => assert_eq!(value, "Opening a pull request against team/ideas.."),
+                        5 => assert_eq!(
+                            value,
+                            "Open it at https://github.com/team/ideas/compare/main...me:main?expand=1"
+                        ),
+                        _ => panic!("Unknown state"),
+                    }
+
+                    Ok(())
+                }),
+                input_header: Some(|value| {
+                    assert_eq!(value, ">> Idea summary");
+                    Ok(())
+                }),
+                error: Some(|value| {
+                    assert_eq!(
+                        value,
+                        "Could not open a pull request: Set $GITHUB_TOKEN to a token that can create pull requests"
+                    );
+                    WARNED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| Ok(String::from("read-input-string"))),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "read-input-string");
+                    Ok(Oid::zero())
+                }),
+                push: Some(|_branch_name| {
+                    panic!("Should push to the fork and not to origin");
+                }),
+                remote_default_branch: Some(|| {
+                    panic!("Should not warn about the default branch of origin");
+                }),
+                push_to: Some(|remote_name, branch_name| {
+                    assert_eq!(remote_name, "fork");
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                remote_url: Some(|remote_name| match remote_name {
+                    "fork" => Ok("git@github.com:me/ideas.git".to_string()),
+                    "origin" => Ok("https://github.com/team/ideas".to_string()),
+                    _ => panic!("Unknown remote {}", remote_name),
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                open_editor: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink {
+                open_pull_request: Some(|_repo, _head, _base, _title, _body| {
+                    Err(Error::new(
+                        ErrorKind::NotFound,
+                        "Set $GITHUB_TOKEN to a token that can create pull requests",
+                    ))
+                }),
+                ..Default::default()
+            },
+        );
+        let opts = EurekaOptions {
+            clear_config: false,
+            view: false,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(WARNED.load(Ordering::SeqCst));
+        assert_eq!(PRINT_COUNTER.load(Ordering::SeqCst), 6);
+    }
+
+    #[test]
+    fn test_open_at_end_opens_editor_at_last_line() {
+        static PRINT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::OpenAtEnd => Ok("true".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    let counter = PRINT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => assert_eq!(value, "Adding and committing your new idea to main.."),
+                        1 => assert_eq!(value, "Added and committed!"),
+                        2 => assert_eq!(value, "Pushing your new idea.."),
+                        3 => assert_eq!(value, "Pushed!"),
+                        _ => panic!("Unknown state"),
+                    }
+
+                    Ok(())
+                }),
+                input_header: Some(|value| {
+                    assert_eq!(value, ">> Idea summary");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| Ok(String::from("read-input-string"))),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "read-input-string");
+                    Ok(Oid::zero())
+                }),
+                push: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                open_editor: Some(|_file_path| {
+                    panic!("Editor should be opened at the last line");
+                }),
+                open_editor_at: Some(|file_path, line| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(line, 12);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockFileHandler {
+                line_count: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    Ok(12)
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            clear_config: false,
+            view: false,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn test_ask_open_editor_no_appends_only_the_summary() {
+        static PRINT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static READ_INPUT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static WRITTEN: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::AskOpenEditor => Ok("true".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    let counter = PRINT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => assert_eq!(value, "Adding and committing your new idea to main.."),
+                        1 => assert_eq!(value, "Added and committed!"),
+                        2 => assert_eq!(value, "Pushing your new idea.."),
+                        3 => assert_eq!(value, "Pushed!"),
+                        _ => panic!("Unknown state"),
+                    }
+
+                    Ok(())
+                }),
+                input_header: Some(|value| {
+                    match READ_INPUT_COUNTER.load(Ordering::SeqCst) {
+                        0 => assert_eq!(value, ">> Idea summary"),
+                        1 => assert_eq!(value, "Open editor to add details? [y/N]"),
+                        _ => panic!("Unknown state"),
+                    }
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| match READ_INPUT_COUNTER.fetch_add(1, Ordering::SeqCst) {
+                    0 => Ok(String::from("read-input-string")),
+                    1 => Ok(String::from("")),
+                    _ => panic!("Unknown state"),
+                }),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "read-input-string");
+                    Ok(Oid::zero())
+                }),
+                push: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                open_editor: Some(|_file_path| {
+                    panic!("Editor should only open when asked to");
+                }),
+                ..Default::default()
+            },
+            MockFileHandler {
+                append_idea: Some(|file_path, summary, body| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(summary, "read-input-string");
+                    assert_eq!(body, "");
+                    WRITTEN.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            clear_config: false,
+            view: false,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(WRITTEN.load(Ordering::SeqCst));
+        assert!(counter_equals(2, &READ_INPUT_COUNTER));
+    }
+
+    #[test]
+    fn test_first_push_sets_upstream() {
+        static PRINT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static READ_INPUT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static WRITTEN: AtomicBool = AtomicBool::new(false);
+        static UPSTREAM_SET: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::AskOpenEditor => Ok("true".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    let counter = PRINT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => assert_eq!(value, "Adding and committing your new idea to main.."),
+                        1 => assert_eq!(value, "Added and committed!"),
+                        2 => assert_eq!(value, "Pushing your new idea.."),
+                        3 => assert_eq!(value, "Set upstream to origin/main."),
+                        4 => assert_eq!(value, "Pushed!"),
+                        _ => panic!("Unknown state"),
+                    }
+
+                    Ok(())
+                }),
+                input_header: Some(|value| {
+                    match READ_INPUT_COUNTER.load(Ordering::SeqCst) {
+                        0 => assert_eq!(value, ">> Idea summary"),
+                        1 => assert_eq!(value, "Open editor to add details? [y/N]"),
+                        _ => panic!("Unknown state"),
+                    }
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| match READ_INPUT_COUNTER.fetch_add(1, Ordering::SeqCst) {
+                    0 => Ok(String::from("read-input-string")),
+                    1 => Ok(String::from("")),
+                    _ => panic!("Unknown state"),
+                }),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "read-input-string");
+                    Ok(Oid::zero())
+                }),
+                push: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                ensure_upstream: Some(|branch_name, remote_name| {
+                    assert_eq!(branch_name, "main");
+                    assert_eq!(remote_name, "origin");
+                    UPSTREAM_SET.store(true, Ordering::SeqCst);
+                    Ok(true)
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                open_editor: Some(|_file_path| {
+                    panic!("Editor should only open when asked to");
+                }),
+                ..Default::default()
+            },
+            MockFileHandler {
+                append_idea: Some(|file_path, summary, body| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(summary, "read-input-string");
+                    assert_eq!(body, "");
+                    WRITTEN.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            clear_config: false,
+            view: false,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(WRITTEN.load(Ordering::SeqCst));
+        assert!(UPSTREAM_SET.load(Ordering::SeqCst));
+        assert!(counter_equals(5, &PRINT_COUNTER));
+        assert!(counter_equals(2, &READ_INPUT_COUNTER));
+    }
+
+    #[test]
+    fn test_verify_setup_pushes_marker_commit() {
+        static PRINT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static MARKER_CREATED: AtomicBool = AtomicBool::new(false);
+        static UPSTREAM_SET: AtomicBool = AtomicBool::new(false);
+        static MARKER_STAGED: AtomicBool = AtomicBool::new(false);
+        static IDEAS_SKIPPED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager::default(),
+            MockPrinter {
+                println: Some(|value| {
+                    let counter = PRINT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => assert_eq!(value, "Adding and committing your new idea to main.."),
+                        1 => assert_eq!(value, "Added and committed!"),
+                        2 => assert_eq!(value, "Pushing main to check your setup.."),
+                        3 => assert_eq!(value, "Set upstream to origin/main."),
+                        4 => assert_eq!(value, "Pushed! Your ideas can be committed and pushed"),
+                        _ => panic!("Unknown state"),
+                    }
+
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "Verify that eureka can push");
+                    Ok(Oid::zero())
+                }),
+                push: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                add_pathspec: Some(|pathspec| {
+                    assert!(IDEAS_SKIPPED.load(Ordering::SeqCst));
+                    assert_eq!(pathspec, ".eureka-idea-repo");
+                    MARKER_STAGED.store(true, Ordering::SeqCst);
+                }),
+                ensure_upstream: Some(|branch_name, remote_name| {
+                    assert_eq!(branch_name, "main");
+                    assert_eq!(remote_name, "origin");
+                    UPSTREAM_SET.store(true, Ordering::SeqCst);
+                    Ok(true)
+                }),
+                skip_idea_pathspecs: Some(|| {
+                    IDEAS_SKIPPED.store(true, Ordering::SeqCst);
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                open_editor: Some(|_file_path| {
+                    panic!("Editor should only open when asked to");
+                }),
+                ..Default::default()
+            },
+            MockFileHandler {
+                append_idea: Some(|_file_path, _summary, _body| {
+                    panic!("Verifying the setup captures no idea");
+                }),
+                create_marker: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/.eureka-idea-repo");
+                    MARKER_CREATED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            verify_setup: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(MARKER_CREATED.load(Ordering::SeqCst));
+        assert!(MARKER_STAGED.load(Ordering::SeqCst));
+        assert!(UPSTREAM_SET.load(Ordering::SeqCst));
+        assert!(counter_equals(5, &PRINT_COUNTER));
+    }
+
+    #[test]
+    fn test_ask_open_editor_yes_opens_editor() {
+        static PRINT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static READ_INPUT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static WRITTEN: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::AskOpenEditor => Ok("true".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    let counter = PRINT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => assert_eq!(value, "Adding and committing your new idea to main.."),
+                        1 => assert_eq!(value, "Added and committed!"),
+                        2 => assert_eq!(value, "Pushing your new idea.."),
+                        3 => assert_eq!(value, "Pushed!"),
+                        _ => panic!("Unknown state"),
+                    }
+
+                    Ok(())
+                }),
+                input_header: Some(|value| {
+                    match READ_INPUT_COUNTER.load(Ordering::SeqCst) {
+                        0 => assert_eq!(value, ">> Idea summary"),
+                        1 => assert_eq!(value, "Open editor to add details? [y/N]"),
+                        _ => panic!("Unknown state"),
+                    }
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| match READ_INPUT_COUNTER.fetch_add(1, Ordering::SeqCst) {
+                    0 => Ok(String::from("read-input-string")),
+                    1 => Ok(String::from("y")),
+                    _ => panic!("Unknown state"),
+                }),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "read-input-string");
+                    Ok(Oid::zero())
+                }),
+                push: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                open_editor: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    WRITTEN.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            clear_config: false,
+            view: false,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(WRITTEN.load(Ordering::SeqCst));
+        assert!(counter_equals(2, &READ_INPUT_COUNTER));
+    }
+
+    #[test]
+    fn test_keyed_append_strategy_writes_id_before_opening_editor() {
+        static PRINT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static READ_INPUT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static WRITTEN: AtomicBool = AtomicBool::new(false);
+        static OPENED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::AskOpenEditor => Ok("true".to_string()),
+                    ConfigType::AppendStrategy => Ok("keyed".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    let counter = PRINT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => assert_eq!(value, "Adding and committing your new idea to main.."),
+                        1 => assert_eq!(value, "Added and committed!"),
+                        2 => assert_eq!(value, "Pushing your new idea.."),
+                        3 => assert_eq!(value, "Pushed!"),
+                        _ => panic!("Unknown state"),
+                    }
+
+                    Ok(())
+                }),
+                input_header: Some(|value| {
+                    match READ_INPUT_COUNTER.load(Ordering::SeqCst) {
+                        0 => assert_eq!(value, ">> Idea summary"),
+                        1 => assert_eq!(value, "Open editor to add details? [y/N]"),
+                        _ => panic!("Unknown state"),
+                    }
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| match READ_INPUT_COUNTER.fetch_add(1, Ordering::SeqCst) {
+                    0 => Ok(String::from("read-input-string")),
+                    1 => Ok(String::from("y")),
+                    _ => panic!("Unknown state"),
+                }),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "read-input-string");
+                    Ok(Oid::zero())
+                }),
+                push: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                open_editor: Some(|_file_path| {
+                    panic!("Editor should open below the new idea");
+                }),
+                open_editor_at: Some(|file_path, line| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(line, 3);
+                    assert!(WRITTEN.load(Ordering::SeqCst));
+                    OPENED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockFileHandler {
+                line_count: Some(|_file_path| Ok(3)),
+                append_keyed_idea: Some(|file_path, id, summary, body| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    // A version 4 UUID
+                    let groups: Vec<usize> = id.split('-').map(str::len).collect();
+                    assert_eq!(groups, [8, 4, 4, 4, 12]);
+                    assert_eq!(id.chars().nth(14), Some('4'));
+                    assert_eq!(summary, "read-input-string");
+                    assert_eq!(body, "");
+                    WRITTEN.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            clear_config: false,
+            view: false,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(OPENED.load(Ordering::SeqCst));
+        assert!(WRITTEN.load(Ordering::SeqCst));
+        assert!(counter_equals(2, &READ_INPUT_COUNTER));
+    }
+
+    #[test]
+    fn test_debounce_commits_once_edits_settle() {
+        static PRINT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static SETTLED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::DebounceSecs => Ok("3".to_string()),
+                    ConfigType::OpenAtEnd => Ok("true".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    let counter = PRINT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => assert_eq!(
+                            value,
+                            "Waiting for specific-repo/README.md to go unchanged for 3s"
+                        ),
+                        1 => assert_eq!(value, "Adding and committing your new idea to main.."),
+                        2 => assert_eq!(value, "Added and committed!"),
+                        3 => assert_eq!(value, "Pushing your new idea.."),
+                        4 => assert_eq!(value, "Pushed!"),
+                        _ => panic!("Unknown state"),
+                    }
+
+                    Ok(())
+                }),
+                input_header: Some(|value| {
+                    assert_eq!(value, ">> Idea summary");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| Ok(String::from("read-input-string"))),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "read-input-string");
+                    assert!(SETTLED.load(Ordering::SeqCst));
+                    Ok(Oid::zero())
+                }),
+                push: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                open_editor: Some(|_file_path| {
+                    panic!("Editor should be opened at the last line");
+                }),
+                open_editor_at: Some(|file_path, line| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(line, 12);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockFileHandler {
+                line_count: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    Ok(12)
+                }),
+                wait_until_settled: Some(|file_path, quiet| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(quiet, Duration::from_secs(3));
+                    SETTLED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            clear_config: false,
+            view: false,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert_eq!(PRINT_COUNTER.load(Ordering::SeqCst), 5);
+    }
+
+    #[test]
+    fn test_commit_body_is_written_in_a_second_buffer() {
+        static PRINT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_path: Some(|| Ok(PathBuf::from("specific-config-dir"))),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    let counter = PRINT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => assert_eq!(value, "Adding and committing your new idea to main.."),
+                        1 => assert_eq!(value, "Added and committed!"),
+                        2 => assert_eq!(value, "Pushing your new idea.."),
+                        3 => assert_eq!(value, "Pushed!"),
+                        _ => panic!("Unknown state"),
+                    }
+
+                    Ok(())
+                }),
+                input_header: Some(|value| {
+                    assert_eq!(value, ">> Idea summary");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| Ok(String::from("read-input-string"))),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                commit: Some(|subject, body, _author_time, _commit_time| {
+                    assert_eq!(subject, "read-input-string");
+                    assert_eq!(body, "specific-body");
+                    Ok(Oid::zero())
+                }),
+                push: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                open_editor: Some(|file_path| {
+                    assert!(
+                        ["specific-repo/README.md", "specific-config-dir/COMMIT_BODY"]
+                            .contains(&file_path)
+                    );
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockFileHandler {
+                prepare_commit_body: Some(|file_path| {
+                    assert_eq!(file_path, "specific-config-dir/COMMIT_BODY");
+                    Ok(())
+                }),
+                read_commit_body: Some(|file_path| {
+                    assert_eq!(file_path, "specific-config-dir/COMMIT_BODY");
+                    Ok(String::from("specific-body"))
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            commit_body: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn test_multiline_summary_moves_the_rest_to_the_commit_body() {
+        static PRINT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_path: Some(|| Ok(PathBuf::from("specific-config-dir"))),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    let counter = PRINT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => assert_eq!(value, "Adding and committing your new idea to main.."),
+                        1 => assert_eq!(value, "Added and committed!"),
+                        2 => assert_eq!(value, "Pushing your new idea.."),
+                        3 => assert_eq!(value, "Pushed!"),
+                        _ => panic!("Unknown state"),
+                    }
+
+                    Ok(())
+                }),
+                input_header: Some(|value| {
+                    assert_eq!(value, ">> Idea summary");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| Ok(String::from("first-line\nsecond-line\nthird-line"))),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                commit: Some(|subject, body, _author_time, _commit_time| {
+                    assert_eq!(subject, "first-line");
+                    assert_eq!(body, "second-line\nthird-line\n\nspecific-body");
+                    Ok(Oid::zero())
+                }),
+                push: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                open_editor: Some(|file_path| {
+                    assert!(
+                        ["specific-repo/README.md", "specific-config-dir/COMMIT_BODY"]
+                            .contains(&file_path)
+                    );
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockFileHandler {
+                prepare_commit_body: Some(|file_path| {
+                    assert_eq!(file_path, "specific-config-dir/COMMIT_BODY");
+                    Ok(())
+                }),
+                read_commit_body: Some(|file_path| {
+                    assert_eq!(file_path, "specific-config-dir/COMMIT_BODY");
+                    Ok(String::from("specific-body"))
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            commit_body: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn test_multiline_summary_asks_again_when_configured() {
+        static PRINT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static READ_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static ERROR_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::MultilineSummary => Ok("ask".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                config_dir_path: Some(|| Ok(PathBuf::from("specific-config-dir"))),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    let counter = PRINT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => assert_eq!(value, "Adding and committing your new idea to main.."),
+                        1 => assert_eq!(value, "Added and committed!"),
+                        2 => assert_eq!(value, "Pushing your new idea.."),
+                        3 => assert_eq!(value, "Pushed!"),
+                        _ => panic!("Unknown state"),
+                    }
+
+                    Ok(())
+                }),
+                input_header: Some(|value| {
+                    assert_eq!(value, ">> Idea summary");
+                    Ok(())
+                }),
+                error: Some(|value| {
+                    ERROR_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    assert_eq!(value, "The summary has to be a single line");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| match READ_COUNTER.fetch_add(1, Ordering::SeqCst) {
+                    0 => Ok(String::from("first-line\nsecond-line")),
+                    _ => Ok(String::from("read-input-string")),
+                }),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                commit: Some(|subject, body, _author_time, _commit_time| {
+                    assert_eq!(subject, "read-input-string");
+                    assert_eq!(body, "");
+                    Ok(Oid::zero())
+                }),
+                push: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                open_editor: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions::default();
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert_eq!(READ_COUNTER.load(Ordering::SeqCst), 2);
+        assert_eq!(ERROR_COUNTER.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_unreachable_remote_warns_and_captures_anyway() {
+        static PRINT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static WARNED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager::default(),
+            MockPrinter {
+                println: Some(|value| {
+                    let counter = PRINT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => assert_eq!(value, "Adding and committing your new idea to main.."),
+                        1 => assert_eq!(value, "Added and committed!"),
+                        2 => assert_eq!(value, "Pushing your new idea.."),
+                        3 => assert_eq!(value, "Pushed!"),
+                        _ => panic!("Unknown state"),
+                    }
+
+                    Ok(())
+                }),
+                input_header: Some(|value| {
+                    assert_eq!(value, ">> Idea summary");
+                    Ok(())
+                }),
+                error: Some(|value| {
+                    assert_eq!(
+                        value,
+                        "Could not reach github.com:22: timed out, pushing your idea will probably fail (skip this check with --offline)"
+                    );
+                    WARNED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| Ok(String::from("read-input-string"))),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "read-input-string");
+                    Ok(Oid::zero())
+                }),
+                push: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                probe_remote: Some(|| {
+                    Err(git2::Error::from_str(
+                        "Could not reach github.com:22: timed out",
+                    ))
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                open_editor: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            clear_config: false,
+            view: false,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(WARNED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_offline_skips_remote_check() {
+        static PRINT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager::default(),
+            MockPrinter {
+                println: Some(|value| {
+                    let counter = PRINT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => assert_eq!(value, "Adding and committing your new idea to main.."),
+                        1 => assert_eq!(value, "Added and committed!"),
+                        2 => assert_eq!(value, "Pushing your new idea.."),
+                        3 => assert_eq!(value, "Pushed!"),
+                        _ => panic!("Unknown state"),
+                    }
+
+                    Ok(())
+                }),
+                input_header: Some(|value| {
+                    assert_eq!(value, ">> Idea summary");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| Ok(String::from("read-input-string"))),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "read-input-string");
+                    Ok(Oid::zero())
+                }),
+                push: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                probe_remote: Some(|| panic!("--offline must not check the remote")),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                open_editor: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            offline: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn test_verbose_traces_each_git_step() {
+        static PRINT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static TRACE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager::default(),
+            MockPrinter {
+                println: Some(|value| {
+                    let counter = PRINT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => assert_eq!(value, "Adding and committing your new idea to main.."),
+                        1 => assert_eq!(value, "Added and committed!"),
+                        2 => assert_eq!(value, "Pushing your new idea.."),
+                        3 => assert_eq!(value, "Pushed!"),
+                        _ => panic!("Unknown state"),
+                    }
+
+                    Ok(())
+                }),
+                input_header: Some(|value| {
+                    assert_eq!(value, ">> Idea summary");
+                    Ok(())
+                }),
+                trace: Some(|value| {
+                    let counter = TRACE_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    let expected = match counter {
+                        0 => "git: open repo specific-repo..",
+                        1 => "git: opened in ",
+                        2 => "git: checkout branch main..",
+                        3 => "git: checked out in ",
+                        4 => "git: stage idea files..",
+                        5 => "git: staged 1 file(s) in ",
+                        6 => "git: commit..",
+                        7 => "git: committed 0000000 in ",
+                        8 => "git: connect to origin and push refs/heads/main:refs/heads/main..",
+                        9 => "git: pushed in ",
+                        10 => "git: set the upstream of main..",
+                        11 => "git: already set in ",
+                        12 => "git: connect to origin for its default branch..",
+                        13 => "git: default branch is unset in ",
+                        _ => panic!("Unknown state"),
+                    };
+                    assert!(value.starts_with(expected), "{}", value);
+
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| Ok(String::from("read-input-string"))),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "read-input-string");
+                    Ok(Oid::zero())
+                }),
+                push: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                open_editor: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            verbose: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(counter_equals(14, &TRACE_COUNTER));
+    }
+
+    #[test]
+    fn test_no_push_commits_without_pushing() {
+        static PRINT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager::default(),
+            MockPrinter {
+                println: Some(|value| {
+                    let counter = PRINT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => assert_eq!(value, "Adding and committing your new idea to main.."),
+                        1 => assert_eq!(value, "Added and committed!"),
+                        2 => assert_eq!(value, "Not pushed, run with --flush to push your ideas"),
+                        _ => panic!("Unknown state"),
+                    }
+
+                    Ok(())
+                }),
+                input_header: Some(|value| {
+                    assert_eq!(value, ">> Idea summary");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| Ok(String::from("read-input-string"))),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    Ok(())
+                }),
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "read-input-string");
+                    Ok(Oid::zero())
+                }),
+                push: Some(|_branch_name| panic!("--no-push must not push")),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                open_editor: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            no_push: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn test_worktree_keeps_repo_checkout_untouched() {
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::Branch => Ok("ideas".to_string()),
+                    ConfigType::Worktree => Ok("true".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                config_dir_path: Some(|| Ok(PathBuf::from("specific-config-dir"))),
+                ..Default::default()
+            },
+            MockPrinter {
+                input_header: Some(|_value| Ok(())),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| Ok(String::from("read-input-string"))),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                ensure_worktree: Some(|worktree_path, branch_name| {
+                    assert_eq!(worktree_path, "specific-config-dir/worktree");
+                    assert_eq!(branch_name, "ideas");
+                    Ok(())
+                }),
+                commit: Some(|_subject, _body, _author_time, _commit_time| Ok(Oid::zero())),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                open_editor: Some(|file_path| {
+                    assert_eq!(file_path, "specific-config-dir/worktree/README.md");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            clear_config: false,
+            view: false,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn test_preview_declined_leaves_idea_uncommitted() {
+        static READ_INPUT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static PRINT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager::default(),
+            MockPrinter {
+                println: Some(|value| {
+                    let counter = PRINT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => assert_eq!(value, "... diff truncated, 50 more lines not shown"),
+                        1 => assert_eq!(value, "Aborted, your idea is left uncommitted"),
+                        _ => panic!("Unknown state"),
+                    }
+                    Ok(())
+                }),
+                input_header: Some(|_value| Ok(())),
+                diff: Some(|value| {
+                    assert_eq!(value.lines().count(), 200);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| {
+                    let counter = READ_INPUT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => Ok(String::from("read-input-string")),
+                        _ => Ok(String::from("N")),
+                    }
+                }),
+                ..Default::default()
+            },
+            MockGit {
+                checkout_branch: Some(|_branch_name| unimplemented!()),
+                add: Some(|| unimplemented!()),
+                diff: Some(|| Ok("+some-line\n".repeat(250))),
+                push: Some(|_branch_name| unimplemented!()),
+                is_ignored: Some(|_file_path| unimplemented!()),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                open_editor: Some(|_file_path| Ok(())),
+                ..Default::default()
+            },
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            preview: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(counter_equals(2, &PRINT_COUNTER));
+    }
+
+    #[test]
+    fn test_clipboard_is_used_as_idea_body() {
+        let mut eureka = Eureka::new(
+            MockConfigManager::default(),
+            MockPrinter {
+                input_header: Some(|_value| {
+                    panic!("Summary is given and should not be asked for");
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_clipboard: Some(|| Ok(String::from("  specific-clipboard-contents\n"))),
+                ..Default::default()
+            },
+            MockGit {
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "specific-summary");
+                    Ok(Oid::zero())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|file_path, summary, body| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(summary, "specific-summary");
+                    assert_eq!(body, "specific-clipboard-contents");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            summary: Some(String::from("specific-summary")),
+            clipboard: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn test_inline_idea_is_read_at_one_prompt() {
+        let mut eureka = Eureka::new(
+            MockConfigManager::default(),
+            MockPrinter {
+                input_header: Some(|value| {
+                    assert_eq!(
+                        value,
+                        ">> Idea summary, then its body. End with an empty line"
+                    );
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_multiline_input: Some(|| {
+                    Ok(String::from(
+                        "specific-summary\nspecific-body\n  over lines",
+                    ))
+                }),
+                ..Default::default()
+            },
+            MockGit {
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "specific-summary");
+                    Ok(Oid::zero())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|file_path, summary, body| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(summary, "specific-summary");
+                    assert_eq!(body, "specific-body\n  over lines");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            inline: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn test_idea_file_is_rotated_when_too_big() {
+        static NOTIFIED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::MaxFileBytes => Ok("100".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    if value.starts_with("README.md is over") {
+                        assert_eq!(
+                            value,
+                            "README.md is over 100 bytes, archived it as README.archive.2024-06-01.md"
+                        );
+                        NOTIFIED.store(true, Ordering::SeqCst);
+                    }
+                    Ok(())
+                }),
+                input_header: Some(|_value| {
+                    panic!("Summary is given and should not be asked for");
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_clipboard: Some(|| Ok(String::from("  specific-clipboard-contents\n"))),
+                ..Default::default()
+            },
+            MockGit {
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "specific-summary");
+                    Ok(Oid::zero())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|file_path, summary, body| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(summary, "specific-summary");
+                    assert_eq!(body, "specific-clipboard-contents");
+                    Ok(())
+                }),
+                rotate_idea_file: Some(|file_path, max_bytes, incoming_bytes, date| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(max_bytes, 100);
+                    // Summary and clipboard contents
+                    assert_eq!(incoming_bytes, 43);
+                    // Today according to FixedClock
+                    assert_eq!(date, "2024-06-01");
+                    Ok(Some(PathBuf::from(
+                        "specific-repo/README.archive.2024-06-01.md",
+                    )))
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            summary: Some(String::from("specific-summary")),
+            clipboard: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(NOTIFIED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_github_backend_creates_an_issue_instead_of_a_commit() {
+        static CREATED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::Backend => Ok("github".to_string()),
+                    ConfigType::GithubRepo => Ok("owner/ideas".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    if value.starts_with("Created") {
+                        assert_eq!(value, "Created https://github.com/owner/ideas/issues/1");
+                    }
+                    Ok(())
+                }),
+                input_header: Some(|_value| {
+                    panic!("Summary is given and should not be asked for");
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_clipboard: Some(|| Ok(String::from("  specific-clipboard-contents\n"))),
+                ..Default::default()
+            },
+            MockGit::default(),
+            MockProgramOpener::default(),
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink {
+                store: Some(|destination, title, body| {
+                    assert_eq!(destination, "owner/ideas");
+                    assert_eq!(title, "specific-summary");
+                    assert_eq!(body, "specific-clipboard-contents");
+                    CREATED.store(true, Ordering::SeqCst);
+                    Ok(String::from("https://github.com/owner/ideas/issues/1"))
+                }),
+                ..Default::default()
+            },
+        );
+        let opts = EurekaOptions {
+            summary: Some(String::from("specific-summary")),
+            clipboard: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(CREATED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_filesystem_backend_saves_the_idea_without_git() {
+        static SAVED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-folder".to_string()),
+                    ConfigType::Backend => Ok("filesystem".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    if value.starts_with("Saved") {
+                        assert_eq!(value, "Saved your idea to specific-folder/README.md");
+                    }
+                    Ok(())
+                }),
+                input_header: Some(|_value| {
+                    panic!("Summary is given and should not be asked for");
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_clipboard: Some(|| Ok(String::from("  specific-clipboard-contents\n"))),
+                ..Default::default()
+            },
+            MockGit::default(),
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|file_path, summary, body| {
+                    assert_eq!(file_path, "specific-folder/README.md");
+                    assert_eq!(summary, "specific-summary");
+                    assert_eq!(body, "specific-clipboard-contents");
+                    SAVED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ensure_dir: Some(|dir_path| {
+                    assert_eq!(dir_path, "specific-folder");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            summary: Some(String::from("specific-summary")),
+            clipboard: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(SAVED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_append_adds_a_bullet_without_prompts() {
+        static APPENDED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager::default(),
+            MockPrinter {
+                input_header: Some(|_value| {
+                    panic!("--append should not prompt");
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "specific-thought");
+                    Ok(Oid::zero())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|_file_path, _summary, _body| {
+                    panic!("--append adds a bullet, not an idea");
+                }),
+                append_bullet: Some(|file_path, text| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(text, "specific-thought");
+                    APPENDED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            append: Some(String::from("  specific-thought ")),
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(APPENDED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_notification_is_shown_after_push() {
+        static NOTIFIED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::Notify => Ok("true".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                input_header: Some(|_value| {
+                    panic!("--append should not prompt");
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "specific-thought");
+                    Ok(Oid::zero())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                notify: Some(|title, body| {
+                    assert_eq!(title, "Idea pushed");
+                    assert_eq!(body, "specific-thought");
+                    NOTIFIED.store(true, Ordering::SeqCst);
+                    // Failing to notify doesn't fail the capture
+                    Err(Error::new(ErrorKind::Unsupported, "no-notifications"))
+                }),
+                ..Default::default()
+            },
+            MockFileHandler {
+                append_idea: Some(|_file_path, _summary, _body| {
+                    panic!("--append adds a bullet, not an idea");
+                }),
+                append_bullet: Some(|file_path, text| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(text, "specific-thought");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            append: Some(String::from("  specific-thought ")),
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(NOTIFIED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_project_repo_is_marked_once_capturing_is_confirmed() {
+        static APPENDED: AtomicBool = AtomicBool::new(false);
+        static MARKED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager::default(),
+            MockPrinter {
+                input_header: Some(|value| {
+                    assert_eq!(value, "Capture your ideas in it anyway? [y/N]");
+                    Ok(())
+                }),
+                error: Some(|value| {
+                    assert_eq!(
+                        value,
+                        "specific-repo looks like a project rather than an idea repo, your ideas would go to its README.md"
+                    );
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| Ok(String::from("y"))),
+                ..Default::default()
+            },
+            MockGit {
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "specific-thought");
+                    Ok(Oid::zero())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|_file_path, _summary, _body| {
+                    panic!("--append adds a bullet, not an idea");
+                }),
+                append_bullet: Some(|file_path, text| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(text, "specific-thought");
+                    APPENDED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                looks_like_project: Some(|dir_path| {
+                    assert_eq!(dir_path, "specific-repo");
+                    Ok(true)
+                }),
+                create_marker: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/.eureka-idea-repo");
+                    MARKED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            append: Some(String::from("  specific-thought ")),
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(MARKED.load(Ordering::SeqCst));
+        assert!(APPENDED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_project_repo_is_left_alone_unless_confirmed() {
+        static APPENDED: AtomicBool = AtomicBool::new(false);
+        static MARKED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager::default(),
+            MockPrinter {
+                input_header: Some(|value| {
+                    assert_eq!(value, "Capture your ideas in it anyway? [y/N]");
+                    Ok(())
+                }),
+                error: Some(|value| {
+                    assert_eq!(
+                        value,
+                        "specific-repo looks like a project rather than an idea repo, your ideas would go to its README.md"
+                    );
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| Ok(String::from(""))),
+                ..Default::default()
+            },
+            MockGit {
+                add: Some(|| {
+                    panic!("Nothing should be committed to a project repo without confirmation");
+                }),
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "specific-thought");
+                    Ok(Oid::zero())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|_file_path, _summary, _body| {
+                    panic!("--append adds a bullet, not an idea");
+                }),
+                append_bullet: Some(|file_path, text| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(text, "specific-thought");
+                    APPENDED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                looks_like_project: Some(|dir_path| {
+                    assert_eq!(dir_path, "specific-repo");
+                    Ok(true)
+                }),
+                create_marker: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/.eureka-idea-repo");
+                    MARKED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            append: Some(String::from("  specific-thought ")),
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(!MARKED.load(Ordering::SeqCst));
+        assert!(!APPENDED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_long_summary_is_shortened_in_commit_subject() {
+        static APPENDED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::MaxSubjectLen => Ok("12".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                input_header: Some(|_value| {
+                    panic!("--append should not prompt");
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                commit: Some(|subject, body, _author_time, _commit_time| {
+                    assert_eq!(subject, "some longer…");
+                    assert_eq!(body, "some longer summary");
+                    Ok(Oid::zero())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|_file_path, _summary, _body| {
+                    panic!("--append adds a bullet, not an idea");
+                }),
+                append_bullet: Some(|file_path, text| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(text, "some longer summary");
+                    APPENDED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            append: Some(String::from("some longer summary")),
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(APPENDED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_oversized_file_is_not_committed() {
+        static APPENDED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::MaxBlobBytes => Ok("1000".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                input_header: Some(|_value| {
+                    panic!("--append should not prompt");
+                }),
+                error: Some(|value| {
+                    assert_eq!(
+                        value,
+                        "README.md is 2000 bytes, track it with Git LFS (git lfs track \"README.md\") or raise max_blob_bytes"
+                    );
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                add: Some(|| {
+                    panic!("An oversized file should not be staged");
+                }),
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "specific-thought");
+                    Ok(Oid::zero())
+                }),
+                oversized_files: Some(|max_bytes| {
+                    assert_eq!(max_bytes, 1000);
+                    Ok(vec![(String::from("README.md"), 2000)])
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|_file_path, _summary, _body| {
+                    panic!("--append adds a bullet, not an idea");
+                }),
+                append_bullet: Some(|file_path, text| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(text, "specific-thought");
+                    APPENDED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            append: Some(String::from("  specific-thought ")),
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert_eq!(
+            actual.unwrap_err().to_string(),
+            "Git error: Refusing to commit files over 1000 bytes that Git LFS doesn't track"
+        );
+        assert!(APPENDED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_status_tokens_for_committed_and_pushed_idea() {
+        static APPENDED: AtomicBool = AtomicBool::new(false);
+        static TOKEN: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager::default(),
+            MockPrinter {
+                status: Some(|token| {
+                    const EXPECTED: [&str; 2] = [
+                        "IDEA_COMMITTED 0000000000000000000000000000000000000000",
+                        "PUSHED origin",
+                    ];
+                    assert_eq!(token, EXPECTED[TOKEN.fetch_add(1, Ordering::SeqCst)]);
+                    Ok(())
+                }),
+                input_header: Some(|_value| {
+                    panic!("--append should not prompt");
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "specific-thought");
+                    Ok(Oid::zero())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|_file_path, _summary, _body| {
+                    panic!("--append adds a bullet, not an idea");
+                }),
+                append_bullet: Some(|file_path, text| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(text, "specific-thought");
+                    APPENDED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            append: Some(String::from("  specific-thought ")),
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(APPENDED.load(Ordering::SeqCst));
+        assert_eq!(TOKEN.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_push_strategy_pulls_and_retries_rejected_push() {
+        static APPENDED: AtomicBool = AtomicBool::new(false);
+        static TOKEN: AtomicUsize = AtomicUsize::new(0);
+        static PUSHES: AtomicUsize = AtomicUsize::new(0);
+        static PULLED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::PushStrategy => Ok("pull-rebase".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    if value.starts_with("origin/") {
+                        assert_eq!(
+                            value,
+                            "origin/main has ideas from elsewhere, pulling them in.."
+                        );
+                        assert_eq!(PUSHES.load(Ordering::SeqCst), 1);
+                    }
+                    Ok(())
+                }),
+                status: Some(|token| {
+                    const EXPECTED: [&str; 2] = [
+                        "IDEA_COMMITTED 0000000000000000000000000000000000000000",
+                        "PUSHED origin",
+                    ];
+                    assert_eq!(token, EXPECTED[TOKEN.fetch_add(1, Ordering::SeqCst)]);
+                    Ok(())
+                }),
+                input_header: Some(|_value| {
+                    panic!("--append should not prompt");
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "specific-thought");
+                    Ok(Oid::zero())
+                }),
+                push: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    if PUSHES.fetch_add(1, Ordering::SeqCst) == 0 {
+                        return Err(git2::Error::new(
+                            git2::ErrorCode::NotFastForward,
+                            git2::ErrorClass::Reference,
+                            "origin rejected refs/heads/main",
+                        ));
+                    }
+                    assert!(PULLED.load(Ordering::SeqCst));
+                    Ok(())
+                }),
+                pull_branch: Some(|branch_name, rebase| {
+                    assert_eq!(branch_name, "main");
+                    assert!(rebase);
+                    PULLED.store(true, Ordering::SeqCst);
+                    Ok(Oid::zero())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|_file_path, _summary, _body| {
+                    panic!("--append adds a bullet, not an idea");
+                }),
+                append_bullet: Some(|file_path, text| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(text, "specific-thought");
+                    APPENDED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            append: Some(String::from("  specific-thought ")),
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(APPENDED.load(Ordering::SeqCst));
+        assert_eq!(TOKEN.load(Ordering::SeqCst), 2);
+        assert_eq!(PUSHES.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_plain_push_strategy_returns_rejected_push() {
+        static APPENDED: AtomicBool = AtomicBool::new(false);
+        static TOKEN: AtomicUsize = AtomicUsize::new(0);
+        static PUSHES: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::PushStrategy => Ok("plain".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                status: Some(|token| {
+                    assert_eq!(
+                        token,
+                        "IDEA_COMMITTED 0000000000000000000000000000000000000000"
+                    );
+                    TOKEN.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                }),
+                input_header: Some(|_value| {
+                    panic!("--append should not prompt");
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "specific-thought");
+                    Ok(Oid::zero())
+                }),
+                push: Some(|_branch_name| {
+                    PUSHES.fetch_add(1, Ordering::SeqCst);
+                    Err(git2::Error::new(
+                        git2::ErrorCode::NotFastForward,
+                        git2::ErrorClass::Reference,
+                        "origin rejected refs/heads/main",
+                    ))
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|_file_path, _summary, _body| {
+                    panic!("--append adds a bullet, not an idea");
+                }),
+                append_bullet: Some(|file_path, text| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(text, "specific-thought");
+                    APPENDED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            append: Some(String::from("  specific-thought ")),
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(
+            matches!(actual, Err(EurekaError::Git(ref err)) if err.code() == git2::ErrorCode::NotFastForward)
+        );
+        assert!(APPENDED.load(Ordering::SeqCst));
+        assert_eq!(TOKEN.load(Ordering::SeqCst), 1);
+        assert_eq!(PUSHES.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_gitignored_idea_file_is_not_committed() {
+        static APPENDED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager::default(),
+            MockPrinter {
+                input_header: Some(|_value| {
+                    panic!("--append should not prompt");
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                checkout_branch: Some(|_branch_name| unimplemented!()),
+                add: Some(|| unimplemented!()),
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    panic!("committed {}", subject);
+                }),
+                push: Some(|_branch_name| unimplemented!()),
+                is_ignored: Some(|file_path| {
+                    assert_eq!(file_path, "README.md");
+                    Ok(true)
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|_file_path, _summary, _body| {
+                    panic!("--append adds a bullet, not an idea");
+                }),
+                append_bullet: Some(|file_path, text| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(text, "specific-thought");
+                    APPENDED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            append: Some(String::from("  specific-thought ")),
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert_eq!(
+            actual.unwrap_err().to_string(),
+            "Git error: Your idea file is gitignored; ideas won't be committed."
+        );
+        // The idea is kept in the file
+        assert!(APPENDED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_process_queue_commits_each_idea_and_pushes_once() {
+        static APPENDED: AtomicUsize = AtomicUsize::new(0);
+        static COMMITTED: AtomicUsize = AtomicUsize::new(0);
+        static PUSHED: AtomicUsize = AtomicUsize::new(0);
+        const QUEUE: [&str; 2] = ["first-thought", "second-thought"];
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_path: Some(|| Ok(PathBuf::from("specific-config-dir"))),
+                ..Default::default()
+            },
+            MockPrinter {
+                input_header: Some(|_value| {
+                    panic!("--process-queue should not prompt");
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    let committed = COMMITTED.fetch_add(1, Ordering::SeqCst);
+                    assert_eq!(subject, QUEUE[committed]);
+                    Ok(Oid::zero())
+                }),
+                push: Some(|_branch_name| {
+                    assert_eq!(COMMITTED.load(Ordering::SeqCst), 2);
+                    PUSHED.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|_file_path, _summary, _body| {
+                    panic!("--process-queue adds bullets, not ideas");
+                }),
+                append_bullet: Some(|file_path, text| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    let appended = APPENDED.fetch_add(1, Ordering::SeqCst);
+                    assert_eq!(text, QUEUE[appended]);
+                    Ok(())
+                }),
+                read_queue: Some(|file_path| {
+                    assert_eq!(file_path, "specific-config-dir/queue");
+                    Ok(QUEUE.iter().map(|entry| entry.to_string()).collect())
+                }),
+                write_queue: Some(|file_path, entries| {
+                    assert_eq!(file_path, "specific-config-dir/queue");
+                    // What's left once the idea just committed is taken off the queue
+                    let committed = COMMITTED.load(Ordering::SeqCst);
+                    assert_eq!(entries, &QUEUE[committed..]);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            process_queue: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert_eq!(APPENDED.load(Ordering::SeqCst), 2);
+        assert_eq!(COMMITTED.load(Ordering::SeqCst), 2);
+        assert_eq!(PUSHED.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_process_queue_keeps_dates_of_imported_ideas_as_author_dates() {
+        static APPENDED: AtomicUsize = AtomicUsize::new(0);
+        static COMMITTED: AtomicUsize = AtomicUsize::new(0);
+        static PUSHED: AtomicUsize = AtomicUsize::new(0);
+        const QUEUE: [&str; 2] = ["[2023-05-01] first-thought", "second-thought"];
+        const TEXTS: [&str; 2] = ["first-thought", "second-thought"];
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_path: Some(|| Ok(PathBuf::from("specific-config-dir"))),
+                ..Default::default()
+            },
+            MockPrinter {
+                input_header: Some(|_value| {
+                    panic!("--process-queue should not prompt");
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                commit: Some(|subject, _body, author_time, commit_time| {
+                    let committed = COMMITTED.fetch_add(1, Ordering::SeqCst);
+                    assert_eq!(subject, TEXTS[committed]);
+                    let imported = Local
+                        .with_ymd_and_hms(2023, 5, 1, 0, 0, 0)
+                        .unwrap()
+                        .timestamp();
+                    let expected = [Some(imported), None];
+                    assert_eq!(author_time.map(|time| time.seconds()), expected[committed]);
+                    // Committed now
+                    assert_eq!(commit_time, None);
+                    Ok(Oid::zero())
+                }),
+                push: Some(|_branch_name| {
+                    assert_eq!(COMMITTED.load(Ordering::SeqCst), 2);
+                    PUSHED.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|_file_path, _summary, _body| {
+                    panic!("--process-queue adds bullets, not ideas");
+                }),
+                append_bullet: Some(|file_path, text| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    let appended = APPENDED.fetch_add(1, Ordering::SeqCst);
+                    assert_eq!(text, TEXTS[appended]);
+                    Ok(())
+                }),
+                read_queue: Some(|file_path| {
+                    assert_eq!(file_path, "specific-config-dir/queue");
+                    Ok(QUEUE.iter().map(|entry| entry.to_string()).collect())
+                }),
+                write_queue: Some(|file_path, entries| {
+                    assert_eq!(file_path, "specific-config-dir/queue");
+                    // What's left once the idea just committed is taken off the queue
+                    let committed = COMMITTED.load(Ordering::SeqCst);
+                    assert_eq!(entries, &QUEUE[committed..]);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            process_queue: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert_eq!(APPENDED.load(Ordering::SeqCst), 2);
+        assert_eq!(COMMITTED.load(Ordering::SeqCst), 2);
+        assert_eq!(PUSHED.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_branch_template_is_expanded_with_the_date() {
+        static PUSHED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::Branch => Ok("ideas/%Y-w%W".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                input_header: Some(|_value| {
+                    panic!("--append should not prompt");
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                checkout_branch: Some(|branch_name| {
+                    assert_eq!(branch_name, "ideas/2024-w22");
+                    Ok(())
+                }),
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "specific-thought");
+                    Ok(Oid::zero())
+                }),
+                push: Some(|branch_name| {
+                    assert_eq!(branch_name, "ideas/2024-w22");
+                    PUSHED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|_file_path, _summary, _body| {
+                    panic!("--append adds a bullet, not an idea");
+                }),
+                append_bullet: Some(|file_path, text| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(text, "specific-thought");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            append: Some(String::from("  specific-thought ")),
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(PUSHED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_branch_template_must_expand_to_a_valid_branch() {
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::Branch => Ok("ideas/%Y..%W".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                input_header: Some(|_value| {
+                    panic!("--append should not prompt");
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                checkout_branch: Some(|branch_name| {
+                    panic!("Invalid branch {} should not be checked out", branch_name);
+                }),
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "specific-thought");
+                    Ok(Oid::zero())
+                }),
+                push: Some(|branch_name| {
+                    panic!("Invalid branch {} should not be pushed", branch_name);
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|_file_path, _summary, _body| {
+                    panic!("--append adds a bullet, not an idea");
+                }),
+                append_bullet: Some(|file_path, text| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(text, "specific-thought");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            append: Some(String::from("  specific-thought ")),
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(matches!(actual, Err(EurekaError::Config(_))));
+    }
+
+    #[test]
+    fn test_audit_log_records_the_pushed_idea() {
+        static LOGGED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::AuditLog => Ok("true".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                config_dir_path: Some(|| Ok(PathBuf::from("specific-config-dir"))),
+                ..Default::default()
+            },
+            MockPrinter {
+                input_header: Some(|_value| {
+                    panic!("--append should not prompt");
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "specific-thought");
+                    Ok(Oid::zero())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|_file_path, _summary, _body| {
+                    panic!("--append adds a bullet, not an idea");
+                }),
+                append_bullet: Some(|file_path, text| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(text, "specific-thought");
+                    Ok(())
+                }),
+                append_log_entry: Some(|file_path, entry| {
+                    assert_eq!(file_path, "specific-config-dir/eureka.log");
+                    let entry: serde_json::Value = serde_json::from_str(entry)?;
+                    assert!(entry["timestamp"]
+                        .as_str()
+                        .unwrap()
+                        .starts_with("2024-06-01T12:00:00"));
+                    assert_eq!(entry["summary"], "specific-thought");
+                    assert_eq!(entry["commit"], Oid::zero().to_string());
+                    assert_eq!(entry["repo"], "specific-repo");
+                    assert_eq!(entry["pushed"], true);
+                    LOGGED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            append: Some(String::from("  specific-thought ")),
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(LOGGED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_background_push_spawns_the_push_and_returns_right_away() {
+        static LOCKED: AtomicBool = AtomicBool::new(false);
+        static SPAWNED: AtomicBool = AtomicBool::new(false);
+        static LOGGED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::AuditLog => Ok("true".to_string()),
+                    ConfigType::BackgroundPush => Ok("true".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                config_dir_path: Some(|| Ok(PathBuf::from("specific-config-dir"))),
+                ..Default::default()
+            },
+            MockPrinter {
+                input_header: Some(|_value| {
+                    panic!("--append should not prompt");
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "specific-thought");
+                    Ok(Oid::zero())
+                }),
+                push: Some(|_branch_name| {
+                    panic!("The detached run pushes");
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                spawn_detached: Some(|args| {
+                    // The lock is taken before the push is started
+                    assert!(LOCKED.load(Ordering::SeqCst));
+                    assert_eq!(args, ["--background-push", "main"]);
+                    SPAWNED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockFileHandler {
+                append_idea: Some(|_file_path, _summary, _body| {
+                    panic!("--append adds a bullet, not an idea");
+                }),
+                append_bullet: Some(|file_path, text| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(text, "specific-thought");
+                    Ok(())
+                }),
+                append_log_entry: Some(|file_path, entry| {
+                    assert_eq!(file_path, "specific-config-dir/eureka.log");
+                    let entry: serde_json::Value = serde_json::from_str(entry)?;
+                    assert_eq!(entry["summary"], "specific-thought");
+                    assert_eq!(entry["pushed"], false);
+                    LOGGED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                try_lock: Some(|file_path| {
+                    assert_eq!(file_path, "specific-config-dir/push.lock");
+                    LOCKED.store(true, Ordering::SeqCst);
+                    Ok(true)
+                }),
+                unlock: Some(|_file_path| {
+                    panic!("The detached run releases the lock once it pushed");
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            append: Some(String::from("  specific-thought ")),
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(SPAWNED.load(Ordering::SeqCst));
+        assert!(LOGGED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_background_push_run_pushes_and_logs_how_it_went() {
+        static PUSHED: AtomicBool = AtomicBool::new(false);
+        static UNLOCKED: AtomicBool = AtomicBool::new(false);
+        static LOGGED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::AuditLog => Ok("true".to_string()),
+                    ConfigType::BackgroundPush => Ok("true".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                config_dir_path: Some(|| Ok(PathBuf::from("specific-config-dir"))),
+                ..Default::default()
+            },
+            MockPrinter {
+                input_header: Some(|_value| {
+                    panic!("--append should not prompt");
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "specific-thought");
+                    Ok(Oid::zero())
+                }),
+                push: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    PUSHED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|_file_path, _summary, _body| {
+                    panic!("--append adds a bullet, not an idea");
+                }),
+                append_bullet: Some(|file_path, text| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(text, "specific-thought");
+                    Ok(())
+                }),
+                append_log_entry: Some(|file_path, entry| {
+                    assert_eq!(file_path, "specific-config-dir/eureka.log");
+                    let entry: serde_json::Value = serde_json::from_str(entry)?;
+                    assert_eq!(entry["repo"], "specific-repo");
+                    assert_eq!(entry["branch"], "main");
+                    assert_eq!(entry["pushed"], true);
+                    assert_eq!(entry["error"], serde_json::Value::Null);
+                    LOGGED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                try_lock: Some(|_file_path| {
+                    panic!("The capture took the lock for the detached run");
+                }),
+                unlock: Some(|file_path| {
+                    assert_eq!(file_path, "specific-config-dir/push.lock");
+                    assert!(LOGGED.load(Ordering::SeqCst));
+                    UNLOCKED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            background_push: Some(String::from("main")),
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(PUSHED.load(Ordering::SeqCst));
+        assert!(UNLOCKED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_background_push_run_sets_upstream_on_first_push() {
+        static PUSHED: AtomicBool = AtomicBool::new(false);
+        static UPSTREAM_SET: AtomicBool = AtomicBool::new(false);
+        static UNLOCKED: AtomicBool = AtomicBool::new(false);
+        static LOGGED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::AuditLog => Ok("true".to_string()),
+                    ConfigType::BackgroundPush => Ok("true".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                config_dir_path: Some(|| Ok(PathBuf::from("specific-config-dir"))),
+                ..Default::default()
+            },
+            MockPrinter {
+                input_header: Some(|_value| {
+                    panic!("--append should not prompt");
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "specific-thought");
+                    Ok(Oid::zero())
+                }),
+                push: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    PUSHED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ensure_upstream: Some(|branch_name, remote_name| {
+                    assert_eq!(branch_name, "main");
+                    assert_eq!(remote_name, "origin");
+                    assert!(PUSHED.load(Ordering::SeqCst));
+                    UPSTREAM_SET.store(true, Ordering::SeqCst);
+                    Ok(true)
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|_file_path, _summary, _body| {
+                    panic!("--append adds a bullet, not an idea");
+                }),
+                append_bullet: Some(|file_path, text| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(text, "specific-thought");
+                    Ok(())
+                }),
+                append_log_entry: Some(|file_path, entry| {
+                    assert_eq!(file_path, "specific-config-dir/eureka.log");
+                    let entry: serde_json::Value = serde_json::from_str(entry)?;
+                    assert_eq!(entry["repo"], "specific-repo");
+                    assert_eq!(entry["branch"], "main");
+                    assert_eq!(entry["pushed"], true);
+                    assert_eq!(entry["error"], serde_json::Value::Null);
+                    LOGGED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                try_lock: Some(|_file_path| {
+                    panic!("The capture took the lock for the detached run");
+                }),
+                unlock: Some(|file_path| {
+                    assert_eq!(file_path, "specific-config-dir/push.lock");
+                    assert!(LOGGED.load(Ordering::SeqCst));
+                    UNLOCKED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            background_push: Some(String::from("main")),
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(PUSHED.load(Ordering::SeqCst));
+        assert!(UNLOCKED.load(Ordering::SeqCst));
+        assert!(UPSTREAM_SET.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_auto_stash_sets_changes_aside_while_committing() {
+        static STASHED: AtomicBool = AtomicBool::new(false);
+        static COMMITTED: AtomicBool = AtomicBool::new(false);
+        static POPPED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::AutoStash => Ok("true".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    if value.contains("stash") {
+                        let expected = if POPPED.load(Ordering::SeqCst) {
+                            "Restored your stashed changes"
+                        } else {
+                            "Stashed your uncommitted changes in the idea repo"
+                        };
+                        assert_eq!(value, expected);
+                    }
+                    Ok(())
+                }),
+                input_header: Some(|_value| {
+                    panic!("--append should not prompt");
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                checkout_branch: Some(|_branch_name| {
+                    assert!(STASHED.load(Ordering::SeqCst));
+                    Ok(())
+                }),
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "specific-thought");
+                    COMMITTED.store(true, Ordering::SeqCst);
+                    Ok(Oid::zero())
+                }),
+                stash_save: Some(|| {
+                    STASHED.store(true, Ordering::SeqCst);
+                    Ok(true)
+                }),
+                stash_pop: Some(|| {
+                    assert!(COMMITTED.load(Ordering::SeqCst));
+                    POPPED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|_file_path, _summary, _body| {
+                    panic!("--append adds a bullet, not an idea");
+                }),
+                append_bullet: Some(|file_path, text| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(text, "specific-thought");
+                    assert!(STASHED.load(Ordering::SeqCst));
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            append: Some(String::from("  specific-thought ")),
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(POPPED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_encrypted_idea_is_decrypted_and_encrypted_again() {
+        static ENCRYPTED: AtomicBool = AtomicBool::new(false);
+        static SKIPPED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::EncryptTo => Ok("specific-recipient".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                input_header: Some(|_value| {
+                    panic!("--append should not prompt");
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "specific-thought");
+                    Ok(Oid::zero())
+                }),
+                add_pathspec: Some(|pathspec| {
+                    assert_eq!(pathspec, "README.md.gpg");
+                }),
+                skip_idea_pathspecs: Some(|| {
+                    SKIPPED.store(true, Ordering::SeqCst);
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                encrypt_file: Some(|file_path, recipient| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(recipient, "specific-recipient");
+                    ENCRYPTED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                decrypt_file: Some(|encrypted_path, file_path| {
+                    assert_eq!(encrypted_path, "specific-repo/README.md.gpg");
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert!(!ENCRYPTED.load(Ordering::SeqCst));
+                    Ok(true)
+                }),
+                ..Default::default()
+            },
+            MockFileHandler {
+                append_idea: Some(|_file_path, _summary, _body| {
+                    panic!("--append adds a bullet, not an idea");
+                }),
+                append_bullet: Some(|file_path, text| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(text, "specific-thought");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            append: Some(String::from("  specific-thought ")),
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(ENCRYPTED.load(Ordering::SeqCst));
+        assert!(SKIPPED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_encrypted_idea_is_encrypted_again_when_the_capture_fails() {
+        static ENCRYPTED: AtomicBool = AtomicBool::new(false);
+        static SKIPPED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::EncryptTo => Ok("specific-recipient".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                input_header: Some(|_value| {
+                    panic!("--append should not prompt");
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    panic!("A failed capture commits nothing, not even {}", subject);
+                }),
+                add_pathspec: Some(|pathspec| {
+                    assert_eq!(pathspec, "README.md.gpg");
+                }),
+                skip_idea_pathspecs: Some(|| {
+                    SKIPPED.store(true, Ordering::SeqCst);
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                encrypt_file: Some(|file_path, recipient| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(recipient, "specific-recipient");
+                    ENCRYPTED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                decrypt_file: Some(|encrypted_path, file_path| {
+                    assert_eq!(encrypted_path, "specific-repo/README.md.gpg");
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert!(!ENCRYPTED.load(Ordering::SeqCst));
+                    Ok(true)
+                }),
+                ..Default::default()
+            },
+            MockFileHandler {
+                append_idea: Some(|_file_path, _summary, _body| {
+                    panic!("--append adds a bullet, not an idea");
+                }),
+                append_bullet: Some(|_file_path, _text| Err(Error::other("disk-full"))),
+                exists: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    true
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            append: Some(String::from("  specific-thought ")),
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_err());
+        assert!(ENCRYPTED.load(Ordering::SeqCst));
+        assert!(SKIPPED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_date_layout_files_idea_under_dated_folders() {
+        static TRACKED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::DateLayout => Ok("%Y/%m/%d.md".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                input_header: Some(|_value| {
+                    panic!("Summary is given and should not be asked for");
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_clipboard: Some(|| Ok(String::from("  specific-clipboard-contents\n"))),
+                ..Default::default()
+            },
+            MockGit {
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "specific-summary");
+                    Ok(Oid::zero())
+                }),
+                add_pathspec: Some(|pathspec| {
+                    assert_eq!(pathspec, "2024/06/01.md");
+                    TRACKED.store(true, Ordering::SeqCst);
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|file_path, summary, body| {
+                    assert_eq!(file_path, "specific-repo/2024/06/01.md");
+                    assert_eq!(summary, "specific-summary");
+                    assert_eq!(body, "specific-clipboard-contents");
+                    Ok(())
+                }),
+                ensure_dir: Some(|dir_path| {
+                    assert_eq!(dir_path, "specific-repo/2024/06");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            summary: Some(String::from("specific-summary")),
+            clipboard: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(TRACKED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_categories_capture_into_the_picked_folder() {
+        static TRACKED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::Categories => Ok("work,personal,tech".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                input_header: Some(|value| {
+                    assert_eq!(
+                        value,
+                        "Number or name of the folder for your idea (default: none)"
+                    );
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| {
+                    // Part of a name picks the one category it matches
+                    Ok(String::from("pers"))
+                }),
+                read_clipboard: Some(|| Ok(String::from("  specific-clipboard-contents\n"))),
+                ..Default::default()
+            },
+            MockGit {
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "specific-summary");
+                    Ok(Oid::zero())
+                }),
+                add_pathspec: Some(|pathspec| {
+                    assert_eq!(pathspec, "personal/README.md");
+                    TRACKED.store(true, Ordering::SeqCst);
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|file_path, summary, body| {
+                    assert_eq!(file_path, "specific-repo/personal/README.md");
+                    assert_eq!(summary, "specific-summary");
+                    assert_eq!(body, "specific-clipboard-contents");
+                    Ok(())
+                }),
+                ensure_dir: Some(|dir_path| {
+                    assert_eq!(dir_path, "specific-repo/personal");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            summary: Some(String::from("specific-summary")),
+            clipboard: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(TRACKED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_categories_hold_the_date_layout_files() {
+        static PATHSPECS: AtomicUsize = AtomicUsize::new(0);
+        static DIRS: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::Categories => Ok("work,personal".to_string()),
+                    ConfigType::DateLayout => Ok("%Y/%m/%d.md".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                input_header: Some(|value| {
+                    assert_eq!(
+                        value,
+                        "Number or name of the folder for your idea (default: none)"
+                    );
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| Ok(String::from("1"))),
+                read_clipboard: Some(|| Ok(String::from("  specific-clipboard-contents\n"))),
+                ..Default::default()
+            },
+            MockGit {
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "specific-summary");
+                    Ok(Oid::zero())
+                }),
+                add_pathspec: Some(|pathspec| {
+                    let expected = ["work/README.md", "work/2024/06/01.md"];
+                    assert_eq!(pathspec, expected[PATHSPECS.fetch_add(1, Ordering::SeqCst)]);
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|file_path, summary, body| {
+                    assert_eq!(file_path, "specific-repo/work/2024/06/01.md");
+                    assert_eq!(summary, "specific-summary");
+                    assert_eq!(body, "specific-clipboard-contents");
+                    Ok(())
+                }),
+                ensure_dir: Some(|dir_path| {
+                    let expected = ["specific-repo/work", "specific-repo/work/2024/06"];
+                    assert_eq!(dir_path, expected[DIRS.fetch_add(1, Ordering::SeqCst)]);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            summary: Some(String::from("specific-summary")),
+            clipboard: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert_eq!(PATHSPECS.load(Ordering::SeqCst), 2);
+        assert_eq!(DIRS.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_capture_context_adds_the_current_directory_as_trailer() {
+        static COMMITTED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::CaptureContext => Ok("true".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                input_header: Some(|_value| {
+                    panic!("Summary is given and should not be asked for");
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_clipboard: Some(|| Ok(String::from("  specific-clipboard-contents\n"))),
+                ..Default::default()
+            },
+            MockGit {
+                commit: Some(|subject, body, _author_time, _commit_time| {
+                    assert_eq!(subject, "specific-summary");
+                    let current_dir = env::current_dir().unwrap();
+                    let trailer = format!("Captured-From: {}", current_dir.display());
+                    assert_eq!(body.lines().next(), Some(trailer.as_str()));
+                    COMMITTED.store(true, Ordering::SeqCst);
+                    Ok(Oid::zero())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|file_path, summary, body| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(summary, "specific-summary");
+                    assert_eq!(body, "specific-clipboard-contents");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            summary: Some(String::from("specific-summary")),
+            clipboard: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(COMMITTED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_file_name_template_names_a_file_per_idea() {
+        static TRACKED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::FileNameTemplate => Ok("{date}-{slug}.{ext}".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                input_header: Some(|_value| {
+                    panic!("Summary is given and should not be asked for");
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_clipboard: Some(|| Ok(String::from("  specific-clipboard-contents\n"))),
+                ..Default::default()
+            },
+            MockGit {
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "Buy a boat!");
+                    Ok(Oid::zero())
+                }),
+                add_pathspec: Some(|pathspec| {
+                    assert_eq!(pathspec, "2024-06-01-buy-a-boat.md");
+                    TRACKED.store(true, Ordering::SeqCst);
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|file_path, summary, body| {
+                    assert_eq!(file_path, "specific-repo/2024-06-01-buy-a-boat.md");
+                    assert_eq!(summary, "Buy a boat!");
+                    assert_eq!(body, "specific-clipboard-contents");
+                    Ok(())
+                }),
+                ensure_dir: Some(|dir_path| {
+                    assert_eq!(dir_path, "specific-repo");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            summary: Some(String::from("Buy a boat!")),
+            clipboard: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(TRACKED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_file_name_template_with_folders_needs_date_layout() {
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::FileNameTemplate => Ok("ideas/{slug}.md".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                input_header: Some(|_value| {
+                    panic!("Summary is given and should not be asked for");
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_clipboard: Some(|| Ok(String::from("  specific-clipboard-contents\n"))),
+                ..Default::default()
+            },
+            MockGit {
+                add: Some(|| unimplemented!()),
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    panic!("Nothing should be committed, got '{}'", subject);
+                }),
+                add_pathspec: Some(|_pathspec| {
+                    panic!("Nothing should be added");
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|_file_path, _summary, _body| {
+                    panic!("The idea should not be written");
+                }),
+                ensure_dir: Some(|_dir_path| {
+                    panic!("No folder should be created");
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            summary: Some(String::from("specific-summary")),
+            clipboard: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(
+            matches!(actual, Err(EurekaError::Config(e)) if e.kind() == ErrorKind::InvalidInput)
+        );
+    }
+
+    #[test]
+    fn test_footer_is_added_below_the_idea() {
+        static FOOTER: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::Footer => {
+                        Ok("Captured via eureka on {date}: {summary}".to_string())
+                    }
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                input_header: Some(|_value| {
+                    panic!("Summary is given and should not be asked for");
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_clipboard: Some(|| Ok(String::from("  specific-clipboard-contents\n"))),
+                ..Default::default()
+            },
+            MockGit {
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "specific-summary");
+                    Ok(Oid::zero())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|file_path, summary, body| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(summary, "specific-summary");
+                    assert_eq!(body, "specific-clipboard-contents");
+                    Ok(())
+                }),
+                append_footer: Some(|file_path, footer| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(
+                        footer,
+                        "Captured via eureka on 2024-06-01: specific-summary"
+                    );
+                    FOOTER.store(true, Ordering::SeqCst);
+                    Ok(true)
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            summary: Some(String::from("specific-summary")),
+            clipboard: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(FOOTER.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_file_extension_names_date_layout_files() {
+        static TRACKED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::DateLayout => Ok("%Y/%m/%d".to_string()),
+                    ConfigType::FileExtension => Ok("org".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                input_header: Some(|_value| {
+                    panic!("Summary is given and should not be asked for");
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_clipboard: Some(|| Ok(String::from("  specific-clipboard-contents\n"))),
+                ..Default::default()
+            },
+            MockGit {
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "specific-summary");
+                    Ok(Oid::zero())
+                }),
+                add_pathspec: Some(|pathspec| {
+                    assert_eq!(pathspec, "2024/06/01.org");
+                    TRACKED.store(true, Ordering::SeqCst);
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|file_path, summary, body| {
+                    assert_eq!(file_path, "specific-repo/2024/06/01.org");
+                    assert_eq!(summary, "specific-summary");
+                    assert_eq!(body, "specific-clipboard-contents");
+                    Ok(())
+                }),
+                ensure_dir: Some(|dir_path| {
+                    assert_eq!(dir_path, "specific-repo/2024/06");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            summary: Some(String::from("specific-summary")),
+            clipboard: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(TRACKED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_backdated_idea_is_filed_and_committed_at_its_date() {
+        static TRACKED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::DateLayout => Ok("%Y/%m/%d.md".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                input_header: Some(|_value| {
+                    panic!("Summary is given and should not be asked for");
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_clipboard: Some(|| Ok(String::from("  specific-clipboard-contents\n"))),
+                ..Default::default()
+            },
+            MockGit {
+                commit: Some(|subject, _body, author_time, commit_time| {
+                    assert_eq!(subject, "specific-summary");
+                    let expected = Local
+                        .with_ymd_and_hms(2024, 5, 20, 9, 30, 0)
+                        .unwrap()
+                        .timestamp();
+                    // Backdated as author and committer
+                    assert_eq!(author_time.map(|time| time.seconds()), Some(expected));
+                    assert_eq!(commit_time.map(|time| time.seconds()), Some(expected));
+                    Ok(Oid::zero())
+                }),
+                add_pathspec: Some(|pathspec| {
+                    assert_eq!(pathspec, "2024/05/20.md");
+                    TRACKED.store(true, Ordering::SeqCst);
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|file_path, summary, body| {
+                    assert_eq!(file_path, "specific-repo/2024/05/20.md");
+                    assert_eq!(summary, "specific-summary");
+                    assert_eq!(body, "specific-clipboard-contents");
+                    Ok(())
+                }),
+                ensure_dir: Some(|dir_path| {
+                    assert_eq!(dir_path, "specific-repo/2024/05");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            summary: Some(String::from("specific-summary")),
+            date: Some(String::from("2024-05-20T09:30")),
+            clipboard: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(TRACKED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_date_in_the_future_is_input_error() {
+        let mut eureka = Eureka::new(
+            MockConfigManager::default(),
+            MockPrinter::default(),
+            MockReader::default(),
+            MockGit::default(),
+            MockProgramOpener::default(),
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        // A week after the fixed clock
+        let opts = EurekaOptions {
+            date: Some(String::from("2024-06-08")),
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts).unwrap_err();
+
+        assert!(matches!(actual, EurekaError::Input(_)));
+        assert_eq!(actual.exit_code(), 4);
+    }
+
+    #[test]
+    fn test_date_layout_outside_repo_fails() {
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::DateLayout => Ok("../%Y.md".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                input_header: Some(|_value| {
+                    panic!("Summary is given and should not be asked for");
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_clipboard: Some(|| Ok(String::from("  specific-clipboard-contents\n"))),
+                ..Default::default()
+            },
+            MockGit {
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "specific-summary");
+                    Ok(Oid::zero())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            summary: Some(String::from("specific-summary")),
+            clipboard: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(matches!(actual, Err(EurekaError::Config(_))));
+    }
+
+    #[test]
+    fn test_multibyte_summary_and_body_are_kept_intact() {
+        let mut eureka = Eureka::new(
+            MockConfigManager::default(),
+            MockPrinter {
+                input_header: Some(|_value| {
+                    panic!("Summary is given and should not be asked for");
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_clipboard: Some(|| Ok(String::from("\u{3000}本文 ✨ — ünïcödé\n"))),
+                ..Default::default()
+            },
+            MockGit {
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "💡 新しい考え");
+                    Ok(Oid::zero())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                append_idea: Some(|file_path, summary, body| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(summary, "💡 新しい考え");
+                    assert_eq!(body, "本文 ✨ — ünïcödé");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            summary: Some(String::from(" 💡 新しい考え\u{3000}")),
+            clipboard: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn test_append_to_asks_which_idea_when_several_match() {
+        static READ_INPUT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager::default(),
+            MockPrinter {
+                input_header: Some(|value| {
+                    assert_eq!(value, "Number of the idea to add to");
+                    Ok(())
+                }),
+                error: Some(|value| {
+                    assert_eq!(value, "Choose a number between 1 and 2");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| {
+                    let counter = READ_INPUT_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    match counter {
+                        0 => Ok(String::from("7")),
+                        _ => Ok(String::from("2")),
+                    }
+                }),
+                ..Default::default()
+            },
+            MockGit {
+                commit: Some(|subject, _body, _author_time, _commit_time| {
+                    assert_eq!(subject, "Add to 'Buy a boat'");
+                    Ok(Oid::zero())
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                open_editor_at: Some(|file_path, line| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(line, 6);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockFileHandler {
+                find_heading_offset: Some(|file_path, title| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(title, "boat");
+                    Ok(vec![
+                        Heading {
+                            title: String::from("Boat trip"),
+                            level: 2,
+                            line: 1,
+                            section_end: 3,
+                        },
+                        Heading {
+                            title: String::from("Buy a boat"),
+                            level: 2,
+                            line: 4,
+                            section_end: 6,
+                        },
+                    ])
+                }),
+                write_front_matter: Some(|file_path, now| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert!(now.starts_with("2024-06-01T12:00:00"));
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            append_to: Some(String::from("boat")),
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(counter_equals(2, &READ_INPUT_COUNTER));
+    }
+
+    #[test]
+    fn test_count_prints_number_of_ideas() {
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_exists: Some(|| unimplemented!()),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    assert_eq!(value, "42");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|_branch_name| unimplemented!()),
+                add: Some(|| unimplemented!()),
+                push: Some(|_branch_name| unimplemented!()),
+                is_ignored: Some(|_file_path| unimplemented!()),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                count_headings: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    Ok(42)
+                }),
+                idea_files: Some(|dir_path, extension| {
+                    assert_eq!(dir_path, "specific-repo");
+                    assert_eq!(extension, "md");
+                    Ok(vec![PathBuf::from("specific-repo/README.md")])
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            count: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn test_count_adds_up_every_idea_file() {
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_exists: Some(|| unimplemented!()),
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::DateLayout => Ok("%Y/%m".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    assert_eq!(value, "42");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|_branch_name| unimplemented!()),
+                add: Some(|| unimplemented!()),
+                push: Some(|_branch_name| unimplemented!()),
+                is_ignored: Some(|_file_path| unimplemented!()),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                count_headings: Some(|file_path| match file_path {
+                    "specific-repo/README.md" => Ok(0),
+                    "specific-repo/2024/05.md" => Ok(40),
+                    "specific-repo/2024/06.md" => Ok(2),
+                    _ => panic!("Unknown file {}", file_path),
+                }),
+                idea_files: Some(|dir_path, extension| {
+                    assert_eq!(dir_path, "specific-repo");
+                    assert_eq!(extension, "md");
+                    Ok(vec![
+                        PathBuf::from("specific-repo/README.md"),
+                        PathBuf::from("specific-repo/2024/05.md"),
+                        PathBuf::from("specific-repo/2024/06.md"),
+                    ])
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            count: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn test_count_of_encrypted_ideas_fails() {
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::EncryptTo => Ok("some-key".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| panic!("Nothing should be printed, got {}", value)),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit::default(),
+            MockProgramOpener::default(),
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            count: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert_eq!(
+            actual.unwrap_err().to_string(),
+            "Input error: --count can't read encrypted ideas yet, unset encrypt_to to use it"
+        );
+    }
+
+    #[test]
+    fn test_random_prints_an_idea_and_opens_it() {
+        // Makes the second idea the pick
+        env::set_var("EUREKA_RANDOM_SEED", "42");
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_exists: Some(|| unimplemented!()),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    assert_eq!(value, "Idea of the day: Learn to swim");
+                    Ok(())
+                }),
+                input_header: Some(|value| {
+                    assert_eq!(value, "Open it? [Y/n]");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| Ok(String::new())),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|_branch_name| unimplemented!()),
+                add: Some(|| unimplemented!()),
+                push: Some(|_branch_name| unimplemented!()),
+                is_ignored: Some(|_file_path| unimplemented!()),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                open_editor_at: Some(|file_path, line| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(line, 5);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockFileHandler {
+                count_headings: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    Ok(42)
+                }),
+                idea_headings: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    Ok(["Buy a boat", "Learn to swim", "Write a book"]
+                        .iter()
+                        .enumerate()
+                        .map(|(i, title)| Heading {
+                            title: title.to_string(),
+                            level: 2,
+                            line: i * 4 + 1,
+                            section_end: i * 4 + 3,
+                        })
+                        .collect())
+                }),
+                idea_files: Some(|dir_path, extension| {
+                    assert_eq!(dir_path, "specific-repo");
+                    assert_eq!(extension, "md");
+                    Ok(vec![PathBuf::from("specific-repo/README.md")])
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            random: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+        env::remove_var("EUREKA_RANDOM_SEED");
+
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn test_random_picks_from_every_idea_file() {
+        // Makes the second idea the pick
+        env::set_var("EUREKA_RANDOM_SEED", "42");
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_exists: Some(|| unimplemented!()),
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::DateLayout => Ok("%Y/%m/%d.md".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    assert_eq!(value, "Idea of the day: Learn to swim");
+                    Ok(())
+                }),
+                input_header: Some(|value| {
+                    assert_eq!(value, "Open it? [Y/n]");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| Ok(String::new())),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|_branch_name| unimplemented!()),
+                add: Some(|| unimplemented!()),
+                push: Some(|_branch_name| unimplemented!()),
+                is_ignored: Some(|_file_path| unimplemented!()),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                open_editor_at: Some(|file_path, line| {
+                    assert_eq!(file_path, "specific-repo/2024/06/01.md");
+                    assert_eq!(line, 1);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockFileHandler {
+                count_headings: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    Ok(42)
+                }),
+                idea_headings: Some(|file_path| {
+                    let titles: &[&str] = match file_path {
+                        "specific-repo/README.md" => &["Buy a boat"],
+                        "specific-repo/2024/06/01.md" => &["Learn to swim", "Write a book"],
+                        _ => panic!("Unknown file {}", file_path),
+                    };
+                    Ok(titles
+                        .iter()
+                        .enumerate()
+                        .map(|(i, title)| Heading {
+                            title: title.to_string(),
+                            level: 2,
+                            line: i * 4 + 1,
+                            section_end: i * 4 + 3,
+                        })
+                        .collect())
+                }),
+                idea_files: Some(|dir_path, extension| {
+                    assert_eq!(dir_path, "specific-repo");
+                    assert_eq!(extension, "md");
+                    Ok(vec![
+                        PathBuf::from("specific-repo/README.md"),
+                        PathBuf::from("specific-repo/2024/06/01.md"),
+                    ])
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            random: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+        env::remove_var("EUREKA_RANDOM_SEED");
+
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn test_private_idea_is_saved_without_git() {
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_exists: Some(|| unimplemented!()),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    assert_eq!(
+                        value,
+                        "Saved your private idea to specific-repo/PRIVATE.md, it's never committed or pushed"
+                    );
+                    Ok(())
+                }),
+                input_header: Some(|_value| {
+                    panic!("Summary is given and should not be asked for");
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit::default(),
+            MockProgramOpener {
+                open_editor: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/PRIVATE.md");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockFileHandler {
+                append_idea: Some(|file_path, summary, body| {
+                    assert_eq!(file_path, "specific-repo/PRIVATE.md");
+                    assert_eq!(summary, "specific-summary");
+                    assert_eq!(body, "");
+                    Ok(())
+                }),
+                count_headings: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    Ok(42)
+                }),
+                ensure_gitignore_entry: Some(|gitignore_path, entry| {
+                    assert_eq!(gitignore_path, "specific-repo/.gitignore");
+                    assert_eq!(entry, "PRIVATE.md");
+                    Ok(true)
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            summary: Some(String::from("specific-summary")),
+            private: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn test_random_without_ideas() {
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_exists: Some(|| unimplemented!()),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    assert_eq!(
+                        value,
+                        "No ideas stored yet, run eureka to capture your first one"
+                    );
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|_branch_name| unimplemented!()),
+                add: Some(|| unimplemented!()),
+                push: Some(|_branch_name| unimplemented!()),
+                is_ignored: Some(|_file_path| unimplemented!()),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                count_headings: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    Ok(42)
+                }),
+                idea_headings: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    Ok(vec![])
+                }),
+                idea_files: Some(|dir_path, extension| {
+                    assert_eq!(dir_path, "specific-repo");
+                    assert_eq!(extension, "md");
+                    Ok(vec![PathBuf::from("specific-repo/README.md")])
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            random: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn test_list_by_tag_groups_ideas_under_their_tags() {
+        static LINE: AtomicUsize = AtomicUsize::new(0);
+        const EXPECTED: [&str; 7] = [
+            "cli",
+            "  - Fast capture (2024/06/01.md)",
+            "rust",
+            "  - Fast capture (2024/06/01.md)",
+            "(untagged)",
+            "  - Learn to swim (README.md)",
+            "  - Sail the world (README.md)",
+        ];
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_exists: Some(|| unimplemented!()),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    let line = LINE.fetch_add(1, Ordering::SeqCst);
+                    assert_eq!(value, EXPECTED[line]);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|_branch_name| unimplemented!()),
+                add: Some(|| unimplemented!()),
+                push: Some(|_branch_name| unimplemented!()),
+                is_ignored: Some(|_file_path| unimplemented!()),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                count_headings: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    Ok(42)
+                }),
+                idea_headings: Some(|file_path| {
+                    let titles = match file_path {
+                        "specific-repo/2024/06/01.md" => vec!["Fast capture"],
+                        "specific-repo/README.md" => vec!["Learn to swim", "Sail the world"],
+                        _ => panic!("Unexpected idea file {}", file_path),
+                    };
+                    Ok(titles
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, title)| Heading {
+                            title: title.to_string(),
+                            level: 2,
+                            line: i + 1,
+                            section_end: i + 1,
+                        })
+                        .collect())
+                }),
+                read_front_matter: Some(|file_path| match file_path {
+                    "specific-repo/2024/06/01.md" => {
+                        Ok(vec!["rust".to_string(), "cli".to_string()])
+                    }
+                    _ => Ok(vec![]),
+                }),
+                idea_files: Some(|dir_path, _extension| {
+                    assert_eq!(dir_path, "specific-repo");
+                    Ok(vec![
+                        PathBuf::from("specific-repo/2024/06/01.md"),
+                        PathBuf::from("specific-repo/README.md"),
+                    ])
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            list: true,
+            by_tag: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert_eq!(LINE.load(Ordering::SeqCst), EXPECTED.len());
+    }
+
+    #[test]
+    fn test_search_fuzzy_ranks_ideas_with_typos() {
+        static LINE: AtomicUsize = AtomicUsize::new(0);
+        const EXPECTED: [&str; 2] = [
+            "- Sail the world (README.md) [62]",
+            "- Buy a boat (2024/06/01.md) [50]",
+        ];
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_exists: Some(|| unimplemented!()),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    let line = LINE.fetch_add(1, Ordering::SeqCst);
+                    assert_eq!(value, EXPECTED[line]);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|_branch_name| unimplemented!()),
+                add: Some(|| unimplemented!()),
+                push: Some(|_branch_name| unimplemented!()),
+                is_ignored: Some(|_file_path| unimplemented!()),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                count_headings: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    Ok(42)
+                }),
+                idea_headings: Some(|file_path| {
+                    let titles = match file_path {
+                        "specific-repo/2024/06/01.md" => vec!["Fast capture", "Buy a boat"],
+                        "specific-repo/README.md" => vec!["Learn to swim", "Sail the world"],
+                        _ => panic!("Unexpected idea file {}", file_path),
+                    };
+                    Ok(titles
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, title)| Heading {
+                            title: title.to_string(),
+                            level: 2,
+                            line: i + 1,
+                            section_end: i + 1,
+                        })
+                        .collect())
+                }),
+                read_front_matter: Some(|file_path| unimplemented!("{}", file_path)),
+                idea_files: Some(|dir_path, _extension| {
+                    assert_eq!(dir_path, "specific-repo");
+                    Ok(vec![
+                        PathBuf::from("specific-repo/2024/06/01.md"),
+                        PathBuf::from("specific-repo/README.md"),
+                    ])
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            search: Some("sail boet".to_string()),
+            fuzzy: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert_eq!(LINE.load(Ordering::SeqCst), EXPECTED.len());
+    }
+
+    #[test]
+    fn test_browse_opens_the_picked_idea_and_lists_ideas_again() {
+        static BROWSED: AtomicUsize = AtomicUsize::new(0);
+        static OPENED: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_exists: Some(|| unimplemented!()),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|_value| unimplemented!()),
+                ..Default::default()
+            },
+            MockReader {
+                browse: Some(|ideas| {
+                    assert_eq!(
+                        ideas,
+                        [
+                            "Fast capture (2024/06/01.md)",
+                            "Buy a boat (2024/06/01.md)",
+                            "Learn to swim (README.md)",
+                            "Sail the world (README.md)",
+                        ]
+                    );
+                    // The idea is picked, then the browser is closed
+                    match BROWSED.fetch_add(1, Ordering::SeqCst) {
+                        0 => Ok(Some(Browsed::Open(3))),
+                        _ => Ok(None),
+                    }
+                }),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|_branch_name| unimplemented!()),
+                add: Some(|| unimplemented!()),
+                push: Some(|_branch_name| unimplemented!()),
+                is_ignored: Some(|_file_path| unimplemented!()),
+                ..Default::default()
+            },
+            MockProgramOpener {
+                open_editor_at: Some(|file_path, line| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    assert_eq!(line, 2);
+                    OPENED.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockFileHandler {
+                count_headings: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    Ok(42)
+                }),
+                idea_headings: Some(|file_path| {
+                    let titles = match file_path {
+                        "specific-repo/2024/06/01.md" => vec!["Fast capture", "Buy a boat"],
+                        "specific-repo/README.md" => vec!["Learn to swim", "Sail the world"],
+                        _ => panic!("Unexpected idea file {}", file_path),
+                    };
+                    Ok(titles
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, title)| Heading {
+                            title: title.to_string(),
+                            level: 2,
+                            line: i + 1,
+                            section_end: i + 1,
+                        })
+                        .collect())
+                }),
+                read_front_matter: Some(|file_path| unimplemented!("{}", file_path)),
+                idea_files: Some(|dir_path, _extension| {
+                    assert_eq!(dir_path, "specific-repo");
+                    Ok(vec![
+                        PathBuf::from("specific-repo/2024/06/01.md"),
+                        PathBuf::from("specific-repo/README.md"),
+                    ])
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            browse: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert_eq!(BROWSED.load(Ordering::SeqCst), 2);
+        assert_eq!(OPENED.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_browse_shows_the_diff_of_the_picked_idea() {
+        static BROWSED: AtomicUsize = AtomicUsize::new(0);
+        static DIFFED: AtomicUsize = AtomicUsize::new(0);
+        static WAITED: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_exists: Some(|| unimplemented!()),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    assert_eq!(value, "1234567 Buy a boat");
+                    Ok(())
+                }),
+                input_header: Some(|value| {
+                    assert_eq!(value, "Press enter to go back to your ideas");
+                    Ok(())
+                }),
+                diff: Some(|value| {
+                    assert_eq!(value, "+## Buy a boat\n");
+                    DIFFED.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| {
+                    WAITED.fetch_add(1, Ordering::SeqCst);
+                    Ok(String::new())
+                }),
+                browse: Some(|ideas| {
+                    assert_eq!(
+                        ideas,
+                        [
+                            "Fast capture (2024/06/01.md)",
+                            "Buy a boat (2024/06/01.md)",
+                            "Learn to swim (README.md)",
+                            "Sail the world (README.md)",
+                        ]
+                    );
+                    // The idea is picked, then the browser is closed
+                    match BROWSED.fetch_add(1, Ordering::SeqCst) {
+                        0 => Ok(Some(Browsed::Diff(1))),
+                        _ => Ok(None),
+                    }
+                }),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|_branch_name| unimplemented!()),
+                add: Some(|| unimplemented!()),
+                push: Some(|_branch_name| unimplemented!()),
+                is_ignored: Some(|_file_path| unimplemented!()),
+                line_commit_diff: Some(|file_path, line| {
+                    // Relative to the idea repo, like git knows it
+                    assert_eq!(file_path, "2024/06/01.md");
+                    assert_eq!(line, 2);
+                    Ok(Some(CommitDiff {
+                        oid: Oid::from_str("1234567890abcdef1234567890abcdef12345678").unwrap(),
+                        summary: "Buy a boat".to_string(),
+                        patch: "+## Buy a boat\n".to_string(),
+                        hunks: vec![],
+                    }))
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                count_headings: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    Ok(42)
+                }),
+                idea_headings: Some(|file_path| {
+                    let titles = match file_path {
+                        "specific-repo/2024/06/01.md" => vec!["Fast capture", "Buy a boat"],
+                        "specific-repo/README.md" => vec!["Learn to swim", "Sail the world"],
+                        _ => panic!("Unexpected idea file {}", file_path),
+                    };
+                    Ok(titles
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, title)| Heading {
+                            title: title.to_string(),
+                            level: 2,
+                            line: i + 1,
+                            section_end: i + 1,
+                        })
+                        .collect())
+                }),
+                read_front_matter: Some(|file_path| unimplemented!("{}", file_path)),
+                idea_files: Some(|dir_path, _extension| {
+                    assert_eq!(dir_path, "specific-repo");
+                    Ok(vec![
+                        PathBuf::from("specific-repo/2024/06/01.md"),
+                        PathBuf::from("specific-repo/README.md"),
+                    ])
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            browse: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert_eq!(BROWSED.load(Ordering::SeqCst), 2);
+        assert_eq!(DIFFED.load(Ordering::SeqCst), 1);
+        assert_eq!(WAITED.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_archive_older_than_moves_stale_ideas_and_commits() {
+        static LINE: AtomicUsize = AtomicUsize::new(0);
+        static ARCHIVED: AtomicUsize = AtomicUsize::new(0);
+        const EXPECTED: [&str; 6] = [
+            "- Fast capture (2024/06/01.md, last changed 2024-01-01)",
+            "- Learn to swim (README.md, last changed 2024-01-01)",
+            "Adding and committing your new idea to main..",
+            "Added and committed!",
+            "Pushing your new idea..",
+            "Pushed!",
+        ];
+        // 2024-01-01, long before the fixed clock's 2024-06-01
+        const OLD: Option<i64> = Some(1_704_110_400);
+        // 2024-05-20
+        const RECENT: Option<i64> = Some(1_716_206_400);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_exists: Some(|| unimplemented!()),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    let line = LINE.fetch_add(1, Ordering::SeqCst);
+                    assert_eq!(value, EXPECTED[line]);
+                    Ok(())
+                }),
+                input_header: Some(|value| {
+                    assert_eq!(value, "Move these 2 idea(s) to ARCHIVE.md? [y/N]");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| Ok("y".to_string())),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                add: Some(|| Ok(3)),
+                commit: Some(|subject, body, _author_time, _commit_time| {
+                    assert_eq!(subject, "Archive 2 idea(s) older than 30 days");
+                    assert_eq!(body, "- Fast capture\n- Learn to swim");
+                    assert_eq!(ARCHIVED.load(Ordering::SeqCst), 2);
+                    Ok(Oid::zero())
+                }),
+                add_pathspec: Some(|pathspec| {
+                    assert!(["2024/06/01.md", "README.md", "ARCHIVE.md"].contains(&pathspec));
+                }),
+                line_times: Some(|file_path| {
+                    match file_path {
+                        "2024/06/01.md" => Ok(vec![OLD, RECENT]),
+                        // Sail the world was changed since it was committed
+                        "README.md" => Ok(vec![OLD, None]),
+                        _ => panic!("Unexpected idea file {}", file_path),
+                    }
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler {
+                count_headings: Some(|file_path| {
+                    assert_eq!(file_path, "specific-repo/README.md");
+                    Ok(42)
+                }),
+                idea_headings: Some(|file_path| {
+                    let titles = match file_path {
+                        "specific-repo/2024/06/01.md" => vec!["Fast capture", "Buy a boat"],
+                        "specific-repo/README.md" => vec!["Learn to swim", "Sail the world"],
+                        _ => panic!("Unexpected idea file {}", file_path),
+                    };
+                    Ok(titles
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, title)| Heading {
+                            title: title.to_string(),
+                            level: 2,
+                            line: i + 1,
+                            section_end: i + 1,
+                        })
+                        .collect())
+                }),
+                read_front_matter: Some(|file_path| unimplemented!("{}", file_path)),
+                idea_files: Some(|dir_path, _extension| {
+                    assert_eq!(dir_path, "specific-repo");
+                    Ok(vec![
+                        PathBuf::from("specific-repo/2024/06/01.md"),
+                        PathBuf::from("specific-repo/README.md"),
+                    ])
+                }),
+                archive_ideas: Some(|file_path, archive_path, headings| {
+                    assert_eq!(archive_path, "specific-repo/ARCHIVE.md");
+                    let titles: Vec<&str> = headings.iter().map(|h| h.title.as_str()).collect();
+                    match file_path {
+                        "specific-repo/2024/06/01.md" => assert_eq!(titles, ["Fast capture"]),
+                        "specific-repo/README.md" => assert_eq!(titles, ["Learn to swim"]),
+                        _ => panic!("Unexpected idea file {}", file_path),
+                    }
+                    ARCHIVED.fetch_add(titles.len(), Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            archive_older_than: Some("30".to_string()),
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert_eq!(LINE.load(Ordering::SeqCst), EXPECTED.len());
+    }
+
+    #[test]
+    fn test_archive_older_than_needs_a_number_of_days() {
+        let mut eureka = Eureka::new(
+            MockConfigManager::default(),
+            MockPrinter::default(),
+            MockReader::default(),
+            MockGit::default(),
+            MockProgramOpener::default(),
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            archive_older_than: Some(String::from("a-month")),
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts).unwrap_err();
+
+        assert!(matches!(actual, EurekaError::Input(_)));
+        assert_eq!(actual.exit_code(), 4);
+    }
+
+    #[test]
+    fn test_flush_pushes_pending_ideas_once() {
+        static PUSHED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_exists: Some(|| unimplemented!()),
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::Branch => Ok("ideas".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    assert!(["Pushing 3 pending commit(s)..", "Pushed!"].contains(&value));
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|_branch_name| unimplemented!()),
+                add: Some(|| unimplemented!()),
+                push: Some(|branch_name| {
+                    assert_eq!(branch_name, "ideas");
+                    assert!(
+                        !PUSHED.swap(true, Ordering::SeqCst),
+                        "pushed more than once"
+                    );
+                    Ok(())
+                }),
+                pending_commits: Some(|branch_name| {
+                    assert_eq!(branch_name, "ideas");
+                    Ok(3)
+                }),
+                is_ignored: Some(|_file_path| unimplemented!()),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            flush: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(PUSHED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_flush_sets_upstream_on_first_push() {
+        static PUSHED: AtomicBool = AtomicBool::new(false);
+        static UPSTREAM_SET: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_exists: Some(|| unimplemented!()),
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::Branch => Ok("ideas".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    assert!([
+                        "Pushing 3 pending commit(s)..",
+                        "Set upstream to origin/ideas.",
+                        "Pushed!"
+                    ]
+                    .contains(&value));
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|_branch_name| unimplemented!()),
+                add: Some(|| unimplemented!()),
+                push: Some(|branch_name| {
+                    assert_eq!(branch_name, "ideas");
+                    assert!(
+                        !PUSHED.swap(true, Ordering::SeqCst),
+                        "pushed more than once"
+                    );
+                    Ok(())
+                }),
+                pending_commits: Some(|branch_name| {
+                    assert_eq!(branch_name, "ideas");
+                    Ok(3)
+                }),
+                is_ignored: Some(|_file_path| unimplemented!()),
+                ensure_upstream: Some(|branch_name, remote_name| {
+                    assert_eq!(branch_name, "ideas");
+                    assert_eq!(remote_name, "origin");
+                    assert!(PUSHED.load(Ordering::SeqCst));
+                    UPSTREAM_SET.store(true, Ordering::SeqCst);
+                    Ok(true)
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            flush: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(PUSHED.load(Ordering::SeqCst));
+        assert!(UPSTREAM_SET.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_status_prints_divergence_and_uncommitted_changes() {
+        static PRINTLN_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_exists: Some(|| unimplemented!()),
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::Branch => Ok("ideas".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    let expected = [
+                        "On branch ideas",
+                        "2 commit(s) ahead and 1 behind origin/ideas (git pull in your idea repo, then --flush)",
+                        "Uncommitted changes in specific-repo",
+                    ];
+                    let index = PRINTLN_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    assert_eq!(value, expected[index]);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|_branch_name| unimplemented!()),
+                add: Some(|| unimplemented!()),
+                push: Some(|_branch_name| {
+                    panic!("--status should not push");
+                }),
+                is_ignored: Some(|_file_path| unimplemented!()),
+                divergence: Some(|branch_name| {
+                    assert_eq!(branch_name, "ideas");
+                    Ok(Divergence::Tracking {
+                        ahead: 2,
+                        behind: 1,
+                    })
+                }),
+                is_clean: Some(|| Ok(false)),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            status: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(counter_equals(3, &PRINTLN_COUNTER));
+    }
+
+    #[test]
+    fn test_diff_prints_nth_idea_commit() {
+        static PRINTLN_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static DIFF_PRINTED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_exists: Some(|| unimplemented!()),
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::Branch => Ok("ideas".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    let expected = ["1234567 specific-summary"];
+                    let index = PRINTLN_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    assert_eq!(value, expected[index]);
+                    Ok(())
+                }),
+                diff: Some(|value| {
+                    assert_eq!(
+                        value,
+                        "+++ b/README.md\n@@ -0,0 +1 @@\n+## specific-summary\n"
+                    );
+                    DIFF_PRINTED.store(true, Ordering::SeqCst);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|_branch_name| unimplemented!()),
+                add: Some(|| unimplemented!()),
+                push: Some(|_branch_name| {
+                    panic!("--diff should not push");
+                }),
+                is_ignored: Some(|_file_path| unimplemented!()),
+                commit_diff: Some(|branch_name, nth| {
+                    assert_eq!(branch_name, "ideas");
+                    assert_eq!(nth, 2);
+                    Ok(Some(CommitDiff {
+                        oid: Oid::from_str("1234567890abcdef1234567890abcdef12345678").unwrap(),
+                        summary: String::from("specific-summary"),
+                        patch: String::from(
+                            "+++ b/README.md\n@@ -0,0 +1 @@\n+## specific-summary\n",
+                        ),
+                        hunks: vec![DiffHunk {
+                            file: String::from("README.md"),
+                            header: String::from("@@ -0,0 +1 @@"),
+                            lines: vec![String::from("+## specific-summary")],
+                        }],
+                    }))
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            diff: Some(String::from("2")),
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(counter_equals(1, &PRINTLN_COUNTER));
+        assert!(DIFF_PRINTED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_diff_prints_hunks_as_json() {
+        static PRINTLN_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_exists: Some(|| unimplemented!()),
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::Branch => Ok("ideas".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    PRINTLN_COUNTER.fetch_add(1, Ordering::SeqCst);
+                    let actual: serde_json::Value = serde_json::from_str(value).unwrap();
+                    assert_eq!(
+                        actual,
+                        serde_json::json!({
+                            "commit": "1234567890abcdef1234567890abcdef12345678",
+                            "summary": "specific-summary",
+                            "hunks": [{
+                                "file": "README.md",
+                                "header": "@@ -0,0 +1 @@",
+                                "lines": ["+## specific-summary"],
+                            }],
+                        })
+                    );
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|_branch_name| unimplemented!()),
+                add: Some(|| unimplemented!()),
+                push: Some(|_branch_name| {
+                    panic!("--diff should not push");
+                }),
+                is_ignored: Some(|_file_path| unimplemented!()),
+                commit_diff: Some(|branch_name, nth| {
+                    assert_eq!(branch_name, "ideas");
+                    assert_eq!(nth, 2);
+                    Ok(Some(CommitDiff {
+                        oid: Oid::from_str("1234567890abcdef1234567890abcdef12345678").unwrap(),
+                        summary: String::from("specific-summary"),
+                        patch: String::from(
+                            "+++ b/README.md\n@@ -0,0 +1 @@\n+## specific-summary\n",
+                        ),
+                        hunks: vec![DiffHunk {
+                            file: String::from("README.md"),
+                            header: String::from("@@ -0,0 +1 @@"),
+                            lines: vec![String::from("+## specific-summary")],
+                        }],
+                    }))
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            diff: Some(String::from("2")),
+            json: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(counter_equals(1, &PRINTLN_COUNTER));
+    }
+
+    #[test]
+    fn test_here_uses_repo_of_current_dir() {
+        static PUSHED: AtomicBool = AtomicBool::new(false);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_exists: Some(|| unimplemented!()),
+                config_read: Some(|file| panic!("config must not be read, read {:?}", file)),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    assert!(["Pushing 3 pending commit(s)..", "Pushed!"].contains(&value));
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                init: Some(|_repo_path| unimplemented!()),
+                checkout_branch: Some(|_branch_name| unimplemented!()),
+                add: Some(|| unimplemented!()),
+                push: Some(|branch_name| {
+                    assert_eq!(branch_name, "feature");
+                    assert!(
+                        !PUSHED.swap(true, Ordering::SeqCst),
+                        "pushed more than once"
+                    );
+                    Ok(())
+                }),
+                upstream_branch: Some(|| unimplemented!()),
+                pending_commits: Some(|branch_name| {
+                    assert_eq!(branch_name, "feature");
+                    Ok(3)
+                }),
+                discover: Some(|path| {
+                    let current_dir = env::current_dir().unwrap();
+                    assert_eq!(path, current_dir.display().to_string());
+                    Ok("project-repo".to_string())
+                }),
+                head_branch: Some(|| Ok(Some("feature".to_string()))),
+                is_ignored: Some(|_file_path| unimplemented!()),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            flush: true,
+            here: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert!(PUSHED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_flush_all_pushes_every_idea_branch_ahead() {
+        static PUSHED: AtomicUsize = AtomicUsize::new(0);
+        static LINE: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_exists: Some(|| unimplemented!()),
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::Branch => Ok("ideas/%Y-w%W".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    const EXPECTED: [&str; 2] = [
+                        "ideas/2024-w22: not pushed, Git error: rejected",
+                        "ideas/2024-w23: pushed 1 commit(s)",
+                    ];
+                    assert_eq!(value, EXPECTED[LINE.fetch_add(1, Ordering::SeqCst)]);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|_branch_name| unimplemented!()),
+                add: Some(|| unimplemented!()),
+                push: Some(|branch_name| {
+                    PUSHED.fetch_add(1, Ordering::SeqCst);
+                    match branch_name {
+                        "ideas/2024-w22" => Err(git2::Error::from_str("rejected")),
+                        "ideas/2024-w23" => Ok(()),
+                        _ => panic!("pushed {}", branch_name),
+                    }
+                }),
+                branches_ahead_of_upstream: Some(|| {
+                    // Only the branches matching the template are idea branches
+                    Ok(vec![
+                        ("ideas/2024-w22".to_string(), 2),
+                        ("ideas/2024-w23".to_string(), 1),
+                        ("ideas/2024-w23/draft".to_string(), 1),
+                        ("main".to_string(), 4),
+                    ])
+                }),
+                is_ignored: Some(|_file_path| unimplemented!()),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            flush_all: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert_eq!(
+            actual.unwrap_err().to_string(),
+            "Git error: 1 of 2 idea branches could not be pushed"
+        );
+        assert_eq!(PUSHED.load(Ordering::SeqCst), 2);
+        assert_eq!(LINE.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_merge_into_pushes_merged_branch() {
+        static PUSHED: AtomicUsize = AtomicUsize::new(0);
+        static LINE: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_exists: Some(|| unimplemented!()),
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::Branch => Ok("ideas/%Y-w%W".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    const EXPECTED: [&str; 4] = [
+                        "Merging ideas/2024-w22 into main..",
+                        "Merged ideas/2024-w22 into main with a merge commit",
+                        "Pushing main..",
+                        "Pushed!",
+                    ];
+                    assert_eq!(value, EXPECTED[LINE.fetch_add(1, Ordering::SeqCst)]);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|_branch_name| unimplemented!()),
+                add: Some(|| unimplemented!()),
+                push: Some(|branch_name| {
+                    assert_eq!(branch_name, "main");
+                    PUSHED.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                }),
+                is_ignored: Some(|_file_path| unimplemented!()),
+                merge_branch: Some(|branch_name, into| {
+                    // This week's branch, from the template
+                    assert_eq!(branch_name, "ideas/2024-w22");
+                    assert_eq!(into, "main");
+                    Ok(MergeOutcome::MergeCommit)
+                }),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            merge_into: Some(String::from("main")),
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert_eq!(PUSHED.load(Ordering::SeqCst), 1);
+        assert_eq!(LINE.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn test_continue_commits_merge_and_pushes() {
+        static PUSHED: AtomicUsize = AtomicUsize::new(0);
+        static LINE: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_exists: Some(|| unimplemented!()),
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::Branch => Ok("ideas/%Y-w%W".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    const EXPECTED: [&str; 3] =
+                        ["Merge committed!", "Pushing ideas/2024-w22..", "Pushed!"];
+                    assert_eq!(value, EXPECTED[LINE.fetch_add(1, Ordering::SeqCst)]);
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader::default(),
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|_branch_name| unimplemented!()),
+                add: Some(|| unimplemented!()),
+                push: Some(|branch_name| {
+                    assert_eq!(branch_name, "ideas/2024-w22");
+                    PUSHED.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                }),
+                head_branch: Some(|| Ok(Some(String::from("ideas/2024-w22")))),
+                is_ignored: Some(|_file_path| unimplemented!()),
+                finish_merge: Some(|| Ok(Some(Oid::zero()))),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            continue_merge: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert_eq!(PUSHED.load(Ordering::SeqCst), 1);
+        assert_eq!(LINE.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_replay_commits_uncommitted_idea_with_suggested_summary() {
+        static PUSHED: AtomicUsize = AtomicUsize::new(0);
+        static LINE: AtomicUsize = AtomicUsize::new(0);
+        static READ_INPUT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let mut eureka = Eureka::new(
+            MockConfigManager {
+                config_dir_exists: Some(|| unimplemented!()),
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::Branch => Ok("ideas/%Y-w%W".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    const EXPECTED: [&str; 4] = [
+                        "Adding and committing your new idea to ideas/2024-w22..",
+                        "Added and committed!",
+                        "Pushing your new idea..",
+                        "Pushed!",
+                    ];
+                    assert_eq!(value, EXPECTED[LINE.fetch_add(1, Ordering::SeqCst)]);
+                    Ok(())
+                }),
+                input_header: Some(|value| {
+                    assert_eq!(value, ">> Idea summary (default: Buy a boat)");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| match READ_INPUT_COUNTER.fetch_add(1, Ordering::SeqCst) {
+                    0 => Ok(String::from("")),
+                    _ => panic!("Unknown state"),
+                }),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|branch_name| {
+                    assert_eq!(branch_name, "ideas/2024-w22");
+                    Ok(())
+                }),
+                diff: Some(|| {
+                    Ok(String::from(
+                        "--- a/README.md\n+++ b/README.md\n@@ -1 +1,4 @@\n ## Old idea\n+\n+## Buy a boat\n+A big one\n",
+                    ))
+                }),
+                commit: Some(|subject, body, _author_time, _commit_time| {
+                    assert_eq!(subject, "Buy a boat");
+                    assert_eq!(body, "");
+                    Ok(Oid::zero())
+                }),
+                push: Some(|branch_name| {
+                    assert_eq!(branch_name, "ideas/2024-w22");
+                    PUSHED.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                }),
+                head_branch: Some(|| Ok(Some(String::from("ideas/2024-w22")))),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
+        );
+        let opts = EurekaOptions {
+            replay: true,
+            ..Default::default()
+        };
+
+        let actual = eureka.run(opts);
+
+        assert!(actual.is_ok());
+        assert_eq!(PUSHED.load(Ordering::SeqCst), 1);
+        assert_eq!(LINE.load(Ordering::SeqCst), 4);
+        assert!(counter_equals(1, &READ_INPUT_COUNTER));
+    }
 
-            fn open_pager(&self, file_path: &str) -> io::Result<()> {
-                assert_eq!(file_path, "specific-repo-path/README.md");
-                Ok(())
-            }
-        }
+    #[test]
+    fn test_replay_without_uncommitted_changes_does_nothing() {
+        static PUSHED: AtomicUsize = AtomicUsize::new(0);
+        static LINE: AtomicUsize = AtomicUsize::new(0);
+        static READ_INPUT_COUNTER: AtomicUsize = AtomicUsize::new(0);
 
         let mut eureka = Eureka::new(
-            MockConfigManager,
-            DefaultMockPrinter {},
-            DefaultMockReader {},
-            DefaultGit {},
-            MockProgramAccess,
+            MockConfigManager {
+                config_dir_exists: Some(|| unimplemented!()),
+                config_read: Some(|file| match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    ConfigType::Branch => Ok("ideas/%Y-w%W".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                }),
+                ..Default::default()
+            },
+            MockPrinter {
+                println: Some(|value| {
+                    const EXPECTED: [&str; 1] =
+                        ["Nothing to replay, your idea files have no uncommitted changes"];
+                    assert_eq!(value, EXPECTED[LINE.fetch_add(1, Ordering::SeqCst)]);
+                    Ok(())
+                }),
+                input_header: Some(|value| {
+                    assert_eq!(value, ">> Idea summary (default: Buy a boat)");
+                    Ok(())
+                }),
+                ..Default::default()
+            },
+            MockReader {
+                read_input: Some(|| match READ_INPUT_COUNTER.fetch_add(1, Ordering::SeqCst) {
+                    0 => Ok(String::from("")),
+                    _ => panic!("Unknown state"),
+                }),
+                ..Default::default()
+            },
+            MockGit {
+                init: Some(|repo_path| {
+                    assert_eq!(repo_path, "specific-repo");
+                    Ok(())
+                }),
+                checkout_branch: Some(|branch_name| {
+                    assert_eq!(branch_name, "ideas/2024-w22");
+                    Ok(())
+                }),
+                diff: Some(|| Ok(String::new())),
+                commit: Some(|subject, body, _author_time, _commit_time| {
+                    assert_eq!(subject, "Buy a boat");
+                    assert_eq!(body, "");
+                    Ok(Oid::zero())
+                }),
+                push: Some(|branch_name| {
+                    assert_eq!(branch_name, "ideas/2024-w22");
+                    PUSHED.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                }),
+                head_branch: Some(|| Ok(Some(String::from("ideas/2024-w22")))),
+                ..Default::default()
+            },
+            MockProgramOpener::default(),
+            MockFileHandler::default(),
+            FixedClock {},
+            MockSink::default(),
         );
         let opts = EurekaOptions {
-            clear_config: false,
-            view: true,
+            replay: true,
+            ..Default::default()
         };
 
         let actual = eureka.run(opts);
 
         assert!(actual.is_ok());
+        assert_eq!(PUSHED.load(Ordering::SeqCst), 0);
+        assert_eq!(LINE.load(Ordering::SeqCst), 1);
+        assert!(counter_equals(0, &READ_INPUT_COUNTER));
     }
 
     #[test]
-    fn test_config_dir_is_missing() {
-        struct MockConfigManager;
-        static READ_COUNTER: AtomicUsize = AtomicUsize::new(0);
+    fn test_describe_lists_every_option_once() {
+        let options = EurekaOptions::describe();
+        let mut names: Vec<&str> = options.iter().map(|option| option.name).collect();
+        names.sort();
+        names.dedup();
+
+        assert_eq!(names.len(), options.len());
+        let summary = options.iter().find(|option| option.name == "summary");
+        assert_eq!(summary.unwrap().kind, OptionKind::Value("SUMMARY"));
+        let view = options.iter().find(|option| option.name == "view").unwrap();
+        assert_eq!((view.short, view.kind), (Some('v'), OptionKind::Flag));
+        assert!(options.iter().all(|option| !option.description.is_empty()));
+    }
 
-        impl ConfigManagement for MockConfigManager {
-            fn config_dir_create(&self) -> io::Result<()> {
-                Ok(())
-            }
+    fn counter_equals(num: u8, counter: &AtomicUsize) -> bool {
+        let counter = counter.fetch_add(0, Ordering::SeqCst);
+        counter == num as usize
+    }
 
-            fn config_dir_exists(&self) -> bool {
-                // Config dir is missing
-                false
-            }
+    // The mocks call what a test sets and otherwise do what most tests need,
+    // or panic if that's nothing
+    #[allow(clippy::type_complexity)]
+    #[derive(Default)]
+    struct MockConfigManager {
+        config_dir_create: Option<fn() -> io::Result<()>>,
+        config_dir_exists: Option<fn() -> bool>,
+        config_read: Option<fn(ConfigType) -> io::Result<String>>,
+        config_write: Option<fn(ConfigType, String) -> io::Result<()>>,
+        config_rm: Option<fn() -> io::Result<()>>,
+        config_dir_path: Option<fn() -> io::Result<PathBuf>>,
+        cache_read: Option<fn() -> io::Result<Option<RepoCache>>>,
+        cache_write: Option<fn(&RepoCache) -> io::Result<()>>,
+    }
 
-            fn config_read(&self, _file: ConfigType) -> io::Result<String> {
-                let counter = READ_COUNTER.fetch_add(1, Ordering::SeqCst);
-                if counter == 0 {
-                    // First it checks if any config can be found and
-                    // based on that it decides to create the config dir
-                    Err(Error::new(ErrorKind::Other, "some-error"))
-                } else {
-                    Ok(String::from("some-ok"))
-                }
+    impl ConfigManagement for MockConfigManager {
+        fn config_dir_create(&self) -> io::Result<()> {
+            match self.config_dir_create {
+                Some(config_dir_create) => config_dir_create(),
+                None => unimplemented!(),
             }
+        }
 
-            fn config_write(&self, _file: ConfigType, _value: String) -> io::Result<()> {
-                unimplemented!()
+        fn config_dir_exists(&self) -> bool {
+            match self.config_dir_exists {
+                Some(config_dir_exists) => config_dir_exists(),
+                None => true,
             }
+        }
 
-            fn config_rm(&self) -> io::Result<()> {
-                unimplemented!()
+        fn config_read(&self, file: ConfigType) -> io::Result<String> {
+            match self.config_read {
+                Some(config_read) => config_read(file),
+                None => match file {
+                    ConfigType::Repo => Ok("specific-repo".to_string()),
+                    _ => Err(Error::new(ErrorKind::NotFound, "not-configured")),
+                },
             }
         }
 
-        struct MockPrinter;
-
-        impl Print for MockPrinter {
-            fn print(&mut self, _value: &str) -> io::Result<()> {
-                unimplemented!()
+        fn config_write(&self, file: ConfigType, value: String) -> io::Result<()> {
+            match self.config_write {
+                Some(config_write) => config_write(file, value),
+                None => unimplemented!(),
             }
+        }
 
-            fn println(&mut self, value: &str) -> io::Result<()> {
-                assert_eq!(value, "First time setup complete. Happy ideation!");
-                Ok(())
+        fn config_rm(&self) -> io::Result<()> {
+            match self.config_rm {
+                Some(config_rm) => config_rm(),
+                None => unimplemented!(),
             }
         }
 
-        impl PrintColor for MockPrinter {
-            fn fts_banner(&mut self) -> io::Result<()> {
-                // noop
-                Ok(())
+        fn config_dir_path(&self) -> io::Result<PathBuf> {
+            match self.config_dir_path {
+                Some(config_dir_path) => config_dir_path(),
+                None => unimplemented!(),
             }
+        }
 
-            fn input_header(&mut self, _value: &str) -> io::Result<()> {
-                unimplemented!()
+        fn cache_read(&self) -> io::Result<Option<RepoCache>> {
+            match self.cache_read {
+                Some(cache_read) => cache_read(),
+                None => Ok(None),
             }
+        }
 
-            fn error(&mut self, _value: &str) -> io::Result<()> {
-                unimplemented!()
+        fn cache_write(&self, cache: &RepoCache) -> io::Result<()> {
+            match self.cache_write {
+                Some(cache_write) => cache_write(cache),
+                None => Ok(()),
             }
         }
-
-        let mut eureka = Eureka::new(
-            MockConfigManager {},
-            MockPrinter {},
-            DefaultMockReader {},
-            DefaultGit {},
-            DefaultMockProgramOpener {},
-        );
-        let opts = EurekaOptions {
-            clear_config: false,
-            view: false,
-        };
-
-        let actual = eureka.run(opts);
-
-        assert!(actual.is_ok());
-        assert!(counter_equals(2, &READ_COUNTER));
     }
 
-    #[test]
-    fn test_setup_repo() {
-        static INPUT_HEADER_COUNTER: AtomicUsize = AtomicUsize::new(0);
-
-        struct MockConfigManager;
+    #[allow(clippy::type_complexity)]
+    #[derive(Default)]
+    struct MockPrinter {
+        print: Option<fn(&str) -> io::Result<()>>,
+        println: Option<fn(&str) -> io::Result<()>>,
+        status: Option<fn(&str) -> io::Result<()>>,
+        fts_banner: Option<fn() -> io::Result<()>>,
+        input_header: Option<fn(&str) -> io::Result<()>>,
+        error: Option<fn(&str) -> io::Result<()>>,
+        diff: Option<fn(&str) -> io::Result<()>>,
+        trace: Option<fn(&str) -> io::Result<()>>,
+    }
 
-        impl ConfigManagement for MockConfigManager {
-            fn config_dir_create(&self) -> io::Result<()> {
-                Ok(())
+    impl Print for MockPrinter {
+        fn print(&mut self, value: &str) -> io::Result<()> {
+            match self.print {
+                Some(print) => print(value),
+                None => unimplemented!(),
             }
+        }
 
-            fn config_dir_exists(&self) -> bool {
-                true
+        fn println(&mut self, value: &str) -> io::Result<()> {
+            match self.println {
+                Some(println) => println(value),
+                None => Ok(()),
             }
+        }
 
-            fn config_read(&self, _file: ConfigType) -> io::Result<String> {
-                Err(Error::new(ErrorKind::Other, "some-error"))
+        fn status(&mut self, token: &str) -> io::Result<()> {
+            match self.status {
+                Some(status) => status(token),
+                None => Ok(()),
             }
+        }
+    }
 
-            fn config_write(&self, file: ConfigType, value: String) -> io::Result<()> {
-                match file {
-                    ConfigType::Repo => assert_eq!(value, "/absolute/path/to/specific-repo-path"),
-                }
-                Ok(())
+    impl PrintColor for MockPrinter {
+        fn fts_banner(&mut self) -> io::Result<()> {
+            match self.fts_banner {
+                Some(fts_banner) => fts_banner(),
+                None => unimplemented!(),
             }
+        }
 
-            fn config_rm(&self) -> io::Result<()> {
-                unimplemented!()
+        fn input_header(&mut self, value: &str) -> io::Result<()> {
+            match self.input_header {
+                Some(input_header) => input_header(value),
+                None => unimplemented!(),
             }
         }
 
-        struct MockPrinter;
-
-        impl Print for MockPrinter {
-            fn print(&mut self, _value: &str) -> io::Result<()> {
-                unimplemented!()
+        fn error(&mut self, value: &str) -> io::Result<()> {
+            match self.error {
+                Some(error) => error(value),
+                None => unimplemented!(),
             }
+        }
 
-            fn println(&mut self, value: &str) -> io::Result<()> {
-                assert_eq!(value, "First time setup complete. Happy ideation!");
-                Ok(())
+        fn diff(&mut self, value: &str) -> io::Result<()> {
+            match self.diff {
+                Some(diff) => diff(value),
+                None => unimplemented!(),
             }
         }
 
-        impl PrintColor for MockPrinter {
-            fn fts_banner(&mut self) -> io::Result<()> {
-                // noop
-                Ok(())
+        fn trace(&mut self, value: &str) -> io::Result<()> {
+            match self.trace {
+                Some(trace) => trace(value),
+                None => unimplemented!(),
             }
+        }
+    }
 
-            fn input_header(&mut self, value: &str) -> io::Result<()> {
-                let counter = INPUT_HEADER_COUNTER.fetch_add(1, Ordering::SeqCst);
-                if counter == 0 {
-                    assert_eq!(value, "Absolute path to your idea repo");
-                } else {
-                    assert_eq!(value, "Name of branch (default: main)");
-                }
+    #[allow(clippy::type_complexity)]
+    #[derive(Default)]
+    struct MockReader {
+        read_input: Option<fn() -> io::Result<String>>,
+        read_clipboard: Option<fn() -> io::Result<String>>,
+        read_multiline_input: Option<fn() -> io::Result<String>>,
+        browse: Option<fn(&[String]) -> io::Result<Option<Browsed>>>,
+    }
 
-                Ok(())
+    impl ReadInput for MockReader {
+        fn read_input(&mut self) -> io::Result<String> {
+            match self.read_input {
+                Some(read_input) => read_input(),
+                None => unimplemented!(),
             }
+        }
 
-            fn error(&mut self, _value: &str) -> io::Result<()> {
-                unimplemented!()
+        fn read_clipboard(&mut self) -> io::Result<String> {
+            match self.read_clipboard {
+                Some(read_clipboard) => read_clipboard(),
+                None => unimplemented!(),
             }
         }
 
-        struct MockReader;
-
-        impl ReadInput for MockReader {
-            fn read_input(&mut self) -> io::Result<String> {
-                Ok(String::from("/absolute/path/to/specific-repo-path"))
+        fn read_multiline_input(&mut self) -> io::Result<String> {
+            match self.read_multiline_input {
+                Some(read_multiline_input) => read_multiline_input(),
+                None => unimplemented!(),
             }
         }
 
-        let mut eureka = Eureka::new(
-            MockConfigManager {},
-            MockPrinter {},
-            MockReader {},
-            DefaultGit {},
-            DefaultMockProgramOpener {},
-        );
-        let opts = EurekaOptions {
-            clear_config: false,
-            view: false,
-        };
-
-        let actual = eureka.run(opts);
-
-        assert!(actual.is_ok());
+        fn browse(&mut self, ideas: &[String]) -> io::Result<Option<Browsed>> {
+            match self.browse {
+                Some(browse) => browse(ideas),
+                None => unimplemented!(),
+            }
+        }
     }
 
-    #[test]
-    fn test_setup_defaults_to_main_branch() {
-        static INPUT_HEADER_COUNTER: AtomicUsize = AtomicUsize::new(0);
-
-        struct MockConfigManager;
-
-        impl ConfigManagement for MockConfigManager {
-            fn config_dir_create(&self) -> io::Result<()> {
-                Ok(())
-            }
+    #[allow(clippy::type_complexity)]
+    #[derive(Default)]
+    struct MockGit {
+        init: Option<fn(&str) -> Result<(), git2::Error>>,
+        ensure_worktree: Option<fn(&str, &str) -> Result<(), git2::Error>>,
+        checkout_branch: Option<fn(&str) -> Result<(), git2::Error>>,
+        add: Option<fn() -> Result<usize, git2::Error>>,
+        diff: Option<fn() -> Result<String, git2::Error>>,
+        commit: Option<
+            fn(&str, &str, Option<git2::Time>, Option<git2::Time>) -> Result<Oid, git2::Error>,
+        >,
+        push: Option<fn(&str) -> Result<(), git2::Error>>,
+        upstream_branch: Option<fn() -> Result<Option<String>, git2::Error>>,
+        pending_commits: Option<fn(&str) -> Result<usize, git2::Error>>,
+        probe_remote: Option<fn() -> Result<(), git2::Error>>,
+        add_pathspec: Option<fn(&str)>,
+        stash_save: Option<fn() -> Result<bool, git2::Error>>,
+        stash_pop: Option<fn() -> Result<(), git2::Error>>,
+        branches_ahead_of_upstream: Option<fn() -> Result<Vec<(String, usize)>, git2::Error>>,
+        discover: Option<fn(&str) -> Result<String, git2::Error>>,
+        head_branch: Option<fn() -> Result<Option<String>, git2::Error>>,
+        is_ignored: Option<fn(&str) -> Result<bool, git2::Error>>,
+        merge_branch: Option<fn(&str, &str) -> Result<MergeOutcome, git2::Error>>,
+        oversized_files: Option<fn(u64) -> Result<Vec<(String, u64)>, git2::Error>>,
+        finish_merge: Option<fn() -> Result<Option<Oid>, git2::Error>>,
+        divergence: Option<fn(&str) -> Result<Divergence, git2::Error>>,
+        is_clean: Option<fn() -> Result<bool, git2::Error>>,
+        line_times: Option<fn(&str) -> Result<Vec<Option<i64>>, git2::Error>>,
+        remote_default_branch: Option<fn() -> Result<Option<String>, git2::Error>>,
+        push_to: Option<fn(&str, &str) -> Result<(), git2::Error>>,
+        remote_url: Option<fn(&str) -> Result<String, git2::Error>>,
+        commit_diff: Option<fn(&str, usize) -> Result<Option<CommitDiff>, git2::Error>>,
+        pull_branch: Option<fn(&str, bool) -> Result<Oid, git2::Error>>,
+        line_commit_diff: Option<fn(&str, usize) -> Result<Option<CommitDiff>, git2::Error>>,
+        ensure_upstream: Option<fn(&str, &str) -> Result<bool, git2::Error>>,
+        skip_idea_pathspecs: Option<fn()>,
+    }
 
-            fn config_dir_exists(&self) -> bool {
-                true
+    impl GitManagement for MockGit {
+        fn init(&mut self, repo_path: &str) -> Result<(), git2::Error> {
+            match self.init {
+                Some(init) => init(repo_path),
+                None => Ok(()),
             }
+        }
 
-            fn config_read(&self, _file: ConfigType) -> io::Result<String> {
-                Err(Error::new(ErrorKind::Other, "some-error"))
+        fn ensure_worktree(
+            &mut self,
+            worktree_path: &str,
+            branch_name: &str,
+        ) -> Result<(), git2::Error> {
+            match self.ensure_worktree {
+                Some(ensure_worktree) => ensure_worktree(worktree_path, branch_name),
+                None => unimplemented!(),
             }
+        }
 
-            fn config_write(&self, file: ConfigType, value: String) -> io::Result<()> {
-                match file {
-                    ConfigType::Repo => assert_eq!(value, "/absolute/path/to/specific-repo-path"),
-                }
-                Ok(())
+        fn checkout_branch(&self, branch_name: &str) -> Result<(), git2::Error> {
+            match self.checkout_branch {
+                Some(checkout_branch) => checkout_branch(branch_name),
+                None => Ok(()),
             }
+        }
 
-            fn config_rm(&self) -> io::Result<()> {
-                unimplemented!()
+        fn add(&self) -> Result<usize, git2::Error> {
+            match self.add {
+                Some(add) => add(),
+                None => Ok(1),
             }
         }
 
-        struct MockPrinter;
-
-        impl Print for MockPrinter {
-            fn print(&mut self, _value: &str) -> io::Result<()> {
-                unimplemented!()
+        fn diff(&self) -> Result<String, git2::Error> {
+            match self.diff {
+                Some(diff) => diff(),
+                None => unimplemented!(),
             }
+        }
 
-            fn println(&mut self, value: &str) -> io::Result<()> {
-                assert_eq!(value, "First time setup complete. Happy ideation!");
-                Ok(())
+        fn commit(
+            &self,
+            subject: &str,
+            body: &str,
+            author_time: Option<git2::Time>,
+            commit_time: Option<git2::Time>,
+        ) -> Result<Oid, git2::Error> {
+            match self.commit {
+                Some(commit) => commit(subject, body, author_time, commit_time),
+                None => unimplemented!(),
             }
         }
 
-        impl PrintColor for MockPrinter {
-            fn fts_banner(&mut self) -> io::Result<()> {
-                // noop
-                Ok(())
+        fn push(&self, branch_name: &str) -> Result<(), git2::Error> {
+            match self.push {
+                Some(push) => push(branch_name),
+                None => Ok(()),
             }
+        }
 
-            fn input_header(&mut self, value: &str) -> io::Result<()> {
-                let counter = INPUT_HEADER_COUNTER.fetch_add(1, Ordering::SeqCst);
-                if counter == 0 {
-                    assert_eq!(value, "Absolute path to your idea repo");
-                } else {
-                    assert_eq!(value, "Name of branch (default: main)");
-                }
-
-                Ok(())
+        fn upstream_branch(&self) -> Result<Option<String>, git2::Error> {
+            match self.upstream_branch {
+                Some(upstream_branch) => upstream_branch(),
+                None => Ok(None),
             }
+        }
 
-            fn error(&mut self, _value: &str) -> io::Result<()> {
-                unimplemented!()
+        fn pending_commits(&self, branch_name: &str) -> Result<usize, git2::Error> {
+            match self.pending_commits {
+                Some(pending_commits) => pending_commits(branch_name),
+                None => unimplemented!(),
             }
         }
 
-        struct MockReader;
-
-        impl ReadInput for MockReader {
-            fn read_input(&mut self) -> io::Result<String> {
-                Ok(String::from("/absolute/path/to/specific-repo-path"))
+        fn probe_remote(&self) -> Result<(), git2::Error> {
+            match self.probe_remote {
+                Some(probe_remote) => probe_remote(),
+                None => Ok(()),
             }
         }
 
-        let mut eureka = Eureka::new(
-            MockConfigManager {},
-            MockPrinter {},
-            MockReader {},
-            DefaultGit {},
-            DefaultMockProgramOpener {},
-        );
-        let opts = EurekaOptions {
-            clear_config: false,
-            view: false,
-        };
-
-        let actual = eureka.run(opts);
-
-        assert!(actual.is_ok());
-    }
-
-    #[test]
-    fn test_setup_repo_path_asks_until_user_provides_value() {
-        static INPUT_HEADER_COUNTER: AtomicUsize = AtomicUsize::new(0);
-        static READ_INPUT_COUNTER: AtomicUsize = AtomicUsize::new(0);
-
-        struct MockConfigManager;
-
-        impl ConfigManagement for MockConfigManager {
-            fn config_dir_create(&self) -> io::Result<()> {
-                Ok(())
+        fn add_pathspec(&mut self, pathspec: &str) {
+            match self.add_pathspec {
+                Some(add_pathspec) => add_pathspec(pathspec),
+                None => unimplemented!(),
             }
+        }
 
-            fn config_dir_exists(&self) -> bool {
-                true
+        fn stash_save(&mut self) -> Result<bool, git2::Error> {
+            match self.stash_save {
+                Some(stash_save) => stash_save(),
+                None => unimplemented!(),
             }
+        }
 
-            fn config_read(&self, _file: ConfigType) -> io::Result<String> {
-                Err(Error::new(ErrorKind::Other, "some-error"))
+        fn stash_pop(&mut self) -> Result<(), git2::Error> {
+            match self.stash_pop {
+                Some(stash_pop) => stash_pop(),
+                None => unimplemented!(),
             }
+        }
 
-            fn config_write(&self, file: ConfigType, value: String) -> io::Result<()> {
-                match file {
-                    ConfigType::Repo => assert_eq!(value, "/absolute/path/to/specific-repo-path"),
-                }
-                Ok(())
+        fn branches_ahead_of_upstream(&self) -> Result<Vec<(String, usize)>, git2::Error> {
+            match self.branches_ahead_of_upstream {
+                Some(branches_ahead_of_upstream) => branches_ahead_of_upstream(),
+                None => unimplemented!(),
             }
+        }
 
-            fn config_rm(&self) -> io::Result<()> {
-                unimplemented!()
+        fn discover(&mut self, path: &str) -> Result<String, git2::Error> {
+            match self.discover {
+                Some(discover) => discover(path),
+                None => unimplemented!(),
             }
         }
 
-        struct MockPrinter;
-
-        impl Print for MockPrinter {
-            fn print(&mut self, _value: &str) -> io::Result<()> {
-                unimplemented!()
+        fn head_branch(&self) -> Result<Option<String>, git2::Error> {
+            match self.head_branch {
+                Some(head_branch) => head_branch(),
+                None => unimplemented!(),
             }
+        }
 
-            fn println(&mut self, value: &str) -> io::Result<()> {
-                assert_eq!(value, "First time setup complete. Happy ideation!");
-                Ok(())
+        fn is_ignored(&self, file_path: &str) -> Result<bool, git2::Error> {
+            match self.is_ignored {
+                Some(is_ignored) => is_ignored(file_path),
+                None => Ok(false),
             }
         }
 
-        impl PrintColor for MockPrinter {
-            fn fts_banner(&mut self) -> io::Result<()> {
-                // noop
-                Ok(())
+        fn merge_branch(&self, branch_name: &str, into: &str) -> Result<MergeOutcome, git2::Error> {
+            match self.merge_branch {
+                Some(merge_branch) => merge_branch(branch_name, into),
+                None => unimplemented!(),
             }
+        }
 
-            fn input_header(&mut self, value: &str) -> io::Result<()> {
-                let counter = INPUT_HEADER_COUNTER.fetch_add(1, Ordering::SeqCst);
-                if counter <= 10 {
-                    assert_eq!(value, "Absolute path to your idea repo");
-                } else {
-                    assert_eq!(value, "Name of branch (default: main)");
-                }
-                Ok(())
+        fn oversized_files(&self, max_bytes: u64) -> Result<Vec<(String, u64)>, git2::Error> {
+            match self.oversized_files {
+                Some(oversized_files) => oversized_files(max_bytes),
+                None => Ok(vec![]),
             }
+        }
 
-            fn error(&mut self, value: &str) -> io::Result<()> {
-                assert_eq!(value, "Path must be absolute");
-                Ok(())
+        fn finish_merge(&mut self) -> Result<Option<Oid>, git2::Error> {
+            match self.finish_merge {
+                Some(finish_merge) => finish_merge(),
+                None => unimplemented!(),
             }
         }
 
-        struct MockReader;
-
-        impl ReadInput for MockReader {
-            fn read_input(&mut self) -> io::Result<String> {
-                let counter = READ_INPUT_COUNTER.fetch_add(1, Ordering::SeqCst);
-                if counter < 5 {
-                    // Return empty string to prompt it to ask again
-                    Ok(String::new())
-                } else if counter < 10 {
-                    // Return relative path to prompt it to ask again
-                    Ok(String::from("some-relative-path"))
-                } else {
-                    Ok(String::from("/absolute/path/to/specific-repo-path"))
-                }
+        fn divergence(&self, branch_name: &str) -> Result<Divergence, git2::Error> {
+            match self.divergence {
+                Some(divergence) => divergence(branch_name),
+                None => unimplemented!(),
             }
         }
 
-        let mut eureka = Eureka::new(
-            MockConfigManager {},
-            MockPrinter {},
-            MockReader {},
-            DefaultGit {},
-            DefaultMockProgramOpener {},
-        );
-        let opts = EurekaOptions {
-            clear_config: false,
-            view: false,
-        };
-
-        let actual = eureka.run(opts);
-
-        assert!(actual.is_ok());
-    }
-
-    #[test]
-    fn test_idea_summary_asks_until_user_provides_value() {
-        static INPUT_HEADER_COUNTER: AtomicUsize = AtomicUsize::new(0);
-        static READ_INPUT_COUNTER: AtomicUsize = AtomicUsize::new(0);
-
-        struct MockConfigManager;
-
-        impl ConfigManagement for MockConfigManager {
-            fn config_dir_create(&self) -> io::Result<()> {
-                Ok(())
+        fn is_clean(&self) -> Result<bool, git2::Error> {
+            match self.is_clean {
+                Some(is_clean) => is_clean(),
+                None => unimplemented!(),
             }
+        }
 
-            fn config_dir_exists(&self) -> bool {
-                true
+        fn line_times(&self, file_path: &str) -> Result<Vec<Option<i64>>, git2::Error> {
+            match self.line_times {
+                Some(line_times) => line_times(file_path),
+                None => unimplemented!(),
             }
+        }
 
-            fn config_read(&self, _file: ConfigType) -> io::Result<String> {
-                Ok(String::from("specific-config-string"))
+        fn remote_default_branch(&self) -> Result<Option<String>, git2::Error> {
+            match self.remote_default_branch {
+                Some(remote_default_branch) => remote_default_branch(),
+                None => Ok(None),
             }
+        }
 
-            fn config_write(&self, file: ConfigType, value: String) -> io::Result<()> {
-                match file {
-                    ConfigType::Repo => assert_eq!(value, "specific-repo-path"),
-                }
-                Ok(())
+        fn push_to(&self, remote_name: &str, branch_name: &str) -> Result<(), git2::Error> {
+            match self.push_to {
+                Some(push_to) => push_to(remote_name, branch_name),
+                None => unimplemented!(),
             }
+        }
 
-            fn config_rm(&self) -> io::Result<()> {
-                unimplemented!()
+        fn remote_url(&self, remote_name: &str) -> Result<String, git2::Error> {
+            match self.remote_url {
+                Some(remote_url) => remote_url(remote_name),
+                None => unimplemented!(),
             }
         }
 
-        struct MockPrinter;
-
-        impl Print for MockPrinter {
-            fn print(&mut self, value: &str) -> io::Result<()> {
-                assert_eq!(value, "First time setup complete. Happy ideation!");
-                Ok(())
+        fn commit_diff(
+            &self,
+            branch_name: &str,
+            nth: usize,
+        ) -> Result<Option<CommitDiff>, git2::Error> {
+            match self.commit_diff {
+                Some(commit_diff) => commit_diff(branch_name, nth),
+                None => unimplemented!(),
             }
+        }
 
-            fn println(&mut self, _value: &str) -> io::Result<()> {
-                // noop
-                Ok(())
+        fn pull_branch(&self, branch_name: &str, rebase: bool) -> Result<Oid, git2::Error> {
+            match self.pull_branch {
+                Some(pull_branch) => pull_branch(branch_name, rebase),
+                None => unimplemented!(),
             }
         }
 
-        impl PrintColor for MockPrinter {
-            fn fts_banner(&mut self) -> io::Result<()> {
-                // noop
-                Ok(())
+        fn line_commit_diff(
+            &self,
+            file_path: &str,
+            line: usize,
+        ) -> Result<Option<CommitDiff>, git2::Error> {
+            match self.line_commit_diff {
+                Some(line_commit_diff) => line_commit_diff(file_path, line),
+                None => unimplemented!(),
             }
+        }
 
-            fn input_header(&mut self, value: &str) -> io::Result<()> {
-                let counter = INPUT_HEADER_COUNTER.fetch_add(1, Ordering::SeqCst);
-                if counter <= 5 {
-                    assert_eq!(value, ">> Idea summary");
-                } else {
-                    assert_eq!(value, "Name of branch (default: main)");
-                }
-                Ok(())
+        fn ensure_upstream(
+            &self,
+            branch_name: &str,
+            remote_name: &str,
+        ) -> Result<bool, git2::Error> {
+            match self.ensure_upstream {
+                Some(ensure_upstream) => ensure_upstream(branch_name, remote_name),
+                None => Ok(false),
             }
+        }
 
-            fn error(&mut self, _value: &str) -> io::Result<()> {
-                unimplemented!()
+        fn skip_idea_pathspecs(&mut self) {
+            match self.skip_idea_pathspecs {
+                Some(skip_idea_pathspecs) => skip_idea_pathspecs(),
+                None => unimplemented!(),
             }
         }
+    }
 
-        struct MockReader;
+    #[allow(clippy::type_complexity)]
+    #[derive(Default)]
+    struct MockProgramOpener {
+        open_editor: Option<fn(&str) -> io::Result<()>>,
+        open_editor_at: Option<fn(&str, usize) -> io::Result<()>>,
+        open_pager: Option<fn(&str) -> io::Result<()>>,
+        resolve_editor: Option<fn() -> Option<String>>,
+        available_editors: Option<fn() -> Vec<String>>,
+        encrypt_file: Option<fn(&str, &str) -> io::Result<()>>,
+        decrypt_file: Option<fn(&str, &str) -> io::Result<bool>>,
+        open_pager_decrypted: Option<fn(&str) -> io::Result<()>>,
+        notify: Option<fn(&str, &str) -> io::Result<()>>,
+        spawn_detached: Option<fn(&[String]) -> io::Result<()>>,
+    }
 
-        impl ReadInput for MockReader {
-            fn read_input(&mut self) -> io::Result<String> {
-                let counter = READ_INPUT_COUNTER.fetch_add(1, Ordering::SeqCst);
-                match counter.cmp(&5) {
-                    CmpOrdering::Less => {
-                        // Return empty string to prompt it to ask again
-                        Ok(String::new())
-                    }
-                    CmpOrdering::Equal => Ok(String::from("specific-idea-summary")),
-                    CmpOrdering::Greater => unimplemented!(),
-                }
+    impl ProgramOpener for MockProgramOpener {
+        fn open_editor(&self, file_path: &str) -> io::Result<()> {
+            match self.open_editor {
+                Some(open_editor) => open_editor(file_path),
+                None => unimplemented!(),
             }
         }
 
-        struct MockGit;
-
-        impl GitManagement for MockGit {
-            fn init(&mut self, _repo_path: &str) -> Result<(), git2::Error> {
-                Ok(())
+        fn open_editor_at(&self, file_path: &str, line: usize) -> io::Result<()> {
+            match self.open_editor_at {
+                Some(open_editor_at) => open_editor_at(file_path, line),
+                None => unimplemented!(),
             }
+        }
 
-            fn checkout_branch(&self, _branch_name: &str) -> Result<(), git2::Error> {
-                Ok(())
+        fn open_pager(&self, file_path: &str) -> io::Result<()> {
+            match self.open_pager {
+                Some(open_pager) => open_pager(file_path),
+                None => unimplemented!(),
             }
+        }
 
-            fn add(&self) -> Result<(), git2::Error> {
-                Ok(())
+        fn resolve_editor(&self) -> Option<String> {
+            match self.resolve_editor {
+                Some(resolve_editor) => resolve_editor(),
+                None => unimplemented!(),
             }
+        }
 
-            fn commit(&self, _subject: &str) -> Result<Oid, git2::Error> {
-                Ok(Oid::zero())
+        fn available_editors(&self) -> Vec<String> {
+            match self.available_editors {
+                Some(available_editors) => available_editors(),
+                None => unimplemented!(),
             }
+        }
 
-            fn push(&self, _branch_name: &str) -> Result<(), git2::Error> {
-                Ok(())
+        fn encrypt_file(&self, file_path: &str, recipient: &str) -> io::Result<()> {
+            match self.encrypt_file {
+                Some(encrypt_file) => encrypt_file(file_path, recipient),
+                None => unimplemented!(),
             }
         }
 
-        struct MockProgramAccess;
-
-        impl ProgramOpener for MockProgramAccess {
-            fn open_editor(&self, _file_path: &str) -> io::Result<()> {
-                Ok(())
+        fn decrypt_file(&self, encrypted_path: &str, file_path: &str) -> io::Result<bool> {
+            match self.decrypt_file {
+                Some(decrypt_file) => decrypt_file(encrypted_path, file_path),
+                None => unimplemented!(),
             }
+        }
 
-            fn open_pager(&self, _file_path: &str) -> io::Result<()> {
-                Ok(())
+        fn open_pager_decrypted(&self, encrypted_path: &str) -> io::Result<()> {
+            match self.open_pager_decrypted {
+                Some(open_pager_decrypted) => open_pager_decrypted(encrypted_path),
+                None => unimplemented!(),
             }
         }
 
-        let mut eureka = Eureka::new(
-            MockConfigManager {},
-            MockPrinter {},
-            MockReader {},
-            MockGit {},
-            MockProgramAccess {},
-        );
-        let opts = EurekaOptions {
-            clear_config: false,
-            view: false,
-        };
-
-        let actual = eureka.run(opts);
+        fn notify(&self, title: &str, body: &str) -> io::Result<()> {
+            match self.notify {
+                Some(notify) => notify(title, body),
+                None => unimplemented!(),
+            }
+        }
 
-        assert!(actual.is_ok());
+        fn spawn_detached(&self, args: &[String]) -> io::Result<()> {
+            match self.spawn_detached {
+                Some(spawn_detached) => spawn_detached(args),
+                None => unimplemented!(),
+            }
+        }
     }
 
-    #[test]
-    fn test_e2e_happy_path() {
-        static PRINT_COUNTER: AtomicUsize = AtomicUsize::new(0);
-
-        struct MockConfigManager;
+    #[allow(clippy::type_complexity)]
+    #[derive(Default)]
+    struct MockFileHandler {
+        append_idea: Option<fn(&str, &str, &str) -> io::Result<()>>,
+        find_heading_offset: Option<fn(&str, &str) -> io::Result<Vec<Heading>>>,
+        count_headings: Option<fn(&str) -> io::Result<usize>>,
+        rotate_idea_file: Option<fn(&str, u64, u64, &str) -> io::Result<Option<PathBuf>>>,
+        idea_headings: Option<fn(&str) -> io::Result<Vec<Heading>>>,
+        ensure_gitignore_entry: Option<fn(&str, &str) -> io::Result<bool>>,
+        prepare_commit_body: Option<fn(&str) -> io::Result<()>>,
+        read_commit_body: Option<fn(&str) -> io::Result<String>>,
+        ensure_dir: Option<fn(&str) -> io::Result<()>>,
+        append_bullet: Option<fn(&str, &str) -> io::Result<()>>,
+        read_front_matter: Option<fn(&str) -> io::Result<Vec<String>>>,
+        idea_files: Option<fn(&str, &str) -> io::Result<Vec<PathBuf>>>,
+        line_count: Option<fn(&str) -> io::Result<usize>>,
+        append_log_entry: Option<fn(&str, &str) -> io::Result<()>>,
+        prepare_issue_body: Option<fn(&str) -> io::Result<()>>,
+        read_issue_body: Option<fn(&str) -> io::Result<String>>,
+        queue_idea: Option<fn(&str, &str) -> io::Result<()>>,
+        read_queue: Option<fn(&str) -> io::Result<Vec<String>>>,
+        write_queue: Option<fn(&str, &[String]) -> io::Result<()>>,
+        append_footer: Option<fn(&str, &str) -> io::Result<bool>>,
+        find_git_repos: Option<fn(&str, usize) -> io::Result<Vec<PathBuf>>>,
+        looks_like_project: Option<fn(&str) -> io::Result<bool>>,
+        has_marker: Option<fn(&str) -> bool>,
+        create_marker: Option<fn(&str) -> io::Result<()>>,
+        write_front_matter: Option<fn(&str, &str) -> io::Result<()>>,
+        wait_until_settled: Option<fn(&str, Duration) -> io::Result<()>>,
+        archive_ideas: Option<fn(&str, &str, &[Heading]) -> io::Result<()>>,
+        try_lock: Option<fn(&str) -> io::Result<bool>>,
+        unlock: Option<fn(&str) -> io::Result<()>>,
+        append_keyed_idea: Option<fn(&str, &str, &str, &str) -> io::Result<()>>,
+        exists: Option<fn(&str) -> bool>,
+    }
 
-        impl ConfigManagement for MockConfigManager {
-            fn config_dir_create(&self) -> io::Result<()> {
-                unimplemented!()
+    impl FileManagement for MockFileHandler {
+        fn append_idea(&self, file_path: &str, summary: &str, body: &str) -> io::Result<()> {
+            match self.append_idea {
+                Some(append_idea) => append_idea(file_path, summary, body),
+                None => unimplemented!(),
             }
+        }
 
-            fn config_dir_exists(&self) -> bool {
-                true
+        fn find_heading_offset(&self, file_path: &str, title: &str) -> io::Result<Vec<Heading>> {
+            match self.find_heading_offset {
+                Some(find_heading_offset) => find_heading_offset(file_path, title),
+                None => unimplemented!(),
             }
+        }
 
-            fn config_read(&self, file: ConfigType) -> io::Result<String> {
-                match file {
-                    ConfigType::Repo => Ok("specific-repo".to_string()),
-                }
+        fn count_headings(&self, file_path: &str) -> io::Result<usize> {
+            match self.count_headings {
+                Some(count_headings) => count_headings(file_path),
+                None => unimplemented!(),
             }
+        }
 
-            fn config_write(&self, _file: ConfigType, _value: String) -> io::Result<()> {
-                unimplemented!()
+        fn rotate_idea_file(
+            &self,
+            file_path: &str,
+            max_bytes: u64,
+            incoming_bytes: u64,
+            date: &str,
+        ) -> io::Result<Option<PathBuf>> {
+            match self.rotate_idea_file {
+                Some(rotate_idea_file) => {
+                    rotate_idea_file(file_path, max_bytes, incoming_bytes, date)
+                }
+                None => unimplemented!(),
             }
+        }
 
-            fn config_rm(&self) -> io::Result<()> {
-                unimplemented!()
+        fn idea_headings(&self, file_path: &str) -> io::Result<Vec<Heading>> {
+            match self.idea_headings {
+                Some(idea_headings) => idea_headings(file_path),
+                None => unimplemented!(),
             }
         }
 
-        struct MockPrinter;
-
-        impl Print for MockPrinter {
-            fn print(&mut self, _value: &str) -> io::Result<()> {
-                unimplemented!()
+        fn ensure_gitignore_entry(&self, gitignore_path: &str, entry: &str) -> io::Result<bool> {
+            match self.ensure_gitignore_entry {
+                Some(ensure_gitignore_entry) => ensure_gitignore_entry(gitignore_path, entry),
+                None => unimplemented!(),
             }
+        }
 
-            fn println(&mut self, value: &str) -> io::Result<()> {
-                let counter = PRINT_COUNTER.fetch_add(1, Ordering::SeqCst);
-                match counter {
-                    0 => assert_eq!(value, "Adding and committing your new idea to main.."),
-                    1 => assert_eq!(value, "Added and committed!"),
-                    2 => assert_eq!(value, "Pushing your new idea.."),
-                    3 => assert_eq!(value, "Pushed!"),
-                    _ => panic!("Unknown state"),
-                }
-
-                Ok(())
+        fn prepare_commit_body(&self, file_path: &str) -> io::Result<()> {
+            match self.prepare_commit_body {
+                Some(prepare_commit_body) => prepare_commit_body(file_path),
+                None => unimplemented!(),
             }
         }
 
-        impl PrintColor for MockPrinter {
-            fn fts_banner(&mut self) -> io::Result<()> {
-                unimplemented!()
+        fn read_commit_body(&self, file_path: &str) -> io::Result<String> {
+            match self.read_commit_body {
+                Some(read_commit_body) => read_commit_body(file_path),
+                None => unimplemented!(),
             }
+        }
 
-            fn input_header(&mut self, value: &str) -> io::Result<()> {
-                assert_eq!(value, ">> Idea summary");
-                Ok(())
+        fn ensure_dir(&self, dir_path: &str) -> io::Result<()> {
+            match self.ensure_dir {
+                Some(ensure_dir) => ensure_dir(dir_path),
+                None => unimplemented!(),
             }
+        }
 
-            fn error(&mut self, _value: &str) -> io::Result<()> {
-                unimplemented!()
+        fn append_bullet(&self, file_path: &str, text: &str) -> io::Result<()> {
+            match self.append_bullet {
+                Some(append_bullet) => append_bullet(file_path, text),
+                None => unimplemented!(),
             }
         }
 
-        struct MockReader;
-
-        impl ReadInput for MockReader {
-            fn read_input(&mut self) -> io::Result<String> {
-                Ok(String::from("read-input-string"))
+        fn read_front_matter(&self, file_path: &str) -> io::Result<Vec<String>> {
+            match self.read_front_matter {
+                Some(read_front_matter) => read_front_matter(file_path),
+                None => unimplemented!(),
             }
         }
 
-        struct MockGit;
-
-        impl GitManagement for MockGit {
-            fn init(&mut self, repo_path: &str) -> Result<(), git2::Error> {
-                assert_eq!(repo_path, "specific-repo");
-                Ok(())
+        fn idea_files(&self, dir_path: &str, extension: &str) -> io::Result<Vec<PathBuf>> {
+            match self.idea_files {
+                Some(idea_files) => idea_files(dir_path, extension),
+                None => unimplemented!(),
             }
+        }
 
-            fn checkout_branch(&self, branch_name: &str) -> Result<(), git2::Error> {
-                assert_eq!(branch_name, "main");
-                Ok(())
+        fn line_count(&self, file_path: &str) -> io::Result<usize> {
+            match self.line_count {
+                Some(line_count) => line_count(file_path),
+                None => unimplemented!(),
             }
+        }
 
-            fn add(&self) -> Result<(), git2::Error> {
-                Ok(())
+        fn append_log_entry(&self, file_path: &str, entry: &str) -> io::Result<()> {
+            match self.append_log_entry {
+                Some(append_log_entry) => append_log_entry(file_path, entry),
+                None => unimplemented!(),
             }
+        }
 
-            fn commit(&self, subject: &str) -> Result<Oid, git2::Error> {
-                assert_eq!(subject, "read-input-string");
-                Ok(Oid::zero())
+        fn prepare_issue_body(&self, file_path: &str) -> io::Result<()> {
+            match self.prepare_issue_body {
+                Some(prepare_issue_body) => prepare_issue_body(file_path),
+                None => unimplemented!(),
             }
+        }
 
-            fn push(&self, branch_name: &str) -> Result<(), git2::Error> {
-                assert_eq!(branch_name, "main");
-                Ok(())
+        fn read_issue_body(&self, file_path: &str) -> io::Result<String> {
+            match self.read_issue_body {
+                Some(read_issue_body) => read_issue_body(file_path),
+                None => unimplemented!(),
             }
         }
 
-        struct MockProgramOpener;
-
-        impl ProgramOpener for MockProgramOpener {
-            fn open_editor(&self, file_path: &str) -> io::Result<()> {
-                assert_eq!(file_path, "specific-repo/README.md");
-                Ok(())
+        fn queue_idea(&self, file_path: &str, text: &str) -> io::Result<()> {
+            match self.queue_idea {
+                Some(queue_idea) => queue_idea(file_path, text),
+                None => unimplemented!(),
             }
+        }
 
-            fn open_pager(&self, _file_path: &str) -> io::Result<()> {
-                unimplemented!()
+        fn read_queue(&self, file_path: &str) -> io::Result<Vec<String>> {
+            match self.read_queue {
+                Some(read_queue) => read_queue(file_path),
+                None => unimplemented!(),
             }
         }
 
-        let mut eureka = Eureka::new(
-            MockConfigManager {},
-            MockPrinter {},
-            MockReader {},
-            MockGit {},
-            MockProgramOpener {},
-        );
-        let opts = EurekaOptions {
-            clear_config: false,
-            view: false,
-        };
-
-        let actual = eureka.run(opts);
-
-        assert!(actual.is_ok());
-    }
-
-    fn counter_equals(num: u8, counter: &AtomicUsize) -> bool {
-        let counter = counter.fetch_add(0, Ordering::SeqCst);
-        counter == num as usize
-    }
-
-    struct DefaultMockPrinter;
-
-    impl Print for DefaultMockPrinter {
-        fn print(&mut self, _value: &str) -> io::Result<()> {
-            unimplemented!()
+        fn write_queue(&self, file_path: &str, entries: &[String]) -> io::Result<()> {
+            match self.write_queue {
+                Some(write_queue) => write_queue(file_path, entries),
+                None => unimplemented!(),
+            }
         }
 
-        fn println(&mut self, _value: &str) -> io::Result<()> {
-            unimplemented!()
+        fn append_footer(&self, file_path: &str, footer: &str) -> io::Result<bool> {
+            match self.append_footer {
+                Some(append_footer) => append_footer(file_path, footer),
+                None => unimplemented!(),
+            }
         }
-    }
 
-    impl PrintColor for DefaultMockPrinter {
-        fn fts_banner(&mut self) -> io::Result<()> {
-            unimplemented!()
+        fn find_git_repos(&self, dir_path: &str, max_depth: usize) -> io::Result<Vec<PathBuf>> {
+            match self.find_git_repos {
+                Some(find_git_repos) => find_git_repos(dir_path, max_depth),
+                None => unimplemented!(),
+            }
         }
 
-        fn input_header(&mut self, _value: &str) -> io::Result<()> {
-            unimplemented!()
+        fn looks_like_project(&self, dir_path: &str) -> io::Result<bool> {
+            match self.looks_like_project {
+                Some(looks_like_project) => looks_like_project(dir_path),
+                None => Ok(false),
+            }
         }
 
-        fn error(&mut self, _value: &str) -> io::Result<()> {
-            unimplemented!()
+        fn has_marker(&self, file_path: &str) -> bool {
+            match self.has_marker {
+                Some(has_marker) => has_marker(file_path),
+                None => false,
+            }
         }
-    }
 
-    struct DefaultMockReader;
-
-    impl ReadInput for DefaultMockReader {
-        fn read_input(&mut self) -> io::Result<String> {
-            unimplemented!()
+        fn create_marker(&self, file_path: &str) -> io::Result<()> {
+            match self.create_marker {
+                Some(create_marker) => create_marker(file_path),
+                None => unimplemented!(),
+            }
         }
-    }
 
-    struct DefaultMockConfigManager;
-
-    impl ConfigManagement for DefaultMockConfigManager {
-        fn config_dir_create(&self) -> io::Result<()> {
-            unimplemented!()
+        fn write_front_matter(&self, file_path: &str, now: &str) -> io::Result<()> {
+            match self.write_front_matter {
+                Some(write_front_matter) => write_front_matter(file_path, now),
+                None => unimplemented!(),
+            }
         }
 
-        fn config_dir_exists(&self) -> bool {
-            unimplemented!()
+        fn wait_until_settled(&self, file_path: &str, quiet: Duration) -> io::Result<()> {
+            match self.wait_until_settled {
+                Some(wait_until_settled) => wait_until_settled(file_path, quiet),
+                None => unimplemented!(),
+            }
         }
 
-        fn config_read(&self, _file: ConfigType) -> io::Result<String> {
-            unimplemented!()
+        fn archive_ideas(
+            &self,
+            file_path: &str,
+            archive_path: &str,
+            headings: &[Heading],
+        ) -> io::Result<()> {
+            match self.archive_ideas {
+                Some(archive_ideas) => archive_ideas(file_path, archive_path, headings),
+                None => unimplemented!(),
+            }
         }
 
-        fn config_write(&self, _file: ConfigType, _value: String) -> io::Result<()> {
-            unimplemented!()
+        fn try_lock(&self, file_path: &str) -> io::Result<bool> {
+            match self.try_lock {
+                Some(try_lock) => try_lock(file_path),
+                None => unimplemented!(),
+            }
         }
 
-        fn config_rm(&self) -> io::Result<()> {
-            unimplemented!()
+        fn unlock(&self, file_path: &str) -> io::Result<()> {
+            match self.unlock {
+                Some(unlock) => unlock(file_path),
+                None => unimplemented!(),
+            }
         }
-    }
 
-    struct DefaultGit;
-
-    impl GitManagement for DefaultGit {
-        fn init(&mut self, _repo_path: &str) -> Result<(), git2::Error> {
-            unimplemented!()
+        fn append_keyed_idea(
+            &self,
+            file_path: &str,
+            id: &str,
+            summary: &str,
+            body: &str,
+        ) -> io::Result<()> {
+            match self.append_keyed_idea {
+                Some(append_keyed_idea) => append_keyed_idea(file_path, id, summary, body),
+                None => unimplemented!(),
+            }
         }
 
-        fn checkout_branch(&self, _branch_name: &str) -> Result<(), git2::Error> {
-            unimplemented!()
+        fn exists(&self, file_path: &str) -> bool {
+            match self.exists {
+                Some(exists) => exists(file_path),
+                None => unimplemented!(),
+            }
         }
+    }
 
-        fn add(&self) -> Result<(), git2::Error> {
-            unimplemented!()
-        }
+    #[allow(clippy::type_complexity)]
+    #[derive(Default)]
+    struct MockSink {
+        store: Option<fn(&str, &str, &str) -> io::Result<String>>,
+        open_pull_request: Option<fn(&str, &str, &str, &str, &str) -> io::Result<String>>,
+    }
 
-        fn commit(&self, _subject: &str) -> Result<Oid, git2::Error> {
-            unimplemented!()
+    impl IdeaSink for MockSink {
+        fn store(&self, destination: &str, title: &str, body: &str) -> io::Result<String> {
+            match self.store {
+                Some(store) => store(destination, title, body),
+                None => unimplemented!(),
+            }
         }
 
-        fn push(&self, _branch_name: &str) -> Result<(), git2::Error> {
-            unimplemented!()
+        fn open_pull_request(
+            &self,
+            repo: &str,
+            head: &str,
+            base: &str,
+            title: &str,
+            body: &str,
+        ) -> io::Result<String> {
+            match self.open_pull_request {
+                Some(open_pull_request) => open_pull_request(repo, head, base, title, body),
+                None => unimplemented!(),
+            }
         }
     }
 
-    struct DefaultMockProgramOpener;
-
-    impl ProgramOpener for DefaultMockProgramOpener {
-        fn open_editor(&self, _file_path: &str) -> io::Result<()> {
-            unimplemented!()
-        }
+    struct FixedClock;
 
-        fn open_pager(&self, _file_path: &str) -> io::Result<()> {
-            unimplemented!()
+    impl Clock for FixedClock {
+        fn now(&self) -> DateTime<Local> {
+            Local.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap()
         }
     }
 }